@@ -0,0 +1,71 @@
+#! /usr/bin/env python
+#
+# Copyright (c) 2024 Linaro LTD
+# SPDX-License-Identifier: Apache-2.0
+
+# Check the function size limits declared with `zephyr::perf::assert_max_size!`.
+#
+# Each limit is a record of a function address and a maximum size, placed in the `rust_size_check`
+# iterable section.  The address is looked up in the symbol table of the linked image, and the
+# build fails if the function is larger than its limit.
+
+import sys
+
+from elftools.elf.elffile import ELFFile
+
+
+def main():
+    if len(sys.argv) != 2:
+        print(f"usage: {sys.argv[0]} zephyr.elf", file=sys.stderr)
+        return 2
+
+    with open(sys.argv[1], "rb") as f:
+        elf = ELFFile(f)
+        word = 8 if elf.elfclass == 64 else 4
+        order = "little" if elf.little_endian else "big"
+        # Thumb function addresses have the low bit set.
+        mask = ~1 if elf["e_machine"] == "EM_ARM" else ~0
+
+        symtab = elf.get_section_by_name(".symtab")
+        start = symtab.get_symbol_by_name("_rust_size_check_list_start")
+        end = symtab.get_symbol_by_name("_rust_size_check_list_end")
+        if not start or not end:
+            return 0
+        start = start[0]["st_value"]
+        end = end[0]["st_value"]
+
+        funcs = {}
+        for sym in symtab.iter_symbols():
+            if sym["st_info"]["type"] == "STT_FUNC":
+                funcs[sym["st_value"] & mask] = sym
+
+        data = read(elf, start, end - start)
+        failed = False
+        for pos in range(0, len(data), 2 * word):
+            addr = int.from_bytes(data[pos:pos + word], order) & mask
+            limit = int.from_bytes(data[pos + word:pos + 2 * word], order)
+            sym = funcs.get(addr)
+            if sym is None:
+                print(f"error: no function at {addr:#x} for assert_max_size!", file=sys.stderr)
+                failed = True
+            elif sym["st_size"] > limit:
+                print(f"error: function {sym.name} is {sym['st_size']} bytes, "
+                      f"over its assert_max_size! limit of {limit} bytes", file=sys.stderr)
+                failed = True
+
+    return 1 if failed else 0
+
+
+def read(elf, addr, size):
+    """Read `size` bytes at virtual address `addr` from the image."""
+    for seg in elf.iter_segments():
+        if seg["p_type"] != "PT_LOAD":
+            continue
+        base = seg["p_vaddr"]
+        if base <= addr and addr + size <= base + seg["p_filesz"]:
+            return seg.data()[addr - base:addr - base + size]
+    raise ValueError(f"address {addr:#x} is not in the image")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -6,4 +6,8 @@ fn main() {
     // #[cfg(dt = "aliases::led0")]
     // ```
     zephyr_build::dt_cfgs();
+
+    // Blinking needs the GPIO driver.  When it is not enabled, this gives a clear error, through
+    // the `include!` in `lib.rs`, rather than failures to find the GPIO APIs.
+    zephyr_build::kconfig_require!(CONFIG_GPIO, "blinky needs the GPIO driver");
 }
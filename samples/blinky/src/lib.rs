@@ -8,6 +8,9 @@
 // build.  So, this is just always necessary.
 #![allow(unexpected_cfgs)]
 
+// Fail the build, with the message from `build.rs`, if a required Kconfig option is missing.
+include!(concat!(env!("OUT_DIR"), "/kconfig_require.rs"));
+
 use log::warn;
 
 use zephyr::raw::GPIO_OUTPUT_ACTIVE;
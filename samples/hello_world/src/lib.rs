@@ -5,8 +5,8 @@
 
 use log::info;
 
-#[no_mangle]
-extern "C" fn rust_main() {
+#[zephyr::main]
+fn main() {
     unsafe {
         zephyr::set_logger().unwrap();
     }
@@ -0,0 +1,71 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! # Backoff adaptor for ForkSync
+//!
+//! This wraps another ForkSync, and, instead of blocking when a fork is taken, sleeps for a random
+//! time and tries again.  The time to wait doubles on each failed attempt, up to a limit.  It is
+//! built only on the trait, so it works with any of the other syncers, although those that can't
+//! check a fork without blocking will just block as before.
+
+use zephyr::sync::atomic::{AtomicU32, Ordering};
+use zephyr::sync::Arc;
+use zephyr::sys::uptime_get;
+use zephyr::time::{sleep, Duration, Tick};
+
+use crate::ForkSync;
+
+/// The first backoff, in ms.
+const MIN_BACKOFF_MS: u64 = 1;
+
+#[derive(Debug)]
+pub struct Debounced {
+    inner: Arc<dyn ForkSync>,
+    max_backoff: Duration,
+    /// State for the random number generator.
+    seed: AtomicU32,
+}
+
+impl Debounced {
+    #[allow(dead_code)]
+    pub fn new(inner: Arc<dyn ForkSync>, max_backoff: Duration) -> Debounced {
+        // Zero is the one seed that xorshift can't get out of.
+        let seed = (uptime_get() as u32) | 1;
+        Debounced {
+            inner,
+            max_backoff,
+            seed: AtomicU32::new(seed),
+        }
+    }
+
+    /// A random number, from a xorshift generator.  This doesn't need to be good, only to keep
+    /// the philosophers from retrying in step.
+    fn random(&self) -> u32 {
+        let mut x = self.seed.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.seed.store(x, Ordering::Relaxed);
+        x
+    }
+}
+
+impl ForkSync for Debounced {
+    fn take(&self, index: usize) {
+        let max = self.max_backoff.to_millis().max(MIN_BACKOFF_MS);
+        let mut backoff = MIN_BACKOFF_MS;
+        while !self.inner.try_take(index) {
+            let delay = self.random() as u64 % backoff + 1;
+            sleep(Duration::millis_at_least(delay as Tick));
+            backoff = (backoff * 2).min(max);
+        }
+    }
+
+    fn try_take(&self, index: usize) -> bool {
+        self.inner.try_take(index)
+    }
+
+    fn release(&self, index: usize) {
+        self.inner.release(index);
+    }
+}
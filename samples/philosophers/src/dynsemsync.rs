@@ -10,7 +10,7 @@ extern crate alloc;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use zephyr::{sync::Arc, sys::sync::Semaphore, time::Forever};
+use zephyr::{sync::Arc, sys::sync::Semaphore, time::{Forever, NoWait}};
 
 use crate::{ForkSync, NUM_PHIL};
 
@@ -26,6 +26,10 @@ impl ForkSync for SemSync {
         self.forks[index].take(Forever).unwrap();
     }
 
+    fn try_take(&self, index: usize) -> bool {
+        self.forks[index].take(NoWait).is_ok()
+    }
+
     fn release(&self, index: usize) {
         self.forks[index].give();
     }
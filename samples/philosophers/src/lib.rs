@@ -25,16 +25,21 @@ use crate::channel::get_channel_syncer;
 #[allow(unused_imports)]
 use crate::condsync::CondSync;
 #[allow(unused_imports)]
+use crate::debounced::Debounced;
+#[allow(unused_imports)]
 use crate::dynsemsync::dyn_semaphore_sync;
 #[allow(unused_imports)]
 use crate::semsync::semaphore_sync;
+use crate::stats::StatsCollector;
 #[allow(unused_imports)]
 use crate::sysmutex::SysMutexSync;
 
 mod channel;
 mod condsync;
+mod debounced;
 mod dynsemsync;
 mod semsync;
+mod stats;
 mod sysmutex;
 
 /// How many philosophers.  There will be the same number of forks.
@@ -44,6 +49,15 @@ const NUM_PHIL: usize = 6;
 /// bytes, when printing messages.  Make a bit larger to work.
 const PHIL_STACK_SIZE: usize = 4096;
 
+/// How much of each philosopher's stack should remain unused, checked along with the stats.
+#[cfg(all(CONFIG_INIT_STACKS, CONFIG_THREAD_STACK_INFO))]
+const PHIL_STACK_HEADROOM: usize = 512;
+
+zephyr::static_assert!(
+    PHIL_STACK_SIZE >= zephyr::arch::MIN_STACK,
+    "Stack too small for this arch"
+);
+
 // The dining philosophers problem is a simple example of cooperation between multiple threads.
 // This implementation use one of several different underlying mechanism to support this cooperation.
 
@@ -59,6 +73,13 @@ trait ForkSync: core::fmt::Debug + Sync + Send {
     /// block until the fork is released.
     fn take(&self, index: usize);
 
+    /// Try to take the given fork, without waiting, returning true if it was taken.  Syncers that
+    /// can't check without blocking just take the fork.
+    fn try_take(&self, index: usize) -> bool {
+        self.take(index);
+        true
+    }
+
     /// Release the given fork.  Index is the same as take.
     fn release(&self, index: usize);
 }
@@ -68,30 +89,44 @@ extern "C" fn rust_main() {
     printkln!("Hello world from Rust on {}", zephyr::kconfig::CONFIG_BOARD);
     printkln!("Time tick: {}", zephyr::time::SYS_FREQUENCY);
 
-    let stats = Arc::new(Mutex::new_from(
-        Stats::default(),
-        STAT_MUTEX.init_once(()).unwrap(),
-    ));
+    let stats = StatsCollector::new(NUM_PHIL);
 
     let syncers = get_syncer();
 
+    // Optionally, wrap each syncer to back off, rather than block, when a fork is taken.
+    #[cfg(CONFIG_SYNC_DEBOUNCED)]
+    let syncers: Vec<Arc<dyn ForkSync>> = syncers
+        .into_iter()
+        .map(|inner| {
+            let syncer = Box::new(Debounced::new(inner, Duration::millis_at_least(200)));
+            Arc::from(syncer as Box<dyn ForkSync>)
+        })
+        .collect();
+
     printkln!("Pre fork");
 
+    let mut threads = Vec::new();
     for (i, syncer) in (0..NUM_PHIL).zip(syncers.into_iter()) {
         let child_stat = stats.clone();
         let thread = PHIL_THREADS[i]
             .init_once(PHIL_STACKS[i].init_once(()).unwrap())
             .unwrap();
-        thread.spawn(move || {
+        threads.push(thread.spawn(move || {
             phil_thread(i, syncer, child_stat);
-        });
+        }));
     }
 
     let delay = Duration::secs_at_least(10);
     loop {
         // Periodically, printout the stats.
         zephyr::time::sleep(delay);
-        stats.lock().unwrap().show();
+        stats.lock().unwrap().print_summary();
+
+        // And make sure the philosophers aren't close to running out of stack.
+        #[cfg(all(CONFIG_INIT_STACKS, CONFIG_THREAD_STACK_INFO))]
+        for thread in &threads {
+            zephyr::debug::check_stack_headroom(thread, PHIL_STACK_HEADROOM);
+        }
     }
 }
 
@@ -133,7 +168,7 @@ fn get_syncer() -> Vec<Arc<dyn ForkSync>> {
     get_channel_syncer()
 }
 
-fn phil_thread(n: usize, syncer: Arc<dyn ForkSync>, stats: Arc<Mutex<Stats>>) {
+fn phil_thread(n: usize, syncer: Arc<dyn ForkSync>, stats: Arc<Mutex<StatsCollector>>) {
     printkln!("Child {} started: {:?}", n, syncer);
 
     // Determine our two forks.
@@ -155,7 +190,7 @@ fn phil_thread(n: usize, syncer: Arc<dyn ForkSync>, stats: Arc<Mutex<Stats>>) {
             let delay = get_random_delay(n, 25);
             // printkln!("Child {} eating ({} ms)", n, delay);
             sleep(delay);
-            stats.lock().unwrap().record_eat(n, delay);
+            stats.lock().unwrap().record_eat(n, delay.to_millis() as u32);
 
             // Release the forks.
             // printkln!("Child {} giving up forks", n);
@@ -165,7 +200,7 @@ fn phil_thread(n: usize, syncer: Arc<dyn ForkSync>, stats: Arc<Mutex<Stats>>) {
             let delay = get_random_delay(n, 25);
             // printkln!("Child {} thinking ({} ms)", n, delay);
             sleep(delay);
-            stats.lock().unwrap().record_think(n, delay);
+            stats.lock().unwrap().record_think(n, delay.to_millis() as u32);
         }
     }
 }
@@ -179,41 +214,7 @@ fn get_random_delay(id: usize, period: usize) -> Duration {
     Duration::millis_at_least(((delay + 1) * period) as Tick)
 }
 
-/// Instead of just printint out so much information that the data just scolls by, gather
-/// statistics.
-#[derive(Default)]
-struct Stats {
-    /// How many times each philosopher has gone through the loop.
-    count: [u64; NUM_PHIL],
-    /// How much time each philosopher has spent eating.
-    eating: [u64; NUM_PHIL],
-    /// How much time each philosopher has spent thinking.
-    thinking: [u64; NUM_PHIL],
-}
-
-impl Stats {
-    fn record_eat(&mut self, index: usize, time: Duration) {
-        self.eating[index] += time.to_millis();
-    }
-
-    fn record_think(&mut self, index: usize, time: Duration) {
-        self.thinking[index] += time.to_millis();
-        self.count[index] += 1;
-    }
-
-    fn show(&self) {
-        printkln!(
-            "c:{:?}, e:{:?}, t:{:?}",
-            self.count,
-            self.eating,
-            self.thinking
-        );
-    }
-}
-
 kobj_define! {
     static PHIL_THREADS: [StaticThread; NUM_PHIL];
     static PHIL_STACKS: [ThreadStack<PHIL_STACK_SIZE>; NUM_PHIL];
-
-    static STAT_MUTEX: StaticMutex;
 }
@@ -0,0 +1,105 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! # Statistics for the philosophers
+//!
+//! Rather than printing so much that it all scrolls by, each philosopher records how long it
+//! spends eating and thinking, and the main thread periodically prints a summary.  The collector
+//! is shared between the threads in an `Arc`, protected by a `sync::Mutex` built on a static
+//! `k_mutex`.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use zephyr::{
+    kobj_define, printkln,
+    sync::{Arc, Mutex},
+};
+
+/// The statistics for one philosopher.
+#[derive(Clone, Copy, Default)]
+struct PhilStats {
+    /// How many times the philosopher has eaten.
+    eat_count: u32,
+    /// The total time spent eating, in ms.
+    eat_ms: u64,
+    /// How many times the philosopher has thought.
+    think_count: u32,
+    /// The total time spent thinking, in ms.
+    think_ms: u64,
+    /// The number of meals at the previous summary.
+    last_eat_count: u32,
+}
+
+/// Statistics gathered from all of the philosophers.
+pub struct StatsCollector {
+    phils: Vec<PhilStats>,
+    /// How many times a philosopher was found to have not eaten since the previous summary.
+    deadlocks: u32,
+}
+
+impl StatsCollector {
+    /// Create the collector for `num_phil` philosophers.  This uses a static mutex, so can only be
+    /// called once.
+    pub fn new(num_phil: usize) -> Arc<Mutex<StatsCollector>> {
+        let stats = StatsCollector {
+            phils: alloc::vec![PhilStats::default(); num_phil],
+            deadlocks: 0,
+        };
+        Arc::new(Mutex::new_from(stats, STAT_MUTEX.init_once(()).unwrap()))
+    }
+
+    /// Record that philosopher `n` ate for `ms` milliseconds.
+    pub fn record_eat(&mut self, n: usize, ms: u32) {
+        let phil = &mut self.phils[n];
+        phil.eat_count += 1;
+        phil.eat_ms += ms as u64;
+    }
+
+    /// Record that philosopher `n` thought for `ms` milliseconds.
+    pub fn record_think(&mut self, n: usize, ms: u32) {
+        let phil = &mut self.phils[n];
+        phil.think_count += 1;
+        phil.think_ms += ms as u64;
+    }
+
+    /// Print the average eating and thinking time of each philosopher.
+    ///
+    /// A philosopher that hasn't eaten since the previous summary is counted as deadlocked.  The
+    /// summaries are far enough apart that this shouldn't happen unless the philosophers are stuck.
+    pub fn print_summary(&mut self) {
+        for (n, phil) in self.phils.iter_mut().enumerate() {
+            if phil.eat_count == phil.last_eat_count {
+                self.deadlocks += 1;
+            }
+            phil.last_eat_count = phil.eat_count;
+            printkln!(
+                "phil {}: ate {} times, avg {} ms; thought {} times, avg {} ms",
+                n,
+                phil.eat_count,
+                average(phil.eat_ms, phil.eat_count),
+                phil.think_count,
+                average(phil.think_ms, phil.think_count),
+            );
+        }
+        printkln!("deadlocks: {}", self.total_deadlocks());
+    }
+
+    /// The number of times a philosopher has been found stuck.
+    pub fn total_deadlocks(&self) -> u32 {
+        self.deadlocks
+    }
+}
+
+fn average(total: u64, count: u32) -> u64 {
+    if count == 0 {
+        0
+    } else {
+        total / count as u64
+    }
+}
+
+kobj_define! {
+    static STAT_MUTEX: StaticMutex;
+}
@@ -8,7 +8,7 @@
 
 use crate::{ForkSync, NUM_PHIL};
 use zephyr::sys::sync::Mutex;
-use zephyr::time::Forever;
+use zephyr::time::{Forever, NoWait};
 
 type SysMutexes = [Mutex; NUM_PHIL];
 
@@ -33,6 +33,10 @@ impl ForkSync for SysMutexSync {
         self.locks[index].lock(Forever).unwrap();
     }
 
+    fn try_take(&self, index: usize) -> bool {
+        self.locks[index].lock(NoWait).is_ok()
+    }
+
     fn release(&self, index: usize) {
         self.locks[index].unlock().unwrap();
     }
@@ -0,0 +1,66 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_std]
+
+use zephyr::{
+    kobj_define, printkln,
+    sys::sync::{semaphore::K_SEM_MAX_LIMIT, Semaphore},
+    time::NoWait,
+};
+
+// Semaphores get their `Wrapped` implementation, and their `kobj_define!` rule, from
+// `#[derive(KernelObject)]`.  Make sure that they can still be declared, singly and in arrays, and
+// initialized.
+kobj_define! {
+    static SEM: StaticSemaphore;
+    static SEMS: [StaticSemaphore; 3];
+    static BAD_SEM: StaticSemaphore;
+}
+
+#[no_mangle]
+extern "C" fn rust_main() {
+    single_test();
+    array_test();
+    error_test();
+    printkln!("All tests passed");
+}
+
+fn single_test() {
+    let sem = SEM.init_once((1, 2)).unwrap().unwrap();
+    // A second init must fail.
+    assert!(SEM.init_once((0, 1)).is_none());
+
+    check(&sem, 1);
+    sem.give();
+    sem.give();
+    // The limit is 2, so the extra give is lost.
+    sem.give();
+    check(&sem, 2);
+}
+
+fn array_test() {
+    let sems: [Semaphore; 3] = core::array::from_fn(|i| {
+        SEMS[i]
+            .init_once((i as u32, K_SEM_MAX_LIMIT))
+            .unwrap()
+            .unwrap()
+    });
+    for (i, sem) in sems.iter().enumerate() {
+        check(sem, i);
+    }
+}
+
+fn error_test() {
+    // The error from `k_sem_init` is returned, rather than giving a broken semaphore.
+    assert!(BAD_SEM.init_once((2, 1)).unwrap().is_err());
+}
+
+/// Check that `sem` has a count of `count`, and leaves it empty.
+fn check(sem: &Semaphore, count: usize) {
+    assert_eq!(sem.count_get(), count);
+    for _ in 0..count {
+        sem.take(NoWait).unwrap();
+    }
+    assert!(sem.take(NoWait).is_err());
+}
@@ -41,6 +41,13 @@ pub trait Augment {
 
     /// A generator to be called when we are compatible.
     fn generate(&self, node: &Node, tree: &DeviceTree) -> TokenStream;
+
+    /// A fallback augment is only applied to nodes that no other (non-fallback) augment is
+    /// compatible with.  This allows a generic rule, such as one for any device on a bus, to give
+    /// way to the rules for specific devices.
+    fn is_fallback(&self) -> bool {
+        false
+    }
 }
 
 /// A top level augmentation.
@@ -56,6 +63,9 @@ pub struct Augmentation {
     rules: Vec<Rule>,
     /// What to do when a given node matches.
     actions: Vec<Action>,
+    /// If set, only apply this augmentation when no other augmentation matches the node.
+    #[serde(default)]
+    fallback: bool,
 }
 
 impl Augment for Augmentation {
@@ -63,6 +73,10 @@ impl Augment for Augmentation {
         self.rules.iter().all(|n| n.is_compatible(node))
     }
 
+    fn is_fallback(&self) -> bool {
+        self.fallback
+    }
+
     fn generate(&self, node: &Node, tree: &DeviceTree) -> TokenStream {
         let name = format_ident!("{}", dt_to_lower_id(&self.name));
         let actions = self.actions.iter().map(|a| a.generate(&name, node, tree));
@@ -229,7 +243,15 @@ impl RawInfo {
                 }
             }
             Self::Parent { level, args } => {
-                let get_args = args.iter().map(|arg| arg.args(node));
+                // A node without the properties for the arguments, such as a child of a bus that
+                // isn't a device, doesn't get an instance.
+                let Some(get_args) = args
+                    .iter()
+                    .map(|arg| arg.args(node))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return TokenStream::new();
+                };
 
                 assert!(*level > 0);
                 let mut path = quote! {super};
@@ -263,13 +285,14 @@ pub enum ArgInfo {
 
 impl ArgInfo {
     /// Extra properties for the argument, assembling the arguents that should be passed in.
-    fn args(&self, node: &Node) -> TokenStream {
+    /// Returns `None` if the node doesn't have the property.
+    fn args(&self, node: &Node) -> Option<TokenStream> {
         match self {
             ArgInfo::Reg => {
-                let reg = node.get_numbers("reg").unwrap();
-                quote! {
+                let reg = node.get_numbers("reg")?;
+                Some(quote! {
                     #(#reg),*
-                }
+                })
             }
         }
     }
@@ -52,6 +52,8 @@ impl DeviceTree {
         // property.
         let props = node.properties.iter().map(|prop| self.property_walk(prop));
         let ord = node.ord;
+        let path = &node.path;
+        let accessors = node.properties.iter().filter_map(|prop| prop.accessor());
 
         // Open the parent as a submodule.  This is the same as 'super', so not particularly useful.
         /*
@@ -68,13 +70,45 @@ impl DeviceTree {
         */
 
         // If this is compatible with an augment, use the augment to add any additional properties.
-        let augs = augments.iter().map(|aug| aug.augment(node, self));
+        // Fallback augments are skipped if a more specific one matches.
+        let specific = augments
+            .iter()
+            .any(|aug| !aug.is_fallback() && aug.is_compatible(node));
+        let augs = augments
+            .iter()
+            .filter(|aug| !(specific && aug.is_fallback()))
+            .map(|aug| aug.augment(node, self));
 
         if let Some(name) = name {
             let name_id = dt_to_lower_id(name);
             quote! {
                 pub mod #name_id {
                     pub const ORD: usize = #ord;
+                    pub const PATH: &str = #path;
+
+                    /// The type of this particular devicetree node.  Each node has its own type, so
+                    /// that a node can be passed around, with its properties available as `const`
+                    /// accessors.
+                    #[derive(Clone, Copy, Debug)]
+                    pub struct Node;
+
+                    /// This node.
+                    pub const NODE: Node = Node;
+
+                    impl Node {
+                        /// The ORD index of this node.
+                        pub const fn ord(&self) -> usize {
+                            ORD
+                        }
+
+                        /// The full path of this node in the devicetree.
+                        pub const fn path(&self) -> &'static str {
+                            PATH
+                        }
+
+                        #(#accessors)*
+                    }
+
                     #(#props)*
                     #(#children)*
                     // #parent
@@ -167,6 +201,43 @@ impl Property {
         }
     }
 
+    // Generate a typed `const fn` accessor for this property, for the simple property types that
+    // have an obvious Rust representation.
+    fn accessor(&self) -> Option<TokenStream> {
+        // Properties such as `#address-cells` describe the tree itself, and would not make
+        // snake_case names.
+        if self.name.starts_with('#') {
+            return None;
+        }
+        let name = fix_id(&self.name);
+        if is_keyword(&name) || name == "ord" || name == "path" {
+            return None;
+        }
+        let name = format_ident!("{}", name);
+        let doc = format!("The value of the `{}` property.", self.name);
+        match self.get_single_value()? {
+            Value::Words(ref words) if words.len() == 1 => {
+                if let Word::Number(n) = &words[0] {
+                    Some(quote! {
+                        #[doc = #doc]
+                        pub const fn #name(&self) -> u32 {
+                            #n
+                        }
+                    })
+                } else {
+                    None
+                }
+            }
+            Value::String(ref text) => Some(quote! {
+                #[doc = #doc]
+                pub const fn #name(&self) -> &'static str {
+                    #text
+                }
+            }),
+            _ => None,
+        }
+    }
+
     // If this property is a single top-level phandle, output that a that path is valid.  It isn't a
     // real node, but acts like one.
     fn output_path<W: Write>(&self, write: &mut W, name: &str) -> Result<()> {
@@ -191,6 +262,49 @@ fn general_property(prop: &Property) -> TokenStream {
     }
 }
 
+/// Is this name a Rust keyword, and therefore not usable as an identifier.
+fn is_keyword(text: &str) -> bool {
+    matches!(
+        text,
+        "as" | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "async"
+            | "await"
+            | "dyn"
+    )
+}
+
 /// Given a DT name, return an identifier for a lower-case version.
 pub fn dt_to_lower_id(text: &str) -> Ident {
     format_ident!("{}", fix_id(text))
@@ -12,10 +12,11 @@
 // output configuration settings that affect the compilation.
 
 use std::env;
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use proc_macro2::TokenStream;
 use regex::Regex;
@@ -24,8 +25,42 @@ use devicetree::{Augment, DeviceTree};
 
 mod devicetree;
 
+/// Kconfig options describing features that library crates commonly need to detect.
+///
+/// Each of these results in a `HAS_<name>` boolean constant in `zephyr::kconfig`, and, when
+/// enabled, a `zephyr_has_<name>` cfg (in lower case), emitted by [`export_bool_kconfig`].
+const FEATURES: &[(&str, &str)] = &[
+    ("ADC", "CONFIG_ADC"),
+    ("BT", "CONFIG_BT"),
+    ("CAN", "CONFIG_CAN"),
+    ("COUNTER", "CONFIG_COUNTER"),
+    ("DMA", "CONFIG_DMA"),
+    ("FILE_SYSTEM", "CONFIG_FILE_SYSTEM"),
+    ("FLASH", "CONFIG_FLASH"),
+    ("GPIO", "CONFIG_GPIO"),
+    ("I2C", "CONFIG_I2C"),
+    ("LOG", "CONFIG_LOG"),
+    ("MULTITHREADING", "CONFIG_MULTITHREADING"),
+    ("NETWORKING", "CONFIG_NETWORKING"),
+    ("PRINTK", "CONFIG_PRINTK"),
+    ("PWM", "CONFIG_PWM"),
+    ("RUST_ALLOC", "CONFIG_RUST_ALLOC"),
+    ("SENSOR", "CONFIG_SENSOR"),
+    ("SMP", "CONFIG_SMP"),
+    ("SPI", "CONFIG_SPI"),
+    ("UART", "CONFIG_SERIAL"),
+    ("USB", "CONFIG_USB_DEVICE_STACK"),
+    ("USERSPACE", "CONFIG_USERSPACE"),
+    ("WATCHDOG", "CONFIG_WATCHDOG"),
+];
+
 /// Export boolean Kconfig entries.  This must happen in any crate that wishes to access the
 /// configuration settings.
+///
+/// In addition to a `CONFIG_...` cfg for each enabled option, this emits a `zephyr_has_...` cfg for
+/// each of the common features that are enabled, for example, `zephyr_has_uart`.  These names
+/// allow crates that are built for targets other than Zephyr to use `#[cfg(zephyr_has_uart)]`
+/// without depending on the Kconfig names.
 pub fn export_bool_kconfig() {
     let dotconfig = env::var("DOTCONFIG").expect("DOTCONFIG must be set by wrapper");
 
@@ -40,14 +75,111 @@ pub fn export_bool_kconfig() {
         let line = line.expect("reading line from dotconfig");
         if let Some(caps) = config_y.captures(&line) {
             println!("cargo:rustc-cfg={}", &caps[1]);
+            for (feature, _) in FEATURES.iter().filter(|(_, config)| *config == &caps[1]) {
+                println!("cargo:rustc-cfg=zephyr_has_{}", feature.to_lowercase());
+            }
         }
     }
 }
 
+/// Determine if a boolean Kconfig option is enabled in this build.
+pub fn kconfig_enabled(name: &str) -> bool {
+    let dotconfig = env::var("DOTCONFIG").expect("DOTCONFIG must be set by wrapper");
+    let wanted = format!("{}=y", name);
+
+    let file = File::open(&dotconfig).expect("Unable to open dotconfig");
+    BufReader::new(file)
+        .lines()
+        .map(|line| line.expect("reading line from dotconfig"))
+        .any(|line| line == wanted)
+}
+
+/// The name of the file, in `OUT_DIR`, that [`kconfig_require`] writes its checks to.
+const KCONFIG_REQUIRE_FILE: &str = "kconfig_require.rs";
+
+/// Set once this build script has started a fresh [`KCONFIG_REQUIRE_FILE`].
+static KCONFIG_REQUIRE_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Require that a boolean Kconfig option be enabled, failing the build if it is not.
+///
+/// This is intended to be called from a crate's `build.rs`, to allow library crates to declare the
+/// Kconfig options they depend on.  Generally, this will be invoked through the
+/// [`kconfig_require!`] macro.
+///
+/// Each missing option results in a `compile_error!` in `kconfig_require.rs` in `OUT_DIR`, which
+/// the crate includes from its root:
+///
+/// ```ignore
+/// include!(concat!(env!("OUT_DIR"), "/kconfig_require.rs"));
+/// ```
+///
+/// The build script itself still succeeds, so the error is reported by the compiler, as an
+/// ordinary error, against the crate that needs the option.
+pub fn kconfig_require(name: &str, message: &str) {
+    let dotconfig = env::var("DOTCONFIG").expect("DOTCONFIG must be set by wrapper");
+
+    // Ensure the check is redone when the dotconfig changes.
+    println!("cargo:rerun-if-env-changed=DOTCONFIG");
+    println!("cargo:rerun-if-changed={}", dotconfig);
+
+    let outdir = env::var("OUT_DIR").expect("OUT_DIR must be set");
+    let gen_path = Path::new(&outdir).join(KCONFIG_REQUIRE_FILE);
+
+    // The first check from this build script replaces the file left by any previous build.
+    let fresh = !KCONFIG_REQUIRE_STARTED.swap(true, Ordering::Relaxed);
+    let mut f = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(fresh)
+        .append(!fresh)
+        .open(&gen_path)
+        .expect("Unable to create kconfig_require.rs");
+
+    if !kconfig_enabled(name) {
+        println!("cargo:warning={} is not enabled: {}", name, message);
+        let text = format!(
+            "Required Kconfig option {} is not enabled: {}",
+            name, message
+        );
+        writeln!(&mut f, "compile_error!({:?});", text).unwrap();
+    }
+}
+
+/// Require a Kconfig option to be enabled, or fail the build.
+///
+/// Used from a `build.rs`:
+///
+/// ```ignore
+/// zephyr_build::kconfig_require!(CONFIG_I2C, "I2C driver is required for this module");
+/// ```
+///
+/// If the option is `n` or unset, the crate fails to compile, through a `compile_error!` with the
+/// given message.  See [`kconfig_require`] for the `include!` this needs in the crate itself.
+#[macro_export]
+macro_rules! kconfig_require {
+    ($name:ident, $message:expr) => {
+        $crate::kconfig_require(stringify!($name), $message)
+    };
+    ($name:ident) => {
+        $crate::kconfig_require(stringify!($name), "required by this crate")
+    };
+}
+
+/// Another name for [`kconfig_require!`].
+#[macro_export]
+macro_rules! kconfig_check {
+    ($($args:tt)*) => {
+        $crate::kconfig_require!($($args)*)
+    };
+}
+
 /// Capture bool, numeric and string kconfig values in a 'kconfig' module.
 /// This is a little simplistic, and will make the entries numeric if they look like numbers.
 /// Ideally, this would be built on the types of the values, but that will require more
 /// introspection.
+///
+/// The module also gets a `HAS_...` bool for each of the common features, which is true if the
+/// feature is enabled.
 pub fn build_kconfig_mod() {
     let dotconfig = env::var("DOTCONFIG").expect("DOTCONFIG must be set by wrapper");
     let outdir = env::var("OUT_DIR").expect("OUT_DIR must be set");
@@ -60,10 +192,14 @@ pub fn build_kconfig_mod() {
     let gen_path = Path::new(&outdir).join("kconfig.rs");
 
     let mut f = File::create(&gen_path).unwrap();
+    let mut enabled = Vec::new();
 
     let file = File::open(&dotconfig).expect("Unable to open dotconfig");
     for line in BufReader::new(file).lines() {
         let line = line.expect("reading line from dotconfig");
+        if let Some(config) = line.strip_suffix("=y") {
+            enabled.push(config.to_string());
+        }
         if let Some(caps) = config_hex.captures(&line) {
             writeln!(&mut f, "#[allow(dead_code)]").unwrap();
             writeln!(&mut f, "pub const {}: usize = {};", &caps[1], &caps[2]).unwrap();
@@ -75,6 +211,12 @@ pub fn build_kconfig_mod() {
             writeln!(&mut f, "pub const {}: &str = {};", &caps[1], &caps[2]).unwrap();
         }
     }
+
+    for (feature, config) in FEATURES {
+        let present = enabled.iter().any(|name| name == config);
+        writeln!(&mut f, "/// True if `{}` is enabled.", config).unwrap();
+        writeln!(&mut f, "pub const HAS_{}: bool = {};", feature, present).unwrap();
+    }
 }
 
 /// Parse the finalized DTS file, generating the Rust devicetree file.
@@ -0,0 +1,417 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Procedural macros for Zephyr applications.
+//!
+//! These are re-exported by the `zephyr` crate, and should be used from there, for example, as
+//! `#[zephyr::main]`.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, Fields, GenericArgument, ItemFn, LitStr,
+    PathArguments, ReturnType, Type,
+};
+
+/// Declare the application's entry point.
+///
+/// Zephyr's `main` (provided by the Rust support module) calls `rust_main`, which must be an
+/// unmangled `extern "C"` function.  This attribute allows the entry point to be written as a
+/// regular function:
+///
+/// ```ignore
+/// #[zephyr::main]
+/// fn main() {
+///     zephyr::printkln!("Hello world");
+/// }
+/// ```
+///
+/// The function must take no arguments, and return nothing.  There is no heap initialization to
+/// perform, as the Rust allocator uses the Zephyr heap directly.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+
+    if !args.is_empty() {
+        let args = proc_macro2::TokenStream::from(args);
+        return syn::Error::new_spanned(args, "#[zephyr::main] does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+    if let Err(err) = check_entry(&item, "#[zephyr::main]") {
+        return err.to_compile_error().into();
+    }
+
+    let name = &item.sig.ident;
+    quote! {
+        #item
+
+        #[no_mangle]
+        extern "C" fn rust_main() {
+            #name()
+        }
+    }
+    .into()
+}
+
+/// Declare a function as the body of a statically allocated thread.
+///
+/// ```ignore
+/// #[zephyr::thread(stack_size = 4096, priority = 5)]
+/// fn worker() {
+///     loop {
+///         // ...
+///     }
+/// }
+///
+/// // Later, perhaps from main.
+/// let mut thread = worker_spawn();
+/// thread.set_priority(3);
+/// thread.join(Forever).unwrap();
+/// ```
+///
+/// This keeps the function as written, and generates a `worker_spawn()` function alongside it,
+/// with the same visibility.  The spawn function declares the `StaticThread` and `ThreadStack`
+/// (with [`kobj_define!`]), initializes them, starts the thread running the function, and returns
+/// the running `Thread`.  As the thread and stack are static, the spawn function will panic if
+/// called more than once.  The thread is started with `Thread::spawn`, so this requires
+/// `CONFIG_RUST_ALLOC`.
+///
+/// Both arguments are optional.  `stack_size` defaults to 2048, and `priority` to 0.  The thread is
+/// given the name of the function.
+///
+/// [`kobj_define!`]: ../zephyr/macro.kobj_define.html
+#[proc_macro_attribute]
+pub fn thread(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+
+    let mut stack_size: Option<Expr> = None;
+    let mut priority: Option<Expr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("stack_size") {
+            stack_size = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("priority") {
+            priority = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported #[zephyr::thread] argument"))
+        }
+    });
+    parse_macro_input!(args with parser);
+
+    if let Err(err) = check_entry(&item, "#[zephyr::thread]") {
+        return err.to_compile_error().into();
+    }
+
+    let stack_size = stack_size.unwrap_or_else(|| syn::parse_quote!(2048));
+    let priority = priority.unwrap_or_else(|| syn::parse_quote!(0));
+
+    let vis = &item.vis;
+    let name = &item.sig.ident;
+    let name_text = name.to_string();
+    let spawn = format_ident!("{}_spawn", name);
+    let spawn_doc = format!(
+        "Start the `{}` thread, returning it.  Panics if called more than once.",
+        name
+    );
+
+    quote! {
+        #item
+
+        #[doc = #spawn_doc]
+        #vis fn #spawn() -> ::zephyr::sys::thread::Thread {
+            ::zephyr::kobj_define! {
+                static THREAD: StaticThread;
+                static STACK: ThreadStack<{ #stack_size }>;
+            }
+
+            let stack = STACK
+                .init_once(())
+                .expect(concat!(#name_text, " thread already spawned"));
+            let mut thread = THREAD
+                .init_once(stack)
+                .expect(concat!(#name_text, " thread already spawned"));
+            thread.set_priority(#priority);
+            thread.set_name(::zephyr::cstr!(#name_text));
+            thread.spawn(#name)
+        }
+    }
+    .into()
+}
+
+/// Declare a test, to be run by `zephyr_test_runner!()`.
+///
+/// ```ignore
+/// #[zephyr::zephyr_test]
+/// fn parses_empty_input() {
+///     assert!(parse(b"").is_none());
+/// }
+/// ```
+///
+/// This keeps the function as written, and adds an entry for it to the table of tests that the
+/// linker collects, which requires `CONFIG_RUST_TEST_HARNESS`.  The test passes if it returns,
+/// and fails if it panics.  As with `#[zephyr::main]`, the function must take no arguments and
+/// return nothing.
+#[proc_macro_attribute]
+pub fn zephyr_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+
+    if !args.is_empty() {
+        let args = proc_macro2::TokenStream::from(args);
+        return syn::Error::new_spanned(args, "#[zephyr_test] does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+    if let Err(err) = check_entry(&item, "#[zephyr_test]") {
+        return err.to_compile_error().into();
+    }
+
+    let name = &item.sig.ident;
+    let name_text = name.to_string();
+    let entry = format_ident!("__ZEPHYR_TEST_{}", name_text.to_uppercase());
+
+    quote! {
+        #item
+
+        #[used]
+        #[link_section = concat!("._rust_test.static.", #name_text)]
+        static #entry: ::zephyr::testing::TestCase = ::zephyr::testing::TestCase {
+            name: #name_text,
+            func: #name,
+        };
+    }
+    .into()
+}
+
+/// Generate the static declaration support for a kernel object wrapper.
+///
+/// ```ignore
+/// /// A zephyr `k_sem`.
+/// #[derive(KernelObject)]
+/// #[kobj(init_fn = "k_sem_init", linker_section = "._k_sem.static")]
+/// #[kobj(args = "(c_uint, c_uint)", errno)]
+/// pub struct Semaphore {
+///     pub(crate) item: Fixed<k_sem>,
+/// }
+/// ```
+///
+/// The struct must have a single field, of type `Fixed<kobj>`, where `kobj` is the Zephyr type,
+/// found in `crate::raw`.  From this, the derive generates:
+///
+/// - A `StaticSemaphore` alias, for `StaticKernelObject<k_sem>`, which is `Sync`.
+/// - The `Wrapped` implementation, whose `get_wrapped` calls the `init_fn` on the object, and
+///   returns the wrapper.
+/// - The rule that lets `kobj_define!` declare `StaticSemaphore`s, and arrays of them, in the given
+///   linker section.
+///
+/// If the init function takes arguments after the object, their types are given as a tuple with
+/// `args`, which becomes the initializer type of `init_once`.  If the init function returns a
+/// negative errno on failure, `errno` makes `init_once` return a `ZResult` of the wrapper, rather
+/// than the wrapper itself.
+///
+/// As `Wrapped` and the Zephyr types both belong to the `zephyr` crate, this is only useful for
+/// kernel objects defined there.  The generated code refers to them through `crate`.  The
+/// `kobj_define!` rule is a macro generated by this derive, and Rust doesn't allow those to be
+/// named by path from within the crate that defines them, so the objects can only be declared with
+/// `kobj_define!` by applications.
+#[proc_macro_derive(KernelObject, attributes(kobj))]
+pub fn kernel_object(item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+    match kernel_object_impl(&item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn kernel_object_impl(item: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut init_fn: Option<syn::Path> = None;
+    let mut section: Option<LitStr> = None;
+    let mut args: Option<Type> = None;
+    let mut errno = false;
+    for attr in item.attrs.iter().filter(|a| a.path().is_ident("kobj")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("init_fn") {
+                init_fn = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("linker_section") {
+                section = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("args") {
+                args = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("errno") {
+                errno = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported #[kobj] argument"))
+            }
+        })?;
+    }
+    let init_fn = init_fn.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &item.ident,
+            "#[derive(KernelObject)] needs #[kobj(init_fn = ..)]",
+        )
+    })?;
+    let section = section.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &item.ident,
+            "#[derive(KernelObject)] needs #[kobj(linker_section = ..)]",
+        )
+    })?;
+    let args = args.unwrap_or_else(|| syn::parse_quote!(()));
+
+    // The wrapper has one field, `Fixed<kobj>`.
+    let field = match &item.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) if fields.named.len() == 1 => &fields.named[0],
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &item.ident,
+                    "kernel object must have a single named field",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item.ident,
+                "#[derive(KernelObject)] only supports structs",
+            ))
+        }
+    };
+    let field_name = field.ident.as_ref().unwrap();
+    let kobj = fixed_inner(&field.ty).ok_or_else(|| {
+        syn::Error::new_spanned(&field.ty, "kernel object field must be a `Fixed<kobj>`")
+    })?;
+
+    // The arguments after the object, taken from the initializer tuple.
+    let arg_count = match &args {
+        Type::Tuple(tuple) => tuple.elems.len(),
+        _ => return Err(syn::Error::new_spanned(&args, "kobj args must be a tuple")),
+    };
+    let arg_index = (0..arg_count).map(syn::Index::from);
+    let arg_name = if arg_count == 0 {
+        format_ident!("_args")
+    } else {
+        format_ident!("args")
+    };
+
+    let name = &item.ident;
+    let vis = &item.vis;
+    let static_name = format_ident!("Static{}", name);
+    let rule_name = format_ident!("_kobj_rule_{}", static_name);
+    let static_doc = format!(
+        "A statically defined [`{}`], declared with `kobj_define!`, and initialized with \
+         `init_once`.",
+        name
+    );
+
+    let init = quote! {
+        #init_fn(ptr #(, #arg_name.#arg_index)*)
+    };
+    let wrapper = quote! {
+        #name {
+            #field_name: crate::object::Fixed::Static(ptr),
+        }
+    };
+    let (wrapped_type, body) = if errno {
+        (
+            quote! { crate::error::ZResult<#name> },
+            quote! {
+                crate::error::ok_or_errno(unsafe { #init })?;
+                Ok(#wrapper)
+            },
+        )
+    } else {
+        (
+            quote! { #name },
+            quote! {
+                unsafe {
+                    #init;
+                }
+                #wrapper
+            },
+        )
+    };
+
+    Ok(quote! {
+        #[doc = #static_doc]
+        #vis type #static_name = crate::object::StaticKernelObject<crate::raw::#kobj>;
+
+        unsafe impl ::core::marker::Sync for #static_name {}
+
+        impl crate::object::Wrapped for #static_name {
+            type T = #wrapped_type;
+            type I = #args;
+
+            fn get_wrapped(&self, #arg_name: Self::I) -> Self::T {
+                let ptr = self.value.get();
+                #body
+            }
+        }
+
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #rule_name {
+            ($v:vis, $name:ident) => {
+                #[link_section = concat!(#section, ".", stringify!($name), ".", file!(), line!())]
+                $v static $name: $crate::object::StaticKernelObject<$crate::raw::#kobj> =
+                    unsafe { ::core::mem::zeroed() };
+            };
+            ($v:vis, $name:ident, $size:expr) => {
+                #[link_section = concat!(#section, ".", stringify!($name), ".", file!(), line!())]
+                $v static $name: [$crate::object::StaticKernelObject<$crate::raw::#kobj>; $size] =
+                    unsafe { ::core::mem::zeroed() };
+            };
+        }
+    })
+}
+
+/// Find `kobj` in a type of the form `Fixed<kobj>`.
+fn fixed_inner(ty: &Type) -> Option<&syn::Ident> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    if last.ident != "Fixed" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(generics) = &last.arguments else {
+        return None;
+    };
+    match generics.args.first()? {
+        GenericArgument::Type(Type::Path(inner)) => inner.path.get_ident(),
+        _ => None,
+    }
+}
+
+/// Ensure that an entry point function has the signature `fn name()`.
+fn check_entry(item: &ItemFn, what: &str) -> syn::Result<()> {
+    let sig = &item.sig;
+    if !sig.inputs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            format!("{} function must not take arguments", what),
+        ));
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        return Err(syn::Error::new_spanned(
+            ty,
+            format!("{} function must not return a value", what),
+        ));
+    }
+    if sig.asyncness.is_some() {
+        return Err(syn::Error::new_spanned(
+            sig.asyncness,
+            format!("{} function can not be async", what),
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            format!("{} function can not be generic", what),
+        ));
+    }
+    Ok(())
+}
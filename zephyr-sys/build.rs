@@ -75,8 +75,96 @@ fn main() -> Result<()> {
         .clang_arg(format!("-I{}/lib/libc/minimal/include", zephyr_base))
         .derive_copy(false)
         .allowlist_function("k_.*")
+        .allowlist_function("arch_irq_.*")
+        .allowlist_function("z_arm_irq_priority_set")
         .allowlist_function("gpio_.*")
         .allowlist_function("flash_.*")
+        .allowlist_function("uart_.*")
+        .allowlist_function("i2c_.*")
+        .allowlist_function("i3c_.*")
+        .allowlist_function("spi_.*")
+        .allowlist_function("lora_.*")
+        .allowlist_function("lorawan_.*")
+        .allowlist_function("gnss_.*")
+        .allowlist_item("gnss_data_callback")
+        .allowlist_function("ipc_service_.*")
+        .allowlist_function("led_strip_.*")
+        .allowlist_function("modem_.*")
+        .allowlist_function("cbor_.*")
+        .allowlist_item("CborError")
+        .allowlist_item("CborType")
+        .allowlist_function("json_.*")
+        .allowlist_item("json_tokens")
+        .allowlist_function("base64_.*")
+        .allowlist_function("crc.*")
+        .allowlist_function("psa_.*")
+        .allowlist_function("mbedtls_pkcs5_.*")
+        .allowlist_item("mbedtls_md_type_t")
+        .allowlist_function("charger_.*")
+        .allowlist_item("charger_.*")
+        .allowlist_function("comparator_.*")
+        .allowlist_item("comparator_trigger")
+        .allowlist_function("fuel_gauge_.*")
+        .allowlist_item("fuel_gauge_.*")
+        .allowlist_function("haptics_.*")
+        .allowlist_function("drv2605_.*")
+        .allowlist_item("drv2605_.*")
+        .allowlist_function("mdio_.*")
+        .allowlist_function("video_.*")
+        .allowlist_item("video_endpoint_id")
+        .allowlist_function("w1_.*")
+        .allowlist_function("disk_access_.*")
+        .allowlist_item("DISK_IOCTL_.*")
+        .allowlist_item("DISK_STATUS_.*")
+        .allowlist_function("sd_init")
+        .allowlist_function("sd_is_card_present")
+        .allowlist_function("sdmmc_.*")
+        .allowlist_item("card_type")
+        .allowlist_function("tcpc_.*")
+        .allowlist_item("tc_cc_voltage_state")
+        .allowlist_item("tc_power_role")
+        .allowlist_item("tc_data_role")
+        .allowlist_function("usb_.*")
+        .allowlist_function("hid_.*")
+        .allowlist_function("zbus_.*")
+        .allowlist_item("zbus_observer_type")
+        .allowlist_function("deflate.*")
+        .allowlist_function("inflate.*")
+        .allowlist_item("ZLIB_VERSION")
+        .allowlist_function("boot_.*")
+        .allowlist_function("mcuboot_.*")
+        .allowlist_item("BOOT_.*")
+        .allowlist_function("fs_.*")
+        .allowlist_item("FS_.*")
+        .allowlist_function("smp_.*")
+        .allowlist_function("net_buf_.*")
+        .allowlist_function("zsock_.*")
+        .allowlist_item("ZSOCK_.*")
+        .allowlist_function("zr_.*")
+        .allowlist_function("net_if_.*")
+        .allowlist_function("net_dhcpv4_.*")
+        .allowlist_item("net_if_flag")
+        .allowlist_item("net_addr_type")
+        .allowlist_item("NET_IPV6_ND_INFINITE_LIFETIME")
+        .allowlist_function("http_client_.*")
+        .allowlist_item("http_method")
+        .allowlist_function("websocket_.*")
+        .allowlist_item("WEBSOCKET_.*")
+        .allowlist_function("mdns_responder_.*")
+        .allowlist_item("dns_sd_rec")
+        .allowlist_function("net_mgmt_.*")
+        .allowlist_function("openthread_.*")
+        .allowlist_function("ot.*")
+        .allowlist_item("otError")
+        .allowlist_function("tls_credential_.*")
+        .allowlist_item("tls_credential_type")
+        .allowlist_item("net_sock_type")
+        .allowlist_item("net_ip_protocol")
+        .allowlist_item("sockaddr.*")
+        .allowlist_item("AF_.*")
+        .allowlist_item("IPPROTO_.*")
+        .allowlist_item("SOL_TLS")
+        .allowlist_item("TLS_.*")
         .allowlist_item("GPIO_.*")
         .allowlist_item("FLASH_.*")
         .allowlist_item("Z_.*")
@@ -88,7 +176,9 @@ fn main() -> Result<()> {
         .allowlist_function("sys_.*")
         .allowlist_function("z_log.*")
         .allowlist_function("bt_.*")
+        .allowlist_item("BT_.*")
         .allowlist_function("SEGGER.*")
+        .allowlist_item("SEGGER_RTT_MODE_.*")
         .allowlist_item("E.*")
         .allowlist_item("K_.*")
         .allowlist_item("ZR_.*")
@@ -41,7 +41,176 @@ extern int errno;
 #include <zephyr/drivers/gpio.h>
 #include <zephyr/logging/log.h>
 #include <zephyr/bluetooth/bluetooth.h>
+#include <zephyr/bluetooth/conn.h>
+#include <zephyr/bluetooth/gatt.h>
+#ifdef CONFIG_BT_MESH
+#include <zephyr/bluetooth/mesh.h>
+#endif
 #include <zephyr/drivers/flash.h>
+#include <zephyr/drivers/uart.h>
+#include <zephyr/drivers/i2c.h>
+#include <zephyr/drivers/spi.h>
+#include <zephyr/drivers/lora.h>
+#include <zephyr/drivers/gnss.h>
+#include <zephyr/drivers/led_strip.h>
+#include <zephyr/ipc/ipc_service.h>
+
+#if defined(CONFIG_MODEM) && defined(CONFIG_MODEM_BACKEND_UART)
+#include <zephyr/modem/pipe.h>
+#include <zephyr/modem/backend/uart.h>
+#endif
+
+#ifdef CONFIG_FILE_SYSTEM
+#include <zephyr/fs/fs.h>
+#endif
+
+#ifdef CONFIG_MCUBOOT_IMG_MANAGER
+#include <zephyr/dfu/mcuboot.h>
+#endif
+
+#ifdef CONFIG_MCUMGR
+#include <zephyr/mgmt/mcumgr/smp/smp.h>
+#include <zephyr/mgmt/mcumgr/transport/smp.h>
+#endif
+
+#ifdef CONFIG_NETWORKING
+#include <zephyr/net/net_if.h>
+#include <zephyr/net/dhcpv4.h>
+#include <zephyr/net/net_mgmt.h>
+#include <zephyr/net/net_event.h>
+#endif
+
+#ifdef CONFIG_NET_SOCKETS
+#include <errno.h>
+#include <zephyr/net/socket.h>
+#endif
+
+#ifdef CONFIG_HTTP_CLIENT
+#include <zephyr/net/http/client.h>
+#endif
+
+#ifdef CONFIG_WEBSOCKET_CLIENT
+#include <zephyr/net/websocket.h>
+#endif
+
+#ifdef CONFIG_MDNS_RESPONDER
+#include <zephyr/net/mdns_responder.h>
+#include <zephyr/net/dns_sd.h>
+#endif
+
+#ifdef CONFIG_NET_SOCKETS_SOCKOPT_TLS
+#include <zephyr/net/tls_credentials.h>
+#endif
+
+#ifdef CONFIG_NET_L2_OPENTHREAD
+#include <zephyr/net/openthread.h>
+#include <openthread/link.h>
+#include <openthread/thread.h>
+#include <openthread/ip6.h>
+#endif
+
+#ifdef CONFIG_LORAWAN
+#include <zephyr/lorawan/lorawan.h>
+#endif
+
+#ifdef CONFIG_TINYCBOR
+#include <tinycbor/cbor.h>
+#include <tinycbor/cbor_buf_writer.h>
+#include <tinycbor/cbor_buf_reader.h>
+#endif
+
+#ifdef CONFIG_JSON_LIBRARY
+#include <zephyr/data/json.h>
+#endif
+
+#ifdef CONFIG_CACHE_MANAGEMENT
+#include <zephyr/cache.h>
+#endif
+
+#ifdef CONFIG_USE_SEGGER_RTT
+#include <SEGGER_RTT.h>
+#endif
+
+#ifdef CONFIG_BASE64
+#include <zephyr/sys/base64.h>
+#endif
+
+#ifdef CONFIG_CRC
+#include <zephyr/sys/crc.h>
+#endif
+
+#ifdef CONFIG_MBEDTLS_PSA_CRYPTO_C
+#include <psa/crypto.h>
+#endif
+
+#ifdef CONFIG_MBEDTLS_PKCS5_C
+#include <mbedtls/pkcs5.h>
+#endif
+
+#ifdef CONFIG_CHARGER
+#include <zephyr/drivers/charger.h>
+#endif
+
+#ifdef CONFIG_COMPARATOR
+#include <zephyr/drivers/comparator.h>
+#endif
+
+#ifdef CONFIG_FUEL_GAUGE
+#include <zephyr/drivers/fuel_gauge.h>
+#endif
+
+#ifdef CONFIG_HAPTICS
+#include <zephyr/drivers/haptics.h>
+#endif
+
+#ifdef CONFIG_HAPTICS_DRV2605
+#include <zephyr/drivers/haptics/drv2605.h>
+#endif
+
+#ifdef CONFIG_I3C
+#include <zephyr/drivers/i3c.h>
+#endif
+
+#ifdef CONFIG_MDIO
+#include <zephyr/drivers/mdio.h>
+#endif
+
+#ifdef CONFIG_VIDEO
+#include <zephyr/drivers/video.h>
+#endif
+
+#ifdef CONFIG_W1
+#include <zephyr/drivers/w1.h>
+#endif
+
+#ifdef CONFIG_DISK_ACCESS
+#include <zephyr/storage/disk_access.h>
+#endif
+
+#ifdef CONFIG_SDMMC_STACK
+#include <zephyr/sd/sd.h>
+#include <zephyr/sd/sdmmc.h>
+#endif
+
+#ifdef CONFIG_USBC_TCPC_DRIVER
+#include <zephyr/drivers/usb_c/usbc_tcpc.h>
+#endif
+
+#ifdef CONFIG_USB_DEVICE_STACK
+#include <zephyr/usb/usb_device.h>
+#endif
+
+#ifdef CONFIG_USB_DEVICE_HID
+#include <zephyr/usb/class/usb_hid.h>
+#endif
+
+#ifdef CONFIG_ZBUS
+#include <zephyr/zbus/zbus.h>
+#endif
+
+#ifdef CONFIG_ZLIB
+#include <zlib.h>
+#endif
 
 /*
  * bindgen will only output #defined constants that resolve to simple numbers.  These are some
@@ -56,3 +225,34 @@ const uintptr_t ZR_STACK_RESERVED = K_KERNEL_STACK_RESERVED;
 const uint32_t ZR_POLL_TYPE_SEM_AVAILABLE = K_POLL_TYPE_SEM_AVAILABLE;
 const uint32_t ZR_POLL_TYPE_SIGNAL = K_POLL_TYPE_SIGNAL;
 const uint32_t ZR_POLL_TYPE_DATA_AVAILABLE = K_POLL_TYPE_DATA_AVAILABLE;
+
+const uint32_t ZR_GPIO_INT_DISABLE = GPIO_INT_DISABLE;
+const uint32_t ZR_GPIO_INT_EDGE_RISING = GPIO_INT_EDGE_RISING;
+const uint32_t ZR_GPIO_INT_EDGE_FALLING = GPIO_INT_EDGE_FALLING;
+const uint32_t ZR_GPIO_INT_EDGE_BOTH = GPIO_INT_EDGE_BOTH;
+const uint32_t ZR_GPIO_INT_EDGE_TO_ACTIVE = GPIO_INT_EDGE_TO_ACTIVE;
+const uint32_t ZR_GPIO_INT_EDGE_TO_INACTIVE = GPIO_INT_EDGE_TO_INACTIVE;
+
+#ifdef CONFIG_NET_SOCKETS
+/* errno may be thread local, which bindgen can't reach, so read it with a function. */
+static inline int zr_errno(void)
+{
+	return errno;
+}
+#endif
+
+#ifdef CONFIG_MBEDTLS_PSA_CRYPTO_C
+const uint32_t ZR_PSA_ALG_SHA_256 = PSA_ALG_SHA_256;
+#endif
+
+#ifdef CONFIG_NET_MGMT_EVENT
+const uint32_t ZR_NET_EVENT_IF_UP = NET_EVENT_IF_UP;
+const uint32_t ZR_NET_EVENT_IF_DOWN = NET_EVENT_IF_DOWN;
+const uint32_t ZR_NET_EVENT_IPV4_ADDR_ADD = NET_EVENT_IPV4_ADDR_ADD;
+const uint32_t ZR_NET_EVENT_IPV4_ADDR_DEL = NET_EVENT_IPV4_ADDR_DEL;
+const uint32_t ZR_NET_EVENT_IPV4_DHCP_BOUND = NET_EVENT_IPV4_DHCP_BOUND;
+const uint32_t ZR_NET_EVENT_IPV6_ADDR_ADD = NET_EVENT_IPV6_ADDR_ADD;
+const uint32_t ZR_NET_EVENT_IPV6_ADDR_DEL = NET_EVENT_IPV6_ADDR_DEL;
+const uint32_t ZR_NET_EVENT_L4_CONNECTED = NET_EVENT_L4_CONNECTED;
+const uint32_t ZR_NET_EVENT_L4_DISCONNECTED = NET_EVENT_L4_DISCONNECTED;
+#endif
@@ -0,0 +1,161 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Rust global allocator that uses the kernel heap in Zephyr
+//!
+//! The zephyr runtime is divided into three crates:
+//! - [core](https://doc.rust-lang.org/stable/core/) is the "dependency-free" foundation of the
+//!   standard library.  It is all of the parts of the standard library that have no dependencies
+//!   outside of the language and the architecture itself.
+//! - [alloc](https://doc.rust-lang.org/stable/alloc/) provides the parts of the standard library
+//!   that depend on memory allocation.  This includes various types of smart pointers, atomically
+//!   referenced counted pointers, and various collections.  This depends on the platform providing
+//!   an allocator.
+//! - [std](https://doc.rust-lang.org/stable/std/) is the rest of the standard library. It include
+//!   both core and alloc, and then everthing else, including filesystem access, networking, etc.  It
+//!   is notable, however, that the Rust standard library is fairly minimal.  A lot of functionality
+//!   that other languages might include will be relegated to other crates, and the ecosystem and
+//!   tooling around cargo make it as easy to use these as the standard library.
+//!
+//! For running application code on Zephyr, the core library (mostly) just works (the a caveat of a
+//! little work needed to use atomics on platforms Zephyr supports but don't have atomic
+//! instructions).  The std library is somewhat explicitly _not_ supported.  Although the intent is
+//! to provide much of the functionality from std, Zephyr is different enough from the conventional
+//! operating system std was built around that just porting it doesn't really give practical
+//! results.  The result is either to complicated to make work, or too different from what is
+//! typically done on Zephyr.  Supporting std could be a future project.
+//!
+//! This leaves alloc, which is mostly independent but is required to know about an allocator to
+//! use.  This module provides an allocator for Rust, [`ZephyrAllocator`], that allocates from the
+//! kernel heap with `k_malloc` and `k_free`.  The size of this heap is set with
+//! `CONFIG_HEAP_MEM_POOL_SIZE`, which must be large enough for the application's allocations, as
+//! well as those of any kernel features that use the same heap.
+//!
+//! Because a given embedded application may or may not want memory allocation, this is controlled
+//! by the `CONFIG_RUST_ALLOC` Kconfig.  When this config is enabled, the alloc crate becomes
+//! available to applications.
+//!
+//! The allocator is installed as the global allocator by the `global-allocator` Cargo feature of
+//! this crate, which is enabled by default.  An application that wants to provide its own global
+//! allocator can disable the default features.
+//!
+//! Since alloc is typically used on Rust as a part of the std library, building in a no-std
+//! environment requires that it be access explicitly.  Generally, alloc must be explicitly added
+//! to every module that needs it.
+//!
+//! ```
+//! extern crate alloc;
+//!
+//! use alloc::boxed::Box;
+//!
+//! let item = Box::new(5);
+//! printkln!("box value {}", item);
+//! ```
+//!
+//! The box holding the value 5 will be allocated by `Box::new`, and freed when the `item` goes out
+//! of scope.
+
+// This entire module is only used if CONFIG_RUST_ALLOC is enabled.
+extern crate alloc;
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::ffi::c_void;
+use core::mem;
+
+use crate::raw::{k_aligned_alloc, k_free, k_malloc};
+
+/// An allocator that uses Zephyr's kernel heap.
+///
+/// This is installed as the global allocator when the `global-allocator` feature is enabled, and
+/// is exported so applications that disable the feature can still use it, for example, to wrap
+/// it with their own accounting.
+pub struct ZephyrAllocator {
+    _private: (),
+}
+
+impl ZephyrAllocator {
+    /// The allocator.  All instances use the same heap.
+    pub const fn new() -> ZephyrAllocator {
+        ZephyrAllocator { _private: () }
+    }
+}
+
+impl Default for ZephyrAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl GlobalAlloc for ZephyrAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // `k_malloc` only aligns to the size of a pointer, so larger alignments need to be asked
+        // for.
+        if layout.align() <= mem::size_of::<*const c_void>() {
+            k_malloc(layout.size()) as *mut u8
+        } else {
+            k_aligned_alloc(layout.align(), layout.size()) as *mut u8
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+        k_free(ptr as *mut c_void)
+    }
+}
+
+/// The global allocator built around the Zephyr kernel heap.
+#[cfg(feature = "global-allocator")]
+#[global_allocator]
+static ALLOCATOR: ZephyrAllocator = ZephyrAllocator::new();
+
+/// The usage of the kernel heap, from [`heap_stats`].
+#[cfg(CONFIG_SYS_HEAP_RUNTIME_STATS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Bytes currently allocated.
+    pub used: usize,
+    /// Bytes still free.
+    pub free: usize,
+    /// The most bytes that have been allocated at once.
+    pub max: usize,
+}
+
+#[cfg(CONFIG_SYS_HEAP_RUNTIME_STATS)]
+extern "C" {
+    /// The heap used by `k_malloc`, defined by the kernel.
+    static mut _system_heap: crate::raw::k_heap;
+}
+
+/// Get the current usage of the kernel heap that the allocator uses.
+///
+/// Zephyr doesn't have a call for the stats of the `k_malloc` heap itself, so this reads the stats
+/// of the kernel's system heap directly.  Requires `CONFIG_SYS_HEAP_RUNTIME_STATS`.
+#[cfg(CONFIG_SYS_HEAP_RUNTIME_STATS)]
+pub fn heap_stats() -> HeapStats {
+    // SAFETY: The stats are plain data, filled in by the call, and the heap functions do their own
+    // locking.
+    let stats = unsafe {
+        let mut stats: crate::raw::sys_memory_stats = mem::zeroed();
+        crate::raw::sys_heap_runtime_stats_get(
+            core::ptr::addr_of_mut!(_system_heap.heap),
+            &mut stats,
+        );
+        stats
+    };
+    HeapStats {
+        used: stats.allocated_bytes,
+        free: stats.free_bytes,
+        max: stats.max_allocated_bytes,
+    }
+}
+
+/// Print the current usage of the kernel heap to the console.
+#[cfg(CONFIG_SYS_HEAP_RUNTIME_STATS)]
+pub fn print_heap_stats() {
+    let stats = heap_stats();
+    crate::printkln!(
+        "heap: {} used, {} free, {} max used",
+        stats.used,
+        stats.free,
+        stats.max
+    );
+}
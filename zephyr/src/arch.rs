@@ -0,0 +1,43 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Architecture specific support.
+//!
+//! Most code should not need these, they are for drivers and board support code that works with
+//! the hardware directly.
+
+#[cfg(target_arch = "arm")]
+pub mod arm;
+
+/// The smallest stack that should be given to a thread that formats messages, such as with
+/// [`printkln!`](crate::printkln), on this architecture.
+///
+/// These are the worst cases seen running the samples, with some room added, and are only a
+/// guide.  Threads with deeper call chains, or large locals, need more, which
+/// [`debug::thread_stack_usage`](crate::debug::thread_stack_usage) can measure.  Check a stack
+/// size against this with [`static_assert!`](crate::static_assert).
+pub const MIN_STACK: usize = if cfg!(target_arch = "riscv64") {
+    // The philosophers sample used 3336 bytes.
+    4096
+} else if cfg!(any(target_arch = "riscv32", target_arch = "x86_64", target_arch = "aarch64")) {
+    3072
+} else if cfg!(any(target_arch = "arm", target_arch = "x86")) {
+    2048
+} else {
+    4096
+};
+
+/// Fail the build if a constant expression is false.
+///
+/// ```ignore
+/// zephyr::static_assert!(STACK_SIZE >= zephyr::arch::MIN_STACK, "Stack too small for this arch");
+/// ```
+#[macro_export]
+macro_rules! static_assert {
+    ($cond:expr $(,)?) => {
+        const _: () = ::core::assert!($cond);
+    };
+    ($cond:expr, $msg:literal $(,)?) => {
+        const _: () = ::core::assert!($cond, $msg);
+    };
+}
@@ -0,0 +1,14 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Arm support.
+
+#[cfg(CONFIG_CPU_CORTEX_M)]
+pub mod nvic;
+#[cfg(all(
+    CONFIG_ARM_MPU,
+    CONFIG_USERSPACE,
+    not(CONFIG_ARMV8_M_BASELINE),
+    not(CONFIG_ARMV8_M_MAINLINE)
+))]
+pub mod mpu;
@@ -0,0 +1,178 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Configuration of additional Cortex-M MPU regions.
+//!
+//! Zephyr programs the MPU itself, with its static regions for flash and RAM, and with dynamic
+//! regions for the current thread's stack and memory domain.  This allows an application to
+//! configure regions of its own, in region numbers that Zephyr is not using, to protect, for
+//! example, a buffer shared with a peripheral.
+//!
+//! This is for the ARMv6-M and ARMv7-M MPU, where regions are a power of two in size, aligned to
+//! that size.  Regions configured here are tracked, and a new region may not overlap an earlier
+//! one.  Where regions do overlap in hardware, such as with Zephyr's own regions, the higher
+//! numbered region takes precedence.
+//!
+//! Requires `CONFIG_ARM_MPU` and `CONFIG_USERSPACE`.
+
+use core::fmt;
+use core::ptr;
+
+use bitflags::bitflags;
+
+use crate::sync::SpinMutex;
+
+/// The MPU Type Register.
+const MPU_TYPE: *const u32 = 0xE000_ED90 as *const u32;
+/// The MPU Region Number Register.
+const MPU_RNR: *mut u32 = 0xE000_ED98 as *mut u32;
+/// The MPU Region Base Address Register.
+const MPU_RBAR: *mut u32 = 0xE000_ED9C as *mut u32;
+/// The MPU Region Attribute and Size Register.
+const MPU_RASR: *mut u32 = 0xE000_EDA0 as *mut u32;
+
+/// The region enable bit in RASR.
+const RASR_ENABLE: u32 = 1;
+
+/// The most regions any of these MPUs implement.
+const MAX_REGIONS: usize = 16;
+
+/// An error configuring a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpuError {
+    /// The region is not implemented, the size is not a power of two of at least 32 bytes, the
+    /// start is not aligned to the size, or the region overlaps another configured here.
+    InvalidConfig,
+}
+
+impl fmt::Display for MpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MpuError::InvalidConfig => write!(f, "mpu: invalid region configuration"),
+        }
+    }
+}
+
+bitflags! {
+    /// The attributes of a region, as the bits of the RASR register.
+    ///
+    /// One access permission, one of the `P_*` values, should be given.  The memory type is made
+    /// from the `TEX0`, `C` and `B` bits; for example, `C | B` is normal write-back memory, and `B`
+    /// alone is shareable device memory.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MpuAttr: u32 {
+        /// Never execute from the region.
+        const XN = 1 << 28;
+
+        /// No access.
+        const P_NA_U_NA = 0b000 << 24;
+        /// Read and write when privileged, no access unprivileged.
+        const P_RW_U_NA = 0b001 << 24;
+        /// Read and write when privileged, read only unprivileged.
+        const P_RW_U_RO = 0b010 << 24;
+        /// Read and write for all.
+        const P_RW_U_RW = 0b011 << 24;
+        /// Read only when privileged, no access unprivileged.
+        const P_RO_U_NA = 0b101 << 24;
+        /// Read only for all.
+        const P_RO_U_RO = 0b110 << 24;
+
+        /// The low bit of the type extension field.
+        const TEX0 = 1 << 19;
+        /// Shareable.
+        const S = 1 << 18;
+        /// Cacheable.
+        const C = 1 << 17;
+        /// Bufferable.
+        const B = 1 << 16;
+
+        /// Disable the first eighth of the region.
+        const SRD0 = 1 << 8;
+        /// Disable the second eighth of the region.
+        const SRD1 = 1 << 9;
+        /// Disable the third eighth of the region.
+        const SRD2 = 1 << 10;
+        /// Disable the fourth eighth of the region.
+        const SRD3 = 1 << 11;
+        /// Disable the fifth eighth of the region.
+        const SRD4 = 1 << 12;
+        /// Disable the sixth eighth of the region.
+        const SRD5 = 1 << 13;
+        /// Disable the seventh eighth of the region.
+        const SRD6 = 1 << 14;
+        /// Disable the last eighth of the region.
+        const SRD7 = 1 << 15;
+    }
+}
+
+/// The regions configured here, as `(start, size)`.
+static REGIONS: SpinMutex<[Option<(usize, usize)>; MAX_REGIONS]> =
+    SpinMutex::new([None; MAX_REGIONS]);
+
+/// The number of regions the MPU implements.
+pub fn region_count() -> u8 {
+    // SAFETY: Reading the type register has no side effects.
+    let mpu_type = unsafe { ptr::read_volatile(MPU_TYPE) };
+    ((mpu_type >> 8) & 0xff) as u8
+}
+
+/// Configure, and enable, `region`, covering `size` bytes from `start`.
+///
+/// # Safety
+///
+/// The region must not be one that Zephyr uses, and the attributes must not remove access that
+/// running code, including Zephyr, relies on.
+pub unsafe fn configure_region(
+    region: u8,
+    start: usize,
+    size: usize,
+    attr: MpuAttr,
+) -> Result<(), MpuError> {
+    if region >= region_count()
+        || region as usize >= MAX_REGIONS
+        || size < 32
+        || !size.is_power_of_two()
+        || start & (size - 1) != 0
+    {
+        return Err(MpuError::InvalidConfig);
+    }
+    let end = start.wrapping_add(size - 1);
+
+    let mut regions = REGIONS.lock().unwrap();
+    let overlaps = regions.iter().enumerate().any(|(other, &configured)| match configured {
+        Some((other_start, other_size)) if other != region as usize => {
+            start <= other_start + (other_size - 1) && other_start <= end
+        }
+        _ => false,
+    });
+    if overlaps {
+        return Err(MpuError::InvalidConfig);
+    }
+
+    // The SIZE field holds log2(size) - 1.
+    let size_field = (size.trailing_zeros() - 1) << 1;
+    critical_section::with(|_| {
+        ptr::write_volatile(MPU_RNR, region as u32);
+        ptr::write_volatile(MPU_RBAR, start as u32);
+        ptr::write_volatile(MPU_RASR, attr.bits() | size_field | RASR_ENABLE);
+    });
+    regions[region as usize] = Some((start, size));
+    Ok(())
+}
+
+/// Disable `region`.
+///
+/// # Safety
+///
+/// As with [`configure_region`], the region must not be one that Zephyr uses.
+pub unsafe fn disable_region(region: u8) -> Result<(), MpuError> {
+    if region >= region_count() || region as usize >= MAX_REGIONS {
+        return Err(MpuError::InvalidConfig);
+    }
+    critical_section::with(|_| {
+        ptr::write_volatile(MPU_RNR, region as u32);
+        ptr::write_volatile(MPU_RASR, 0);
+    });
+    REGIONS.lock().unwrap()[region as usize] = None;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Direct management of the Cortex-M NVIC.
+//!
+//! Zephyr's `irq_enable` and friends cover the common cases, and are also available here.  The
+//! pending state is not exposed by Zephyr, so it is managed through the NVIC registers directly.
+//!
+//! All of these are `unsafe`, as changing the state of an interrupt that Zephyr, or a driver,
+//! manages can break it.  They are meant for drivers of peripherals that Zephyr doesn't have a
+//! driver for.
+
+use core::ptr;
+
+use crate::raw;
+
+/// The Interrupt Set-Pending Registers.
+const NVIC_ISPR: *mut u32 = 0xE000_E200 as *mut u32;
+/// The Interrupt Clear-Pending Registers.
+const NVIC_ICPR: *mut u32 = 0xE000_E280 as *mut u32;
+
+/// The register, and bit within it, for `irq`.
+fn reg_bit(base: *mut u32, irq: u32) -> (*mut u32, u32) {
+    (base.wrapping_add((irq / 32) as usize), 1 << (irq % 32))
+}
+
+/// Set `irq` pending, so that its handler runs once it is enabled, and of high enough priority.
+///
+/// # Safety
+///
+/// The handler for `irq` must be able to cope with running without its peripheral having raised
+/// the interrupt.
+pub unsafe fn set_pending(irq: u32) {
+    let (reg, bit) = reg_bit(NVIC_ISPR, irq);
+    ptr::write_volatile(reg, bit);
+}
+
+/// Clear the pending state of `irq`.
+///
+/// # Safety
+///
+/// Clearing a pending interrupt can lose an event that a driver is waiting for.
+pub unsafe fn clear_pending(irq: u32) {
+    let (reg, bit) = reg_bit(NVIC_ICPR, irq);
+    ptr::write_volatile(reg, bit);
+}
+
+/// Returns true if `irq` is pending.
+///
+/// # Safety
+///
+/// `irq` must be an interrupt implemented by this device.
+pub unsafe fn is_pending(irq: u32) -> bool {
+    let (reg, bit) = reg_bit(NVIC_ISPR, irq);
+    ptr::read_volatile(reg) & bit != 0
+}
+
+/// Set the priority of `irq`.
+///
+/// `prio` is a Zephyr priority, where 0 is the highest priority available to applications.  It is
+/// offset, and shifted into the implemented priority bits, by Zephyr, the same as `IRQ_CONNECT`.
+///
+/// # Safety
+///
+/// Changing the priority of an interrupt can break a driver that relies on it.
+pub unsafe fn set_priority(irq: u32, prio: u8) {
+    raw::z_arm_irq_priority_set(irq, prio as u32, 0);
+}
+
+/// Enable `irq`.
+///
+/// # Safety
+///
+/// A handler must have been connected for `irq`.
+pub unsafe fn enable(irq: u32) {
+    raw::arch_irq_enable(irq);
+}
+
+/// Disable `irq`.
+///
+/// # Safety
+///
+/// Disabling an interrupt can break a driver that relies on it.
+pub unsafe fn disable(irq: u32) {
+    raw::arch_irq_disable(irq);
+}
+
+/// Returns true if `irq` is enabled.
+///
+/// # Safety
+///
+/// `irq` must be an interrupt implemented by this device.
+pub unsafe fn is_enabled(irq: u32) -> bool {
+    raw::arch_irq_is_enabled(irq) != 0
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bluetooth Low Energy.
+//!
+//! Wrappers around Zephyr's Bluetooth host stack.  The common types, addresses and UUIDs, are
+//! here, with each role in its own module.  The stack must already be enabled, with `bt_enable`.
+//!
+//! - [`scan`], scanning for advertisements, requires `CONFIG_BT_OBSERVER`.
+//! - [`conn`], connecting to peripherals, requires `CONFIG_BT_CENTRAL`.
+//! - [`gatt`], the GATT client, requires `CONFIG_BT_GATT_CLIENT`.
+//! - [`mesh`], Bluetooth mesh, requires `CONFIG_BT_MESH`.
+//!
+//! Requires `CONFIG_BT`.
+
+use core::fmt;
+
+use crate::raw;
+
+#[cfg(CONFIG_BT_CENTRAL)]
+pub mod conn;
+#[cfg(all(CONFIG_BT_CENTRAL, CONFIG_BT_GATT_CLIENT))]
+pub mod gatt;
+#[cfg(CONFIG_BT_MESH)]
+pub mod mesh;
+#[cfg(CONFIG_BT_OBSERVER)]
+pub mod scan;
+
+/// A Bluetooth LE device address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BleAddr {
+    /// The address type, `BT_ADDR_LE_*`.
+    kind: u8,
+    /// The address, least significant byte first, as it is sent over the air.
+    val: [u8; 6],
+}
+
+impl BleAddr {
+    /// A public address.  The bytes are in the usual written order, most significant first.
+    pub fn public(addr: [u8; 6]) -> BleAddr {
+        Self::from_written(raw::BT_ADDR_LE_PUBLIC as u8, addr)
+    }
+
+    /// A random address.  The bytes are in the usual written order, most significant first.
+    pub fn random(addr: [u8; 6]) -> BleAddr {
+        Self::from_written(raw::BT_ADDR_LE_RANDOM as u8, addr)
+    }
+
+    fn from_written(kind: u8, mut addr: [u8; 6]) -> BleAddr {
+        addr.reverse();
+        BleAddr { kind, val: addr }
+    }
+
+    /// The address bytes, most significant first.
+    pub fn bytes(&self) -> [u8; 6] {
+        let mut addr = self.val;
+        addr.reverse();
+        addr
+    }
+
+    /// Returns true if this is a random address.
+    pub fn is_random(&self) -> bool {
+        self.kind == raw::BT_ADDR_LE_RANDOM as u8
+    }
+
+    pub(crate) fn from_raw(addr: &raw::bt_addr_le_t) -> BleAddr {
+        BleAddr {
+            kind: addr.type_,
+            val: addr.a.val,
+        }
+    }
+
+    pub(crate) fn to_raw(self) -> raw::bt_addr_le_t {
+        raw::bt_addr_le_t {
+            type_: self.kind,
+            a: raw::bt_addr_t { val: self.val },
+        }
+    }
+}
+
+impl fmt::Display for BleAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = self.bytes();
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            b[0], b[1], b[2], b[3], b[4], b[5]
+        )?;
+        if self.is_random() {
+            write!(f, " (random)")
+        } else {
+            write!(f, " (public)")
+        }
+    }
+}
+
+impl fmt::Debug for BleAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BleAddr({})", self)
+    }
+}
+
+/// A Bluetooth UUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BleUuid {
+    /// A 16-bit UUID, assigned by the Bluetooth SIG.
+    Uuid16(u16),
+    /// A full 128-bit UUID, least significant byte first, as it is sent over the air.
+    Uuid128([u8; 16]),
+}
+
+impl BleUuid {
+    /// A 128-bit UUID from its usual written form, such as
+    /// `0x6e400001_b5a3_f393_e0a9_e50e24dcca9e`.
+    pub const fn from_u128(uuid: u128) -> BleUuid {
+        BleUuid::Uuid128(uuid.to_le_bytes())
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Connections, as a central.
+//!
+//! [`BleConnection::connect`] starts connecting to a peripheral, returning a [`BleConnHandle`]
+//! right away.  The connection is only usable once it has been established, which is reported to
+//! the `connected` callback of the registered [`BleConnectionCallbacks`].
+//!
+//! ```ignore
+//! fn connected(conn: &BleConnHandle, err: u8) {
+//!     // ...
+//! }
+//!
+//! BleConnectionCallbacks {
+//!     connected: Some(connected),
+//!     ..Default::default()
+//! }
+//! .register()?;
+//!
+//! let conn = BleConnection::connect(&addr, &BleConnParams::default())?;
+//! ```
+//!
+//! Requires `CONFIG_BT_CENTRAL`.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem;
+use core::ptr;
+
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+use crate::sync::atomic::{AtomicBool, Ordering};
+use crate::sync::SpinMutex;
+
+use super::BleAddr;
+
+/// The connection parameters to request.
+///
+/// The intervals are in units of 1.25 ms, and the supervision timeout in units of 10 ms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BleConnParams {
+    /// The minimum connection interval.
+    pub interval_min: u16,
+    /// The maximum connection interval.
+    pub interval_max: u16,
+    /// The number of connection events the peripheral may skip.
+    pub latency: u16,
+    /// The supervision timeout.
+    pub timeout: u16,
+}
+
+impl Default for BleConnParams {
+    /// The defaults used by Zephyr's `BT_LE_CONN_PARAM_DEFAULT`.
+    fn default() -> Self {
+        BleConnParams {
+            interval_min: raw::BT_GAP_INIT_CONN_INT_MIN as u16,
+            interval_max: raw::BT_GAP_INIT_CONN_INT_MAX as u16,
+            latency: 0,
+            timeout: 400,
+        }
+    }
+}
+
+/// The security level of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BleSecurity {
+    /// No encryption or authentication.
+    L1,
+    /// Encryption, without authentication.
+    L2,
+    /// Encryption and authentication.
+    L3,
+    /// Authenticated LE Secure Connections.
+    L4,
+}
+
+#[cfg(CONFIG_BT_SMP)]
+impl BleSecurity {
+    fn raw(self) -> raw::bt_security_t {
+        match self {
+            BleSecurity::L1 => raw::bt_security_t_BT_SECURITY_L1,
+            BleSecurity::L2 => raw::bt_security_t_BT_SECURITY_L2,
+            BleSecurity::L3 => raw::bt_security_t_BT_SECURITY_L3,
+            BleSecurity::L4 => raw::bt_security_t_BT_SECURITY_L4,
+        }
+    }
+}
+
+/// The local role in a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BleRole {
+    /// This device is the central.
+    Central,
+    /// This device is the peripheral.
+    Peripheral,
+}
+
+/// Information about an LE connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BleConnInfo {
+    /// The local role.
+    pub role: BleRole,
+    /// The address of the peer.
+    pub peer: BleAddr,
+    /// The connection interval, in units of 1.25 ms.
+    pub interval: u16,
+    /// The peripheral latency.
+    pub latency: u16,
+    /// The supervision timeout, in units of 10 ms.
+    pub timeout: u16,
+}
+
+/// Creating connections.
+pub struct BleConnection;
+
+impl BleConnection {
+    /// Start connecting to the peripheral at `addr`.
+    ///
+    /// This returns once the controller has started trying to connect.  The result is given to
+    /// the `connected` callback.
+    pub fn connect(addr: &BleAddr, params: &BleConnParams) -> ZResult<BleConnHandle> {
+        // SAFETY: The parameters are plain data, the fields not set here are meant to be zero.
+        let mut create: raw::bt_conn_le_create_param = unsafe { mem::zeroed() };
+        create.interval = raw::BT_GAP_SCAN_FAST_INTERVAL as u16;
+        create.window = raw::BT_GAP_SCAN_FAST_INTERVAL as u16;
+
+        let conn_param = raw::bt_le_conn_param {
+            interval_min: params.interval_min,
+            interval_max: params.interval_max,
+            latency: params.latency,
+            timeout: params.timeout,
+        };
+        let peer = addr.to_raw();
+        let mut conn: *mut raw::bt_conn = ptr::null_mut();
+        ok_or_errno(unsafe { raw::bt_conn_le_create(&peer, &create, &conn_param, &mut conn) })?;
+        // The reference from bt_conn_le_create is now owned by the handle.
+        Ok(BleConnHandle { conn })
+    }
+}
+
+impl fmt::Debug for BleConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BleConnection")
+    }
+}
+
+/// A reference to a connection.
+///
+/// This holds a reference on the Zephyr connection object, which is released on drop.  Dropping
+/// the handle does not disconnect.
+pub struct BleConnHandle {
+    pub(crate) conn: *mut raw::bt_conn,
+}
+
+// SAFETY: The connection API can be used from any thread.
+unsafe impl Send for BleConnHandle {}
+unsafe impl Sync for BleConnHandle {}
+
+impl BleConnHandle {
+    /// Take a new reference to a connection from the stack.
+    pub(crate) unsafe fn from_raw_ref(conn: *mut raw::bt_conn) -> BleConnHandle {
+        BleConnHandle {
+            conn: raw::bt_conn_ref(conn),
+        }
+    }
+
+    /// Disconnect, with the given HCI reason code, such as `BT_HCI_ERR_REMOTE_USER_TERM_CONN`.
+    pub fn disconnect(&self, reason: u8) -> ZResult<()> {
+        ok_or_errno(unsafe { raw::bt_conn_disconnect(self.conn, reason) })
+    }
+
+    /// Get information about the connection.
+    pub fn get_info(&self) -> ZResult<BleConnInfo> {
+        // SAFETY: Filled in by `bt_conn_get_info`.
+        let mut info: raw::bt_conn_info = unsafe { mem::zeroed() };
+        ok_or_errno(unsafe { raw::bt_conn_get_info(self.conn, &mut info) })?;
+        // SAFETY: This is an LE connection, so the `le` member is the one that was filled in.
+        let le = unsafe { &info.__bindgen_anon_1.le };
+        let role = if info.role as u32 == raw::BT_CONN_ROLE_CENTRAL as u32 {
+            BleRole::Central
+        } else {
+            BleRole::Peripheral
+        };
+        Ok(BleConnInfo {
+            role,
+            peer: BleAddr::from_raw(unsafe { &*le.dst }),
+            interval: le.interval,
+            latency: le.latency,
+            timeout: le.timeout,
+        })
+    }
+
+    /// Raise the security of the connection, pairing if needed.
+    ///
+    /// This returns once the request has started.  Requires `CONFIG_BT_SMP`.
+    #[cfg(CONFIG_BT_SMP)]
+    pub fn set_security(&self, level: BleSecurity) -> ZResult<()> {
+        ok_or_errno(unsafe { raw::bt_conn_set_security(self.conn, level.raw()) })
+    }
+
+    /// The raw connection pointer.
+    pub fn as_raw(&self) -> *mut raw::bt_conn {
+        self.conn
+    }
+}
+
+impl Clone for BleConnHandle {
+    fn clone(&self) -> Self {
+        // SAFETY: We hold a reference, so the connection is valid.
+        unsafe { Self::from_raw_ref(self.conn) }
+    }
+}
+
+impl PartialEq for BleConnHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.conn == other.conn
+    }
+}
+
+impl Eq for BleConnHandle {}
+
+impl Drop for BleConnHandle {
+    fn drop(&mut self) {
+        unsafe {
+            raw::bt_conn_unref(self.conn);
+        }
+    }
+}
+
+impl fmt::Debug for BleConnHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BleConnHandle({:p})", self.conn)
+    }
+}
+
+/// Callbacks for connection events.
+///
+/// These are called from the Bluetooth thread, for all connections, including those not made by
+/// [`BleConnection::connect`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BleConnectionCallbacks {
+    /// A connection has been established, or failed, with a non-zero HCI error.
+    pub connected: Option<fn(conn: &BleConnHandle, err: u8)>,
+    /// A connection has been lost, with the HCI reason.
+    pub disconnected: Option<fn(conn: &BleConnHandle, reason: u8)>,
+}
+
+impl BleConnectionCallbacks {
+    /// Register these callbacks.  Registering again replaces the previous callbacks.
+    pub fn register(self) -> ZResult<()> {
+        *CALLBACKS.lock().unwrap() = self;
+        if REGISTERED.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        // SAFETY: Only the caller that set `REGISTERED` gets here, before Zephyr knows about the
+        // struct.
+        let result = unsafe {
+            let cb = &mut *CONN_CB.0.get();
+            cb.connected = Some(connected);
+            cb.disconnected = Some(disconnected);
+            ok_or_errno(raw::bt_conn_cb_register(cb))
+        };
+        if result.is_err() {
+            // Zephyr doesn't have the struct, so a later register can try again.
+            REGISTERED.store(false, Ordering::Release);
+        }
+        result
+    }
+}
+
+/// The callbacks given to Zephyr, which dispatch to the registered Rust callbacks.
+struct ConnCb(UnsafeCell<raw::bt_conn_cb>);
+
+// SAFETY: Only written by the one caller of `register` that set `REGISTERED`, before it is
+// registered.
+unsafe impl Sync for ConnCb {}
+
+// SAFETY: A zeroed callback struct has no callbacks, and no list node.
+static CONN_CB: ConnCb = ConnCb(UnsafeCell::new(unsafe { mem::zeroed() }));
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+static CALLBACKS: SpinMutex<BleConnectionCallbacks> = SpinMutex::new(BleConnectionCallbacks {
+    connected: None,
+    disconnected: None,
+});
+
+unsafe extern "C" fn connected(conn: *mut raw::bt_conn, err: u8) {
+    let callback = CALLBACKS.lock().unwrap().connected;
+    if let Some(callback) = callback {
+        callback(&BleConnHandle::from_raw_ref(conn), err);
+    }
+}
+
+unsafe extern "C" fn disconnected(conn: *mut raw::bt_conn, reason: u8) {
+    let callback = CALLBACKS.lock().unwrap().disconnected;
+    if let Some(callback) = callback {
+        callback(&BleConnHandle::from_raw_ref(conn), reason);
+    }
+}
@@ -0,0 +1,428 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! The GATT client.
+//!
+//! Once connected, a central finds the services of the peripheral, and then reads, writes, and
+//! subscribes to their characteristics by attribute handle.  Zephyr runs these operations
+//! asynchronously, with a callback on completion.  The calls here instead wait for the operation
+//! to complete, so must not be called from a Bluetooth callback, which would deadlock.
+//!
+//! ```ignore
+//! let svc = GattClient::discover_service(&conn, &BleUuid::Uuid16(0x180f))?;
+//! GattClient::read(&conn, svc.start_handle + 2, 0, |data| printkln!("{:?}", data))?;
+//! ```
+//!
+//! Requires `CONFIG_BT_GATT_CLIENT`.
+
+#[cfg(CONFIG_RUST_ALLOC)]
+extern crate alloc;
+
+#[cfg(CONFIG_RUST_ALLOC)]
+use alloc::boxed::Box;
+
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::fmt;
+#[cfg(CONFIG_RUST_ALLOC)]
+use core::marker::PhantomPinned;
+use core::mem;
+#[cfg(CONFIG_RUST_ALLOC)]
+use core::pin::Pin;
+
+use crate::error::{ok_or_errno, ZephyrError};
+use crate::raw;
+use crate::sys::K_FOREVER;
+#[cfg(CONFIG_RUST_ALLOC)]
+use crate::time::{Duration, Timeout};
+
+use super::conn::BleConnHandle;
+use super::BleUuid;
+
+/// An error from a GATT operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GattError {
+    /// The stack could not start the operation.
+    Stack(ZephyrError),
+    /// The peer answered with an ATT error code.
+    Att(u8),
+    /// Discovery did not find what was asked for.
+    NotFound,
+}
+
+impl fmt::Display for GattError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GattError::Stack(err) => write!(f, "gatt: {}", err),
+            GattError::Att(code) => write!(f, "gatt: ATT error 0x{:02x}", code),
+            GattError::NotFound => write!(f, "gatt: not found"),
+        }
+    }
+}
+
+impl From<ZephyrError> for GattError {
+    fn from(err: ZephyrError) -> GattError {
+        GattError::Stack(err)
+    }
+}
+
+/// The range of attribute handles belonging to a service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceHandle {
+    /// The handle of the service declaration.
+    pub start_handle: u16,
+    /// The last handle within the service.
+    pub end_handle: u16,
+}
+
+/// A UUID in the form the stack takes.
+enum RawUuid {
+    U16(raw::bt_uuid_16),
+    U128(raw::bt_uuid_128),
+}
+
+impl RawUuid {
+    fn new(uuid: &BleUuid) -> RawUuid {
+        match *uuid {
+            BleUuid::Uuid16(val) => RawUuid::U16(raw::bt_uuid_16 {
+                uuid: raw::bt_uuid {
+                    type_: raw::BT_UUID_TYPE_16 as u8,
+                },
+                val,
+            }),
+            BleUuid::Uuid128(val) => RawUuid::U128(raw::bt_uuid_128 {
+                uuid: raw::bt_uuid {
+                    type_: raw::BT_UUID_TYPE_128 as u8,
+                },
+                val,
+            }),
+        }
+    }
+
+    fn as_ptr(&self) -> *const raw::bt_uuid {
+        match self {
+            RawUuid::U16(uuid) => &uuid.uuid,
+            RawUuid::U128(uuid) => &uuid.uuid,
+        }
+    }
+}
+
+/// A semaphore given when an operation completes.  Lives on the waiting thread's stack.
+struct Completion(UnsafeCell<raw::k_sem>);
+
+impl Completion {
+    fn new() -> Completion {
+        // SAFETY: Initialized by `init` before use.
+        Completion(UnsafeCell::new(unsafe { mem::zeroed() }))
+    }
+
+    /// Initialize, once the completion is at its final address.
+    fn init(&self) {
+        unsafe {
+            raw::k_sem_init(self.0.get(), 0, 1);
+        }
+    }
+
+    fn done(&self) {
+        unsafe {
+            raw::k_sem_give(self.0.get());
+        }
+    }
+
+    fn wait(&self) {
+        unsafe {
+            raw::k_sem_take(self.0.get(), K_FOREVER);
+        }
+    }
+
+    /// Wait up to `timeout`, returning true if the operation completed.
+    #[cfg(CONFIG_RUST_ALLOC)]
+    fn wait_timeout(&self, timeout: impl Into<Timeout>) -> bool {
+        let timeout: Timeout = timeout.into();
+        unsafe { raw::k_sem_take(self.0.get(), timeout.0) == 0 }
+    }
+}
+
+/// Recover the operation containing a params field.
+///
+/// # Safety
+///
+/// `params` must point to the `$field` of a `$op`.
+macro_rules! container_of {
+    ($params:expr, $op:ty, $field:ident) => {
+        &mut *($params
+            .cast::<u8>()
+            .sub(mem::offset_of!($op, $field))
+            .cast::<$op>())
+    };
+}
+
+struct DiscoverOp {
+    params: raw::bt_gatt_discover_params,
+    result: Option<ServiceHandle>,
+    done: Completion,
+}
+
+struct ReadOp {
+    params: raw::bt_gatt_read_params,
+    callback: fn(&[u8]),
+    err: u8,
+    done: Completion,
+}
+
+struct WriteOp {
+    params: raw::bt_gatt_write_params,
+    err: u8,
+    done: Completion,
+}
+
+/// GATT client operations.
+pub struct GattClient;
+
+impl GattClient {
+    /// Find the primary service with the given UUID.
+    pub fn discover_service(
+        conn: &BleConnHandle,
+        uuid: &BleUuid,
+    ) -> Result<ServiceHandle, GattError> {
+        let uuid = RawUuid::new(uuid);
+        let mut op = DiscoverOp {
+            // SAFETY: The parameters are plain data, the fields not set here are meant to be zero.
+            params: unsafe { mem::zeroed() },
+            result: None,
+            done: Completion::new(),
+        };
+        op.done.init();
+        op.params.uuid = uuid.as_ptr();
+        op.params.func = Some(discover_cb);
+        op.params.__bindgen_anon_1.start_handle = raw::BT_ATT_FIRST_ATTRIBUTE_HANDLE as u16;
+        op.params.end_handle = raw::BT_ATT_LAST_ATTRIBUTE_HANDLE as u16;
+        op.params.type_ = raw::BT_GATT_DISCOVER_PRIMARY as u8;
+
+        // SAFETY: `op` stays alive, and in place, until the callback reports completion.
+        ok_or_errno(unsafe { raw::bt_gatt_discover(conn.conn, &mut op.params) })?;
+        op.done.wait();
+        op.result.ok_or(GattError::NotFound)
+    }
+
+    /// Read the attribute at `handle`, starting at `offset`.
+    ///
+    /// The value is given to `callback`, which may be called more than once for a long value.
+    pub fn read(
+        conn: &BleConnHandle,
+        handle: u16,
+        offset: u16,
+        callback: fn(&[u8]),
+    ) -> Result<(), GattError> {
+        let mut op = ReadOp {
+            // SAFETY: The parameters are plain data, the fields not set here are meant to be zero.
+            params: unsafe { mem::zeroed() },
+            callback,
+            err: 0,
+            done: Completion::new(),
+        };
+        op.done.init();
+        op.params.func = Some(read_cb);
+        op.params.handle_count = 1;
+        op.params.__bindgen_anon_1.single.handle = handle;
+        op.params.__bindgen_anon_1.single.offset = offset;
+
+        // SAFETY: `op` stays alive, and in place, until the callback reports completion.
+        ok_or_errno(unsafe { raw::bt_gatt_read(conn.conn, &mut op.params) })?;
+        op.done.wait();
+        match op.err {
+            0 => Ok(()),
+            err => Err(GattError::Att(err)),
+        }
+    }
+
+    /// Write `data` to the attribute at `handle`, waiting for the peer to respond.
+    pub fn write(conn: &BleConnHandle, handle: u16, data: &[u8]) -> Result<(), GattError> {
+        let length =
+            u16::try_from(data.len()).map_err(|_| GattError::Stack(ZephyrError::Invalid))?;
+        let mut op = WriteOp {
+            // SAFETY: The parameters are plain data, the fields not set here are meant to be zero.
+            params: unsafe { mem::zeroed() },
+            err: 0,
+            done: Completion::new(),
+        };
+        op.done.init();
+        op.params.func = Some(write_cb);
+        op.params.handle = handle;
+        op.params.data = data.as_ptr() as *const c_void;
+        op.params.length = length;
+
+        // SAFETY: `op` and `data` stay alive until the callback reports completion.
+        ok_or_errno(unsafe { raw::bt_gatt_write(conn.conn, &mut op.params) })?;
+        op.done.wait();
+        match op.err {
+            0 => Ok(()),
+            err => Err(GattError::Att(err)),
+        }
+    }
+
+    /// Subscribe to notifications of the characteristic with the given value handle, enabling them
+    /// with its client characteristic configuration descriptor at `ccc_handle`.
+    ///
+    /// `callback` is called from the Bluetooth thread with each notification.  Notifications
+    /// continue until the returned [`Subscription`] is dropped.  Requires `CONFIG_RUST_ALLOC`.
+    #[cfg(CONFIG_RUST_ALLOC)]
+    pub fn subscribe(
+        conn: &BleConnHandle,
+        value_handle: u16,
+        ccc_handle: u16,
+        callback: fn(&[u8]),
+    ) -> Result<Subscription, GattError> {
+        let inner = Box::pin(SubscribeInner {
+            // SAFETY: The parameters are plain data, the fields not set here are meant to be zero.
+            params: UnsafeCell::new(unsafe { mem::zeroed() }),
+            callback,
+            ended: Completion::new(),
+            _pin: PhantomPinned,
+        });
+        inner.ended.init();
+        // SAFETY: Zephyr doesn't know about the params yet.
+        unsafe {
+            let params = &mut *inner.params.get();
+            params.notify = Some(notify_cb);
+            params.value_handle = value_handle;
+            params.ccc_handle = ccc_handle;
+            params.value = raw::BT_GATT_CCC_NOTIFY as u16;
+        }
+
+        ok_or_errno(unsafe { raw::bt_gatt_subscribe(conn.conn, inner.params.get()) })?;
+        Ok(Subscription {
+            conn: conn.clone(),
+            inner,
+        })
+    }
+}
+
+impl fmt::Debug for GattClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GattClient")
+    }
+}
+
+unsafe extern "C" fn discover_cb(
+    _conn: *mut raw::bt_conn,
+    attr: *const raw::bt_gatt_attr,
+    params: *mut raw::bt_gatt_discover_params,
+) -> u8 {
+    let op = container_of!(params, DiscoverOp, params);
+    if !attr.is_null() {
+        let service = &*((*attr).user_data as *const raw::bt_gatt_service_val);
+        op.result = Some(ServiceHandle {
+            start_handle: (*attr).handle,
+            end_handle: service.end_handle,
+        });
+    }
+    // Either the service was found, or discovery is complete.  Stopping also completes it.
+    op.done.done();
+    raw::BT_GATT_ITER_STOP as u8
+}
+
+unsafe extern "C" fn read_cb(
+    _conn: *mut raw::bt_conn,
+    err: u8,
+    params: *mut raw::bt_gatt_read_params,
+    data: *const c_void,
+    length: u16,
+) -> u8 {
+    let op = container_of!(params, ReadOp, params);
+    if err != 0 || data.is_null() {
+        op.err = err;
+        op.done.done();
+        return raw::BT_GATT_ITER_STOP as u8;
+    }
+    (op.callback)(core::slice::from_raw_parts(data as *const u8, length as usize));
+    raw::BT_GATT_ITER_CONTINUE as u8
+}
+
+unsafe extern "C" fn write_cb(
+    _conn: *mut raw::bt_conn,
+    err: u8,
+    params: *mut raw::bt_gatt_write_params,
+) {
+    let op = container_of!(params, WriteOp, params);
+    op.err = err;
+    op.done.done();
+}
+
+/// A subscription to notifications.  Unsubscribes on drop.
+#[cfg(CONFIG_RUST_ALLOC)]
+pub struct Subscription {
+    conn: BleConnHandle,
+    inner: Pin<Box<SubscribeInner>>,
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+struct SubscribeInner {
+    params: UnsafeCell<raw::bt_gatt_subscribe_params>,
+    callback: fn(&[u8]),
+    /// Given once the stack has let go of the params.
+    ended: Completion,
+    _pin: PhantomPinned,
+}
+
+// SAFETY: The params are only touched by the stack, which does its own locking.
+#[cfg(CONFIG_RUST_ALLOC)]
+unsafe impl Send for Subscription {}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let ret = unsafe { raw::bt_gatt_unsubscribe(self.conn.conn, self.inner.params.get()) };
+        if ret < 0 {
+            // Not subscribed, such as after a disconnect, so the stack has let go already.
+            return;
+        }
+        // The stack reports the end of the subscription through the notify callback.  If that
+        // never comes, leak the params rather than free them while they might still be used.
+        if !self.inner.ended.wait_timeout(Duration::secs_at_least(5)) {
+            log::warn!("GATT unsubscribe did not complete, leaking subscription");
+            let inner = mem::replace(&mut self.inner, Box::pin(SubscribeInner::empty()));
+            mem::forget(inner);
+        }
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl SubscribeInner {
+    fn empty() -> SubscribeInner {
+        SubscribeInner {
+            // SAFETY: Never given to the stack.
+            params: UnsafeCell::new(unsafe { mem::zeroed() }),
+            callback: |_| (),
+            ended: Completion::new(),
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Subscription")
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+unsafe extern "C" fn notify_cb(
+    _conn: *mut raw::bt_conn,
+    params: *mut raw::bt_gatt_subscribe_params,
+    data: *const c_void,
+    length: u16,
+) -> u8 {
+    let inner = &*(params
+        .cast::<u8>()
+        .sub(mem::offset_of!(SubscribeInner, params))
+        .cast::<SubscribeInner>());
+    if data.is_null() {
+        // Unsubscribed.
+        inner.ended.done();
+        return raw::BT_GATT_ITER_STOP as u8;
+    }
+    (inner.callback)(core::slice::from_raw_parts(data as *const u8, length as usize));
+    raw::BT_GATT_ITER_CONTINUE as u8
+}
@@ -0,0 +1,323 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bluetooth mesh.
+//!
+//! Zephyr keeps pointers to the provisioning information and the node composition for as long as
+//! the mesh is running, so these are built as statics.  The elements and models themselves have a
+//! layout that is specific to the Zephyr version, and are usually easiest to declare in C, with
+//! `BT_MESH_MODEL` and friends, and brought in with [`MeshComposition::from_raw`].  The opcode
+//! handlers for a model can be written in Rust, with [`mesh_model_op!`], which builds the
+//! [`MeshModelOp`] that goes into the model's opcode list.
+//!
+//! ```ignore
+//! fn onoff_get(model: &MeshModel, ctx: &MeshMsgCtx, buf: &[u8]) {
+//!     // ...
+//! }
+//!
+//! static OPS: [MeshModelOp; 2] = [mesh_model_op!(0x8201, 0, onoff_get), MeshModelOp::END];
+//! static PROV: MeshProvisioner = MeshProvisioner::new(&DEV_UUID);
+//!
+//! BtMesh::init(&PROV, &COMP)?;
+//! ```
+//!
+//! Requires `CONFIG_BT_MESH`.
+
+use core::ffi::{c_int, c_void};
+use core::fmt;
+use core::mem;
+
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+
+/// The provisioning information for this device.
+pub struct MeshProvisioner {
+    prov: raw::bt_mesh_prov,
+}
+
+// SAFETY: Only read by the mesh stack once it has been given to `bt_mesh_init`.
+unsafe impl Sync for MeshProvisioner {}
+
+impl MeshProvisioner {
+    /// Provisioning information with the given device UUID, and no OOB authentication.
+    pub const fn new(uuid: &'static [u8; 16]) -> MeshProvisioner {
+        // SAFETY: The unset fields, callbacks and OOB actions, are meant to be zero when unused.
+        let mut prov: raw::bt_mesh_prov = unsafe { mem::zeroed() };
+        prov.uuid = uuid.as_ptr();
+        MeshProvisioner { prov }
+    }
+
+    /// Wrap provisioning information built elsewhere, such as in C.
+    ///
+    /// # Safety
+    ///
+    /// `prov` must be valid for `bt_mesh_init`, and any pointers in it must be `'static`.
+    pub const unsafe fn from_raw(prov: raw::bt_mesh_prov) -> MeshProvisioner {
+        MeshProvisioner { prov }
+    }
+}
+
+impl fmt::Debug for MeshProvisioner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MeshProvisioner")
+    }
+}
+
+/// The composition of this node, its elements and their models.
+pub struct MeshComposition {
+    comp: raw::bt_mesh_comp,
+}
+
+// SAFETY: As with the provisioner.
+unsafe impl Sync for MeshComposition {}
+
+impl MeshComposition {
+    /// Wrap a composition built elsewhere, usually in C.
+    ///
+    /// # Safety
+    ///
+    /// `comp` must be valid for `bt_mesh_init`, and the elements and models it points to must be
+    /// `'static`.
+    pub const unsafe fn from_raw(comp: raw::bt_mesh_comp) -> MeshComposition {
+        MeshComposition { comp }
+    }
+}
+
+impl fmt::Debug for MeshComposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MeshComposition")
+            .field("cid", &self.comp.cid)
+            .field("pid", &self.comp.pid)
+            .field("vid", &self.comp.vid)
+            .finish()
+    }
+}
+
+/// The Bluetooth mesh stack.
+pub struct BtMesh;
+
+impl BtMesh {
+    /// Initialize the mesh stack, with this node's provisioning information and composition.
+    pub fn init(prov: &'static MeshProvisioner, comp: &'static MeshComposition) -> ZResult<()> {
+        ok_or_errno(unsafe { raw::bt_mesh_init(&prov.prov, &comp.comp) })
+    }
+
+    /// Provision this node directly, without a provisioner, with the given network key, unicast
+    /// address, and device key.
+    pub fn provision(
+        net_key: &[u8; 16],
+        net_idx: u16,
+        iv_index: u32,
+        addr: u16,
+        dev_key: &[u8; 16],
+    ) -> ZResult<()> {
+        ok_or_errno(unsafe {
+            raw::bt_mesh_provision(net_key.as_ptr(), net_idx, 0, iv_index, addr, dev_key.as_ptr())
+        })
+    }
+
+    /// Provision the unprovisioned device with `uuid` over PB-ADV, giving it the unicast address
+    /// `addr` on the network `net_idx`.
+    ///
+    /// The IV index is that of the local network, so is not given here.  The device is asked to
+    /// draw attention to itself for `attention_duration` seconds.  Requires
+    /// `CONFIG_BT_MESH_PROVISIONER`.
+    #[cfg(CONFIG_BT_MESH_PROVISIONER)]
+    pub fn provision_adv(
+        uuid: &[u8; 16],
+        net_idx: u16,
+        addr: u16,
+        attention_duration: u8,
+    ) -> ZResult<()> {
+        ok_or_errno(unsafe {
+            raw::bt_mesh_provision_adv(uuid.as_ptr(), net_idx, addr, attention_duration)
+        })
+    }
+
+    /// Returns true if this node has been provisioned.
+    pub fn is_provisioned() -> bool {
+        unsafe { raw::bt_mesh_is_provisioned() }
+    }
+
+    /// Reset this node to the unprovisioned state.
+    pub fn reset() {
+        unsafe {
+            raw::bt_mesh_reset();
+        }
+    }
+}
+
+impl fmt::Debug for BtMesh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BtMesh")
+    }
+}
+
+/// A model, as given to a message handler.
+#[repr(transparent)]
+pub struct MeshModel(raw::bt_mesh_model);
+
+impl MeshModel {
+    /// The user data given when the model was declared.
+    pub fn user_data(&self) -> *mut c_void {
+        self.0.user_data
+    }
+
+    /// The raw model.
+    pub fn as_raw(&self) -> *const raw::bt_mesh_model {
+        &self.0
+    }
+}
+
+impl fmt::Debug for MeshModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MeshModel({:p})", self)
+    }
+}
+
+/// The context of a received message.
+#[repr(transparent)]
+pub struct MeshMsgCtx(raw::bt_mesh_msg_ctx);
+
+impl MeshMsgCtx {
+    /// The network key index the message came in on.
+    pub fn net_idx(&self) -> u16 {
+        self.0.net_idx
+    }
+
+    /// The application key index used, or a device key index.
+    pub fn app_idx(&self) -> u16 {
+        self.0.app_idx
+    }
+
+    /// The source address of the message.
+    pub fn addr(&self) -> u16 {
+        self.0.addr
+    }
+
+    /// The destination address the message was sent to.
+    pub fn recv_dst(&self) -> u16 {
+        self.0.recv_dst
+    }
+
+    /// The signal strength the message was received with.
+    pub fn recv_rssi(&self) -> i8 {
+        self.0.recv_rssi
+    }
+
+    /// The TTL the message was received with.
+    pub fn recv_ttl(&self) -> u8 {
+        self.0.recv_ttl
+    }
+
+    /// The raw context, which can be used to reply.
+    pub fn as_raw(&self) -> *const raw::bt_mesh_msg_ctx {
+        &self.0
+    }
+}
+
+impl fmt::Debug for MeshMsgCtx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MeshMsgCtx")
+            .field("net_idx", &self.net_idx())
+            .field("app_idx", &self.app_idx())
+            .field("addr", &self.addr())
+            .field("recv_dst", &self.recv_dst())
+            .finish()
+    }
+}
+
+/// A handler for a model message.
+pub type MeshHandler = fn(model: &MeshModel, ctx: &MeshMsgCtx, buf: &[u8]);
+
+/// The C signature of a model opcode handler.
+pub type RawMeshHandler = unsafe extern "C" fn(
+    model: *const raw::bt_mesh_model,
+    ctx: *mut raw::bt_mesh_msg_ctx,
+    buf: *mut raw::net_buf_simple,
+) -> c_int;
+
+/// An entry in a model's opcode list.
+///
+/// Built with [`mesh_model_op!`].  The list must end with [`MeshModelOp::END`].
+#[repr(transparent)]
+pub struct MeshModelOp(raw::bt_mesh_model_op);
+
+// SAFETY: The opcode entries are constant.
+unsafe impl Sync for MeshModelOp {}
+
+impl MeshModelOp {
+    /// The end of an opcode list.
+    // SAFETY: The terminator is all zeros.
+    pub const END: MeshModelOp = MeshModelOp(unsafe { mem::zeroed() });
+
+    /// An entry for `opcode`, with messages shorter than `min_len` being rejected by the stack.
+    ///
+    /// # Safety
+    ///
+    /// `func` must be a valid handler, usually the one generated by [`mesh_model_op!`].
+    #[doc(hidden)]
+    pub const unsafe fn from_raw(
+        opcode: u32,
+        min_len: usize,
+        func: RawMeshHandler,
+    ) -> MeshModelOp {
+        MeshModelOp(raw::bt_mesh_model_op {
+            opcode,
+            len: min_len as _,
+            func: Some(func),
+        })
+    }
+
+    /// A pointer to the list, for the model's `op` field.
+    pub const fn list_ptr(ops: &'static [MeshModelOp]) -> *const raw::bt_mesh_model_op {
+        ops.as_ptr() as *const raw::bt_mesh_model_op
+    }
+}
+
+impl fmt::Debug for MeshModelOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MeshModelOp(0x{:x})", self.0.opcode)
+    }
+}
+
+/// Call a Rust handler for a received message.  Used by [`mesh_model_op!`].
+///
+/// # Safety
+///
+/// The pointers must be those given to a model opcode handler.
+#[doc(hidden)]
+pub unsafe fn dispatch(
+    model: *const raw::bt_mesh_model,
+    ctx: *mut raw::bt_mesh_msg_ctx,
+    buf: *mut raw::net_buf_simple,
+    handler: MeshHandler,
+) -> c_int {
+    let model = &*(model as *const MeshModel);
+    let ctx = &*(ctx as *const MeshMsgCtx);
+    let data = if (*buf).data.is_null() {
+        &[][..]
+    } else {
+        core::slice::from_raw_parts((*buf).data, (*buf).len as usize)
+    };
+    handler(model, ctx, data);
+    0
+}
+
+/// Build a [`MeshModelOp`] that calls a Rust handler.
+///
+/// Takes the opcode, the minimum message length, and a [`MeshHandler`] function.
+#[macro_export]
+macro_rules! mesh_model_op {
+    ($opcode:expr, $min_len:expr, $handler:expr) => {{
+        unsafe extern "C" fn trampoline(
+            model: *const $crate::raw::bt_mesh_model,
+            ctx: *mut $crate::raw::bt_mesh_msg_ctx,
+            buf: *mut $crate::raw::net_buf_simple,
+        ) -> ::core::ffi::c_int {
+            $crate::bluetooth::mesh::dispatch(model, ctx, buf, $handler)
+        }
+        // SAFETY: The trampoline has the signature the stack expects.
+        unsafe { $crate::bluetooth::mesh::MeshModelOp::from_raw($opcode, $min_len, trampoline) }
+    }};
+}
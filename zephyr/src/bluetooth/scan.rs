@@ -0,0 +1,237 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scanning for advertisements.
+//!
+//! The observer role receives the advertisements of nearby devices.  [`BleScanner::start`] starts
+//! scanning, with a callback that receives each advertisement that passes the filters in the
+//! [`BleScanParams`].
+//!
+//! ```ignore
+//! fn found(addr: BleAddr, rssi: i8, data: &[u8]) {
+//!     printkln!("{} {}dBm, {} bytes", addr, rssi, data.len());
+//! }
+//!
+//! let params = BleScanParams::new().active(true).rssi_threshold(-70);
+//! let _scan = BleScanner::start(params, found)?;
+//! ```
+//!
+//! Zephyr can only run a single scan, so only one [`BleScanHandle`] can exist at a time.
+//!
+//! Requires `CONFIG_BT_OBSERVER`.
+
+use core::fmt;
+use core::mem;
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+use crate::sync::SpinMutex;
+
+use super::{BleAddr, BleUuid};
+
+/// The AD types that list service UUIDs.
+const AD_UUID16_SOME: u8 = 0x02;
+const AD_UUID16_ALL: u8 = 0x03;
+const AD_UUID128_SOME: u8 = 0x06;
+const AD_UUID128_ALL: u8 = 0x07;
+
+/// The callback for each advertisement found.
+pub type ScanCallback = fn(addr: BleAddr, rssi: i8, data: &[u8]);
+
+/// The parameters for a scan.
+#[derive(Debug, Clone, Copy)]
+pub struct BleScanParams {
+    active: bool,
+    filter_duplicates: bool,
+    interval: u16,
+    window: u16,
+    rssi_threshold: Option<i8>,
+    address: Option<BleAddr>,
+    uuid: Option<BleUuid>,
+}
+
+impl BleScanParams {
+    /// Passive scanning, at Zephyr's fast interval, with no filters.
+    pub fn new() -> BleScanParams {
+        BleScanParams {
+            active: false,
+            filter_duplicates: false,
+            interval: raw::BT_GAP_SCAN_FAST_INTERVAL as u16,
+            window: raw::BT_GAP_SCAN_FAST_WINDOW as u16,
+            rssi_threshold: None,
+            address: None,
+            uuid: None,
+        }
+    }
+
+    /// Request scan responses from the advertisers.
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    /// Have the controller only report the first advertisement from each device.
+    pub fn filter_duplicates(mut self, filter: bool) -> Self {
+        self.filter_duplicates = filter;
+        self
+    }
+
+    /// The scan interval and window, in units of 0.625 ms.  The window must not be larger than
+    /// the interval.
+    pub fn timing(mut self, interval: u16, window: u16) -> Self {
+        self.interval = interval;
+        self.window = window;
+        self
+    }
+
+    /// Only report advertisements received at or above this signal strength, in dBm.
+    pub fn rssi_threshold(mut self, rssi: i8) -> Self {
+        self.rssi_threshold = Some(rssi);
+        self
+    }
+
+    /// Only report advertisements from this address.
+    pub fn address(mut self, addr: BleAddr) -> Self {
+        self.address = Some(addr);
+        self
+    }
+
+    /// Only report advertisements that list this service UUID.
+    pub fn uuid(mut self, uuid: BleUuid) -> Self {
+        self.uuid = Some(uuid);
+        self
+    }
+
+    /// Returns true if an advertisement passes the filters.
+    fn matches(&self, addr: &BleAddr, rssi: i8, data: &[u8]) -> bool {
+        if self.rssi_threshold.is_some_and(|threshold| rssi < threshold) {
+            return false;
+        }
+        if self.address.is_some_and(|want| want != *addr) {
+            return false;
+        }
+        match self.uuid {
+            Some(uuid) => has_uuid(data, uuid),
+            None => true,
+        }
+    }
+}
+
+impl Default for BleScanParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns true if the advertising data lists the given service UUID.
+fn has_uuid(data: &[u8], uuid: BleUuid) -> bool {
+    let mut rest = data;
+    while let [len, tail @ ..] = rest {
+        let len = *len as usize;
+        if len == 0 || len > tail.len() {
+            break;
+        }
+        let (ad_type, value) = (tail[0], &tail[1..len]);
+        let found = match (ad_type, uuid) {
+            (AD_UUID16_SOME | AD_UUID16_ALL, BleUuid::Uuid16(want)) => value
+                .chunks_exact(2)
+                .any(|c| u16::from_le_bytes([c[0], c[1]]) == want),
+            (AD_UUID128_SOME | AD_UUID128_ALL, BleUuid::Uuid128(want)) => {
+                value.chunks_exact(16).any(|c| c == want)
+            }
+            _ => false,
+        };
+        if found {
+            return true;
+        }
+        rest = &tail[len..];
+    }
+    false
+}
+
+/// The running scan.  The Zephyr callback has no context argument, so this must be global.
+struct ScanState {
+    params: BleScanParams,
+    callback: ScanCallback,
+}
+
+static SCAN: SpinMutex<Option<ScanState>> = SpinMutex::new(None);
+
+/// Scanning for advertisements.
+pub struct BleScanner;
+
+impl BleScanner {
+    /// Start scanning.
+    ///
+    /// `callback` is called from the Bluetooth receive thread, for each advertisement that passes
+    /// the filters in `params`.  Scanning continues until the returned handle is dropped.
+    pub fn start(params: BleScanParams, callback: ScanCallback) -> ZResult<BleScanHandle> {
+        {
+            let mut scan = SCAN.lock().unwrap();
+            if scan.is_some() {
+                return Err(ZephyrError::Already);
+            }
+            *scan = Some(ScanState { params, callback });
+        }
+
+        // SAFETY: The parameters are plain data, the fields not set here are meant to be zero.
+        let mut param: raw::bt_le_scan_param = unsafe { mem::zeroed() };
+        param.type_ = if params.active {
+            raw::BT_LE_SCAN_TYPE_ACTIVE as _
+        } else {
+            raw::BT_LE_SCAN_TYPE_PASSIVE as _
+        };
+        if params.filter_duplicates {
+            param.options = raw::BT_LE_SCAN_OPT_FILTER_DUPLICATE as _;
+        }
+        param.interval = params.interval;
+        param.window = params.window;
+
+        let ret = unsafe { raw::bt_le_scan_start(&param, Some(device_found)) };
+        if let Err(err) = ok_or_errno(ret) {
+            *SCAN.lock().unwrap() = None;
+            return Err(err);
+        }
+        Ok(BleScanHandle { _private: () })
+    }
+}
+
+impl fmt::Debug for BleScanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BleScanner")
+    }
+}
+
+/// A running scan.  Scanning stops when this is dropped.
+#[derive(Debug)]
+pub struct BleScanHandle {
+    _private: (),
+}
+
+impl Drop for BleScanHandle {
+    fn drop(&mut self) {
+        unsafe {
+            raw::bt_le_scan_stop();
+        }
+        *SCAN.lock().unwrap() = None;
+    }
+}
+
+/// Called by the stack for each advertisement received.
+unsafe extern "C" fn device_found(
+    addr: *const raw::bt_addr_le_t,
+    rssi: i8,
+    _adv_type: u8,
+    buf: *mut raw::net_buf_simple,
+) {
+    // Copy the state out, so the callback doesn't run with the lock held.
+    let (params, callback) = match &*SCAN.lock().unwrap() {
+        Some(state) => (state.params, state.callback),
+        None => return,
+    };
+    let addr = BleAddr::from_raw(&*addr);
+    let data = core::slice::from_raw_parts((*buf).data, (*buf).len as usize);
+    if params.matches(&addr, rssi, data) {
+        callback(addr, rssi, data);
+    }
+}
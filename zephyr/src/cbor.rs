@@ -0,0 +1,490 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! CBOR encoding and decoding with TinyCBOR.
+//!
+//! These wrap the TinyCBOR library from Zephyr's tinycbor module, which uses buffer writers and
+//! readers, rather than plain pointers.  [`CborEncoder`] writes into a caller supplied buffer,
+//! and maps and arrays are written through the [`MapEncoder`] returned by
+//! [`begin_map`](CborWrite::begin_map), which closes the container when it is ended, or dropped.
+//!
+//! ```ignore
+//! let mut buf = [0u8; 64];
+//! let mut enc = CborEncoder::new(&mut buf);
+//! let mut map = enc.begin_map()?;
+//! map.write_str("interval")?;
+//! map.write_u32(100)?;
+//! map.end()?;
+//! let len = enc.len();
+//! ```
+//!
+//! [`CborDecoder`] reads items back, as a flat stream of [`CborValue`]s, with the contents of each
+//! map or array followed by a [`CborValue::End`].  Strings are borrowed from the buffer.
+//!
+//! Requires `CONFIG_TINYCBOR`.
+
+use core::ffi::c_char;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+
+use crate::raw;
+
+/// The deepest nesting of maps and arrays that the decoder follows.
+const MAX_DEPTH: usize = 8;
+
+/// An error encoding or decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CborError {
+    /// The buffer is too small for the encoded data.
+    OutOfMemory,
+    /// The data ended in the middle of an item.
+    UnexpectedEof,
+    /// The data is not valid CBOR.
+    Invalid,
+    /// A text string is not valid UTF-8.
+    InvalidUtf8,
+    /// Maps and arrays are nested more deeply than the decoder supports.
+    TooDeep,
+    /// The item is valid, but not supported here, such as a string in indefinite length chunks.
+    Unsupported,
+    /// Another TinyCBOR error.
+    Other(i32),
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::OutOfMemory => write!(f, "cbor: buffer too small"),
+            CborError::UnexpectedEof => write!(f, "cbor: unexpected end of data"),
+            CborError::Invalid => write!(f, "cbor: invalid data"),
+            CborError::InvalidUtf8 => write!(f, "cbor: invalid UTF-8"),
+            CborError::TooDeep => write!(f, "cbor: nested too deeply"),
+            CborError::Unsupported => write!(f, "cbor: unsupported item"),
+            CborError::Other(code) => write!(f, "cbor: error {}", code),
+        }
+    }
+}
+
+fn check(err: raw::CborError) -> Result<(), CborError> {
+    #[allow(non_upper_case_globals)]
+    match err {
+        raw::CborError_CborNoError => Ok(()),
+        raw::CborError_CborErrorOutOfMemory => Err(CborError::OutOfMemory),
+        raw::CborError_CborErrorUnexpectedEOF => Err(CborError::UnexpectedEof),
+        raw::CborError_CborErrorIllegalType
+        | raw::CborError_CborErrorIllegalNumber
+        | raw::CborError_CborErrorIllegalSimpleType
+        | raw::CborError_CborErrorUnknownType => Err(CborError::Invalid),
+        raw::CborError_CborErrorInvalidUtf8TextString => Err(CborError::InvalidUtf8),
+        err => Err(CborError::Other(err as i32)),
+    }
+}
+
+/// Writing CBOR items, to a [`CborEncoder`] or into a [`MapEncoder`].
+pub trait CborWrite {
+    /// The encoder to write to.
+    #[doc(hidden)]
+    fn raw_encoder(&mut self) -> *mut raw::CborEncoder;
+
+    /// Write an unsigned integer.
+    fn write_u32(&mut self, v: u32) -> Result<(), CborError> {
+        self.write_u64(v as u64)
+    }
+
+    /// Write an unsigned integer.
+    fn write_u64(&mut self, v: u64) -> Result<(), CborError> {
+        check(unsafe { raw::cbor_encode_uint(self.raw_encoder(), v) })
+    }
+
+    /// Write a signed integer.
+    fn write_i64(&mut self, v: i64) -> Result<(), CborError> {
+        check(unsafe { raw::cbor_encode_int(self.raw_encoder(), v) })
+    }
+
+    /// Write a boolean.
+    fn write_bool(&mut self, v: bool) -> Result<(), CborError> {
+        check(unsafe { raw::cbor_encode_boolean(self.raw_encoder(), v) })
+    }
+
+    /// Write a null.
+    fn write_null(&mut self) -> Result<(), CborError> {
+        check(unsafe { raw::cbor_encode_null(self.raw_encoder()) })
+    }
+
+    /// Write a text string.
+    fn write_str(&mut self, s: &str) -> Result<(), CborError> {
+        let ptr = s.as_ptr() as *const c_char;
+        check(unsafe { raw::cbor_encode_text_string(self.raw_encoder(), ptr, s.len()) })
+    }
+
+    /// Write a byte string.
+    fn write_bytes(&mut self, b: &[u8]) -> Result<(), CborError> {
+        check(unsafe { raw::cbor_encode_byte_string(self.raw_encoder(), b.as_ptr(), b.len()) })
+    }
+
+    /// Start a map.  Its keys and values are written, alternately, to the returned encoder.
+    fn begin_map(&mut self) -> Result<MapEncoder<'_>, CborError> {
+        MapEncoder::new(self.raw_encoder(), false)
+    }
+
+    /// Start an array.  Its items are written to the returned encoder.
+    fn begin_array(&mut self) -> Result<MapEncoder<'_>, CborError> {
+        MapEncoder::new(self.raw_encoder(), true)
+    }
+}
+
+/// An encoder, writing into a buffer.
+pub struct CborEncoder<'a> {
+    writer: raw::cbor_buf_writer,
+    enc: raw::CborEncoder,
+    start: *mut u8,
+    _buf: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> CborEncoder<'a> {
+    /// An encoder writing to the start of `buf`.
+    pub fn new(buf: &'a mut [u8]) -> CborEncoder<'a> {
+        // SAFETY: Both are initialized by TinyCBOR here.
+        let mut encoder = CborEncoder {
+            writer: unsafe { mem::zeroed() },
+            enc: unsafe { mem::zeroed() },
+            start: buf.as_mut_ptr(),
+            _buf: PhantomData,
+        };
+        unsafe {
+            raw::cbor_buf_writer_init(&mut encoder.writer, buf.as_mut_ptr(), buf.len());
+            raw::cbor_encoder_init(&mut encoder.enc, &mut encoder.writer.enc, 0);
+        }
+        encoder
+    }
+
+    /// The number of bytes written so far.
+    pub fn len(&mut self) -> usize {
+        unsafe { raw::cbor_buf_writer_buffer_size(&mut self.writer, self.start) }
+    }
+
+    /// Returns true if nothing has been written.
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CborWrite for CborEncoder<'_> {
+    fn raw_encoder(&mut self) -> *mut raw::CborEncoder {
+        // The encoder points at the writer, which moves with this struct.
+        self.enc.writer = &mut self.writer.enc;
+        &mut self.enc
+    }
+}
+
+impl fmt::Debug for CborEncoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CborEncoder")
+    }
+}
+
+/// An open map or array.  The container is closed by [`end`](Self::end), or when dropped.
+pub struct MapEncoder<'p> {
+    parent: *mut raw::CborEncoder,
+    enc: raw::CborEncoder,
+    closed: bool,
+    _parent: PhantomData<&'p mut ()>,
+}
+
+impl<'p> MapEncoder<'p> {
+    fn new(parent: *mut raw::CborEncoder, array: bool) -> Result<MapEncoder<'p>, CborError> {
+        // SAFETY: Initialized by TinyCBOR.
+        let mut enc: raw::CborEncoder = unsafe { mem::zeroed() };
+        // TinyCBOR's `CborIndefiniteLength`.
+        let len = usize::MAX;
+        check(unsafe {
+            if array {
+                raw::cbor_encoder_create_array(parent, &mut enc, len)
+            } else {
+                raw::cbor_encoder_create_map(parent, &mut enc, len)
+            }
+        })?;
+        Ok(MapEncoder {
+            parent,
+            enc,
+            closed: false,
+            _parent: PhantomData,
+        })
+    }
+
+    /// Close the container.
+    pub fn end(mut self) -> Result<(), CborError> {
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<(), CborError> {
+        self.closed = true;
+        check(unsafe { raw::cbor_encoder_close_container(self.parent, &self.enc) })
+    }
+}
+
+impl CborWrite for MapEncoder<'_> {
+    fn raw_encoder(&mut self) -> *mut raw::CborEncoder {
+        &mut self.enc
+    }
+}
+
+impl Drop for MapEncoder<'_> {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.close();
+        }
+    }
+}
+
+impl fmt::Debug for MapEncoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MapEncoder")
+    }
+}
+
+/// A decoded item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CborValue<'a> {
+    /// A non-negative integer.
+    Unsigned(u64),
+    /// A negative integer.
+    Negative(i64),
+    /// A boolean.
+    Bool(bool),
+    /// Null.
+    Null,
+    /// Undefined.
+    Undefined,
+    /// A floating point number.
+    Float(f64),
+    /// A text string.
+    Text(&'a str),
+    /// A byte string.
+    Bytes(&'a [u8]),
+    /// A tag, which applies to the next item.
+    Tag(u64),
+    /// The start of a map, with its number of pairs if known.  The keys and values follow,
+    /// alternately, then an [`End`](Self::End).
+    Map(Option<usize>),
+    /// The start of an array, with its length if known.  The items follow, then an
+    /// [`End`](Self::End).
+    Array(Option<usize>),
+    /// The end of the innermost map or array.
+    End,
+}
+
+/// A decoder, reading from a buffer.
+pub struct CborDecoder<'a> {
+    buf: &'a [u8],
+    reader: raw::cbor_buf_reader,
+    parser: raw::CborParser,
+}
+
+impl<'a> CborDecoder<'a> {
+    /// A decoder reading from `buf`.
+    pub fn new(buf: &'a [u8]) -> CborDecoder<'a> {
+        CborDecoder {
+            buf,
+            // SAFETY: Both are initialized by `iter`, before they are used.
+            reader: unsafe { mem::zeroed() },
+            parser: unsafe { mem::zeroed() },
+        }
+    }
+
+    /// Iterate over the items, from the start of the buffer.
+    pub fn iter(&mut self) -> CborIter<'_, 'a> {
+        // SAFETY: Initialized by TinyCBOR.
+        let mut stack: [raw::CborValue; MAX_DEPTH] = unsafe { mem::zeroed() };
+        // The parser points at the reader, and the values at the parser, so these are set up here,
+        // where they will stay while borrowed by the iterator.
+        let err = unsafe {
+            raw::cbor_buf_reader_init(&mut self.reader, self.buf.as_ptr(), self.buf.len());
+            raw::cbor_parser_init(&mut self.reader.r, 0, &mut self.parser, &mut stack[0])
+        };
+        CborIter {
+            buf: self.buf,
+            stack,
+            depth: 0,
+            error: check(err).err(),
+            done: false,
+            _decoder: PhantomData,
+        }
+    }
+}
+
+impl fmt::Debug for CborDecoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CborDecoder({} bytes)", self.buf.len())
+    }
+}
+
+/// The items of a [`CborDecoder`].
+///
+/// After an error, the error is returned once, and the iteration ends.
+pub struct CborIter<'d, 'a> {
+    buf: &'a [u8],
+    stack: [raw::CborValue; MAX_DEPTH],
+    depth: usize,
+    error: Option<CborError>,
+    done: bool,
+    _decoder: PhantomData<&'d mut CborDecoder<'a>>,
+}
+
+impl<'a> CborIter<'_, 'a> {
+    fn next_value(&mut self) -> Result<Option<CborValue<'a>>, CborError> {
+        let depth = self.depth;
+        let (parents, rest) = self.stack.split_at_mut(depth);
+        let (it, children) = rest.split_first_mut().unwrap();
+        unsafe {
+            if raw::cbor_value_at_end(it) {
+                if depth == 0 {
+                    return Ok(None);
+                }
+                check(raw::cbor_value_leave_container(&mut parents[depth - 1], it))?;
+                self.depth -= 1;
+                return Ok(Some(CborValue::End));
+            }
+
+            #[allow(non_upper_case_globals)]
+            let value = match raw::cbor_value_get_type(it) {
+                raw::CborType_CborIntegerType => {
+                    let value = if raw::cbor_value_is_unsigned_integer(it) {
+                        let mut v = 0u64;
+                        check(raw::cbor_value_get_uint64(it, &mut v))?;
+                        CborValue::Unsigned(v)
+                    } else {
+                        let mut v = 0i64;
+                        check(raw::cbor_value_get_int64(it, &mut v))?;
+                        CborValue::Negative(v)
+                    };
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    value
+                }
+                raw::CborType_CborBooleanType => {
+                    let mut v = false;
+                    check(raw::cbor_value_get_boolean(it, &mut v))?;
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    CborValue::Bool(v)
+                }
+                raw::CborType_CborNullType => {
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    CborValue::Null
+                }
+                raw::CborType_CborUndefinedType => {
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    CborValue::Undefined
+                }
+                raw::CborType_CborDoubleType => {
+                    let mut v = 0f64;
+                    check(raw::cbor_value_get_double(it, &mut v))?;
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    CborValue::Float(v)
+                }
+                raw::CborType_CborFloatType => {
+                    let mut v = 0f32;
+                    check(raw::cbor_value_get_float(it, &mut v))?;
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    CborValue::Float(v as f64)
+                }
+                raw::CborType_CborTagType => {
+                    let mut v = 0u64;
+                    check(raw::cbor_value_get_tag(it, &mut v))?;
+                    check(raw::cbor_value_advance_fixed(it))?;
+                    CborValue::Tag(v)
+                }
+                kind @ (raw::CborType_CborTextStringType | raw::CborType_CborByteStringType) => {
+                    let bytes = string_bytes(self.buf, it)?;
+                    check(raw::cbor_value_advance(it))?;
+                    if kind == raw::CborType_CborTextStringType {
+                        let text = core::str::from_utf8(bytes);
+                        CborValue::Text(text.map_err(|_| CborError::InvalidUtf8)?)
+                    } else {
+                        CborValue::Bytes(bytes)
+                    }
+                }
+                kind @ (raw::CborType_CborMapType | raw::CborType_CborArrayType) => {
+                    let map = kind == raw::CborType_CborMapType;
+                    let mut len = 0usize;
+                    let len = if !raw::cbor_value_is_length_known(it) {
+                        None
+                    } else if map {
+                        check(raw::cbor_value_get_map_length(it, &mut len))?;
+                        Some(len)
+                    } else {
+                        check(raw::cbor_value_get_array_length(it, &mut len))?;
+                        Some(len)
+                    };
+                    let child = children.first_mut().ok_or(CborError::TooDeep)?;
+                    check(raw::cbor_value_enter_container(it, child))?;
+                    self.depth += 1;
+                    if map {
+                        CborValue::Map(len)
+                    } else {
+                        CborValue::Array(len)
+                    }
+                }
+                _ => return Err(CborError::Unsupported),
+            };
+            Ok(Some(value))
+        }
+    }
+}
+
+/// The contents of a definite length string, borrowed from the buffer.
+///
+/// # Safety
+///
+/// `it` must be a string value from a parser reading `buf`.
+unsafe fn string_bytes<'a>(buf: &'a [u8], it: &raw::CborValue) -> Result<&'a [u8], CborError> {
+    if !raw::cbor_value_is_length_known(it) {
+        return Err(CborError::Unsupported);
+    }
+    let mut len = 0usize;
+    check(raw::cbor_value_get_string_length(it, &mut len))?;
+    // The value's offset is that of the initial byte, whose low bits give the size of the length
+    // that follows it.
+    let offset = it.offset as usize;
+    let header = match buf.get(offset).ok_or(CborError::UnexpectedEof)? & 0x1f {
+        0..=23 => 1,
+        24 => 2,
+        25 => 3,
+        26 => 5,
+        27 => 9,
+        _ => return Err(CborError::Invalid),
+    };
+    let start = offset + header;
+    buf.get(start..start + len).ok_or(CborError::UnexpectedEof)
+}
+
+impl<'a> Iterator for CborIter<'_, 'a> {
+    type Item = Result<CborValue<'a>, CborError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.error.take() {
+            Some(err) => Err(err),
+            None => self.next_value(),
+        };
+        match result {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CborIter<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CborIter")
+    }
+}
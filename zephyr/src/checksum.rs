@@ -0,0 +1,68 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checksums with Zephyr's CRC library.
+//!
+//! The one-shot functions compute the CRC of a whole buffer.  For data that is processed in
+//! chunks, such as an image being read back from flash, [`Crc32`] carries the CRC from one chunk
+//! to the next.
+//!
+//! ```ignore
+//! let mut crc = Crc32::new();
+//! while let Some(chunk) = next_chunk() {
+//!     crc.update(chunk);
+//! }
+//! assert_eq!(crc.finalize(), expected);
+//! ```
+//!
+//! Requires `CONFIG_CRC`.
+
+use core::ffi::c_void;
+
+use crate::raw;
+
+/// The CRC-32 used by Ethernet, zlib and PNG, among others.
+pub fn crc32_ieee(data: &[u8]) -> u32 {
+    unsafe { raw::crc32_ieee(data.as_ptr(), data.len()) }
+}
+
+/// The CRC-16-CCITT, with a seed of zero, as used by XMODEM and Bluetooth.
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    unsafe { raw::crc16_ccitt(0, data.as_ptr(), data.len()) }
+}
+
+/// The CRC-8 used by the RObust Header Compression protocol, with an initial value of `0xff`.
+pub fn crc8_rohc(data: &[u8]) -> u8 {
+    unsafe { raw::crc8_rohc(0xff, data.as_ptr() as *const c_void, data.len()) }
+}
+
+/// A CRC-32 computed over data given in chunks.
+///
+/// The result is the same as [`crc32_ieee`] over all of the chunks together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// A CRC of no data.
+    pub const fn new() -> Crc32 {
+        Crc32 { crc: 0 }
+    }
+
+    /// Add the next chunk of data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.crc = unsafe { raw::crc32_ieee_update(self.crc, data.as_ptr(), data.len()) };
+    }
+
+    /// The CRC of all of the data given so far.
+    pub fn finalize(&self) -> u32 {
+        self.crc
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Collections that don't need allocation.
+//!
+//! These have a fixed capacity, given as a const generic, and can be placed in statics or on the
+//! stack, for use in code that runs without `CONFIG_RUST_ALLOC`.  The [`heapless`] collections are
+//! re-exported here, so applications don't need their own dependency on it, along with aliases
+//! for strings of the sizes commonly needed.
+
+mod priority_queue;
+
+pub use arrayvec::CapacityError;
+pub use heapless::{Deque, FnvIndexMap, String, Vec};
+pub use priority_queue::StaticPriorityQueue;
+
+/// A string for short text, such as a name, or a formatted number or message.
+pub type SmallString = String<64>;
+
+/// A string large enough for a file system path.
+pub type PathBuf = String<256>;
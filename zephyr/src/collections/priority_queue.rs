@@ -0,0 +1,150 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A priority queue with a fixed capacity.
+
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use super::CapacityError;
+
+/// A priority queue holding up to `CAP` items, kept as a binary max-heap in an array.
+///
+/// [`pop`](Self::pop) returns the greatest item, as with `alloc::collections::BinaryHeap`, so
+/// wrap items in [`core::cmp::Reverse`] to get the least first.  Items of equal priority are not
+/// returned in any particular order.
+pub struct StaticPriorityQueue<T: Ord, const CAP: usize> {
+    items: [MaybeUninit<T>; CAP],
+    /// The number of items, which are all at the start of `items`.
+    len: usize,
+}
+
+impl<T: Ord, const CAP: usize> StaticPriorityQueue<T, CAP> {
+    /// An empty queue.
+    pub const fn new() -> Self {
+        StaticPriorityQueue {
+            // SAFETY: An array of `MaybeUninit` needs no initialization.
+            items: unsafe { MaybeUninit::<[MaybeUninit<T>; CAP]>::uninit().assume_init() },
+            len: 0,
+        }
+    }
+
+    /// Add an item.  If the queue is full, the item is given back in the error.
+    pub fn push(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        if self.len == CAP {
+            return Err(CapacityError::new(item));
+        }
+        self.items[self.len].write(item);
+        self.len += 1;
+        self.sift_up(self.len - 1);
+        Ok(())
+    }
+
+    /// Remove and return the greatest item.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.items.swap(0, self.len);
+        // SAFETY: The item was at index `len - 1` before the decrement, so was initialized, and is
+        // now outside of the queue, so will not be read again.
+        let item = unsafe { self.items[self.len].assume_init_read() };
+        self.sift_down(0);
+        Some(item)
+    }
+
+    /// The greatest item, without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        if self.len == 0 {
+            None
+        } else {
+            Some(self.get(0))
+        }
+    }
+
+    /// The number of items in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the queue holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the queue can't take any more items.
+    pub fn is_full(&self) -> bool {
+        self.len == CAP
+    }
+
+    /// Remove, and drop, all of the items.
+    pub fn clear(&mut self) {
+        let len = self.len;
+        // Set the length first, so a panic in a drop can't lead to a double drop.
+        self.len = 0;
+        // SAFETY: The first `len` items are initialized, and no longer part of the queue.
+        unsafe {
+            let items = ptr::slice_from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, len);
+            ptr::drop_in_place(items);
+        }
+    }
+
+    fn get(&self, index: usize) -> &T {
+        debug_assert!(index < self.len);
+        // SAFETY: All items below `len` are initialized.
+        unsafe { self.items[index].assume_init_ref() }
+    }
+
+    /// Move the item at `index` up until its parent is not less than it.
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.get(index) <= self.get(parent) {
+                break;
+            }
+            self.items.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    /// Move the item at `index` down until neither child is greater than it.
+    fn sift_down(&mut self, mut index: usize) {
+        loop {
+            let left = 2 * index + 1;
+            if left >= self.len {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.len && self.get(right) > self.get(left) {
+                right
+            } else {
+                left
+            };
+            if self.get(index) >= self.get(child) {
+                break;
+            }
+            self.items.swap(index, child);
+            index = child;
+        }
+    }
+}
+
+impl<T: Ord, const CAP: usize> Default for StaticPriorityQueue<T, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, const CAP: usize> Drop for StaticPriorityQueue<T, CAP> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Ord, const CAP: usize> fmt::Debug for StaticPriorityQueue<T, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StaticPriorityQueue({}/{})", self.len, CAP)
+    }
+}
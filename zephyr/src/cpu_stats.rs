@@ -0,0 +1,90 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! CPU usage of threads.
+//!
+//! With `CONFIG_SCHED_THREAD_USAGE`, Zephyr counts the cycles each thread spends running, and the
+//! cycles spent by all threads together.  The counts are in the units of the cycle counter,
+//! `k_cycle_get_32`, and start from boot, so usage over an interval is found from the difference
+//! between two readings, with [`RuntimeStats::since`].
+//!
+//! Requires `CONFIG_SCHED_THREAD_USAGE`.  The idle cycles are also counted with
+//! `CONFIG_SCHED_THREAD_USAGE_ALL`, and are zero otherwise.
+
+use core::mem;
+
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+use crate::sys::thread::Thread;
+
+/// Cycle counts, for one thread, or for all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuntimeStats {
+    /// All cycles counted, including any spent idle.
+    pub execution_cycles: u64,
+    /// The cycles spent running, not counting idle.
+    pub total_cycles: u64,
+    /// The cycles spent idle.  Only counted for all threads together.
+    pub idle_cycles: u64,
+}
+
+/// The stats, summed over all threads.
+pub type AllStats = RuntimeStats;
+
+impl RuntimeStats {
+    /// The cycles used by `thread`.
+    pub fn get(thread: &Thread) -> ZResult<RuntimeStats> {
+        let mut stats: raw::k_thread_runtime_stats_t = unsafe { mem::zeroed() };
+        let tid = thread.id().as_raw();
+        ok_or_errno(unsafe { raw::k_thread_runtime_stats_get(tid, &mut stats) })?;
+        Ok(Self::from_raw(&stats))
+    }
+
+    /// The cycles used by all threads, and spent idle.
+    pub fn get_all() -> ZResult<AllStats> {
+        let mut stats: raw::k_thread_runtime_stats_t = unsafe { mem::zeroed() };
+        ok_or_errno(unsafe { raw::k_thread_runtime_stats_all_get(&mut stats) })?;
+        Ok(Self::from_raw(&stats))
+    }
+
+    fn from_raw(stats: &raw::k_thread_runtime_stats_t) -> RuntimeStats {
+        RuntimeStats {
+            execution_cycles: stats.execution_cycles,
+            total_cycles: stats.total_cycles,
+            #[cfg(CONFIG_SCHED_THREAD_USAGE_ALL)]
+            idle_cycles: stats.idle_cycles,
+            #[cfg(not(CONFIG_SCHED_THREAD_USAGE_ALL))]
+            idle_cycles: 0,
+        }
+    }
+
+    /// The cycles counted between `earlier` and these stats.
+    pub fn since(&self, earlier: &RuntimeStats) -> RuntimeStats {
+        RuntimeStats {
+            execution_cycles: self.execution_cycles.wrapping_sub(earlier.execution_cycles),
+            total_cycles: self.total_cycles.wrapping_sub(earlier.total_cycles),
+            idle_cycles: self.idle_cycles.wrapping_sub(earlier.idle_cycles),
+        }
+    }
+
+    /// The percentage of the counted cycles that were not idle.
+    ///
+    /// This is the CPU load, for stats from [`get_all`](Self::get_all).  For a single thread,
+    /// which isn't counted while idle, use [`share_of`](Self::share_of) instead.
+    pub fn cpu_utilization_pct(&self) -> u8 {
+        pct(self.total_cycles, self.execution_cycles)
+    }
+
+    /// The percentage of all of the cycles in `all` that were used by this thread.
+    pub fn share_of(&self, all: &AllStats) -> u8 {
+        pct(self.total_cycles, all.execution_cycles)
+    }
+}
+
+fn pct(part: u64, whole: u64) -> u8 {
+    if whole == 0 {
+        0
+    } else {
+        (part.min(whole) as u128 * 100 / whole as u128) as u8
+    }
+}
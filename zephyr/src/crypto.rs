@@ -0,0 +1,87 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cryptographic hashes.
+//!
+//! With `CONFIG_MBEDTLS_PSA_CRYPTO_C`, hashing goes through the PSA Crypto API, which uses a
+//! hardware accelerator when a driver for one is configured, and mbed TLS otherwise.  Without it,
+//! a small software implementation is used, so the hashes are available on any build, such as
+//! for checking an image that has been downloaded.
+//!
+//! With `CONFIG_MBEDTLS_PKCS5_C`, [`pbkdf2_hmac_sha256`] derives keys from passwords, with mbed
+//! TLS.
+
+#[cfg(CONFIG_MBEDTLS_PSA_CRYPTO_C)]
+mod psa;
+#[cfg(not(CONFIG_MBEDTLS_PSA_CRYPTO_C))]
+mod soft;
+
+#[cfg(CONFIG_MBEDTLS_PSA_CRYPTO_C)]
+use psa::Sha256State;
+#[cfg(not(CONFIG_MBEDTLS_PSA_CRYPTO_C))]
+use soft::Sha256State;
+
+#[cfg(CONFIG_MBEDTLS_PKCS5_C)]
+mod pbkdf2;
+
+#[cfg(CONFIG_MBEDTLS_PKCS5_C)]
+pub use pbkdf2::pbkdf2_hmac_sha256;
+
+/// An error from the crypto library, with its negative mbed TLS error code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CryptoError(pub i32);
+
+impl core::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "crypto: error -0x{:04x}", self.0.unsigned_abs())
+    }
+}
+
+/// The length of a SHA-256 digest.
+pub const SHA256_LEN: usize = 32;
+
+/// The SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; SHA256_LEN] {
+    let mut sha = Sha256::new();
+    sha.update(data);
+    sha.finalize()
+}
+
+/// A SHA-256 digest computed over data given in chunks.
+///
+/// With the PSA backend, a failure from the crypto library, which would mean it is misconfigured,
+/// panics.
+pub struct Sha256 {
+    state: Sha256State,
+}
+
+impl Sha256 {
+    /// Start a new digest.
+    pub fn new() -> Sha256 {
+        Sha256 {
+            state: Sha256State::new(),
+        }
+    }
+
+    /// Add the next chunk of data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    /// The digest of all of the data given.
+    pub fn finalize(self) -> [u8; SHA256_LEN] {
+        self.state.finalize()
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Sha256 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sha256")
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Key derivation with PBKDF2, from PKCS #5.
+
+use super::CryptoError;
+use crate::raw;
+
+/// Derive a key from `password` and `salt`, filling all of `output`, with PBKDF2 using
+/// HMAC-SHA-256.
+///
+/// More `iterations` make the key slower to guess, and slower to derive; a few thousand takes a
+/// noticeable time on a small microcontroller.
+pub fn pbkdf2_hmac_sha256(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    output: &mut [u8],
+) -> Result<(), CryptoError> {
+    let key_len = u32::try_from(output.len()).map_err(|_| CryptoError(BAD_INPUT_DATA))?;
+    let ret = unsafe {
+        raw::mbedtls_pkcs5_pbkdf2_hmac_ext(
+            raw::mbedtls_md_type_t_MBEDTLS_MD_SHA256,
+            password.as_ptr(),
+            password.len(),
+            salt.as_ptr(),
+            salt.len(),
+            iterations,
+            key_len,
+            output.as_mut_ptr(),
+        )
+    };
+    if ret != 0 {
+        Err(CryptoError(ret))
+    } else {
+        Ok(())
+    }
+}
+
+/// `MBEDTLS_ERR_PKCS5_BAD_INPUT_DATA`.
+const BAD_INPUT_DATA: i32 = -0x2f80;
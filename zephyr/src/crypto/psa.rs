@@ -0,0 +1,67 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hashing with the PSA Crypto API.
+
+use core::mem;
+
+use super::SHA256_LEN;
+use crate::raw;
+
+const PSA_SUCCESS: raw::psa_status_t = 0;
+
+fn check(status: raw::psa_status_t, what: &str) {
+    if status != PSA_SUCCESS {
+        panic!("psa {} failed: {}", what, status);
+    }
+}
+
+pub(super) struct Sha256State {
+    op: raw::psa_hash_operation_t,
+    /// Whether the operation still needs to be aborted, when it is dropped before finishing.
+    active: bool,
+}
+
+impl Sha256State {
+    pub(super) fn new() -> Sha256State {
+        // SAFETY: A zeroed operation is the initial state, as given by `PSA_HASH_OPERATION_INIT`.
+        let mut op: raw::psa_hash_operation_t = unsafe { mem::zeroed() };
+        unsafe {
+            // Initializing more than once is allowed, and does nothing.
+            check(raw::psa_crypto_init(), "init");
+            check(raw::psa_hash_setup(&mut op, raw::ZR_PSA_ALG_SHA_256), "hash setup");
+        }
+        Sha256State { op, active: true }
+    }
+
+    pub(super) fn update(&mut self, data: &[u8]) {
+        check(
+            unsafe { raw::psa_hash_update(&mut self.op, data.as_ptr(), data.len()) },
+            "hash update",
+        );
+    }
+
+    pub(super) fn finalize(mut self) -> [u8; SHA256_LEN] {
+        let mut digest = [0u8; SHA256_LEN];
+        let mut len = 0;
+        // A finished operation is back in the initial state, so doesn't need aborting.
+        self.active = false;
+        check(
+            unsafe {
+                raw::psa_hash_finish(&mut self.op, digest.as_mut_ptr(), digest.len(), &mut len)
+            },
+            "hash finish",
+        );
+        digest
+    }
+}
+
+impl Drop for Sha256State {
+    fn drop(&mut self) {
+        if self.active {
+            unsafe {
+                raw::psa_hash_abort(&mut self.op);
+            }
+        }
+    }
+}
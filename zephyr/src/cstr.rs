@@ -0,0 +1,37 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Static C strings.
+//!
+//! Many Zephyr APIs take a `*const c_char`, or in the Rust wrappers, a `&'static CStr`.  For
+//! literals, the `c"text"` syntax handles this.  The [`cstr!`] macro extends this to anything that
+//! expands to a string literal, such as `concat!` or `env!`, while still producing the string at
+//! compile time.
+//!
+//! On deduplication: it is tempting to place these strings in a custom `.rodata.cstr.*` section so
+//! that the linker can merge them.  However, the linker only merges identical strings in sections
+//! that are marked as mergeable (`SHF_MERGE | SHF_STRINGS`), which `#[link_section]` has no way of
+//! requesting.  Instead, the string here is left as an anonymous constant, which LLVM already emits
+//! into the mergeable `.rodata.str1.1` sections, so identical strings from different crates are
+//! merged by the linker.
+//!
+//! [`cstr!`]: crate::cstr
+
+/// Produce a `&'static CStr` from a string literal, or a macro that expands to one.
+///
+/// ```ignore
+/// let name: &'static CStr = zephyr::cstr!(concat!("worker-", env!("CARGO_PKG_NAME")));
+/// ```
+///
+/// A string containing an interior NUL is a compile time error.
+#[macro_export]
+macro_rules! cstr {
+    ($text:expr) => {{
+        const CSTR: &::core::ffi::CStr =
+            match ::core::ffi::CStr::from_bytes_with_nul(concat!($text, "\0").as_bytes()) {
+                Ok(text) => text,
+                Err(_) => panic!("cstr! string contains an interior NUL"),
+            };
+        CSTR
+    }};
+}
@@ -0,0 +1,60 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Debugging aids.
+//!
+//! With `CONFIG_INIT_STACKS`, Zephyr fills each thread stack with a known value when the thread is
+//! created, so the amount of the stack a thread has used can be found by looking for the deepest
+//! byte that no longer has that value.  This measures the most used since the thread started, not
+//! the current depth.
+//!
+//! Requires `CONFIG_INIT_STACKS` and `CONFIG_THREAD_STACK_INFO`.
+
+use crate::printkln;
+use crate::raw;
+use crate::sys::thread::Thread;
+
+/// The number of bytes of its stack that `thread` has used at most, or `None` if it could not be
+/// found, such as when the thread has not been started.
+pub fn thread_stack_usage(thread: &Thread) -> Option<usize> {
+    let (size, unused) = stack_space(thread)?;
+    Some(size - unused)
+}
+
+/// Check that `thread` still has at least `min_free` bytes of stack that have never been used.
+///
+/// Prints a warning, and returns false, when it does not.  Also returns false if the usage could
+/// not be found.
+pub fn check_stack_headroom(thread: &Thread, min_free: usize) -> bool {
+    let tid = thread.id().as_raw();
+    let Some((size, unused)) = stack_space(thread) else {
+        printkln!("warning: thread {:?}: stack usage unavailable", tid);
+        return false;
+    };
+    if unused < min_free {
+        printkln!(
+            "warning: thread {:?}: {} of {} stack bytes used, {} free, wanted {}",
+            tid,
+            size - unused,
+            size,
+            unused,
+            min_free
+        );
+        return false;
+    }
+    true
+}
+
+/// The size of the thread's stack, and how much of it has never been used.
+fn stack_space(thread: &Thread) -> Option<(usize, usize)> {
+    let tid = thread.id().as_raw();
+    let mut unused = 0;
+    // SAFETY: The thread object is static, and the call only reads its stack.
+    let ret = unsafe { raw::k_thread_stack_space_get(tid, &mut unused) };
+    if ret != 0 {
+        return None;
+    }
+    // SAFETY: The stack info is set when the thread is created, and not changed after.
+    let size = unsafe { (*tid).stack_info.size };
+    Some((size, unused))
+}
@@ -10,8 +10,25 @@
 
 use crate::sync::atomic::{AtomicBool, Ordering};
 
+pub mod charger;
+pub mod comparator;
 pub mod flash;
+pub mod fuel_gauge;
+pub mod gnss;
 pub mod gpio;
+pub mod haptics;
+pub mod i2c;
+pub mod i3c;
+pub mod ipc;
+pub mod led_strip;
+pub mod lora;
+pub mod mdio;
+pub mod sdhc;
+pub mod spi;
+pub mod tcpc;
+pub mod uart;
+pub mod video;
+pub mod w1;
 
 // Allow dead code, because it isn't required for a given build to have any devices.
 /// Device uniqueness.
@@ -0,0 +1,99 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for battery chargers.
+//!
+//! The charger API requires `CONFIG_CHARGER`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// A battery charger.
+pub struct ChargerDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The charger drivers can be called from any thread.
+unsafe impl Send for ChargerDevice {}
+
+impl ChargerDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<ChargerDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(ChargerDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for ChargerDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ChargerDevice({:p})", self.device)
+    }
+}
+
+/// What the charger is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargerStatus {
+    /// The battery is powering the system.
+    Discharging,
+    /// The battery is being charged.
+    Charging,
+    /// The battery is fully charged.
+    Full,
+    /// There is external power, but the battery is not being charged.
+    NotCharging,
+    /// The charger does not know.
+    Unknown,
+}
+
+#[cfg(CONFIG_CHARGER)]
+mod api {
+    use core::mem;
+
+    use super::{ChargerDevice, ChargerStatus};
+    use crate::error::{ok_or_errno, ok_or_errno_val, ZResult};
+    use crate::raw;
+
+    impl ChargerDevice {
+        fn get_prop(&self, prop: u32) -> ZResult<raw::charger_propval> {
+            // SAFETY: The value is a union of plain data, filled in by the call.
+            let mut val: raw::charger_propval = unsafe { mem::zeroed() };
+            let ret = unsafe { raw::charger_get_prop(self.device, prop as _, &mut val) };
+            ok_or_errno_val(ret, val)
+        }
+
+        /// Whether the battery is charging.
+        pub fn get_status(&self) -> ZResult<ChargerStatus> {
+            let val = self.get_prop(raw::charger_property_CHARGER_PROP_STATUS)?;
+            // SAFETY: The status property sets the status field.
+            Ok(match unsafe { val.status } {
+                raw::charger_status_CHARGER_STATUS_DISCHARGING => ChargerStatus::Discharging,
+                raw::charger_status_CHARGER_STATUS_CHARGING => ChargerStatus::Charging,
+                raw::charger_status_CHARGER_STATUS_FULL => ChargerStatus::Full,
+                raw::charger_status_CHARGER_STATUS_NOT_CHARGING => ChargerStatus::NotCharging,
+                _ => ChargerStatus::Unknown,
+            })
+        }
+
+        /// The charge current the charger is set to, in microamps.
+        pub fn get_charge_current_ua(&self) -> ZResult<i32> {
+            let val = self.get_prop(raw::charger_property_CHARGER_PROP_CONSTANT_CHARGE_CURRENT_UA)?;
+            // SAFETY: The charge current property sets this field.
+            Ok(unsafe { val.const_charge_current_ua } as i32)
+        }
+
+        /// Enable, or disable, charging.
+        pub fn enable(&mut self, on: bool) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::charger_charge_enable(self.device, on) })
+        }
+    }
+}
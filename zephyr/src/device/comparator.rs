@@ -0,0 +1,193 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for analog comparators.
+//!
+//! A comparator compares two analog inputs, and its output says which is higher.  A trigger can be
+//! set on the edges of the output, to detect a threshold being crossed, such as an over-voltage,
+//! or a zero crossing.
+//!
+//! The inputs, and any reference voltage, are chosen in the devicetree, as they vary too much
+//! between comparators to have a common API.  A comparator whose driver can switch between input
+//! channels at runtime can also be given one in its [`CompConfig`].
+//!
+//! The comparator API requires `CONFIG_COMPARATOR`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// An analog comparator.
+pub struct CompDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The comparator drivers can be called from any thread.
+unsafe impl Send for CompDevice {}
+
+impl CompDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<CompDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(CompDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for CompDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CompDevice({:p})", self.device)
+    }
+}
+
+#[cfg(CONFIG_COMPARATOR)]
+pub use api::*;
+
+#[cfg(CONFIG_COMPARATOR)]
+mod api {
+    use core::ffi::c_void;
+
+    use super::CompDevice;
+    use crate::error::{ok_or_errno, ok_or_errno_val, ZResult, ZephyrError};
+    use crate::raw;
+    use crate::sync::SpinMutex;
+
+    /// The most comparators with a trigger callback.
+    const MAX_CALLBACKS: usize = 4;
+
+    /// The trigger callbacks, by device.
+    static CALLBACKS: SpinMutex<heapless::Vec<(usize, fn()), MAX_CALLBACKS>> =
+        SpinMutex::new(heapless::Vec::new());
+
+    /// Which edges of the output trigger.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CompTrigger {
+        /// No trigger.
+        None,
+        /// The output going high.
+        RisingEdge,
+        /// The output going low.
+        FallingEdge,
+        /// Either edge.
+        BothEdges,
+    }
+
+    /// The configuration of a comparator.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CompConfig {
+        /// The edges that trigger.
+        pub trigger: CompTrigger,
+        /// The input channel to compare, or `None` to keep the input from the devicetree.
+        ///
+        /// Zephyr's comparator API has no common call to select an input, so this fails with
+        /// `ENOTSUP` on drivers that can't change it at runtime.
+        pub input: Option<u8>,
+    }
+
+    impl CompConfig {
+        /// A configuration that triggers on `trigger`, with the input from the devicetree.
+        pub const fn new(trigger: CompTrigger) -> CompConfig {
+            CompConfig {
+                trigger,
+                input: None,
+            }
+        }
+
+        /// The same configuration, comparing input channel `input`.
+        pub const fn with_input(self, input: u8) -> CompConfig {
+            CompConfig {
+                input: Some(input),
+                ..self
+            }
+        }
+    }
+
+    impl CompDevice {
+        /// Apply `config`.
+        ///
+        /// The input is checked first, so a configuration that can't be applied changes nothing.
+        /// Setting a trigger clears any trigger already pending.
+        pub fn set_config(&mut self, config: CompConfig) -> ZResult<()> {
+            if config.input.is_some() {
+                return Err(ZephyrError::NotSupported);
+            }
+            let trigger = match config.trigger {
+                CompTrigger::None => raw::comparator_trigger_COMPARATOR_TRIGGER_NONE,
+                CompTrigger::RisingEdge => raw::comparator_trigger_COMPARATOR_TRIGGER_RISING_EDGE,
+                CompTrigger::FallingEdge => raw::comparator_trigger_COMPARATOR_TRIGGER_FALLING_EDGE,
+                CompTrigger::BothEdges => raw::comparator_trigger_COMPARATOR_TRIGGER_BOTH_EDGES,
+            };
+            ok_or_errno(unsafe { raw::comparator_set_trigger(self.device, trigger) })
+        }
+
+        /// The current output, true when the positive input is higher.
+        pub fn get_output(&self) -> ZResult<bool> {
+            let ret = unsafe { raw::comparator_get_output(self.device) };
+            ok_or_errno_val(ret, ret != 0)
+        }
+
+        /// Returns true if a trigger has happened since the last check, clearing it.
+        ///
+        /// Triggers only stay pending while there is no callback.
+        pub fn trigger_is_pending(&mut self) -> ZResult<bool> {
+            let ret = unsafe { raw::comparator_trigger_is_pending(self.device) };
+            ok_or_errno_val(ret, ret != 0)
+        }
+
+        /// Call `cb` on each trigger, from interrupt context.
+        ///
+        /// Setting a callback again replaces the earlier one.
+        pub fn set_trigger_callback(&mut self, cb: fn()) -> ZResult<()> {
+            {
+                let mut callbacks = CALLBACKS.lock().unwrap();
+                let key = self.device as usize;
+                if let Some(entry) = callbacks.iter_mut().find(|(dev, _)| *dev == key) {
+                    entry.1 = cb;
+                } else {
+                    callbacks
+                        .push((key, cb))
+                        .map_err(|_| ZephyrError::NoMemory)?;
+                }
+            }
+            ok_or_errno(unsafe {
+                raw::comparator_set_trigger_callback(
+                    self.device,
+                    Some(trigger_callback),
+                    core::ptr::null_mut(),
+                )
+            })
+        }
+
+        /// Stop calling the trigger callback.
+        pub fn clear_trigger_callback(&mut self) -> ZResult<()> {
+            ok_or_errno(unsafe {
+                raw::comparator_set_trigger_callback(self.device, None, core::ptr::null_mut())
+            })?;
+            let key = self.device as usize;
+            CALLBACKS.lock().unwrap().retain(|(dev, _)| *dev != key);
+            Ok(())
+        }
+    }
+
+    unsafe extern "C" fn trigger_callback(dev: *const raw::device, _user_data: *mut c_void) {
+        let key = dev as usize;
+        let cb = CALLBACKS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(d, _)| *d == key)
+            .map(|(_, cb)| *cb);
+        // The lock is released before calling, so the callback is free to change it.
+        if let Some(cb) = cb {
+            cb();
+        }
+    }
+}
@@ -3,7 +3,13 @@
 // Note that currently, the flash partition shares the controller, so the underlying operations
 // are not actually safe.  Need to rethink how to manage this.
 
+use embedded_storage::nor_flash::{
+    check_erase, check_read, check_write, ErrorType, NorFlash, NorFlashError, NorFlashErrorKind,
+    ReadNorFlash,
+};
+
 use super::Unique;
+use crate::error::ZephyrError;
 use crate::raw;
 
 /// A flash controller
@@ -69,3 +75,148 @@ impl FlashPartition {
         })
     }
 }
+
+/// A flash partition, usable through the [`embedded_storage`] NOR flash traits.
+///
+/// This allows file systems, and other storage written in Rust, such as `littlefs2` or
+/// `sequential-storage`, to use a partition of a Zephyr managed flash device.  Offsets are
+/// relative to the start of the partition.
+///
+/// The traits describe the geometry of the flash with constants, but Zephyr only knows these at
+/// runtime, so they are given as parameters.  [`new`] checks them against the driver.
+///
+/// [`new`]: Self::new
+pub struct ZephyrFlashStorage<const WRITE_SIZE: usize, const ERASE_SIZE: usize> {
+    partition: FlashPartition,
+}
+
+/// Errors from [`ZephyrFlashStorage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashStorageError {
+    /// The request was out of range, or not aligned.
+    Kind(NorFlashErrorKind),
+    /// The `WRITE_SIZE` or `ERASE_SIZE` given does not match the flash device.
+    Geometry,
+    /// An error from the flash driver.
+    Flash(ZephyrError),
+}
+
+impl NorFlashError for FlashStorageError {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            FlashStorageError::Kind(kind) => *kind,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+impl From<NorFlashErrorKind> for FlashStorageError {
+    fn from(kind: NorFlashErrorKind) -> Self {
+        FlashStorageError::Kind(kind)
+    }
+}
+
+impl<const WRITE_SIZE: usize, const ERASE_SIZE: usize>
+    ZephyrFlashStorage<WRITE_SIZE, ERASE_SIZE>
+{
+    /// Use the given partition for storage.
+    ///
+    /// Fails with [`FlashStorageError::Geometry`] if the write block size of the device is not
+    /// `WRITE_SIZE`, or, when `CONFIG_FLASH_PAGE_LAYOUT` is enabled, the page at the start of the
+    /// partition is not `ERASE_SIZE`.
+    pub fn new(partition: FlashPartition) -> Result<Self, FlashStorageError> {
+        let device = partition.controller.device;
+        if unsafe { raw::flash_get_write_block_size(device) } != WRITE_SIZE {
+            return Err(FlashStorageError::Geometry);
+        }
+        #[cfg(CONFIG_FLASH_PAGE_LAYOUT)]
+        {
+            // SAFETY: The info is filled in by the call.
+            let mut info: raw::flash_pages_info = unsafe { core::mem::zeroed() };
+            let ret = unsafe {
+                raw::flash_get_page_info_by_offs(device, partition.offset as _, &mut info)
+            };
+            if ret < 0 {
+                return Err(FlashStorageError::Flash(ZephyrError::from_errno(ret)));
+            }
+            if info.size != ERASE_SIZE {
+                return Err(FlashStorageError::Geometry);
+            }
+        }
+        Ok(ZephyrFlashStorage { partition })
+    }
+
+    /// Recover the partition.
+    pub fn into_inner(self) -> FlashPartition {
+        self.partition
+    }
+
+    fn device(&self) -> *const raw::device {
+        self.partition.controller.device
+    }
+
+    fn abs(&self, offset: u32) -> raw::off_t {
+        (self.partition.offset + offset) as raw::off_t
+    }
+}
+
+fn flash_check(ret: core::ffi::c_int) -> Result<(), FlashStorageError> {
+    if ret < 0 {
+        Err(FlashStorageError::Flash(ZephyrError::from_errno(ret)))
+    } else {
+        Ok(())
+    }
+}
+
+impl<const WRITE_SIZE: usize, const ERASE_SIZE: usize> ErrorType
+    for ZephyrFlashStorage<WRITE_SIZE, ERASE_SIZE>
+{
+    type Error = FlashStorageError;
+}
+
+impl<const WRITE_SIZE: usize, const ERASE_SIZE: usize> ReadNorFlash
+    for ZephyrFlashStorage<WRITE_SIZE, ERASE_SIZE>
+{
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+        flash_check(unsafe {
+            raw::flash_read(
+                self.device(),
+                self.abs(offset),
+                bytes.as_mut_ptr() as *mut _,
+                bytes.len(),
+            )
+        })
+    }
+
+    fn capacity(&self) -> usize {
+        self.partition.size as usize
+    }
+}
+
+impl<const WRITE_SIZE: usize, const ERASE_SIZE: usize> NorFlash
+    for ZephyrFlashStorage<WRITE_SIZE, ERASE_SIZE>
+{
+    const WRITE_SIZE: usize = WRITE_SIZE;
+    const ERASE_SIZE: usize = ERASE_SIZE;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        check_erase(self, from, to)?;
+        let size = (to - from) as usize;
+        flash_check(unsafe { raw::flash_erase(self.device(), self.abs(from), size) })
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        check_write(self, offset, bytes.len())?;
+        flash_check(unsafe {
+            raw::flash_write(
+                self.device(),
+                self.abs(offset),
+                bytes.as_ptr() as *const _,
+                bytes.len(),
+            )
+        })
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for battery fuel gauges.
+//!
+//! The values are in the units the fuel gauge API uses: microvolts, microamps, and tenths of a
+//! kelvin, with current into the battery positive.
+//!
+//! The fuel gauge API requires `CONFIG_FUEL_GAUGE`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// A battery fuel gauge.
+pub struct FuelGauge {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The fuel gauge drivers can be called from any thread.
+unsafe impl Send for FuelGauge {}
+
+impl FuelGauge {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<FuelGauge> {
+        if !unique.once() {
+            return None;
+        }
+        Some(FuelGauge { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for FuelGauge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FuelGauge({:p})", self.device)
+    }
+}
+
+#[cfg(CONFIG_FUEL_GAUGE)]
+mod api {
+    use core::mem;
+
+    use super::FuelGauge;
+    use crate::error::{ok_or_errno_val, ZResult};
+    use crate::raw;
+
+    impl FuelGauge {
+        fn get_prop(&self, prop: u32) -> ZResult<raw::fuel_gauge_prop_val> {
+            // SAFETY: The value is a union of plain data, filled in by the call.
+            let mut val: raw::fuel_gauge_prop_val = unsafe { mem::zeroed() };
+            let ret = unsafe { raw::fuel_gauge_get_prop(self.device, prop as _, &mut val) };
+            ok_or_errno_val(ret, val)
+        }
+
+        /// The charge left, as a percentage of the full capacity.
+        pub fn state_of_charge(&self) -> ZResult<u8> {
+            let val = self.get_prop(raw::fuel_gauge_prop_type_FUEL_GAUGE_RELATIVE_STATE_OF_CHARGE)?;
+            // SAFETY: Each property sets its own field of the value.
+            Ok(unsafe { val.relative_state_of_charge })
+        }
+
+        /// The battery voltage, in microvolts.
+        pub fn voltage_uv(&self) -> ZResult<i32> {
+            let val = self.get_prop(raw::fuel_gauge_prop_type_FUEL_GAUGE_VOLTAGE)?;
+            Ok(unsafe { val.voltage })
+        }
+
+        /// The battery current, in microamps, positive while charging.
+        pub fn current_ua(&self) -> ZResult<i32> {
+            let val = self.get_prop(raw::fuel_gauge_prop_type_FUEL_GAUGE_CURRENT)?;
+            Ok(unsafe { val.current })
+        }
+
+        /// The battery temperature, in tenths of a kelvin.
+        pub fn temperature_dk(&self) -> ZResult<i32> {
+            let val = self.get_prop(raw::fuel_gauge_prop_type_FUEL_GAUGE_TEMPERATURE)?;
+            Ok(unsafe { val.temperature } as i32)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for GNSS receivers.
+//!
+//! Zephyr's GNSS drivers publish each fix through a statically registered callback.  This module
+//! registers one callback for all receivers, which keeps the latest fix of each, for
+//! [`GnssDevice::get_latest_fix`], and calls any callback set with [`GnssDevice::on_fix`].
+//!
+//! Receiving fixes requires `CONFIG_GNSS`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+use crate::sync::SpinMutex;
+use crate::time::Duration;
+
+/// The most receivers whose fixes are tracked.
+const MAX_DEVICES: usize = 2;
+
+/// A position fix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GnssFix {
+    /// The latitude, in degrees, positive to the north.
+    pub latitude: f64,
+    /// The longitude, in degrees, positive to the east.
+    pub longitude: f64,
+    /// The altitude above mean sea level, in meters.
+    pub altitude: f32,
+    /// The horizontal dilution of precision.
+    pub hdop: f32,
+    /// The number of satellites used.
+    pub satellites: u16,
+}
+
+impl GnssFix {
+    /// Convert the driver's data, or None if the receiver doesn't have a fix.
+    fn from_raw(data: &raw::gnss_data) -> Option<GnssFix> {
+        if data.info.fix_status == raw::gnss_fix_status_GNSS_FIX_STATUS_NO_FIX {
+            return None;
+        }
+        Some(GnssFix {
+            latitude: data.nav_data.latitude as f64 / 1e9,
+            longitude: data.nav_data.longitude as f64 / 1e9,
+            altitude: data.nav_data.altitude as f32 / 1000.0,
+            hdop: data.info.hdop as f32 / 1000.0,
+            satellites: data.info.satellites_cnt,
+        })
+    }
+}
+
+/// The fix callback type.
+pub type FixCallback = fn(GnssFix);
+
+/// The tracked state of one receiver.
+#[derive(Clone, Copy)]
+struct Slot {
+    device: *const raw::device,
+    latest: Option<GnssFix>,
+    callback: Option<FixCallback>,
+}
+
+// SAFETY: The device pointer is only compared.
+unsafe impl Send for Slot {}
+
+const EMPTY_SLOT: Slot = Slot {
+    device: core::ptr::null(),
+    latest: None,
+    callback: None,
+};
+
+static SLOTS: SpinMutex<[Slot; MAX_DEVICES]> = SpinMutex::new([EMPTY_SLOT; MAX_DEVICES]);
+
+/// Run `f` on the slot for `device`, taking a free slot if it doesn't have one yet.
+fn with_slot<R>(device: *const raw::device, f: impl FnOnce(&mut Slot) -> R) -> Option<R> {
+    let mut slots = SLOTS.lock().unwrap();
+    let pos = slots
+        .iter()
+        .position(|slot| slot.device == device)
+        .or_else(|| slots.iter().position(|slot| slot.device.is_null()))?;
+    slots[pos].device = device;
+    Some(f(&mut slots[pos]))
+}
+
+/// A GNSS receiver.
+pub struct GnssDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The operations all require `&mut self`.
+unsafe impl Send for GnssDevice {}
+
+impl GnssDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<GnssDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(GnssDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+
+    /// Set how often the receiver produces a fix.
+    pub fn set_fix_rate(&mut self, rate: Duration) -> ZResult<()> {
+        let ms = rate.to_millis().min(u32::MAX as _) as u32;
+        ok_or_errno(unsafe { raw::gnss_set_fix_rate(self.device, ms) })
+    }
+
+    /// The most recent fix, or None if the receiver has not reported one, or lost its fix.
+    pub fn get_latest_fix(&self) -> Option<GnssFix> {
+        with_slot(self.device, |slot| slot.latest).flatten()
+    }
+
+    /// Call `callback` for each fix from this receiver, replacing any earlier callback.
+    ///
+    /// The callback is called from the driver's context, so should be short.
+    pub fn on_fix(&mut self, callback: FixCallback) {
+        with_slot(self.device, |slot| slot.callback = Some(callback));
+    }
+}
+
+impl fmt::Debug for GnssDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GnssDevice({:p})", self.device)
+    }
+}
+
+/// Called by the drivers with each new set of data.
+#[cfg(CONFIG_GNSS)]
+unsafe extern "C" fn gnss_data(device: *const raw::device, data: *const raw::gnss_data) {
+    let fix = GnssFix::from_raw(&*data);
+    let callback = with_slot(device, |slot| {
+        slot.latest = fix;
+        slot.callback
+    })
+    .flatten();
+    if let (Some(callback), Some(fix)) = (callback, fix) {
+        callback(fix);
+    }
+}
+
+/// The registration, the same as `GNSS_DATA_CALLBACK_DEFINE(NULL, gnss_data)`.
+#[cfg(CONFIG_GNSS)]
+#[repr(transparent)]
+struct DataCallback(raw::gnss_data_callback);
+
+// SAFETY: Constant, and only read by the drivers.
+#[cfg(CONFIG_GNSS)]
+unsafe impl Sync for DataCallback {}
+
+#[cfg(CONFIG_GNSS)]
+#[used]
+#[link_section = "._gnss_data_callback.static.rust_gnss_data"]
+static DATA_CALLBACK: DataCallback = DataCallback(raw::gnss_data_callback {
+    dev: core::ptr::null(),
+    callback: Some(gnss_data),
+});
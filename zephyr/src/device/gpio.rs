@@ -6,10 +6,26 @@
 //! Safey: In general, even just using gpio pins is unsafe in Zephyr.  The gpio drivers are used
 //! pervasively throughout Zephyr device drivers.  As such, most of the calls in this module are
 //! unsafe.
+//!
+//! With `CONFIG_RUST_ALLOC`, a pin can be made into a [`GpioInterrupt`], to wait for edges on it
+//! from a thread, two pins into a [`RotaryEncoder`], and step and direction pins into a
+//! [`Stepper`].
 
 use super::Unique;
 use crate::raw;
 
+#[cfg(CONFIG_RUST_ALLOC)]
+mod encoder;
+#[cfg(CONFIG_RUST_ALLOC)]
+mod stepper;
+
+#[cfg(CONFIG_RUST_ALLOC)]
+pub use encoder::RotaryEncoder;
+#[cfg(CONFIG_RUST_ALLOC)]
+pub use interrupt::*;
+#[cfg(CONFIG_RUST_ALLOC)]
+pub use stepper::Stepper;
+
 /// Global instance to help make gpio in Rust slightly safer.
 ///
 /// # Safety
@@ -148,3 +164,191 @@ impl GpioPin {
         }
     }
 }
+
+#[cfg(CONFIG_RUST_ALLOC)]
+mod interrupt {
+    extern crate alloc;
+
+    use alloc::boxed::Box;
+    use core::cell::UnsafeCell;
+    use core::ffi::c_void;
+    use core::fmt;
+    use core::marker::PhantomPinned;
+    use core::mem;
+    use core::pin::Pin;
+    use core::ptr;
+
+    use super::{GpioPin, GpioToken};
+    use crate::error::{ok_or_errno, ok_or_errno_val, ZResult};
+    use crate::raw;
+    use crate::time::{Duration, Timeout};
+
+    /// The edges that trigger a [`GpioInterrupt`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InterruptMode {
+        /// A physical low to high transition.
+        EdgeRising,
+        /// A physical high to low transition.
+        EdgeFalling,
+        /// Either transition.
+        EdgeBoth,
+        /// A transition to the logically active level, taking `GPIO_ACTIVE_LOW` into account.
+        EdgeToActive,
+        /// A transition to the logically inactive level.
+        EdgeToInactive,
+    }
+
+    impl InterruptMode {
+        fn flags(self) -> raw::gpio_flags_t {
+            (match self {
+                InterruptMode::EdgeRising => raw::ZR_GPIO_INT_EDGE_RISING,
+                InterruptMode::EdgeFalling => raw::ZR_GPIO_INT_EDGE_FALLING,
+                InterruptMode::EdgeBoth => raw::ZR_GPIO_INT_EDGE_BOTH,
+                InterruptMode::EdgeToActive => raw::ZR_GPIO_INT_EDGE_TO_ACTIVE,
+                InterruptMode::EdgeToInactive => raw::ZR_GPIO_INT_EDGE_TO_INACTIVE,
+            }) as raw::gpio_flags_t
+        }
+    }
+
+    /// The parts of the interrupt that the gpio driver holds a pointer to.
+    struct Inner {
+        cb: UnsafeCell<raw::gpio_callback>,
+        sem: UnsafeCell<raw::k_sem>,
+        callback: Option<fn(*mut c_void)>,
+        user_data: *mut c_void,
+        _pin: PhantomPinned,
+    }
+
+    /// A gpio pin configured as an input, with an interrupt on some of its edges.
+    ///
+    /// The interrupt gives a semaphore, which [`wait`](Self::wait) takes, so that a thread can
+    /// block until the pin changes.  Edges that happen before the thread waits are not lost, but
+    /// several of them are seen as one.  The interrupt is disabled when this is dropped.
+    pub struct GpioInterrupt {
+        pin: GpioPin,
+        inner: Pin<Box<Inner>>,
+    }
+
+    // SAFETY: The callback and semaphore are safe to use from any thread.
+    unsafe impl Send for GpioInterrupt {}
+    unsafe impl Sync for GpioInterrupt {}
+
+    impl GpioInterrupt {
+        /// Configure `pin` as an input, with an interrupt on the edges given by `mode`.
+        ///
+        /// # Safety
+        ///
+        /// As with [`GpioPin::configure`], the caller must make sure that nothing else is using
+        /// the pin, and that the gpio driver can be used from this thread.
+        pub unsafe fn new(
+            pin: GpioPin,
+            mode: InterruptMode,
+            token: &mut GpioToken,
+        ) -> ZResult<GpioInterrupt> {
+            Self::build(pin, mode, token, None, ptr::null_mut())
+        }
+
+        /// Configure `pin` as with [`new`](Self::new), and also call `callback` with `user_data`
+        /// on each edge.
+        ///
+        /// The callback is called from the interrupt handler, so must be short, and must not
+        /// block.
+        ///
+        /// # Safety
+        ///
+        /// As with [`new`](Self::new).  In addition, whatever `user_data` points to must remain
+        /// valid until the interrupt is dropped.
+        pub unsafe fn with_callback(
+            pin: GpioPin,
+            mode: InterruptMode,
+            token: &mut GpioToken,
+            callback: fn(*mut c_void),
+            user_data: *mut c_void,
+        ) -> ZResult<GpioInterrupt> {
+            Self::build(pin, mode, token, Some(callback), user_data)
+        }
+
+        unsafe fn build(
+            pin: GpioPin,
+            mode: InterruptMode,
+            _token: &mut GpioToken,
+            callback: Option<fn(*mut c_void)>,
+            user_data: *mut c_void,
+        ) -> ZResult<GpioInterrupt> {
+            let inner = Box::pin(Inner {
+                // SAFETY: Initialized below, once pinned.
+                cb: UnsafeCell::new(mem::zeroed()),
+                sem: UnsafeCell::new(mem::zeroed()),
+                callback,
+                user_data,
+                _pin: PhantomPinned,
+            });
+            raw::k_sem_init(inner.sem.get(), 0, 1);
+            raw::gpio_init_callback(inner.cb.get(), Some(handler), 1 << pin.pin.pin);
+
+            let spec = &pin.pin;
+            ok_or_errno(raw::gpio_pin_configure_dt(spec, raw::GPIO_INPUT as raw::gpio_flags_t))?;
+            // SAFETY: The callback is pinned, and removed again on drop.
+            ok_or_errno(raw::gpio_add_callback_dt(spec, inner.cb.get()))?;
+            let ret = raw::gpio_pin_interrupt_configure_dt(spec, mode.flags());
+            if let Err(err) = ok_or_errno(ret) {
+                raw::gpio_remove_callback_dt(spec, inner.cb.get());
+                return Err(err);
+            }
+            Ok(GpioInterrupt { pin, inner })
+        }
+
+        /// Wait for an edge, returning the level of the pin once there has been one.
+        ///
+        /// The level is the logical level, so true means active.  If there is no edge within
+        /// `timeout`, this returns an `EAGAIN` error.
+        pub fn wait(&self, timeout: Duration) -> ZResult<bool> {
+            let timeout: Timeout = timeout.into();
+            // SAFETY: The semaphore was initialized with the interrupt.
+            ok_or_errno(unsafe { raw::k_sem_take(self.inner.sem.get(), timeout.0) })?;
+            self.level()
+        }
+
+        /// Read the logical level of the pin.
+        pub fn level(&self) -> ZResult<bool> {
+            // SAFETY: The pin is configured as an input, and owned by this interrupt.
+            let level = unsafe { raw::gpio_pin_get_dt(&self.pin.pin) };
+            ok_or_errno_val(level, level != 0)
+        }
+    }
+
+    impl Drop for GpioInterrupt {
+        fn drop(&mut self) {
+            let spec = &self.pin.pin;
+            // SAFETY: Once the interrupt is disabled, and the callback removed, the driver no
+            // longer refers to the callback.
+            unsafe {
+                raw::gpio_pin_interrupt_configure_dt(spec, raw::ZR_GPIO_INT_DISABLE);
+                raw::gpio_remove_callback_dt(spec, self.inner.cb.get());
+            }
+        }
+    }
+
+    impl fmt::Debug for GpioInterrupt {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "GpioInterrupt({:p}, {})", self.pin.pin.port, self.pin.pin.pin)
+        }
+    }
+
+    /// The gpio callback, called from the interrupt handler.
+    unsafe extern "C" fn handler(
+        _port: *const raw::device,
+        cb: *mut raw::gpio_callback,
+        _pins: raw::gpio_port_pins_t,
+    ) {
+        // SAFETY: The callback is a field of a pinned `Inner`, which outlives the registration.
+        let inner = &*cb
+            .cast::<u8>()
+            .sub(mem::offset_of!(Inner, cb))
+            .cast::<Inner>();
+        if let Some(callback) = inner.callback {
+            callback(inner.user_data);
+        }
+        raw::k_sem_give(inner.sem.get());
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A quadrature rotary encoder, read with interrupts on its two gpio pins.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::fmt;
+use core::pin::Pin;
+
+use super::{GpioInterrupt, GpioPin, GpioToken, InterruptMode};
+use crate::error::ZResult;
+use crate::raw;
+use crate::sync::atomic::{AtomicI32, AtomicU8, Ordering};
+
+/// The change in position for each transition, indexed by `(previous << 2) | current`, where
+/// each state is `(a << 1) | b`.  Transitions that skip a state, with both pins changing, can't
+/// be given a direction, so count as no movement.
+const STEPS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+/// The state shared with the interrupt handlers.
+struct State {
+    pin_a: raw::gpio_dt_spec,
+    pin_b: raw::gpio_dt_spec,
+    last: AtomicU8,
+    position: AtomicI32,
+}
+
+impl State {
+    /// Read the current state of the two pins.
+    fn read(&self) -> u8 {
+        // SAFETY: The pins are configured as inputs, and owned by the encoder.
+        let (a, b) = unsafe {
+            (
+                raw::gpio_pin_get_dt(&self.pin_a),
+                raw::gpio_pin_get_dt(&self.pin_b),
+            )
+        };
+        (((a > 0) as u8) << 1) | (b > 0) as u8
+    }
+}
+
+/// A rotary encoder, with quadrature outputs on two pins.
+///
+/// An interrupt on each edge of either pin decodes the gray code transition, and moves a signed
+/// position up or down by one.  So the position changes by four for each full cycle of the
+/// outputs, which, depending on the encoder, may be one detent or several.  Which direction is
+/// positive depends on which pin is given as `pin_a`.
+pub struct RotaryEncoder {
+    // The interrupts are declared first, so they are dropped before the state they refer to.
+    _irq_a: GpioInterrupt,
+    _irq_b: GpioInterrupt,
+    state: Pin<Box<State>>,
+}
+
+// SAFETY: The state is only changed atomically, and the pins are only read.
+unsafe impl Send for RotaryEncoder {}
+unsafe impl Sync for RotaryEncoder {}
+
+impl RotaryEncoder {
+    /// Set up the encoder, with interrupts on both edges of both pins.
+    ///
+    /// # Safety
+    ///
+    /// As with [`GpioInterrupt::new`], nothing else may be using these pins.
+    pub unsafe fn new(
+        pin_a: GpioPin,
+        pin_b: GpioPin,
+        token: &mut GpioToken,
+    ) -> ZResult<RotaryEncoder> {
+        let state = Box::pin(State {
+            pin_a: copy_spec(&pin_a),
+            pin_b: copy_spec(&pin_b),
+            last: AtomicU8::new(0),
+            position: AtomicI32::new(0),
+        });
+        // The state is pinned, and outlives the interrupts.
+        let data = &*state as *const State as *mut c_void;
+        let irq_a =
+            GpioInterrupt::with_callback(pin_a, InterruptMode::EdgeBoth, token, on_edge, data)?;
+        let irq_b =
+            GpioInterrupt::with_callback(pin_b, InterruptMode::EdgeBoth, token, on_edge, data)?;
+        state.last.store(state.read(), Ordering::Release);
+
+        Ok(RotaryEncoder {
+            _irq_a: irq_a,
+            _irq_b: irq_b,
+            state,
+        })
+    }
+
+    /// The current position.
+    pub fn position(&self) -> i32 {
+        self.state.position.load(Ordering::Acquire)
+    }
+
+    /// Set the position back to zero.
+    pub fn reset(&self) {
+        self.state.position.store(0, Ordering::Release);
+    }
+}
+
+impl fmt::Debug for RotaryEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RotaryEncoder({})", self.position())
+    }
+}
+
+fn copy_spec(pin: &GpioPin) -> raw::gpio_dt_spec {
+    raw::gpio_dt_spec {
+        port: pin.pin.port,
+        pin: pin.pin.pin,
+        dt_flags: pin.pin.dt_flags,
+    }
+}
+
+/// Called from the interrupt handler of either pin.
+fn on_edge(data: *mut c_void) {
+    // SAFETY: The data is the encoder's pinned state, which outlives the interrupts.
+    let state = unsafe { &*(data as *const State) };
+    let current = state.read();
+    let previous = state.last.swap(current, Ordering::AcqRel);
+    let step = STEPS[((previous << 2) | current) as usize];
+    if step != 0 {
+        state.position.fetch_add(step as i32, Ordering::AcqRel);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A stepper motor, driven through step and direction pins, such as on an A4988 or DRV8825.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::pin::Pin;
+
+use super::{GpioPin, GpioToken};
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+use crate::sync::{Arc, SpinMutex};
+use crate::sys::sync::Semaphore;
+use crate::time::{Duration, Forever, Tick, Timeout};
+use crate::timer::{Callback, CallbackTimer, StoppedTimer};
+
+/// A pin spec that the timer callback can use.
+struct PinSpec(raw::gpio_dt_spec);
+
+// SAFETY: The spec is only read after construction.
+unsafe impl Send for PinSpec {}
+unsafe impl Sync for PinSpec {}
+
+/// The progress of a move, updated from the timer.
+struct Motion {
+    /// The number of edges left to put on the step pin, two for each step.
+    remaining: u32,
+    /// The current level of the step pin.
+    level: bool,
+}
+
+/// The state shared with the timer callback.
+struct Shared {
+    step: PinSpec,
+    motion: SpinMutex<Motion>,
+    /// Given when a move finishes.
+    done: Semaphore,
+}
+
+/// A stepper motor, driven by a pulse on the step pin for each step, in the direction set by the
+/// direction pin.
+///
+/// The pulses are timed by a timer, with each step taking one timer period high and one low, so
+/// the top speed depends on the kernel tick rate.  Moves can either block until they finish, or
+/// run in the background, to be waited for with [`wait`](Self::wait).
+pub struct Stepper {
+    dir: GpioPin,
+    enable: Option<GpioPin>,
+    steps_per_rev: u32,
+    /// Half of the time for each step, or `None` if no speed has been set.
+    half_step: Option<Duration>,
+    shared: Arc<Shared>,
+    /// The timer for the current, or last, move.
+    timer: Option<Pin<Box<CallbackTimer<Arc<Shared>>>>>,
+    _step: GpioPin,
+}
+
+// SAFETY: The pins are only used by the stepper, and its timer state is shared atomically.
+unsafe impl Send for Stepper {}
+
+impl Stepper {
+    /// Set up a stepper with `steps_per_rev` steps for each revolution of the motor, counting
+    /// any microstepping the driver is configured for.
+    ///
+    /// The step and direction pins are configured as outputs, and a speed must be set with
+    /// [`set_speed_rpm`](Self::set_speed_rpm) before moving.
+    ///
+    /// # Safety
+    ///
+    /// As with [`GpioPin::configure`], nothing else may be using these pins.
+    pub unsafe fn new(
+        step_pin: GpioPin,
+        dir_pin: GpioPin,
+        steps_per_rev: u32,
+        _token: &mut GpioToken,
+    ) -> ZResult<Stepper> {
+        let output = raw::GPIO_OUTPUT_INACTIVE as raw::gpio_flags_t;
+        ok_or_errno(raw::gpio_pin_configure_dt(&step_pin.pin, output))?;
+        ok_or_errno(raw::gpio_pin_configure_dt(&dir_pin.pin, output))?;
+
+        let done = Semaphore::new(0, 1).map_err(ZephyrError::from)?;
+        let shared = Arc::new(Shared {
+            step: PinSpec(raw::gpio_dt_spec {
+                port: step_pin.pin.port,
+                pin: step_pin.pin.pin,
+                dt_flags: step_pin.pin.dt_flags,
+            }),
+            motion: SpinMutex::new(Motion {
+                remaining: 0,
+                level: false,
+            }),
+            done,
+        });
+        Ok(Stepper {
+            dir: dir_pin,
+            enable: None,
+            steps_per_rev,
+            half_step: None,
+            shared,
+            timer: None,
+            _step: step_pin,
+        })
+    }
+
+    /// Use `pin` as the driver's enable pin, configured to be active, enabling the driver.
+    ///
+    /// # Safety
+    ///
+    /// As with [`new`](Self::new).
+    pub unsafe fn enable_pin(&mut self, pin: GpioPin, _token: &mut GpioToken) -> ZResult<()> {
+        ok_or_errno(raw::gpio_pin_configure_dt(
+            &pin.pin,
+            raw::GPIO_OUTPUT_ACTIVE as raw::gpio_flags_t,
+        ))?;
+        self.enable = Some(pin);
+        Ok(())
+    }
+
+    /// Enable, or disable, the driver, if there is an enable pin.
+    ///
+    /// A disabled driver doesn't hold the motor in place, which saves power.
+    pub fn set_enabled(&mut self, enabled: bool) -> ZResult<()> {
+        match &self.enable {
+            Some(pin) => ok_or_errno(unsafe { raw::gpio_pin_set_dt(&pin.pin, enabled as i32) }),
+            None => Ok(()),
+        }
+    }
+
+    /// Set the speed for the following moves.  A speed of zero prevents moving.
+    pub fn set_speed_rpm(&mut self, rpm: u32) {
+        let steps_per_min = rpm as u64 * self.steps_per_rev as u64;
+        self.half_step = if steps_per_min == 0 {
+            None
+        } else {
+            Some(Duration::micros_at_least((30_000_000 / steps_per_min) as Tick))
+        };
+    }
+
+    /// Move `count` steps, forward for a positive count, and backward for a negative one.
+    ///
+    /// If `blocking`, this returns once the move is finished.  Otherwise it returns once the move
+    /// has started.  Starting a move while one is running fails with `EBUSY`, and moving before a
+    /// speed has been set fails with `EINVAL`.
+    pub fn step(&mut self, count: i32, blocking: bool) -> ZResult<()> {
+        let half_step = self.half_step.ok_or(ZephyrError::Invalid)?;
+        if self.is_moving() {
+            return Err(ZephyrError::Busy);
+        }
+        if count == 0 {
+            return Ok(());
+        }
+
+        // Stop the last move's timer before changing direction.
+        self.timer = None;
+        ok_or_errno(unsafe { raw::gpio_pin_set_dt(&self.dir.pin, (count > 0) as i32) })?;
+        self.shared.done.reset();
+        {
+            let mut motion = self.shared.motion.lock().unwrap();
+            motion.remaining = count.unsigned_abs() * 2;
+            motion.level = false;
+        }
+
+        let callback = Callback {
+            call: Self::tick,
+            data: self.shared.clone(),
+        };
+        self.timer = Some(StoppedTimer::new().start_callback(callback, half_step, half_step));
+
+        if blocking {
+            self.wait(Forever)?;
+        }
+        Ok(())
+    }
+
+    /// Returns true while a move is running.
+    pub fn is_moving(&self) -> bool {
+        self.shared.motion.lock().unwrap().remaining != 0
+    }
+
+    /// Wait for the current move, if any, to finish.
+    pub fn wait(&mut self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        if !self.is_moving() {
+            self.timer = None;
+            return Ok(());
+        }
+        self.shared.done.take(timeout).map_err(ZephyrError::from)?;
+        self.timer = None;
+        Ok(())
+    }
+
+    /// Stop the current move, wherever it has got to.
+    pub fn stop(&mut self) {
+        self.timer = None;
+        let mut motion = self.shared.motion.lock().unwrap();
+        motion.remaining = 0;
+        if motion.level {
+            motion.level = false;
+            unsafe {
+                raw::gpio_pin_set_dt(&self.shared.step.0, 0);
+            }
+        }
+    }
+
+    /// Called from the timer, in IRQ context, to put the next edge on the step pin.
+    fn tick(shared: &Arc<Shared>) {
+        let mut motion = shared.motion.lock().unwrap();
+        if motion.remaining == 0 {
+            return;
+        }
+        motion.remaining -= 1;
+        motion.level = !motion.level;
+        // SAFETY: The step pin is configured as an output, and owned by the stepper.
+        unsafe {
+            raw::gpio_pin_set_dt(&shared.step.0, motion.level as i32);
+        }
+        if motion.remaining == 0 {
+            shared.done.give();
+        }
+    }
+}
+
+impl fmt::Debug for Stepper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stepper({} steps/rev)", self.steps_per_rev)
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for haptic drivers, which run vibration motors.
+//!
+//! The common haptics API only starts and stops the output.  What is played is specific to the
+//! driver.  For the DRV2605, a waveform is one of the effects in its ROM library, numbered as in
+//! its datasheet, and [`HapticsEffect`] names a few of the common ones:
+//!
+//! ```ignore
+//! let mut haptics = zephyr::devicetree::labels::haptics::get_instance()?;
+//! haptics.start_effect(HapticsEffect::Click)?;
+//! ```
+//!
+//! The haptics API requires `CONFIG_HAPTICS`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// A haptic driver.
+pub struct HapticsDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The haptics drivers can be called from any thread.
+unsafe impl Send for HapticsDevice {}
+
+impl HapticsDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<HapticsDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(HapticsDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for HapticsDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HapticsDevice({:p})", self.device)
+    }
+}
+
+/// Some of the effects in the DRV2605 ROM library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HapticsEffect {
+    /// A single strong click.
+    Click = 1,
+    /// Two strong clicks.
+    DoubleClick = 10,
+    /// A buzz, at full strength.
+    Buzz = 47,
+}
+
+#[cfg(CONFIG_HAPTICS)]
+mod api {
+    use super::{HapticsDevice, HapticsEffect};
+    use crate::error::{ok_or_errno, ZResult};
+    use crate::raw;
+
+    impl HapticsDevice {
+        /// Play `waveform`.
+        ///
+        /// For the DRV2605, this is an effect number in its ROM library, played from the library
+        /// for an ERM motor.  Other drivers have no way to choose a waveform here, so it is
+        /// ignored, and they play whatever they have been configured with.
+        pub fn start(&mut self, waveform: u8) -> ZResult<()> {
+            #[cfg(CONFIG_HAPTICS_DRV2605)]
+            self.select_rom_waveform(waveform)?;
+            #[cfg(not(CONFIG_HAPTICS_DRV2605))]
+            let _ = waveform;
+            ok_or_errno(unsafe { raw::haptics_start_output(self.device) })
+        }
+
+        /// Play one of the named effects.
+        pub fn start_effect(&mut self, effect: HapticsEffect) -> ZResult<()> {
+            self.start(effect as u8)
+        }
+
+        /// Stop the output.
+        pub fn stop(&mut self) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::haptics_stop_output(self.device) })
+        }
+
+        /// Load a sequence of just `waveform` into the DRV2605, to play on an internal trigger.
+        #[cfg(CONFIG_HAPTICS_DRV2605)]
+        fn select_rom_waveform(&mut self, waveform: u8) -> ZResult<()> {
+            // SAFETY: The ROM data is plain data, with zero being no time offsets, and the rest
+            // of the sequence empty.
+            let mut rom: raw::drv2605_rom_data = unsafe { core::mem::zeroed() };
+            rom.trigger = raw::drv2605_mode_DRV2605_MODE_INTERNAL_TRIGGER;
+            rom.library = raw::drv2605_library_DRV2605_LIBRARY_TS2200_A;
+            rom.seq_regs[0] = waveform;
+            let config = raw::drv2605_config_data { rom_data: &mut rom };
+            ok_or_errno(unsafe {
+                raw::drv2605_haptic_config(
+                    self.device,
+                    raw::drv2605_haptics_source_DRV2605_HAPTICS_SOURCE_ROM,
+                    &config,
+                )
+            })
+        }
+    }
+}
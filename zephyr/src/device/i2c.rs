@@ -0,0 +1,126 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrappers for I2C controllers, and the devices attached to them.
+//!
+//! The controller is represented by [`I2cBus`].  Each child node of the controller in the device
+//! tree becomes an [`I2cDevice`], which is the bus plus the address of the device on it.
+//!
+//! [`I2cDevice`] implements [`embedded_io`]'s `Read` and `Write`, with each call being a single
+//! I2C read or write transaction to the device.
+
+use embedded_io::{ErrorType, Read, Write};
+
+use super::Unique;
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+
+/// An I2C controller.
+pub struct I2cBus {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The operations all require `&mut self`.
+unsafe impl Send for I2cBus {}
+
+impl I2cBus {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<I2cBus> {
+        if !unique.once() {
+            return None;
+        }
+        Some(I2cBus { device })
+    }
+
+    /// Verify that the device is ready for use.  At a minimum, this means the device has been
+    /// successfully initialized.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+/// A device on an I2C bus.
+///
+/// Note that several devices can share a bus.  The Zephyr I2C drivers serialize transfers on the
+/// bus, so operations on different devices are safe.
+pub struct I2cDevice {
+    pub(crate) device: *const raw::device,
+    pub(crate) addr: u16,
+}
+
+// SAFETY: The operations all require `&mut self`, and the driver serializes access to the bus.
+unsafe impl Send for I2cDevice {}
+
+impl I2cDevice {
+    /// Constructor, used by the devicetree generated code.  The address comes from the `reg`
+    /// property of the device's node.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(
+        unique: &Unique,
+        device: *const raw::device,
+        addr: u32,
+    ) -> Option<I2cDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(I2cDevice {
+            device,
+            addr: addr as u16,
+        })
+    }
+
+    /// Verify that the bus is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+
+    /// The address of this device on the bus.
+    pub fn address(&self) -> u16 {
+        self.addr
+    }
+
+    /// Write `write` to the device, and then read `read` from it, with a repeated start between
+    /// them.  This is the common way of reading a register.
+    pub fn write_read(&mut self, write: &[u8], read: &mut [u8]) -> ZResult<()> {
+        ok_or_errno(unsafe {
+            raw::i2c_write_read(
+                self.device,
+                self.addr,
+                write.as_ptr() as *const _,
+                write.len(),
+                read.as_mut_ptr() as *mut _,
+                read.len(),
+            )
+        })
+    }
+}
+
+impl ErrorType for I2cDevice {
+    type Error = ZephyrError;
+}
+
+impl Read for I2cDevice {
+    /// Read `buf.len()` bytes from the device, as a single transfer.
+    fn read(&mut self, buf: &mut [u8]) -> ZResult<usize> {
+        ok_or_errno(unsafe {
+            raw::i2c_read(self.device, buf.as_mut_ptr(), buf.len() as u32, self.addr)
+        })?;
+        Ok(buf.len())
+    }
+}
+
+impl Write for I2cDevice {
+    /// Write all of `buf` to the device, as a single transfer.
+    fn write(&mut self, buf: &[u8]) -> ZResult<usize> {
+        ok_or_errno(unsafe {
+            raw::i2c_write(self.device, buf.as_ptr(), buf.len() as u32, self.addr)
+        })?;
+        Ok(buf.len())
+    }
+
+    /// Each write is a complete transfer, so there is nothing to flush.
+    fn flush(&mut self) -> ZResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for I3C controllers, and the targets on their buses.
+//!
+//! With I3C, targets are given dynamic addresses by the controller, so a target is found by its
+//! 48-bit provisioned ID rather than by an address.  The targets must also be listed in the
+//! devicetree, under the controller, for the driver to know about them.
+//!
+//! ```ignore
+//! let mut bus = zephyr::devicetree::labels::i3c0::get_instance()?;
+//! bus.do_daa()?;
+//! let mut sensor = I3cDevice::new(&bus, I3cAddr(0x0208_0000_1234)).unwrap();
+//! sensor.write(&[0x75])?;
+//! ```
+//!
+//! The bus API requires `CONFIG_I3C`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// An I3C controller.
+pub struct I3cBus {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The I3C drivers lock the bus for each transfer.
+unsafe impl Send for I3cBus {}
+
+impl I3cBus {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<I3cBus> {
+        if !unique.once() {
+            return None;
+        }
+        Some(I3cBus { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for I3cBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "I3cBus({:p})", self.device)
+    }
+}
+
+/// The provisioned ID of an I3C target, of which only the low 48 bits are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct I3cAddr(pub u64);
+
+/// A Common Command Code, sent with [`I3cBus::send_ccc`] to every target, or with
+/// [`I3cDevice::send_ccc`] to just one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I3cCcc {
+    /// Enable the events in the mask, made of the `EVENT` constants.
+    EnableEvents(u8),
+    /// Disable the events in the mask.
+    DisableEvents(u8),
+    /// Forget the dynamic address, so it will be assigned again.
+    ResetDaa,
+}
+
+impl I3cCcc {
+    /// Events for target interrupts.
+    pub const EVENT_INTR: u8 = 1 << 0;
+    /// Events for controller role requests.
+    pub const EVENT_CR: u8 = 1 << 1;
+    /// Events for hot-join.
+    pub const EVENT_HJ: u8 = 1 << 3;
+
+    /// The command code, in its broadcast or direct form, and the byte it carries, if any.
+    #[allow(dead_code)]
+    fn encode(self, direct: bool) -> (u8, Option<u8>) {
+        let (code, data) = match self {
+            I3cCcc::EnableEvents(events) => (0x00, Some(events)),
+            I3cCcc::DisableEvents(events) => (0x01, Some(events)),
+            I3cCcc::ResetDaa => (0x06, None),
+        };
+        if direct {
+            (code | 0x80, data)
+        } else {
+            (code, data)
+        }
+    }
+}
+
+/// A target on an I3C bus.
+pub struct I3cDevice {
+    desc: *mut raw::i3c_device_desc,
+}
+
+// SAFETY: As with the bus.
+unsafe impl Send for I3cDevice {}
+
+impl fmt::Debug for I3cDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "I3cDevice({:p})", self.desc)
+    }
+}
+
+#[cfg(CONFIG_I3C)]
+mod api {
+    use super::{I3cAddr, I3cBus, I3cCcc, I3cDevice};
+    use crate::error::{ok_or_errno, ZResult};
+    use crate::raw;
+
+    impl I3cBus {
+        /// Run dynamic address assignment, giving addresses to the targets that need one.
+        pub fn do_daa(&mut self) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::i3c_do_daa(self.device) })
+        }
+
+        /// Broadcast `ccc` to every target on the bus.
+        pub fn send_ccc(&mut self, ccc: I3cCcc) -> ZResult<()> {
+            let (code, data) = ccc.encode(false);
+            let mut byte = data.unwrap_or(0);
+            // SAFETY: The unset fields of the payload are allowed to be zero.
+            let mut payload: raw::i3c_ccc_payload = unsafe { core::mem::zeroed() };
+            payload.ccc.id = code;
+            if data.is_some() {
+                payload.ccc.data = &mut byte;
+                payload.ccc.data_len = 1;
+            }
+            ok_or_errno(unsafe { raw::i3c_do_ccc(self.device, &mut payload) })
+        }
+    }
+
+    impl I3cDevice {
+        /// The target with the provisioned ID `addr`, if the controller knows of it.
+        pub fn new(bus: &I3cBus, addr: I3cAddr) -> Option<I3cDevice> {
+            let id = raw::i3c_device_id { pid: addr.0 };
+            let desc = unsafe { raw::i3c_device_find(bus.device, &id) };
+            if desc.is_null() {
+                None
+            } else {
+                Some(I3cDevice { desc })
+            }
+        }
+
+        /// The dynamic address of the target, which is zero until one is assigned.
+        pub fn dynamic_addr(&self) -> u8 {
+            unsafe { (*self.desc).dynamic_addr }
+        }
+
+        /// Write `buf` to the target.
+        pub fn write(&mut self, buf: &[u8]) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::i3c_write(self.desc, buf.as_ptr(), buf.len() as u32) })
+        }
+
+        /// Read into all of `buf` from the target, returning the number of bytes read.
+        pub fn read(&mut self, buf: &mut [u8]) -> ZResult<usize> {
+            ok_or_errno(unsafe { raw::i3c_read(self.desc, buf.as_mut_ptr(), buf.len() as u32) })?;
+            Ok(buf.len())
+        }
+
+        /// Send `ccc` to just this target.
+        pub fn send_ccc(&mut self, ccc: I3cCcc) -> ZResult<()> {
+            let (code, data) = ccc.encode(true);
+            let mut byte = data.unwrap_or(0);
+            // SAFETY: The unset fields of the payloads are allowed to be zero.
+            let mut target: raw::i3c_ccc_target_payload = unsafe { core::mem::zeroed() };
+            target.addr = self.dynamic_addr();
+            target.set_rnw(0);
+            if data.is_some() {
+                target.data = &mut byte;
+                target.data_len = 1;
+            }
+            let mut payload: raw::i3c_ccc_payload = unsafe { core::mem::zeroed() };
+            payload.ccc.id = code;
+            payload.targets.payloads = &mut target;
+            payload.targets.num_targets = 1;
+            ok_or_errno(unsafe { raw::i3c_do_ccc((*self.desc).bus, &mut payload) })
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for IPC service instances, for messaging between cores.
+//!
+//! An [`IpcInstance`] is a backend from the devicetree, such as `zephyr,ipc-icmsg`, shared
+//! between two cores.  Each side registers an [`IpcEndpoint`] with the same name on the instance,
+//! and once both have, the endpoint is bound, and messages can be sent in both directions.
+//!
+//! Endpoints require `CONFIG_IPC_SERVICE` and `CONFIG_RUST_ALLOC`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+#[cfg(all(CONFIG_IPC_SERVICE, CONFIG_RUST_ALLOC))]
+pub use endpoint::*;
+
+/// An IPC service instance.
+pub struct IpcInstance {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The IPC service API can be used from any thread.
+unsafe impl Send for IpcInstance {}
+unsafe impl Sync for IpcInstance {}
+
+impl IpcInstance {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<IpcInstance> {
+        if !unique.once() {
+            return None;
+        }
+        Some(IpcInstance { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for IpcInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IpcInstance({:p})", self.device)
+    }
+}
+
+#[cfg(all(CONFIG_IPC_SERVICE, CONFIG_RUST_ALLOC))]
+mod endpoint {
+    extern crate alloc;
+
+    use alloc::boxed::Box;
+    use core::cell::UnsafeCell;
+    use core::ffi::{c_void, CStr};
+    use core::fmt;
+    use core::marker::PhantomPinned;
+    use core::mem;
+    use core::pin::Pin;
+
+    use super::IpcInstance;
+    use crate::error::{ok_or_errno, ZResult, ZephyrError};
+    use crate::raw;
+    use crate::sync::atomic::{AtomicBool, Ordering};
+
+    /// The configuration of an endpoint.
+    #[derive(Debug, Clone, Copy)]
+    pub struct EptConfig {
+        /// The priority of the endpoint, used by backends that support it.
+        pub prio: i32,
+        /// Called for each message received.
+        pub on_receive: fn(data: &[u8]),
+        /// Called once the other side has registered its endpoint.
+        pub on_bound: Option<fn()>,
+    }
+
+    impl EptConfig {
+        /// A configuration with the given receive callback.
+        pub fn new(on_receive: fn(data: &[u8])) -> EptConfig {
+            EptConfig {
+                prio: 0,
+                on_receive,
+                on_bound: None,
+            }
+        }
+    }
+
+    /// The parts of an endpoint that the backend holds pointers to.
+    struct Inner {
+        ept: UnsafeCell<raw::ipc_ept>,
+        cfg: UnsafeCell<raw::ipc_ept_cfg>,
+        config: EptConfig,
+        bound: AtomicBool,
+        _pin: PhantomPinned,
+    }
+
+    /// A registered endpoint.  The endpoint is deregistered when dropped.
+    pub struct IpcEndpoint {
+        inner: Pin<Box<Inner>>,
+    }
+
+    // SAFETY: The IPC service API can be used from any thread.
+    unsafe impl Send for IpcEndpoint {}
+    unsafe impl Sync for IpcEndpoint {}
+
+    impl IpcEndpoint {
+        /// Open the instance, if it isn't already, and register an endpoint named `name` on it.
+        ///
+        /// The callbacks in `config` are called from the backend's context, usually a work queue,
+        /// so should be short.
+        pub fn new(
+            ipc_dev: &IpcInstance,
+            name: &'static CStr,
+            config: &EptConfig,
+        ) -> ZResult<IpcEndpoint> {
+            match ok_or_errno(unsafe { raw::ipc_service_open_instance(ipc_dev.device) }) {
+                // Another endpoint may have already opened the instance.
+                Ok(()) | Err(ZephyrError::Already) => (),
+                Err(err) => return Err(err),
+            }
+
+            let inner = Box::pin(Inner {
+                // SAFETY: Filled in by the backend on registration.
+                ept: UnsafeCell::new(unsafe { mem::zeroed() }),
+                // SAFETY: The unset callbacks are meant to be null.
+                cfg: UnsafeCell::new(unsafe { mem::zeroed() }),
+                config: *config,
+                bound: AtomicBool::new(false),
+                _pin: PhantomPinned,
+            });
+            // SAFETY: The box is pinned, so the pointers given to the backend stay valid until the
+            // endpoint is deregistered on drop.
+            unsafe {
+                let cfg = &mut *inner.cfg.get();
+                cfg.name = name.as_ptr();
+                cfg.prio = config.prio;
+                cfg.cb.bound = Some(bound);
+                cfg.cb.received = Some(received);
+                cfg.priv_ = &*inner as *const Inner as *mut c_void;
+                let ret = raw::ipc_service_register_endpoint(ipc_dev.device, inner.ept.get(), cfg);
+                ok_or_errno(ret)?;
+            }
+            Ok(IpcEndpoint { inner })
+        }
+
+        /// Returns true once the other side has registered its endpoint.
+        pub fn is_bound(&self) -> bool {
+            self.inner.bound.load(Ordering::Acquire)
+        }
+
+        /// Send a message to the other side.
+        pub fn send(&self, data: &[u8]) -> ZResult<()> {
+            let ret = unsafe {
+                raw::ipc_service_send(self.inner.ept.get(), data.as_ptr() as *const _, data.len())
+            };
+            ok_or_errno(ret)
+        }
+    }
+
+    impl Drop for IpcEndpoint {
+        fn drop(&mut self) {
+            unsafe {
+                raw::ipc_service_deregister_endpoint(self.inner.ept.get());
+            }
+        }
+    }
+
+    impl fmt::Debug for IpcEndpoint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "IpcEndpoint({:p})", self.inner.ept.get())
+        }
+    }
+
+    unsafe extern "C" fn bound(priv_: *mut c_void) {
+        let inner = &*(priv_ as *const Inner);
+        inner.bound.store(true, Ordering::Release);
+        if let Some(on_bound) = inner.config.on_bound {
+            on_bound();
+        }
+    }
+
+    unsafe extern "C" fn received(data: *const c_void, len: usize, priv_: *mut c_void) {
+        let inner = &*(priv_ as *const Inner);
+        let data = if data.is_null() {
+            &[][..]
+        } else {
+            core::slice::from_raw_parts(data as *const u8, len)
+        };
+        (inner.config.on_receive)(data);
+    }
+}
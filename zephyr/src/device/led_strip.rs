@@ -0,0 +1,146 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for LED strips, such as WS2812 (NeoPixel) chains.
+//!
+//! The [`LedStripDevice`] comes from the devicetree.  A [`LedStrip`] holds the pixels for it, with
+//! the length of the chain as a const generic, so the buffer is fixed in size:
+//!
+//! ```ignore
+//! let mut strip = LedStrip::<16>::new(zephyr::devicetree::labels::led_strip::get_instance()?);
+//! strip.set_brightness(25);
+//! strip.set_pixel(0, 255, 0, 0);
+//! strip.update()?;
+//! ```
+//!
+//! The pixel API requires `CONFIG_LED_STRIP`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// An LED strip.
+pub struct LedStripDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The LED strip drivers can be used from any thread, one at a time, which `&mut self`
+// on the updates enforces.
+unsafe impl Send for LedStripDevice {}
+
+impl LedStripDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(
+        unique: &Unique,
+        device: *const raw::device,
+    ) -> Option<LedStripDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(LedStripDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for LedStripDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LedStripDevice({:p})", self.device)
+    }
+}
+
+#[cfg(CONFIG_LED_STRIP)]
+pub use strip::*;
+
+#[cfg(CONFIG_LED_STRIP)]
+mod strip {
+    use core::fmt;
+    use core::mem;
+
+    use super::LedStripDevice;
+    use crate::error::{ok_or_errno, ZResult};
+    use crate::raw;
+
+    /// The pixels of an LED strip of `N` LEDs, along with the device to send them to.
+    ///
+    /// Changes to the pixels are only shown on the strip once [`update`](Self::update) is called.
+    pub struct LedStrip<const N: usize> {
+        device: LedStripDevice,
+        pixels: [(u8, u8, u8); N],
+        /// The buffer given to the driver, which may change its contents.
+        pixel_buf: [raw::led_rgb; N],
+        brightness: u8,
+    }
+
+    impl<const N: usize> LedStrip<N> {
+        /// A strip with all of its pixels off, at full brightness.
+        pub fn new(device: LedStripDevice) -> LedStrip<N> {
+            LedStrip {
+                device,
+                pixels: [(0, 0, 0); N],
+                // SAFETY: The pixel struct is plain data, filled in on update.
+                pixel_buf: unsafe { mem::zeroed() },
+                brightness: 100,
+            }
+        }
+
+        /// The number of pixels on the strip.
+        pub fn len(&self) -> usize {
+            N
+        }
+
+        /// Returns true for a strip with no pixels.
+        pub fn is_empty(&self) -> bool {
+            N == 0
+        }
+
+        /// Set the color of the pixel at `idx`.  Pixels past the end of the strip are ignored.
+        pub fn set_pixel(&mut self, idx: usize, r: u8, g: u8, b: u8) {
+            if let Some(pixel) = self.pixels.get_mut(idx) {
+                *pixel = (r, g, b);
+            }
+        }
+
+        /// Set every pixel to the same color.
+        pub fn fill(&mut self, r: u8, g: u8, b: u8) {
+            self.pixels = [(r, g, b); N];
+        }
+
+        /// Set the brightness, as a percentage, that the colors are scaled by on update.
+        ///
+        /// Values over 100 are treated as 100.
+        pub fn set_brightness(&mut self, pct: u8) {
+            self.brightness = pct.min(100);
+        }
+
+        /// Send the pixels, scaled by the brightness, to the strip.
+        pub fn update(&mut self) -> ZResult<()> {
+            let scale = |c: u8| (c as u16 * self.brightness as u16 / 100) as u8;
+            for (out, &(r, g, b)) in self.pixel_buf.iter_mut().zip(self.pixels.iter()) {
+                out.r = scale(r);
+                out.g = scale(g);
+                out.b = scale(b);
+            }
+            let ret = unsafe {
+                raw::led_strip_update_rgb(self.device.device, self.pixel_buf.as_mut_ptr(), N)
+            };
+            ok_or_errno(ret)
+        }
+
+        /// The device the strip is sent to.
+        pub fn device(&self) -> &LedStripDevice {
+            &self.device
+        }
+    }
+
+    impl<const N: usize> fmt::Debug for LedStrip<N> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "LedStrip<{}>({:p})", N, self.device.device)
+        }
+    }
+}
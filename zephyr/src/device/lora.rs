@@ -0,0 +1,274 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for LoRa radios, and the LoRaWAN stack.
+//!
+//! [`LoRaDevice`] uses the radio directly, for point to point links.  The modem settings are kept
+//! in a [`LoRaConfig`], and applied before each send or receive, with the data rate and frequency
+//! of a send also becoming those used to receive.
+//!
+//! [`LoRaWan`], with `CONFIG_LORAWAN`, instead joins a LoRaWAN network, with Zephyr's stack
+//! driving the radio chosen by `zephyr,lora` in the devicetree.
+
+use core::fmt;
+
+use super::Unique;
+use crate::error::{ok_or_errno, ok_or_errno_val, ZResult, ZephyrError};
+use crate::raw;
+use crate::time::{Duration, Tick, Timeout};
+
+/// The spreading factor, which trades range for speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataRate {
+    /// Spreading factor 6, the fastest.
+    Sf6,
+    /// Spreading factor 7.
+    Sf7,
+    /// Spreading factor 8.
+    Sf8,
+    /// Spreading factor 9.
+    Sf9,
+    /// Spreading factor 10.
+    Sf10,
+    /// Spreading factor 11.
+    Sf11,
+    /// Spreading factor 12, the longest range.
+    Sf12,
+}
+
+impl DataRate {
+    fn raw(self) -> raw::lora_datarate {
+        match self {
+            DataRate::Sf6 => raw::lora_datarate_SF_6,
+            DataRate::Sf7 => raw::lora_datarate_SF_7,
+            DataRate::Sf8 => raw::lora_datarate_SF_8,
+            DataRate::Sf9 => raw::lora_datarate_SF_9,
+            DataRate::Sf10 => raw::lora_datarate_SF_10,
+            DataRate::Sf11 => raw::lora_datarate_SF_11,
+            DataRate::Sf12 => raw::lora_datarate_SF_12,
+        }
+    }
+}
+
+/// The signal bandwidth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bandwidth {
+    /// 125 kHz.
+    Bw125,
+    /// 250 kHz.
+    Bw250,
+    /// 500 kHz.
+    Bw500,
+}
+
+impl Bandwidth {
+    fn raw(self) -> raw::lora_signal_bandwidth {
+        match self {
+            Bandwidth::Bw125 => raw::lora_signal_bandwidth_BW_125_KHZ,
+            Bandwidth::Bw250 => raw::lora_signal_bandwidth_BW_250_KHZ,
+            Bandwidth::Bw500 => raw::lora_signal_bandwidth_BW_500_KHZ,
+        }
+    }
+}
+
+/// The forward error correction coding rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodingRate {
+    /// 4/5.
+    Cr4_5,
+    /// 4/6.
+    Cr4_6,
+    /// 4/7.
+    Cr4_7,
+    /// 4/8.
+    Cr4_8,
+}
+
+impl CodingRate {
+    fn raw(self) -> raw::lora_coding_rate {
+        match self {
+            CodingRate::Cr4_5 => raw::lora_coding_rate_CR_4_5,
+            CodingRate::Cr4_6 => raw::lora_coding_rate_CR_4_6,
+            CodingRate::Cr4_7 => raw::lora_coding_rate_CR_4_7,
+            CodingRate::Cr4_8 => raw::lora_coding_rate_CR_4_8,
+        }
+    }
+}
+
+/// The modem settings.  Both ends of a link must agree on these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoRaConfig {
+    /// The frequency, in Hz.
+    pub frequency: u32,
+    /// The spreading factor.
+    pub data_rate: DataRate,
+    /// The bandwidth.
+    pub bandwidth: Bandwidth,
+    /// The coding rate.
+    pub coding_rate: CodingRate,
+    /// The preamble length, in symbols.
+    pub preamble_len: u16,
+    /// The transmit power, in dBm.
+    pub tx_power: i8,
+}
+
+impl Default for LoRaConfig {
+    /// 868.1 MHz, SF10, 125 kHz, 4/5, with an 8 symbol preamble at 4 dBm.
+    fn default() -> Self {
+        LoRaConfig {
+            frequency: 868_100_000,
+            data_rate: DataRate::Sf10,
+            bandwidth: Bandwidth::Bw125,
+            coding_rate: CodingRate::Cr4_5,
+            preamble_len: 8,
+            tx_power: 4,
+        }
+    }
+}
+
+/// A LoRa radio.
+pub struct LoRaDevice {
+    pub(crate) device: *const raw::device,
+    config: LoRaConfig,
+}
+
+// SAFETY: The operations all require `&mut self`.
+unsafe impl Send for LoRaDevice {}
+
+impl LoRaDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<LoRaDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(LoRaDevice {
+            device,
+            config: LoRaConfig::default(),
+        })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+
+    /// The current modem settings.
+    pub fn config(&self) -> &LoRaConfig {
+        &self.config
+    }
+
+    /// Change the modem settings, used from the next send or receive.
+    pub fn set_config(&mut self, config: LoRaConfig) {
+        self.config = config;
+    }
+
+    fn apply(&mut self, tx: bool) -> ZResult<()> {
+        // SAFETY: The config is plain data, the fields not set here are meant to be zero.
+        let mut modem: raw::lora_modem_config = unsafe { core::mem::zeroed() };
+        modem.frequency = self.config.frequency;
+        modem.bandwidth = self.config.bandwidth.raw();
+        modem.datarate = self.config.data_rate.raw();
+        modem.coding_rate = self.config.coding_rate.raw();
+        modem.preamble_len = self.config.preamble_len;
+        modem.tx_power = self.config.tx_power;
+        modem.tx = tx;
+        ok_or_errno(unsafe { raw::lora_config(self.device, &mut modem) })
+    }
+
+    /// Send a packet, at the given data rate and frequency, in Hz.  Blocks until it has been sent.
+    pub fn send(&mut self, data: &[u8], dr: DataRate, freq: u32) -> ZResult<()> {
+        self.config.data_rate = dr;
+        self.config.frequency = freq;
+        self.apply(true)?;
+        let len = data.len() as u32;
+        ok_or_errno(unsafe { raw::lora_send(self.device, data.as_ptr() as *mut u8, len) })
+    }
+
+    /// Receive a packet, waiting up to `timeout_ms` milliseconds.  Returns the length received.
+    pub fn recv(&mut self, buf: &mut [u8], timeout_ms: u32) -> ZResult<usize> {
+        self.recv_with_signal(buf, timeout_ms)
+            .map(|(len, _, _)| len)
+    }
+
+    /// Receive a packet, also returning the RSSI, in dBm, and the SNR, in dB.
+    pub fn recv_with_signal(
+        &mut self,
+        buf: &mut [u8],
+        timeout_ms: u32,
+    ) -> ZResult<(usize, i16, i8)> {
+        self.apply(false)?;
+        let timeout: Timeout = Duration::millis_at_least(timeout_ms as Tick).into();
+        let size = buf.len().min(u8::MAX as usize) as u8;
+        let mut rssi = 0i16;
+        let mut snr = 0i8;
+        let ret = unsafe {
+            raw::lora_recv(
+                self.device,
+                buf.as_mut_ptr(),
+                size,
+                timeout.0,
+                &mut rssi,
+                &mut snr,
+            )
+        };
+        ok_or_errno_val(ret, (ret as usize, rssi, snr))
+    }
+}
+
+impl fmt::Debug for LoRaDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LoRaDevice({:p})", self.device)
+    }
+}
+
+/// The LoRaWAN stack.
+#[cfg(CONFIG_LORAWAN)]
+pub struct LoRaWan;
+
+#[cfg(CONFIG_LORAWAN)]
+impl LoRaWan {
+    /// Start the stack.  This must be done before joining.
+    pub fn start() -> ZResult<()> {
+        ok_or_errno(unsafe { raw::lorawan_start() })
+    }
+
+    /// Join a network with over the air activation.  Blocks until the join has been accepted or
+    /// has failed.
+    ///
+    /// For LoRaWAN 1.0, the application key is also used as the network key.
+    pub fn join_otaa(deveui: &[u8; 8], appeui: &[u8; 8], appkey: &[u8; 16]) -> ZResult<()> {
+        // SAFETY: The config is plain data, the fields not set here are meant to be zero.
+        let mut config: raw::lorawan_join_config = unsafe { core::mem::zeroed() };
+        config.mode = raw::lorawan_act_type_LORAWAN_ACT_OTAA;
+        config.dev_eui = deveui.as_ptr() as *mut u8;
+        // SAFETY: `otaa` is the member used with OTAA.  The keys are only read.
+        unsafe {
+            let otaa = &mut config.__bindgen_anon_1.otaa;
+            otaa.join_eui = appeui.as_ptr() as *mut u8;
+            otaa.app_key = appkey.as_ptr() as *mut u8;
+            otaa.nwk_key = appkey.as_ptr() as *mut u8;
+        }
+        ok_or_errno(unsafe { raw::lorawan_join(&config) })
+    }
+
+    /// Send an uplink on `port`.  A confirmed uplink blocks until it has been acknowledged.
+    ///
+    /// Returns [`ZephyrError::MessageSize`] if `data` is longer than 255 bytes.
+    pub fn send(port: u8, data: &[u8], confirmed: bool) -> ZResult<()> {
+        let kind = if confirmed {
+            raw::lorawan_message_type_LORAWAN_MSG_CONFIRMED
+        } else {
+            raw::lorawan_message_type_LORAWAN_MSG_UNCONFIRMED
+        };
+        let len = u8::try_from(data.len()).map_err(|_| ZephyrError::MessageSize)?;
+        ok_or_errno(unsafe { raw::lorawan_send(port, data.as_ptr() as *mut u8, len, kind) })
+    }
+}
+
+#[cfg(CONFIG_LORAWAN)]
+impl fmt::Debug for LoRaWan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LoRaWan")
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for MDIO buses, used to manage Ethernet PHYs.
+//!
+//! The bus comes from the devicetree, and each PHY on it is reached through an [`MdioDevice`],
+//! made with the PHY's port address.  Several PHYs can share a bus.
+//!
+//! ```ignore
+//! let bus = zephyr::devicetree::labels::mdio::get_instance()?;
+//! let mut phy = MdioDevice::new(&bus, 0);
+//! let id1 = phy.read(0x02)?;
+//! ```
+//!
+//! The bus API requires `CONFIG_MDIO`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// An MDIO bus.
+pub struct MdioBus {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The MDIO drivers lock the bus for each access.
+unsafe impl Send for MdioBus {}
+
+impl MdioBus {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<MdioBus> {
+        if !unique.once() {
+            return None;
+        }
+        Some(MdioBus { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for MdioBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MdioBus({:p})", self.device)
+    }
+}
+
+/// A PHY, or other device, at one port address on an MDIO bus.
+pub struct MdioDevice {
+    device: *const raw::device,
+    prtad: u8,
+}
+
+// SAFETY: As with the bus.
+unsafe impl Send for MdioDevice {}
+
+impl MdioDevice {
+    /// The device at port address `prtad` on `bus`.
+    pub fn new(bus: &MdioBus, prtad: u8) -> MdioDevice {
+        MdioDevice {
+            device: bus.device,
+            prtad,
+        }
+    }
+
+    /// The port address of the device.
+    pub fn prtad(&self) -> u8 {
+        self.prtad
+    }
+}
+
+impl fmt::Debug for MdioDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MdioDevice({:p}, {})", self.device, self.prtad)
+    }
+}
+
+#[cfg(CONFIG_MDIO)]
+mod api {
+    use super::MdioDevice;
+    use crate::error::{ok_or_errno, ZResult};
+    use crate::raw;
+
+    impl MdioDevice {
+        /// Read register `regad` with a Clause 22 access.
+        pub fn read(&mut self, regad: u8) -> ZResult<u16> {
+            let mut data = 0;
+            ok_or_errno(unsafe { raw::mdio_read(self.device, self.prtad, regad, &mut data) })?;
+            Ok(data)
+        }
+
+        /// Write register `regad` with a Clause 22 access.
+        pub fn write(&mut self, regad: u8, data: u16) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::mdio_write(self.device, self.prtad, regad, data) })
+        }
+
+        /// Read register `regad` of MMD `devad` with a Clause 45 access.
+        ///
+        /// Not every bus supports Clause 45, and those that don't fail with `ENOSYS`.
+        pub fn read_c45(&mut self, devad: u8, regad: u16) -> ZResult<u16> {
+            let mut data = 0;
+            ok_or_errno(unsafe {
+                raw::mdio_read_c45(self.device, self.prtad, devad, regad, &mut data)
+            })?;
+            Ok(data)
+        }
+
+        /// Write register `regad` of MMD `devad` with a Clause 45 access.
+        pub fn write_c45(&mut self, devad: u8, regad: u16, data: u16) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::mdio_write_c45(self.device, self.prtad, devad, regad, data) })
+        }
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for SD host controllers, and the SD cards in them.
+//!
+//! The [`SdhcDevice`] comes from the devicetree, either a controller, or an SD card slot on a SPI
+//! bus.  An [`SdCard`] uses the card in it, through Zephyr's SD/MMC stack, in blocks of
+//! [`SD_BLOCK_SIZE`] bytes:
+//!
+//! ```ignore
+//! let mut card = SdCard::new(zephyr::devicetree::labels::sdhc0::get_instance()?)?;
+//! let mut block = [0u8; SD_BLOCK_SIZE];
+//! card.read_block(0, &mut block)?;
+//! ```
+//!
+//! The card API requires `CONFIG_SDMMC_STACK` and `CONFIG_RUST_ALLOC`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// An SD host controller.
+pub struct SdhcDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The controller drivers do their own locking.
+unsafe impl Send for SdhcDevice {}
+
+impl SdhcDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<SdhcDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(SdhcDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for SdhcDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SdhcDevice({:p})", self.device)
+    }
+}
+
+#[cfg(all(CONFIG_SDMMC_STACK, CONFIG_RUST_ALLOC))]
+pub use card::*;
+
+#[cfg(all(CONFIG_SDMMC_STACK, CONFIG_RUST_ALLOC))]
+mod card {
+    extern crate alloc;
+
+    use alloc::boxed::Box;
+    use core::cell::UnsafeCell;
+    use core::fmt;
+    use core::mem;
+
+    use embedded_storage::{ReadStorage, Storage};
+
+    use super::SdhcDevice;
+    use crate::error::{ok_or_errno, ZResult, ZephyrError};
+    use crate::raw;
+
+    /// The size of a block on an SD card.
+    pub const SD_BLOCK_SIZE: usize = 512;
+
+    /// The kind of card.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SdCardType {
+        /// An SD memory card.
+        Sdmmc,
+        /// An SDIO card, such as a WiFi module.
+        Sdio,
+        /// A card with both memory and SDIO functions.
+        Combo,
+        /// An MMC, or eMMC, device.
+        Mmc,
+    }
+
+    /// The size and kind of a card, from [`SdCard::card_info`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SdCardInfo {
+        /// The kind of card.
+        pub card_type: SdCardType,
+        /// The number of blocks.
+        pub block_count: u32,
+        /// The size of each block, in bytes.
+        pub block_size: u32,
+        /// The size of the card, in bytes.
+        pub capacity: u64,
+    }
+
+    /// An SD card, initialized and ready to use.
+    ///
+    /// The card state is boxed, as the stack keeps a lock within it, which can't move once in
+    /// use.
+    pub struct SdCard {
+        card: Box<UnsafeCell<raw::sd_card>>,
+        _sdhc: SdhcDevice,
+    }
+
+    // SAFETY: The card is only used through `&mut self`, and the stack locks it during each call.
+    unsafe impl Send for SdCard {}
+
+    impl SdCard {
+        /// Initialize the card in the given controller.
+        ///
+        /// Fails with `ENODEV` if no card is present.
+        pub fn new(sdhc: SdhcDevice) -> ZResult<SdCard> {
+            if !unsafe { raw::sd_is_card_present(sdhc.device) } {
+                return Err(ZephyrError::NoDevice);
+            }
+            // SAFETY: The card is plain data, filled in by `sd_init`.
+            let card: Box<UnsafeCell<raw::sd_card>> =
+                Box::new(UnsafeCell::new(unsafe { mem::zeroed() }));
+            ok_or_errno(unsafe { raw::sd_init(sdhc.device, card.get()) })?;
+            Ok(SdCard { card, _sdhc: sdhc })
+        }
+
+        fn raw(&self) -> &raw::sd_card {
+            // SAFETY: The stack only changes the card during calls, all of which need
+            // `&mut self`.
+            unsafe { &*self.card.get() }
+        }
+
+        /// The size and kind of the card.
+        pub fn card_info(&self) -> SdCardInfo {
+            let card = self.raw();
+            let card_type = match card.type_ {
+                raw::card_type_CARD_SDIO => SdCardType::Sdio,
+                raw::card_type_CARD_COMBO => SdCardType::Combo,
+                raw::card_type_CARD_MMC => SdCardType::Mmc,
+                _ => SdCardType::Sdmmc,
+            };
+            SdCardInfo {
+                card_type,
+                block_count: card.block_count,
+                block_size: card.block_size,
+                capacity: card.block_count as u64 * card.block_size as u64,
+            }
+        }
+
+        /// Read the block at `sector`.
+        pub fn read_block(&mut self, sector: u32, buf: &mut [u8; SD_BLOCK_SIZE]) -> ZResult<()> {
+            self.read_blocks(sector, buf)
+        }
+
+        /// Write the block at `sector`.
+        pub fn write_block(&mut self, sector: u32, buf: &[u8; SD_BLOCK_SIZE]) -> ZResult<()> {
+            self.write_blocks(sector, buf)
+        }
+
+        /// Read consecutive blocks, starting at `sector`, filling `buf`, which must be a whole
+        /// number of blocks.
+        pub fn read_blocks(&mut self, sector: u32, buf: &mut [u8]) -> ZResult<()> {
+            let count = Self::block_count(buf.len())?;
+            ok_or_errno(unsafe {
+                raw::sdmmc_read_blocks(self.card.get(), buf.as_mut_ptr(), sector, count)
+            })
+        }
+
+        /// Write consecutive blocks, starting at `sector`, from `buf`, which must be a whole
+        /// number of blocks.
+        pub fn write_blocks(&mut self, sector: u32, buf: &[u8]) -> ZResult<()> {
+            let count = Self::block_count(buf.len())?;
+            ok_or_errno(unsafe {
+                raw::sdmmc_write_blocks(self.card.get(), buf.as_ptr(), sector, count)
+            })
+        }
+
+        fn block_count(len: usize) -> ZResult<u32> {
+            if len % SD_BLOCK_SIZE != 0 {
+                return Err(ZephyrError::Invalid);
+            }
+            Ok((len / SD_BLOCK_SIZE) as u32)
+        }
+    }
+
+    impl fmt::Debug for SdCard {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "SdCard({:?})", self.card_info())
+        }
+    }
+
+    /// Byte addressed access, for storage written against [`embedded_storage`].
+    ///
+    /// Partial blocks are read, and written back, whole.  The offsets are 32 bits, so only the
+    /// first 4 GiB of a larger card can be reached this way.
+    impl ReadStorage for SdCard {
+        type Error = ZephyrError;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> ZResult<()> {
+            let mut block = [0u8; SD_BLOCK_SIZE];
+            let mut offset = offset as usize;
+            let mut bytes = bytes;
+            while !bytes.is_empty() {
+                let start = offset % SD_BLOCK_SIZE;
+                let count = bytes.len().min(SD_BLOCK_SIZE - start);
+                self.read_block((offset / SD_BLOCK_SIZE) as u32, &mut block)?;
+                bytes[..count].copy_from_slice(&block[start..start + count]);
+                bytes = &mut bytes[count..];
+                offset += count;
+            }
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.card_info().capacity.min(u32::MAX as u64) as usize
+        }
+    }
+
+    impl Storage for SdCard {
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> ZResult<()> {
+            let mut block = [0u8; SD_BLOCK_SIZE];
+            let mut offset = offset as usize;
+            let mut bytes = bytes;
+            while !bytes.is_empty() {
+                let sector = (offset / SD_BLOCK_SIZE) as u32;
+                let start = offset % SD_BLOCK_SIZE;
+                let count = bytes.len().min(SD_BLOCK_SIZE - start);
+                if count < SD_BLOCK_SIZE {
+                    self.read_block(sector, &mut block)?;
+                }
+                block[start..start + count].copy_from_slice(&bytes[..count]);
+                self.write_block(sector, &block)?;
+                bytes = &bytes[count..];
+                offset += count;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrappers for SPI controllers, and the devices attached to them.
+//!
+//! The controller is represented by [`SpiBus`].  Each child node of the controller in the device
+//! tree becomes a [`SpiDevice`], which is the bus, plus the configuration used to talk to that
+//! device.
+//!
+//! [`SpiDevice`] implements [`embedded_io`]'s `Read` and `Write`, with each call being a single SPI
+//! transfer in one direction.
+//!
+//! Chip select is currently left to the controller hardware.  GPIO chip selects (`cs-gpios`) are
+//! not yet configured from the device tree.
+
+use core::ffi::c_void;
+use core::mem;
+
+use embedded_io::{ErrorType, Read, Write};
+
+use super::Unique;
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+
+/// The operation word used by default: controller mode, mode 0, MSB first, with 8 bit words.
+/// This is `SPI_WORD_SET(8)`, as the macro isn't available to Rust.
+pub const SPI_DEFAULT_OPERATION: u32 = 8 << 5;
+
+/// The default frequency, used until [`SpiDevice::set_config`] is called.
+pub const SPI_DEFAULT_FREQUENCY: u32 = 1_000_000;
+
+/// A SPI controller.
+pub struct SpiBus {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The operations all require `&mut self`.
+unsafe impl Send for SpiBus {}
+
+impl SpiBus {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<SpiBus> {
+        if !unique.once() {
+            return None;
+        }
+        Some(SpiBus { device })
+    }
+
+    /// Verify that the device is ready for use.  At a minimum, this means the device has been
+    /// successfully initialized.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+/// A device on a SPI bus.
+pub struct SpiDevice {
+    pub(crate) device: *const raw::device,
+    pub(crate) config: raw::spi_config,
+}
+
+// SAFETY: The operations all require `&mut self`, and the driver serializes access to the bus.
+unsafe impl Send for SpiDevice {}
+
+impl SpiDevice {
+    /// Constructor, used by the devicetree generated code.  The `slave` number comes from the `reg`
+    /// property of the device's node.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(
+        unique: &Unique,
+        device: *const raw::device,
+        slave: u32,
+    ) -> Option<SpiDevice> {
+        if !unique.once() {
+            return None;
+        }
+        // SAFETY: A zeroed `spi_config` has no chip select control, which is valid.
+        let mut config: raw::spi_config = unsafe { mem::zeroed() };
+        config.frequency = SPI_DEFAULT_FREQUENCY;
+        config.operation = SPI_DEFAULT_OPERATION as _;
+        config.slave = slave as _;
+        Some(SpiDevice { device, config })
+    }
+
+    /// Verify that the bus is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+
+    /// Set the bus frequency, and the operation word (the `SPI_*` flags from Zephyr) used when
+    /// talking to this device.
+    pub fn set_config(&mut self, frequency: u32, operation: u32) {
+        self.config.frequency = frequency;
+        self.config.operation = operation as _;
+    }
+}
+
+impl ErrorType for SpiDevice {
+    type Error = ZephyrError;
+}
+
+impl Read for SpiDevice {
+    /// Read `buf.len()` bytes from the device, as a single transfer.
+    fn read(&mut self, buf: &mut [u8]) -> ZResult<usize> {
+        let spi_buf = raw::spi_buf {
+            buf: buf.as_mut_ptr() as *mut c_void,
+            len: buf.len(),
+        };
+        let set = raw::spi_buf_set {
+            buffers: &spi_buf,
+            count: 1,
+        };
+        ok_or_errno(unsafe { raw::spi_read(self.device, &self.config, &set) })?;
+        Ok(buf.len())
+    }
+}
+
+impl Write for SpiDevice {
+    /// Write all of `buf` to the device, as a single transfer.
+    fn write(&mut self, buf: &[u8]) -> ZResult<usize> {
+        let spi_buf = raw::spi_buf {
+            buf: buf.as_ptr() as *mut c_void,
+            len: buf.len(),
+        };
+        let set = raw::spi_buf_set {
+            buffers: &spi_buf,
+            count: 1,
+        };
+        ok_or_errno(unsafe { raw::spi_write(self.device, &self.config, &set) })?;
+        Ok(buf.len())
+    }
+
+    /// Each write is a complete transfer, so there is nothing to flush.
+    fn flush(&mut self) -> ZResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for USB Type-C Port Controllers.
+//!
+//! A port controller drives, and senses, the CC lines of a Type-C port, from which the role of
+//! each end, and the current a source offers, are found.  This is the low level interface, for
+//! applications that manage the port themselves.  Zephyr's USB-C stack, when enabled, uses the
+//! controller itself, and should not be mixed with this.
+//!
+//! The port controller API requires `CONFIG_USBC_TCPC_DRIVER`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// The power role of a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerRole {
+    /// The port takes power.
+    Sink,
+    /// The port supplies power.
+    Source,
+}
+
+/// The data role of a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataRole {
+    /// Upstream facing, as a device.
+    Ufp,
+    /// Downstream facing, as a host.
+    Dfp,
+}
+
+/// What is seen on a CC line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TcpcCcVoltageState {
+    /// Nothing is connected.
+    #[default]
+    Open,
+    /// The Ra pull down of a cable that needs VCONN.
+    Ra,
+    /// The Rd pull down of a sink.
+    Rd,
+    /// A source offering the default USB current.
+    RpDefault,
+    /// A source offering 1.5 A.
+    Rp1A5,
+    /// A source offering 3 A.
+    Rp3A0,
+}
+
+/// A USB Type-C port controller.
+pub struct TcpcDriver {
+    pub(crate) device: *const raw::device,
+    /// The last power role set, as the controller can't be asked.
+    power_role: PowerRole,
+}
+
+// SAFETY: The port controller drivers can be called from any thread.
+unsafe impl Send for TcpcDriver {}
+
+impl TcpcDriver {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<TcpcDriver> {
+        if !unique.once() {
+            return None;
+        }
+        Some(TcpcDriver {
+            device,
+            power_role: PowerRole::Sink,
+        })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+
+    /// The power role last set with `set_roles`, which starts as [`PowerRole::Sink`].
+    pub fn get_power_role(&self) -> PowerRole {
+        self.power_role
+    }
+}
+
+impl fmt::Debug for TcpcDriver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TcpcDriver({:p}, {:?})", self.device, self.power_role)
+    }
+}
+
+#[cfg(CONFIG_USBC_TCPC_DRIVER)]
+mod api {
+    use super::{DataRole, PowerRole, TcpcCcVoltageState, TcpcDriver};
+    use crate::error::{ok_or_errno, ZResult};
+    use crate::raw;
+
+    fn cc_state(state: raw::tc_cc_voltage_state) -> TcpcCcVoltageState {
+        match state {
+            raw::tc_cc_voltage_state_TC_CC_VOLT_RA => TcpcCcVoltageState::Ra,
+            raw::tc_cc_voltage_state_TC_CC_VOLT_RD => TcpcCcVoltageState::Rd,
+            raw::tc_cc_voltage_state_TC_CC_VOLT_RP_DEF => TcpcCcVoltageState::RpDefault,
+            raw::tc_cc_voltage_state_TC_CC_VOLT_RP_1A5 => TcpcCcVoltageState::Rp1A5,
+            raw::tc_cc_voltage_state_TC_CC_VOLT_RP_3A0 => TcpcCcVoltageState::Rp3A0,
+            _ => TcpcCcVoltageState::Open,
+        }
+    }
+
+    impl TcpcDriver {
+        /// Sense the state of both CC lines.
+        pub fn get_cc(
+            &mut self,
+            cc1: &mut TcpcCcVoltageState,
+            cc2: &mut TcpcCcVoltageState,
+        ) -> ZResult<()> {
+            let mut raw1 = raw::tc_cc_voltage_state_TC_CC_VOLT_OPEN;
+            let mut raw2 = raw::tc_cc_voltage_state_TC_CC_VOLT_OPEN;
+            ok_or_errno(unsafe { raw::tcpc_get_cc(self.device, &mut raw1, &mut raw2) })?;
+            *cc1 = cc_state(raw1);
+            *cc2 = cc_state(raw2);
+            Ok(())
+        }
+
+        /// Turn VCONN, the supply for an active cable, on or off.
+        pub fn set_vconn(&mut self, enable: bool) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::tcpc_set_vconn(self.device, enable) })
+        }
+
+        /// Set the roles the controller reports in Power Delivery messages.
+        pub fn set_roles(&mut self, power: PowerRole, data: DataRole) -> ZResult<()> {
+            let power_raw = match power {
+                PowerRole::Sink => raw::tc_power_role_TC_ROLE_SINK,
+                PowerRole::Source => raw::tc_power_role_TC_ROLE_SOURCE,
+            };
+            let data_raw = match data {
+                DataRole::Ufp => raw::tc_data_role_TC_ROLE_UFP,
+                DataRole::Dfp => raw::tc_data_role_TC_ROLE_DFP,
+            };
+            ok_or_errno(unsafe { raw::tcpc_set_roles(self.device, power_raw, data_raw) })?;
+            self.power_role = power;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrappers for UARTs.
+//!
+//! This currently supports the polling API of the UART driver, which is available on all UART
+//! drivers.  The [`embedded_io`] `Read` and `Write` traits are implemented on top of this, so that
+//! protocol code written against those traits can run over a Zephyr UART.
+
+use embedded_io::{ErrorType, Read, Write};
+
+use super::Unique;
+use crate::error::{ZResult, ZephyrError};
+use crate::raw;
+use crate::time::{sleep, Duration};
+
+/// A UART device.
+///
+/// This is a wrapper around the `struct device` in Zephyr that represents a UART.
+pub struct UartDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The UART can be used from another thread, but the `&mut self` on the operations
+// prevents simultaneous use.
+unsafe impl Send for UartDevice {}
+
+impl UartDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<UartDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(UartDevice { device })
+    }
+
+    /// Verify that the device is ready for use.  At a minimum, this means the device has been
+    /// successfully initialized.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+
+    /// Read a single character, if one is available.
+    ///
+    /// Returns `Ok(None)` if there is no character waiting.  Does not block.
+    pub fn poll_in(&mut self) -> ZResult<Option<u8>> {
+        let mut ch = 0u8;
+        match unsafe { raw::uart_poll_in(self.device, &mut ch) } {
+            0 => Ok(Some(ch)),
+            // The driver returns -1 when the input buffer is empty.
+            -1 => Ok(None),
+            err => Err(ZephyrError::from_errno(err)),
+        }
+    }
+
+    /// Write a single character.
+    ///
+    /// This blocks until the UART is able to accept the character.
+    pub fn poll_out(&mut self, ch: u8) {
+        unsafe { raw::uart_poll_out(self.device, ch) }
+    }
+}
+
+impl ErrorType for UartDevice {
+    type Error = ZephyrError;
+}
+
+impl Read for UartDevice {
+    /// Read at least one byte, waiting for one to arrive if necessary.
+    ///
+    /// As the polling API has no way to wait for data, this checks for input once per tick until
+    /// something arrives.  Once a byte has been read, this returns whatever additional bytes are
+    /// immediately available.
+    fn read(&mut self, buf: &mut [u8]) -> ZResult<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let first = loop {
+            if let Some(ch) = self.poll_in()? {
+                break ch;
+            }
+            sleep(Duration::from_ticks(1));
+        };
+        buf[0] = first;
+
+        let mut count = 1;
+        while count < buf.len() {
+            match self.poll_in()? {
+                Some(ch) => {
+                    buf[count] = ch;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl Write for UartDevice {
+    fn write(&mut self, buf: &[u8]) -> ZResult<usize> {
+        for &ch in buf {
+            self.poll_out(ch);
+        }
+        Ok(buf.len())
+    }
+
+    /// The polling output is complete when `poll_out` returns, so there is nothing to flush.
+    fn flush(&mut self) -> ZResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for video capture devices, such as cameras.
+//!
+//! Frames are captured into [`VideoBuffer`]s, which are handed to the driver with
+//! [`enqueue`](VideoDevice::enqueue), and come back filled from [`dequeue`](VideoDevice::dequeue):
+//!
+//! ```ignore
+//! let mut camera = zephyr::devicetree::labels::camera::get_instance()?;
+//! let format = VideoFormat::new(PixelFormat::RGB565, 320, 240);
+//! camera.set_format(format)?;
+//! for _ in 0..2 {
+//!     camera.enqueue(VideoBuffer::alloc(format.pitch * format.height).unwrap())?;
+//! }
+//! camera.stream_start()?;
+//! loop {
+//!     let frame = camera.dequeue(Forever)?;
+//!     process(frame.data());
+//!     camera.enqueue(frame)?;
+//! }
+//! ```
+//!
+//! The buffers come from the video buffer pool, so `CONFIG_VIDEO_BUFFER_POOL_NUM_MAX` and
+//! `CONFIG_VIDEO_BUFFER_POOL_SZ_MAX` must allow for them.  The video API requires `CONFIG_VIDEO`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// A video capture device.
+pub struct VideoDevice {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The video drivers can be called from any thread.
+unsafe impl Send for VideoDevice {}
+
+impl VideoDevice {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<VideoDevice> {
+        if !unique.once() {
+            return None;
+        }
+        Some(VideoDevice { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for VideoDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VideoDevice({:p})", self.device)
+    }
+}
+
+/// A pixel format, as a four character code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormat(pub u32);
+
+impl PixelFormat {
+    /// The format with the code made of these four characters, as `video_fourcc` does.
+    pub const fn fourcc(code: &[u8; 4]) -> PixelFormat {
+        PixelFormat(u32::from_le_bytes(*code))
+    }
+
+    /// 16-bit RGB, with 5 bits of red, 6 of green and 5 of blue.
+    pub const RGB565: PixelFormat = PixelFormat::fourcc(b"RGBP");
+    /// 24-bit RGB.
+    pub const RGB24: PixelFormat = PixelFormat::fourcc(b"RGB3");
+    /// YUV 4:2:2, packed as Y0 U Y1 V.
+    pub const YUYV: PixelFormat = PixelFormat::fourcc(b"YUYV");
+    /// Raw Bayer samples, 8 bits each, in RGGB order.
+    pub const RGGB8: PixelFormat = PixelFormat::fourcc(b"RGGB");
+    /// JPEG compressed frames.
+    pub const JPEG: PixelFormat = PixelFormat::fourcc(b"JPEG");
+}
+
+/// The format of the frames captured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoFormat {
+    /// The pixel format.
+    pub pixel_format: PixelFormat,
+    /// The width, in pixels.
+    pub width: u32,
+    /// The height, in pixels.
+    pub height: u32,
+    /// The length of each line, in bytes.
+    pub pitch: u32,
+}
+
+impl VideoFormat {
+    /// A format of `width` by `height` pixels, with the pitch worked out for the uncompressed
+    /// formats above.  For other formats, the pitch has to be set by hand.
+    pub fn new(pixel_format: PixelFormat, width: u32, height: u32) -> VideoFormat {
+        let bytes_per_pixel = match pixel_format {
+            PixelFormat::RGB24 => 3,
+            PixelFormat::RGB565 | PixelFormat::YUYV => 2,
+            PixelFormat::RGGB8 => 1,
+            _ => 0,
+        };
+        VideoFormat {
+            pixel_format,
+            width,
+            height,
+            pitch: width * bytes_per_pixel,
+        }
+    }
+}
+
+/// A buffer for one frame, from the video buffer pool.
+pub struct VideoBuffer {
+    buf: *mut raw::video_buffer,
+}
+
+// SAFETY: The buffer is only used by its owner, or by the driver while it is queued.
+unsafe impl Send for VideoBuffer {}
+
+impl fmt::Debug for VideoBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VideoBuffer({:p})", self.buf)
+    }
+}
+
+#[cfg(CONFIG_VIDEO)]
+mod api {
+    use core::mem;
+    use core::ptr;
+    use core::slice;
+
+    use super::{PixelFormat, VideoBuffer, VideoDevice, VideoFormat};
+    use crate::error::{ok_or_errno, ZResult};
+    use crate::raw;
+    use crate::time::Timeout;
+
+    /// The endpoint that frames are captured from.
+    const EP: raw::video_endpoint_id = raw::video_endpoint_id_VIDEO_EP_OUT;
+
+    impl VideoBuffer {
+        /// Allocate a buffer of `size` bytes, or `None` if the pool has no room.
+        pub fn alloc(size: u32) -> Option<VideoBuffer> {
+            let buf = unsafe { raw::video_buffer_alloc(size as usize) };
+            if buf.is_null() {
+                None
+            } else {
+                Some(VideoBuffer { buf })
+            }
+        }
+
+        /// The size of the buffer.
+        pub fn size(&self) -> usize {
+            unsafe { (*self.buf).size as usize }
+        }
+
+        /// The captured data, which is empty until a frame has been captured into the buffer.
+        pub fn data(&self) -> &[u8] {
+            unsafe { slice::from_raw_parts((*self.buf).buffer, (*self.buf).bytesused as usize) }
+        }
+
+        /// When the frame was captured, in milliseconds of uptime.
+        pub fn timestamp(&self) -> u32 {
+            unsafe { (*self.buf).timestamp }
+        }
+    }
+
+    impl Drop for VideoBuffer {
+        fn drop(&mut self) {
+            unsafe { raw::video_buffer_release(self.buf) }
+        }
+    }
+
+    impl VideoDevice {
+        /// Set the format of the frames to capture.
+        pub fn set_format(&mut self, format: VideoFormat) -> ZResult<()> {
+            // SAFETY: The format is plain data, and any fields not set here are meant to be zero.
+            let mut fmt: raw::video_format = unsafe { mem::zeroed() };
+            fmt.pixelformat = format.pixel_format.0;
+            fmt.width = format.width;
+            fmt.height = format.height;
+            fmt.pitch = format.pitch;
+            ok_or_errno(unsafe { raw::video_set_format(self.device, EP, &mut fmt) })
+        }
+
+        /// The current format of the captured frames.
+        pub fn get_format(&self) -> ZResult<VideoFormat> {
+            let mut fmt: raw::video_format = unsafe { mem::zeroed() };
+            ok_or_errno(unsafe { raw::video_get_format(self.device, EP, &mut fmt) })?;
+            Ok(VideoFormat {
+                pixel_format: PixelFormat(fmt.pixelformat),
+                width: fmt.width,
+                height: fmt.height,
+                pitch: fmt.pitch,
+            })
+        }
+
+        /// Give `buf` to the driver, to capture a frame into.
+        ///
+        /// If the driver won't take the buffer, it is released.
+        pub fn enqueue(&mut self, buf: VideoBuffer) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::video_enqueue(self.device, EP, buf.buf) })?;
+            // The driver has the buffer until it is dequeued.
+            mem::forget(buf);
+            Ok(())
+        }
+
+        /// Wait for a captured frame.
+        pub fn dequeue(&mut self, timeout: impl Into<Timeout>) -> ZResult<VideoBuffer> {
+            let mut buf = ptr::null_mut();
+            ok_or_errno(unsafe {
+                raw::video_dequeue(self.device, EP, &mut buf, timeout.into().0)
+            })?;
+            Ok(VideoBuffer { buf })
+        }
+
+        /// Start capturing frames.
+        pub fn stream_start(&mut self) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::video_stream_start(self.device) })
+        }
+
+        /// Stop capturing frames.
+        pub fn stream_stop(&mut self) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::video_stream_stop(self.device) })
+        }
+    }
+}
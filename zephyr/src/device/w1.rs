@@ -0,0 +1,149 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device wrapper for 1-Wire bus masters.
+//!
+//! Each device on a 1-Wire bus has a unique 64-bit ROM code, which [`W1Bus::search`] finds.  A
+//! transaction starts with a reset, followed by a ROM command to pick devices, and then the
+//! device's own commands:
+//!
+//! ```ignore
+//! let mut bus = zephyr::devicetree::labels::w1_0::get_instance()?;
+//! if bus.reset()? {
+//!     bus.write_byte(0xcc)?; // Skip ROM, to address every device.
+//!     bus.write_byte(0x44)?; // DS18B20: Convert T.
+//! }
+//! ```
+//!
+//! The bus API requires `CONFIG_W1`.
+
+use core::fmt;
+
+use super::Unique;
+use crate::raw;
+
+/// A 1-Wire bus.
+pub struct W1Bus {
+    pub(crate) device: *const raw::device,
+}
+
+// SAFETY: The 1-Wire drivers can be used from any thread, and lock the bus for each call.
+unsafe impl Send for W1Bus {}
+
+impl W1Bus {
+    /// Constructor, used by the devicetree generated code.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(unique: &Unique, device: *const raw::device) -> Option<W1Bus> {
+        if !unique.once() {
+            return None;
+        }
+        Some(W1Bus { device })
+    }
+
+    /// Verify that the device is ready for use.
+    pub fn is_ready(&self) -> bool {
+        unsafe { raw::device_is_ready(self.device) }
+    }
+}
+
+impl fmt::Debug for W1Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "W1Bus({:p})", self.device)
+    }
+}
+
+/// The ROM code of a 1-Wire device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct W1RomCode {
+    /// The family code, which gives the kind of device, such as 0x28 for a DS18B20.
+    pub family: u8,
+    /// The serial number.
+    pub serial: [u8; 6],
+    /// The CRC of the family and serial.
+    pub crc: u8,
+}
+
+impl W1RomCode {
+    /// The ROM code as the 8 bytes sent on the bus, family first.
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let s = self.serial;
+        [self.family, s[0], s[1], s[2], s[3], s[4], s[5], self.crc]
+    }
+}
+
+#[cfg(CONFIG_W1)]
+pub use api::*;
+
+#[cfg(CONFIG_W1)]
+mod api {
+    use core::ffi::c_void;
+
+    use super::{W1Bus, W1RomCode};
+    use crate::error::{ok_or_errno, ok_or_errno_val, ZResult};
+    use crate::raw;
+
+    /// The most devices a search returns.
+    pub const MAX_SEARCH: usize = 16;
+
+    /// The devices found by [`W1Bus::search`].
+    pub struct W1SearchIter {
+        roms: heapless::Vec<W1RomCode, MAX_SEARCH>,
+        next: usize,
+    }
+
+    impl Iterator for W1SearchIter {
+        type Item = W1RomCode;
+
+        fn next(&mut self) -> Option<W1RomCode> {
+            let rom = self.roms.get(self.next).copied();
+            self.next += 1;
+            rom
+        }
+    }
+
+    impl W1Bus {
+        /// Reset the bus.  Returns true if any device answered with a presence pulse.
+        pub fn reset(&mut self) -> ZResult<bool> {
+            let ret = unsafe { raw::w1_reset_bus(self.device) };
+            ok_or_errno_val(ret, ret != 0)
+        }
+
+        /// Write a byte.
+        pub fn write_byte(&mut self, byte: u8) -> ZResult<()> {
+            ok_or_errno(unsafe { raw::w1_write_byte(self.device, byte) })
+        }
+
+        /// Read a byte.
+        pub fn read_byte(&mut self) -> ZResult<u8> {
+            let ret = unsafe { raw::w1_read_byte(self.device) };
+            ok_or_errno_val(ret, ret as u8)
+        }
+
+        /// Find the devices on the bus, up to [`MAX_SEARCH`] of them.
+        ///
+        /// The whole search is done here, so the iterator only returns the results.
+        pub fn search(&mut self) -> ZResult<W1SearchIter> {
+            let mut roms = heapless::Vec::new();
+            ok_or_errno(unsafe {
+                raw::w1_search_rom(
+                    self.device,
+                    Some(found),
+                    &mut roms as *mut heapless::Vec<W1RomCode, MAX_SEARCH> as *mut c_void,
+                )
+            })?;
+            Ok(W1SearchIter { roms, next: 0 })
+        }
+    }
+
+    /// Called by the search, for each device found.
+    unsafe extern "C" fn found(rom: raw::w1_rom, user_data: *mut c_void) {
+        // SAFETY: The user data is the list given by `search`, which waits for the search.
+        let roms = &mut *(user_data as *mut heapless::Vec<W1RomCode, MAX_SEARCH>);
+        // Devices past the limit are left out.
+        let _ = roms.push(W1RomCode {
+            family: rom.family,
+            serial: rom.serial,
+            crc: rom.crc,
+        });
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Block storage, with Zephyr's disk access API.
+//!
+//! Disks are named by their driver, such as `"SD"` for an SD card, `"RAM"` for a RAM disk, or the
+//! name given in the devicetree for a flash disk.  Reads and writes are in whole sectors, with the
+//! buffer holding `num_sectors` times the sector size.
+//!
+//! Requires `CONFIG_DISK_ACCESS`.
+
+use core::ffi::{c_void, CStr};
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+
+/// A request to [`DiskDevice::ioctl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskIoctl {
+    /// The number of sectors on the disk.
+    SectorCount,
+    /// The size of each sector, in bytes.
+    SectorSize,
+    /// The size of the erase block, in sectors.
+    EraseBlockSize,
+    /// Finish any writes that are still cached.  Returns zero.
+    Sync,
+}
+
+/// A disk.
+#[derive(Debug, Clone, Copy)]
+pub struct DiskDevice {
+    name: &'static CStr,
+}
+
+impl DiskDevice {
+    /// The disk with the given name, initialized, so it is ready to use.
+    pub fn new(name: &'static CStr) -> ZResult<DiskDevice> {
+        ok_or_errno(unsafe { raw::disk_access_init(name.as_ptr()) })?;
+        Ok(DiskDevice { name })
+    }
+
+    /// The disk's name.
+    pub fn name(&self) -> &'static CStr {
+        self.name
+    }
+
+    /// Read `num_sectors` sectors, starting at `start_sector`, into `buf`.
+    pub fn read(&self, buf: &mut [u8], start_sector: u32, num_sectors: u32) -> ZResult<()> {
+        self.check_len(buf.len(), num_sectors)?;
+        ok_or_errno(unsafe {
+            raw::disk_access_read(
+                self.name.as_ptr(),
+                buf.as_mut_ptr(),
+                start_sector,
+                num_sectors,
+            )
+        })
+    }
+
+    /// Write `num_sectors` sectors, starting at `start_sector`, from `buf`.
+    pub fn write(&self, buf: &[u8], start_sector: u32, num_sectors: u32) -> ZResult<()> {
+        self.check_len(buf.len(), num_sectors)?;
+        ok_or_errno(unsafe {
+            raw::disk_access_write(self.name.as_ptr(), buf.as_ptr(), start_sector, num_sectors)
+        })
+    }
+
+    /// Query, or control, the disk.
+    pub fn ioctl(&self, cmd: DiskIoctl) -> ZResult<u32> {
+        let cmd = match cmd {
+            DiskIoctl::SectorCount => raw::DISK_IOCTL_GET_SECTOR_COUNT,
+            DiskIoctl::SectorSize => raw::DISK_IOCTL_GET_SECTOR_SIZE,
+            DiskIoctl::EraseBlockSize => raw::DISK_IOCTL_GET_ERASE_BLOCK_SZ,
+            DiskIoctl::Sync => raw::DISK_IOCTL_CTRL_SYNC,
+        };
+        let mut value: u32 = 0;
+        ok_or_errno(unsafe {
+            raw::disk_access_ioctl(
+                self.name.as_ptr(),
+                cmd as u8,
+                &mut value as *mut u32 as *mut c_void,
+            )
+        })?;
+        Ok(value)
+    }
+
+    /// Check that a buffer is large enough for the sectors, as the driver doesn't know its size.
+    fn check_len(&self, len: usize, num_sectors: u32) -> ZResult<()> {
+        let sector_size = self.ioctl(DiskIoctl::SectorSize)? as usize;
+        if len < sector_size * num_sectors as usize {
+            return Err(ZephyrError::Invalid);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Buffers for DMA, with the cache maintenance that DMA needs.
+//!
+//! On targets with a data cache, such as the Cortex-M7, a DMA controller reads and writes memory
+//! without going through the cache.  So before a DMA transfer reads a buffer, the CPU's writes to
+//! it must be flushed from the cache, and after a transfer has written one, any stale lines for it
+//! must be invalidated before the CPU reads it.
+//!
+//! A [`DmaBuffer`] is aligned to, and padded to, 32 bytes, the cache line size on the Cortex-M7,
+//! so that maintaining its lines never affects other data.  For targets with larger cache lines,
+//! the buffer should be placed in memory that isn't cached.
+//!
+//! ```ignore
+//! let mut tx: DmaBuffer<u8, 64> = DmaBuffer::new([0; 64]);
+//!
+//! tx[..message.len()].copy_from_slice(&message);
+//! tx.flush_dcache()?;
+//! // Start the transfer from `tx.as_ptr()`.
+//! ```
+//!
+//! Requires `CONFIG_CACHE_MANAGEMENT`.
+
+use core::ffi::c_void;
+use core::fmt;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+
+/// A fixed size array of `N` `T`s, aligned for DMA.
+#[repr(C, align(32))]
+pub struct DmaBuffer<T: Copy, const N: usize> {
+    data: [T; N],
+}
+
+impl<T: Copy, const N: usize> DmaBuffer<T, N> {
+    /// A buffer holding `data`.
+    pub const fn new(data: [T; N]) -> DmaBuffer<T, N> {
+        DmaBuffer { data }
+    }
+
+    /// Write any cached changes to the buffer out to memory, before a DMA transfer reads it.
+    ///
+    /// On targets without a data cache, this does nothing.
+    pub fn flush_dcache(&self) -> ZResult<()> {
+        let ret = unsafe {
+            raw::sys_cache_data_flush_range(self.as_ptr() as *mut c_void, mem::size_of::<Self>())
+        };
+        Self::result(ret)
+    }
+
+    /// Discard any cached contents of the buffer, after a DMA transfer has written it.
+    ///
+    /// This takes `&mut self`, as changes the CPU has made to the buffer, and not flushed, can be
+    /// lost.  On targets without a data cache, this does nothing.
+    pub fn invalidate_dcache(&mut self) -> ZResult<()> {
+        let ret = unsafe {
+            raw::sys_cache_data_invd_range(
+                self.as_mut_ptr() as *mut c_void,
+                mem::size_of::<Self>(),
+            )
+        };
+        Self::result(ret)
+    }
+
+    /// Flush and then invalidate the buffer, for a transfer that both reads and writes it.
+    pub fn flush_and_invalidate_dcache(&mut self) -> ZResult<()> {
+        let ret = unsafe {
+            raw::sys_cache_data_flush_and_invd_range(
+                self.as_mut_ptr() as *mut c_void,
+                mem::size_of::<Self>(),
+            )
+        };
+        Self::result(ret)
+    }
+
+    /// A target without a data cache reports that maintenance is not supported, which is fine, as
+    /// there is nothing to maintain.
+    fn result(ret: i32) -> ZResult<()> {
+        match ok_or_errno(ret) {
+            Err(ZephyrError::NotSupported) => Ok(()),
+            other => other,
+        }
+    }
+
+    /// A pointer to the start of the buffer, to give to the DMA controller.
+    pub fn as_ptr(&self) -> *const T {
+        self.data.as_ptr()
+    }
+
+    /// A mutable pointer to the start of the buffer, to give to the DMA controller.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr()
+    }
+}
+
+impl<T: Copy, const N: usize> Deref for DmaBuffer<T, N> {
+    type Target = [T; N];
+
+    fn deref(&self) -> &[T; N] {
+        &self.data
+    }
+}
+
+impl<T: Copy, const N: usize> DerefMut for DmaBuffer<T, N> {
+    fn deref_mut(&mut self) -> &mut [T; N] {
+        &mut self.data
+    }
+}
+
+impl<T: Copy, const N: usize> fmt::Debug for DmaBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DmaBuffer<{}>({:p})", N, self.data.as_ptr())
+    }
+}
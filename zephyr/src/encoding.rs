@@ -0,0 +1,158 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Base64 encoding and decoding, with Zephyr's base64 library.
+//!
+//! Zephyr only implements the standard alphabet of RFC 4648.  The URL and filename safe alphabet,
+//! which uses `-` and `_` in place of `+` and `/`, is handled here by translating those two
+//! characters, so [`Alphabet::UrlSafe`] works the same way, padding included.  Unpadded input is
+//! also accepted when decoding with it.
+//!
+//! Requires `CONFIG_BASE64`.
+
+use core::fmt;
+
+use crate::error::{ok_or_errno_val, ZephyrError};
+use crate::raw;
+
+/// An error encoding or decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Error {
+    /// The output buffer is too small.  `needed` is the size it needs to be, except for decoding
+    /// the URL safe alphabet, where it is only the size needed to get further.
+    BufferTooSmall {
+        /// The size of output buffer needed.
+        needed: usize,
+    },
+    /// The input is not valid base64.
+    InvalidInput,
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base64Error::BufferTooSmall { needed } => {
+                write!(f, "base64: output buffer too small, {} needed", needed)
+            }
+            Base64Error::InvalidInput => write!(f, "base64: invalid input"),
+        }
+    }
+}
+
+/// The characters used to encode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// The standard alphabet, with `+` and `/`.
+    Standard,
+    /// The URL and filename safe alphabet, with `-` and `_`.
+    UrlSafe,
+}
+
+/// The result of a base64 call, which gives the size needed in `olen` when it fails with
+/// `ENOMEM`.
+fn base64_result(ret: i32, olen: usize) -> Result<usize, Base64Error> {
+    ok_or_errno_val(ret, olen).map_err(|err| match err {
+        ZephyrError::NoMemory => Base64Error::BufferTooSmall { needed: olen },
+        _ => Base64Error::InvalidInput,
+    })
+}
+
+/// The size of the buffer [`base64_encode`] needs for `len` bytes of input, including the NUL.
+pub const fn encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4 + 1
+}
+
+/// Encode `input` into `output`, returning the length of the encoded text.
+///
+/// The text is also NUL terminated, so `output` needs room for one more byte, as given by
+/// [`encoded_len`].
+pub fn base64_encode(
+    input: &[u8],
+    output: &mut [u8],
+    alphabet: Alphabet,
+) -> Result<usize, Base64Error> {
+    let mut olen = 0;
+    let ret = unsafe {
+        raw::base64_encode(
+            output.as_mut_ptr(),
+            output.len(),
+            &mut olen,
+            input.as_ptr(),
+            input.len(),
+        )
+    };
+    let len = base64_result(ret, olen)?;
+    if alphabet == Alphabet::UrlSafe {
+        for c in &mut output[..len] {
+            *c = match *c {
+                b'+' => b'-',
+                b'/' => b'_',
+                other => other,
+            };
+        }
+    }
+    Ok(len)
+}
+
+/// Decode `input` into `output`, returning the number of bytes decoded.
+pub fn base64_decode(
+    input: &[u8],
+    output: &mut [u8],
+    alphabet: Alphabet,
+) -> Result<usize, Base64Error> {
+    match alphabet {
+        Alphabet::Standard => decode_raw(input, output),
+        Alphabet::UrlSafe => decode_url_safe(input, output),
+    }
+}
+
+fn decode_raw(input: &[u8], output: &mut [u8]) -> Result<usize, Base64Error> {
+    let mut olen = 0;
+    let ret = unsafe {
+        raw::base64_decode(
+            output.as_mut_ptr(),
+            output.len(),
+            &mut olen,
+            input.as_ptr(),
+            input.len(),
+        )
+    };
+    base64_result(ret, olen)
+}
+
+/// Decode the URL safe alphabet, by translating it to the standard one in chunks small enough to
+/// keep on the stack.
+fn decode_url_safe(input: &[u8], output: &mut [u8]) -> Result<usize, Base64Error> {
+    // A multiple of 4, so only the last chunk can have padding.
+    const CHUNK: usize = 64;
+    let mut chunk = [0u8; CHUNK];
+    let mut written = 0;
+
+    for part in input.chunks(CHUNK) {
+        if part.iter().any(|&c| c == b'+' || c == b'/') {
+            return Err(Base64Error::InvalidInput);
+        }
+        for (out, &c) in chunk.iter_mut().zip(part) {
+            *out = match c {
+                b'-' => b'+',
+                b'_' => b'/',
+                other => other,
+            };
+        }
+        // Padding is often left off with this alphabet, so put it back.
+        let mut len = part.len();
+        while len % 4 != 0 {
+            chunk[len] = b'=';
+            len += 1;
+        }
+        written += match decode_raw(&chunk[..len], &mut output[written..]) {
+            Err(Base64Error::BufferTooSmall { needed }) => {
+                return Err(Base64Error::BufferTooSmall {
+                    needed: written + needed,
+                })
+            }
+            other => other?,
+        };
+    }
+    Ok(written)
+}
@@ -7,9 +7,10 @@
 //! operations in Zephyr return an int result where negative values correspond with errnos.
 //! Convert those to a `Result` type where the `Error` condition maps to errnos.
 //!
-//! Initially, this will just simply wrap the numeric error code, but it might make sense to make
-//! this an enum itself, however, it would probably be better to auto-generate this enum instead of
-//! trying to maintain the list manually.
+//! The [`Error`] type simply wraps the numeric error code.  To make errors easier to match on, and
+//! more readable when printed, [`ZephyrError`] gives names to the common errno values.  An `Error`
+//! can be converted to a `ZephyrError` with [`Error::kind`], and the formatting of `Error` uses
+//! these names.
 
 use core::ffi::c_int;
 use core::fmt;
@@ -24,15 +25,158 @@ pub struct Error(pub u32);
 
 impl core::error::Error for Error {}
 
+impl Error {
+    /// Return the named version of this error.
+    pub fn kind(&self) -> ZephyrError {
+        ZephyrError::from_errno(self.0 as i32)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "zephyr error errno:{}", self.0)
+        write!(f, "zephyr error errno:{} ({})", self.0, self.kind())
     }
 }
 
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "zephyr error errno:{}", self.0)
+        write!(f, "zephyr error errno:{} {:?}", self.0, self.kind())
+    }
+}
+
+macro_rules! zephyr_errors {
+    ($($name:ident = $errno:ident, $desc:literal;)*) => {
+        /// A Zephyr errno value, with names for the common values.
+        ///
+        /// `Debug` gives the name of the variant, and `Display` gives a description of the error,
+        /// similar to what `strerror` would return.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[non_exhaustive]
+        pub enum ZephyrError {
+            $(
+                #[doc = $desc]
+                $name,
+            )*
+            /// An errno value without a name here.
+            Other(u32),
+        }
+
+        impl ZephyrError {
+            /// Map an errno value to a `ZephyrError`.
+            ///
+            /// As Zephyr functions return negative errno values, the sign is ignored, so either the
+            /// raw return value, or the positive errno can be given.
+            pub fn from_errno(errno: i32) -> ZephyrError {
+                match errno.unsigned_abs() {
+                    $(zephyr_sys::$errno => ZephyrError::$name,)*
+                    other => ZephyrError::Other(other),
+                }
+            }
+
+            /// The errno value (positive) for this error.
+            pub fn errno(&self) -> u32 {
+                match self {
+                    $(ZephyrError::$name => zephyr_sys::$errno,)*
+                    ZephyrError::Other(errno) => *errno,
+                }
+            }
+
+            /// A description of this error.
+            pub fn description(&self) -> &'static str {
+                match self {
+                    $(ZephyrError::$name => $desc,)*
+                    ZephyrError::Other(_) => "Unknown error",
+                }
+            }
+        }
+    };
+}
+
+zephyr_errors! {
+    NotPermitted = EPERM, "Not owner";
+    NoEntry = ENOENT, "No such file or directory";
+    NoProcess = ESRCH, "No such context";
+    Interrupted = EINTR, "Interrupted system call";
+    Io = EIO, "I/O error";
+    NoDeviceOrAddress = ENXIO, "No such device or address";
+    TooBig = E2BIG, "Arg list too long";
+    BadFile = EBADF, "Bad file number";
+    Again = EAGAIN, "No more contexts";
+    NoMemory = ENOMEM, "Not enough core";
+    AccessDenied = EACCES, "Permission denied";
+    Fault = EFAULT, "Bad address";
+    Busy = EBUSY, "Device or resource busy";
+    Exists = EEXIST, "File exists";
+    NoDevice = ENODEV, "No such device";
+    NotDirectory = ENOTDIR, "Not a directory";
+    IsDirectory = EISDIR, "Is a directory";
+    Invalid = EINVAL, "Invalid argument";
+    TooManyFiles = EMFILE, "Too many open files";
+    NoSpace = ENOSPC, "No space left on device";
+    ReadOnly = EROFS, "Read-only file system";
+    BrokenPipe = EPIPE, "Broken pipe";
+    Domain = EDOM, "Argument too large";
+    Range = ERANGE, "Result too large";
+    NoMessage = ENOMSG, "Unexpected message type";
+    NotImplemented = ENOSYS, "Function not implemented";
+    NotSupported = ENOTSUP, "Unsupported value";
+    MessageSize = EMSGSIZE, "Message size";
+    NoBuffers = ENOBUFS, "No buffer space available";
+    AddressInUse = EADDRINUSE, "Address already in use";
+    ConnectionReset = ECONNRESET, "Connection reset by peer";
+    NotConnected = ENOTCONN, "Socket is not connected";
+    TimedOut = ETIMEDOUT, "Connection timed out";
+    ConnectionRefused = ECONNREFUSED, "Connection refused";
+    Already = EALREADY, "Operation already in progress";
+    InProgress = EINPROGRESS, "Operation now in progress";
+    Canceled = ECANCELED, "Operation canceled";
+    BadMessage = EBADMSG, "Invalid message";
+    NoData = ENODATA, "Missing expected message data";
+}
+
+impl fmt::Display for ZephyrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl core::error::Error for ZephyrError {}
+
+impl embedded_io::Error for ZephyrError {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        use embedded_io::ErrorKind;
+
+        match self {
+            ZephyrError::NoEntry | ZephyrError::NoDevice | ZephyrError::NoDeviceOrAddress => {
+                ErrorKind::NotFound
+            }
+            ZephyrError::NotPermitted | ZephyrError::AccessDenied => ErrorKind::PermissionDenied,
+            ZephyrError::ConnectionRefused => ErrorKind::ConnectionRefused,
+            ZephyrError::ConnectionReset => ErrorKind::ConnectionReset,
+            ZephyrError::NotConnected => ErrorKind::NotConnected,
+            ZephyrError::AddressInUse => ErrorKind::AddrInUse,
+            ZephyrError::BrokenPipe => ErrorKind::BrokenPipe,
+            ZephyrError::Exists => ErrorKind::AlreadyExists,
+            ZephyrError::Invalid => ErrorKind::InvalidInput,
+            ZephyrError::BadMessage => ErrorKind::InvalidData,
+            ZephyrError::TimedOut | ZephyrError::Again => ErrorKind::TimedOut,
+            ZephyrError::Interrupted => ErrorKind::Interrupted,
+            ZephyrError::NotSupported | ZephyrError::NotImplemented => ErrorKind::Unsupported,
+            ZephyrError::NoMemory | ZephyrError::NoBuffers => ErrorKind::OutOfMemory,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<Error> for ZephyrError {
+    fn from(err: Error) -> ZephyrError {
+        err.kind()
+    }
+}
+
+impl From<ZephyrError> for Error {
+    fn from(err: ZephyrError) -> Error {
+        Error(err.errno())
     }
 }
 
@@ -56,3 +200,31 @@ pub fn to_result(code: c_int) -> Result<c_int> {
 pub fn to_result_void(code: c_int) -> Result<()> {
     to_result(code).map(|_| ())
 }
+
+/// Wraps a value with a possible named Zephyr error.
+pub type ZResult<T> = core::result::Result<T, ZephyrError>;
+
+/// Map a return result from Zephyr into a [`ZResult`].
+///
+/// Negative values are errors, and anything else is treated as success.  This allows, for example:
+///
+/// ```ignore
+/// ok_or_errno(unsafe { k_sem_take(sem, timeout) })?;
+/// ```
+#[inline(always)]
+pub fn ok_or_errno(ret: c_int) -> ZResult<()> {
+    ok_or_errno_val(ret, ())
+}
+
+/// Map a return result from Zephyr into a [`ZResult`], giving `val` on success.
+///
+/// This is for calls that return a non-zero value on success, but where the result is something
+/// other than that return value.
+#[inline(always)]
+pub fn ok_or_errno_val<T>(ret: c_int, val: T) -> ZResult<T> {
+    if ret < 0 {
+        Err(ZephyrError::from_errno(ret))
+    } else {
+        Ok(val)
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A single threaded executor for a fixed set of futures.
+//!
+//! The futures support in [`work`] runs each future as work on a Zephyr work queue.  For
+//! firmware that has several logical tasks written with async/await, but wants them all to run on
+//! a single thread, the [`StaticExecutor`] instead polls up to `N` futures, round robin, from the
+//! calling thread.  There is no allocation, and no context switch between the tasks.
+//!
+//! ```
+//! let mut first = pin!(async { /* ... */ });
+//! let mut second = pin!(async { /* ... */ });
+//!
+//! let mut executor = pin!(StaticExecutor::<2>::new());
+//! executor.as_mut().spawn(first.as_mut()).unwrap();
+//! executor.as_mut().spawn(second.as_mut()).unwrap();
+//! executor.as_mut().run_until_complete();
+//! ```
+//!
+//! Each future has a bit in a ready mask, which its waker sets.  The executor polls the futures
+//! with their bit set, and sleeps on a semaphore when none are ready.  The wakers refer back to
+//! the executor, which is why the executor itself must be pinned.  Wakers can be invoked from
+//! other threads, or from IRQ context.
+//!
+//! Wakers don't hold a pointer to the executor.  Instead, they hold a token, which is looked up in
+//! a table of the executors that are running.  A waker that outlives the run it was made for, or
+//! the executor itself, finds nothing in the table, and does nothing.  At most
+//! [`MAX_RUNNING`] executors can be running at once.
+//!
+//! [`work`]: crate::work
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::future::Future;
+use core::marker::PhantomPinned;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, RawWaker, RawWakerVTable, Waker};
+
+use zephyr_sys::{k_sem, k_sem_give, k_sem_init, k_sem_take};
+
+use crate::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use crate::sync::SpinMutex;
+use crate::sys::K_FOREVER;
+
+/// The number of executors that can be running, on different threads, at the same time.
+pub const MAX_RUNNING: usize = 4;
+
+/// The executors that are running, as the token of each, and the address of its [`Shared`].
+static RUNNING: SpinMutex<heapless::Vec<(usize, usize), MAX_RUNNING>> =
+    SpinMutex::new(heapless::Vec::new());
+
+/// The source of tokens.  The low 5 bits of a waker's token are its task index, so step by 32.
+static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(0);
+
+/// A future that can be run by the [`StaticExecutor`].
+type Task<'a> = Pin<&'a mut (dyn Future<Output = ()> + 'a)>;
+
+/// An executor that runs up to `N` futures on the current thread.
+///
+/// `N` can be at most 32.
+pub struct StaticExecutor<'a, const N: usize> {
+    tasks: [Option<Task<'a>>; N],
+    shared: Shared,
+    _pin: PhantomPinned,
+}
+
+/// The state shared with the wakers.
+struct Shared {
+    /// A bit for each task that has been woken.
+    ready: AtomicU32,
+    /// Given by the wakers, so the executor can sleep when nothing is ready.
+    sem: UnsafeCell<k_sem>,
+}
+
+// SAFETY: The wakers only use the atomic fields, and the semaphore, which are safe from any thread.
+unsafe impl Sync for Shared {}
+
+impl<'a, const N: usize> StaticExecutor<'a, N> {
+    /// Create a new executor, with no futures.
+    pub fn new() -> StaticExecutor<'a, N> {
+        assert!(N <= 32, "StaticExecutor supports at most 32 futures");
+        StaticExecutor {
+            tasks: core::array::from_fn(|_| None),
+            shared: Shared {
+                ready: AtomicU32::new(0),
+                // SAFETY: The semaphore is initialized by `k_sem_init` before it is used.
+                sem: unsafe { mem::zeroed() },
+            },
+            _pin: PhantomPinned,
+        }
+    }
+
+    /// Add a future to the executor.
+    ///
+    /// The future will be polled the next time the executor is run.  Returns [`ExecutorFull`] if
+    /// there are already `N` futures that have not completed.
+    pub fn spawn(
+        self: Pin<&mut Self>,
+        future: Pin<&'a mut (dyn Future<Output = ()> + 'a)>,
+    ) -> Result<(), ExecutorFull> {
+        // SAFETY: Nothing here is moved out of the executor.
+        let this = unsafe { self.get_unchecked_mut() };
+        let index = this
+            .tasks
+            .iter()
+            .position(Option::is_none)
+            .ok_or(ExecutorFull)?;
+        this.tasks[index] = Some(future);
+        this.shared.ready.fetch_or(1 << index, Ordering::Release);
+        Ok(())
+    }
+
+    /// Run the futures, until all of them have completed.
+    ///
+    /// The futures are polled round robin, each time they are woken.  When none are ready to run,
+    /// the thread sleeps until one is woken.  This can be called again after spawning more futures.
+    ///
+    /// Panics if [`MAX_RUNNING`] other executors are already running.
+    pub fn run_until_complete(self: Pin<&mut Self>) {
+        // SAFETY: Nothing here is moved out of the executor.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        unsafe {
+            k_sem_init(this.shared.sem.get(), 0, 1);
+        }
+        let token = NEXT_TOKEN.fetch_add(32, Ordering::Relaxed);
+        let shared = &this.shared as *const Shared as usize;
+        RUNNING
+            .lock()
+            .unwrap()
+            .push((token, shared))
+            .expect("Too many StaticExecutors running");
+
+        while this.tasks.iter().any(Option::is_some) {
+            let ready = this.shared.ready.swap(0, Ordering::Acquire);
+            if ready == 0 {
+                unsafe {
+                    k_sem_take(this.shared.sem.get(), K_FOREVER);
+                }
+                continue;
+            }
+
+            for (i, task) in this.tasks.iter_mut().enumerate() {
+                if ready & (1 << i) == 0 {
+                    continue;
+                }
+                if let Some(future) = task {
+                    let waker = new_waker(token | i);
+                    let mut cx = Context::from_waker(&waker);
+                    if future.as_mut().poll(&mut cx).is_ready() {
+                        *task = None;
+                    }
+                }
+            }
+        }
+
+        // Any wakers left over from this run now do nothing.
+        RUNNING.lock().unwrap().retain(|&(t, _)| t != token);
+    }
+}
+
+impl<'a, const N: usize> Default for StaticExecutor<'a, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> fmt::Debug for StaticExecutor<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StaticExecutor<{}>", N)
+    }
+}
+
+/// Returned from [`StaticExecutor::spawn`] when the executor is already running `N` futures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutorFull;
+
+impl fmt::Display for ExecutorFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "executor has no free task slots")
+    }
+}
+
+static VTABLE: RawWakerVTable =
+    RawWakerVTable::new(waker_clone, waker_wake, waker_wake_by_ref, waker_drop);
+
+fn new_waker(token: usize) -> Waker {
+    // SAFETY: The data is only used as a token, never dereferenced.
+    unsafe { Waker::from_raw(waker_clone(token as *const ())) }
+}
+
+unsafe fn waker_clone(data: *const ()) -> RawWaker {
+    RawWaker::new(data, &VTABLE)
+}
+
+unsafe fn waker_wake(data: *const ()) {
+    waker_wake_by_ref(data);
+}
+
+unsafe fn waker_wake_by_ref(data: *const ()) {
+    let token = data as usize;
+    let running = RUNNING.lock().unwrap();
+    // The executor can't finish its run, and so be dropped, while the table is locked.
+    if let Some(&(_, shared)) = running.iter().find(|&&(t, _)| t == token & !31) {
+        let shared = &*(shared as *const Shared);
+        shared.ready.fetch_or(1 << (token & 31), Ordering::Release);
+        k_sem_give(shared.sem.get());
+    }
+}
+
+unsafe fn waker_drop(_data: *const ()) {}
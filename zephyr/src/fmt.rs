@@ -0,0 +1,56 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Formatting without allocation.
+//!
+//! The `format!` macro from `alloc` requires the heap, which isn't always available, and isn't
+//! appropriate from places such as interrupt handlers.  This module provides a way to format into a
+//! fixed size buffer, which will typically live on the stack.
+//!
+//! For output to the console, [`printkln!`] already avoids allocation.  This is for the cases where
+//! the formatted text needs to be handed to some other function.
+//!
+//! [`printkln!`]: crate::printkln
+
+use core::fmt::{self, Arguments, Write};
+
+pub use heapless::String;
+
+/// Format the given arguments into a string of fixed capacity `N`.
+///
+/// If the formatted text does not fit, it is truncated at the last character that does fit.  The
+/// truncation always happens on a character boundary, so the result is still valid UTF-8.
+///
+/// ```ignore
+/// let msg = zephyr::fmt::format_stack::<32>(format_args!("count: {}", count));
+/// ```
+pub fn format_stack<const N: usize>(args: Arguments<'_>) -> String<N> {
+    let mut result = String::new();
+    // An error here just indicates that the output was truncated.
+    let _ = fmt::write(&mut Truncating(&mut result), args);
+    result
+}
+
+/// Format into a stack buffer, like `format!`.
+///
+/// The first argument is the capacity of the buffer, the rest are as `format!`.  See
+/// [`format_stack`] for details on truncation.
+#[macro_export]
+macro_rules! format_stack {
+    ($size:expr, $($arg:tt)*) => {
+        $crate::fmt::format_stack::<$size>(format_args!($($arg)*))
+    };
+}
+
+/// A writer that stops, rather than failing, when the underlying string is full.
+struct Truncating<'a, const N: usize>(&'a mut String<N>);
+
+impl<const N: usize> Write for Truncating<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for ch in s.chars() {
+            // Once full, stop the formatting.  The error is discarded by `format_stack`.
+            self.0.push(ch).map_err(|_| fmt::Error)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! File system access.
+//!
+//! Zephyr's virtual file system gives a common API to the mounted file systems, such as FAT and
+//! LittleFS.  This wraps that API with [`File`] and [`Dir`] types, that close themselves when
+//! dropped.  Paths are absolute, and include the mount point, for example `c"/lfs/config"`.
+//!
+//! Mounting the file systems is still done through Zephyr, usually from the devicetree with
+//! `zephyr,fstab`.
+//!
+//! Requires `CONFIG_FILE_SYSTEM`.
+
+use core::ffi::{c_int, CStr};
+use core::fmt;
+use core::mem;
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+
+/// Flags for opening a file.
+///
+/// These can be combined with `|`, for example `OpenFlags::WRITE | OpenFlags::CREATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenFlags(raw::fs_mode_t);
+
+impl OpenFlags {
+    /// Open for reading.
+    pub const READ: OpenFlags = OpenFlags(raw::FS_O_READ as raw::fs_mode_t);
+    /// Open for writing.
+    pub const WRITE: OpenFlags = OpenFlags(raw::FS_O_WRITE as raw::fs_mode_t);
+    /// Open for reading and writing.
+    pub const RDWR: OpenFlags = OpenFlags(raw::FS_O_RDWR as raw::fs_mode_t);
+    /// Create the file if it does not exist.
+    pub const CREATE: OpenFlags = OpenFlags(raw::FS_O_CREATE as raw::fs_mode_t);
+    /// Move to the end of the file before each write.
+    pub const APPEND: OpenFlags = OpenFlags(raw::FS_O_APPEND as raw::fs_mode_t);
+}
+
+impl core::ops::BitOr for OpenFlags {
+    type Output = OpenFlags;
+
+    fn bitor(self, rhs: OpenFlags) -> OpenFlags {
+        OpenFlags(self.0 | rhs.0)
+    }
+}
+
+/// A position to seek to, the same as `std::io::SeekFrom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// An offset from the start of the file.
+    Start(u64),
+    /// An offset from the end of the file.
+    End(i64),
+    /// An offset from the current position.
+    Current(i64),
+}
+
+/// An open file.
+pub struct File {
+    file: raw::fs_file_t,
+}
+
+// SAFETY: The file can be used from another thread, all operations take `&mut self`.
+unsafe impl Send for File {}
+
+impl File {
+    /// Open the file at `path`.
+    pub fn open(path: &CStr, flags: OpenFlags) -> ZResult<File> {
+        // SAFETY: `fs_file_t_init` initializes the zeroed struct.
+        let mut file: raw::fs_file_t = unsafe { mem::zeroed() };
+        unsafe {
+            raw::fs_file_t_init(&mut file);
+            ok_or_errno(raw::fs_open(&mut file, path.as_ptr(), flags.0))?;
+        }
+        Ok(File { file })
+    }
+
+    /// Read from the file, returning the number of bytes read.  Returns 0 at the end of the file.
+    pub fn read(&mut self, buf: &mut [u8]) -> ZResult<usize> {
+        let ret = unsafe { raw::fs_read(&mut self.file, buf.as_mut_ptr() as *mut _, buf.len()) };
+        // Only a negative errno is narrowed, so large counts are not truncated.
+        if ret < 0 {
+            return Err(ZephyrError::from_errno(ret as i32));
+        }
+        Ok(ret as usize)
+    }
+
+    /// Write to the file, returning the number of bytes written.
+    pub fn write(&mut self, buf: &[u8]) -> ZResult<usize> {
+        let ret = unsafe { raw::fs_write(&mut self.file, buf.as_ptr() as *const _, buf.len()) };
+        if ret < 0 {
+            return Err(ZephyrError::from_errno(ret as i32));
+        }
+        Ok(ret as usize)
+    }
+
+    /// Move the file position, returning the new position from the start of the file.
+    pub fn seek(&mut self, pos: SeekFrom) -> ZResult<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (
+                offset.try_into().map_err(|_| ZephyrError::Invalid)?,
+                raw::FS_SEEK_SET,
+            ),
+            SeekFrom::End(offset) => (offset, raw::FS_SEEK_END),
+            SeekFrom::Current(offset) => (offset, raw::FS_SEEK_CUR),
+        };
+        // The offset must fit in an `off_t`, which may be 32 bits.
+        let offset = offset.try_into().map_err(|_| ZephyrError::Invalid)?;
+        let pos = unsafe {
+            ok_or_errno(raw::fs_seek(&mut self.file, offset, whence as c_int))?;
+            raw::fs_tell(&mut self.file)
+        };
+        // Positions past 2GiB don't fit in a `c_int`, so only the negative errno is narrowed.
+        if pos < 0 {
+            return Err(ZephyrError::from_errno(pos as i32));
+        }
+        Ok(pos as u64)
+    }
+
+    /// Flush any cached writes to the storage.
+    pub fn sync(&mut self) -> ZResult<()> {
+        ok_or_errno(unsafe { raw::fs_sync(&mut self.file) })
+    }
+
+    /// Truncate, or extend, the file to `length` bytes.
+    pub fn truncate(&mut self, length: u64) -> ZResult<()> {
+        let length = length.try_into().map_err(|_| ZephyrError::Invalid)?;
+        ok_or_errno(unsafe { raw::fs_truncate(&mut self.file, length) })
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        unsafe {
+            raw::fs_close(&mut self.file);
+        }
+    }
+}
+
+impl fmt::Debug for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "File")
+    }
+}
+
+/// An open directory.
+///
+/// Iterating over the directory gives its entries.
+pub struct Dir {
+    dir: raw::fs_dir_t,
+    /// Set once the end of the directory has been reached, or there was an error.
+    done: bool,
+}
+
+// SAFETY: As with File.
+unsafe impl Send for Dir {}
+
+impl Dir {
+    /// Open the directory at `path`.
+    pub fn open(path: &CStr) -> ZResult<Dir> {
+        // SAFETY: `fs_dir_t_init` initializes the zeroed struct.
+        let mut dir: raw::fs_dir_t = unsafe { mem::zeroed() };
+        unsafe {
+            raw::fs_dir_t_init(&mut dir);
+            ok_or_errno(raw::fs_opendir(&mut dir, path.as_ptr()))?;
+        }
+        Ok(Dir { dir, done: false })
+    }
+}
+
+impl Iterator for Dir {
+    type Item = ZResult<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        // SAFETY: `fs_readdir` fills in the entry.
+        let mut entry: raw::fs_dirent = unsafe { mem::zeroed() };
+        if let Err(err) = ok_or_errno(unsafe { raw::fs_readdir(&mut self.dir, &mut entry) }) {
+            self.done = true;
+            return Some(Err(err));
+        }
+        // The end of the directory is indicated by an empty name.
+        if entry.name[0] == 0 {
+            self.done = true;
+            return None;
+        }
+        Some(Ok(DirEntry { entry }))
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        unsafe {
+            raw::fs_closedir(&mut self.dir);
+        }
+    }
+}
+
+impl fmt::Debug for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Dir")
+    }
+}
+
+/// A single entry in a directory, or the result of [`stat`].
+pub struct DirEntry {
+    entry: raw::fs_dirent,
+}
+
+impl DirEntry {
+    /// The name of the entry.
+    pub fn name(&self) -> &CStr {
+        // SAFETY: The name is always NUL terminated by the file system.
+        unsafe { CStr::from_ptr(self.entry.name.as_ptr()) }
+    }
+
+    /// Returns true if the entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.entry.type_ == raw::fs_dir_entry_type_FS_DIR_ENTRY_DIR
+    }
+
+    /// Returns true if the entry is a regular file.
+    pub fn is_file(&self) -> bool {
+        self.entry.type_ == raw::fs_dir_entry_type_FS_DIR_ENTRY_FILE
+    }
+
+    /// The size of the file, in bytes.
+    pub fn size(&self) -> usize {
+        self.entry.size
+    }
+}
+
+impl fmt::Debug for DirEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirEntry")
+            .field("name", &self.name())
+            .field("is_dir", &self.is_dir())
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+/// Get information about the file or directory at `path`.
+pub fn stat(path: &CStr) -> ZResult<DirEntry> {
+    // SAFETY: `fs_stat` fills in the entry.
+    let mut entry: raw::fs_dirent = unsafe { mem::zeroed() };
+    ok_or_errno(unsafe { raw::fs_stat(path.as_ptr(), &mut entry) })?;
+    Ok(DirEntry { entry })
+}
+
+/// Remove the file, or empty directory, at `path`.
+pub fn unlink(path: &CStr) -> ZResult<()> {
+    ok_or_errno(unsafe { raw::fs_unlink(path.as_ptr()) })
+}
+
+/// Rename, or move, `from` to `to`.
+pub fn rename(from: &CStr, to: &CStr) -> ZResult<()> {
+    ok_or_errno(unsafe { raw::fs_rename(from.as_ptr(), to.as_ptr()) })
+}
+
+/// Create a directory at `path`.
+pub fn mkdir(path: &CStr) -> ZResult<()> {
+    ok_or_errno(unsafe { raw::fs_mkdir(path.as_ptr()) })
+}
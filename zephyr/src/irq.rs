@@ -0,0 +1,60 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Interrupt context.
+//!
+//! Many Zephyr calls are safe to make from an interrupt handler, but only when they don't block.
+//! The [`IrqContext`] token marks code that is running in an ISR, so that APIs intended for use
+//! there can require one, similar to the way `critical_section::CriticalSection` marks code
+//! running within a critical section.  This makes the context explicit in the code, which helps
+//! when reviewing it.
+//!
+//! A token is normally obtained with [`with_irq_context`], which limits it to the scope of a
+//! closure.
+
+use core::marker::PhantomData;
+
+/// A token indicating that the code is running in interrupt context.
+///
+/// The token can't be sent to another thread, or kept past the handler it was obtained in.
+#[derive(Clone, Copy, Debug)]
+pub struct IrqContext<'a> {
+    _private: PhantomData<(&'a (), *mut ())>,
+}
+
+impl<'a> IrqContext<'a> {
+    /// Create a token, without checking the context.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called from within an interrupt handler, and the token must not outlive that
+    /// handler invocation.
+    pub unsafe fn new() -> IrqContext<'a> {
+        IrqContext {
+            _private: PhantomData,
+        }
+    }
+}
+
+/// Run `f` with an interrupt context token, if the code is currently running in interrupt context.
+///
+/// The token is only valid for the duration of `f`, so it can't be kept past the handler.  Returns
+/// `None`, without calling `f`, when not in interrupt context.
+///
+/// ```ignore
+/// zephyr::irq::with_irq_context(|ctx| sem.give_in_isr(ctx));
+/// ```
+pub fn with_irq_context<R>(f: impl FnOnce(IrqContext<'_>) -> R) -> Option<R> {
+    if in_isr() {
+        // SAFETY: We have just checked that this is interrupt context, and the token can't escape
+        // from `f`.
+        Some(f(unsafe { IrqContext::new() }))
+    } else {
+        None
+    }
+}
+
+/// Returns true if the code is running in interrupt context.
+pub fn in_isr() -> bool {
+    unsafe { crate::raw::k_is_in_isr() }
+}
@@ -0,0 +1,331 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON encoding and decoding with Zephyr's JSON library.
+//!
+//! Zephyr's library works from descriptors of a C struct, with the offset and type of each field.
+//! Here the descriptors are built at runtime, over an array of fixed size slots, so a
+//! [`JsonObject`] can be put together with a builder, and fields can be decoded with a list of
+//! [`JsonDescr`]s, each naming a key and where to put its value.
+//!
+//! ```ignore
+//! let mut obj = JsonObject::new();
+//! obj.add_str(c"name", "sensor")?;
+//! obj.add_i64(c"count", 3)?;
+//! let len = obj.encode(&mut buf)?;
+//!
+//! let mut count = 0;
+//! let mut schema = [JsonDescr::i64(c"count", &mut count)];
+//! JsonObject::decode(&buf[..len], &mut schema)?;
+//! ```
+//!
+//! Only flat objects, of strings, integers and booleans, are supported.
+//!
+//! Requires `CONFIG_JSON_LIBRARY`.
+
+use core::ffi::{c_char, c_void, CStr};
+use core::fmt;
+use core::mem;
+
+use crate::error::ZephyrError;
+use crate::raw;
+
+/// The most fields in an object.
+pub const MAX_FIELDS: usize = 16;
+
+/// The room for string values while encoding, and for the whole input while decoding.
+const SCRATCH_SIZE: usize = 512;
+
+/// An error encoding or decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonError {
+    /// There are more than [`MAX_FIELDS`] fields.
+    TooManyFields,
+    /// The data, or a string in it, does not fit in the space available.
+    TooLarge,
+    /// An error from the JSON library, such as invalid input.
+    Zephyr(ZephyrError),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::TooManyFields => write!(f, "json: too many fields"),
+            JsonError::TooLarge => write!(f, "json: too large"),
+            JsonError::Zephyr(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+
+/// The storage for one field's value, as the C struct field the library reads or writes.
+#[repr(C, align(8))]
+#[derive(Clone, Copy)]
+union Slot {
+    string: *const c_char,
+    int: i64,
+    boolean: bool,
+}
+
+const EMPTY_SLOT: Slot = Slot { int: 0 };
+
+/// The type of a field.
+#[derive(Clone, Copy)]
+enum Kind {
+    Str,
+    I64,
+    Bool,
+}
+
+impl Kind {
+    fn token(self) -> u32 {
+        match self {
+            Kind::Str => raw::json_tokens_JSON_TOK_STRING as u32,
+            Kind::I64 => raw::json_tokens_JSON_TOK_INT64 as u32,
+            Kind::Bool => raw::json_tokens_JSON_TOK_TRUE as u32,
+        }
+    }
+
+    fn align(self) -> usize {
+        match self {
+            Kind::Str => mem::align_of::<*const c_char>(),
+            Kind::I64 => mem::align_of::<i64>(),
+            Kind::Bool => mem::align_of::<bool>(),
+        }
+    }
+}
+
+/// Build the descriptor of the field in slot `index`.
+fn descr(key: &CStr, kind: Kind, index: usize) -> raw::json_obj_descr {
+    // SAFETY: The descriptor is plain data, with the fields not set here meant to be zero for a
+    // field of a primitive type.
+    let mut descr: raw::json_obj_descr = unsafe { mem::zeroed() };
+    descr.field_name = key.as_ptr();
+    descr.set_align_shift(kind.align().trailing_zeros());
+    descr.set_field_name_len(key.to_bytes().len() as u32);
+    descr.set_type_(kind.token());
+    descr.set_offset((index * mem::size_of::<Slot>()) as u32);
+    descr
+}
+
+/// A value in a [`JsonObject`].
+#[derive(Debug, Clone, Copy)]
+enum Value<'a> {
+    Str(&'a str),
+    I64(i64),
+    Bool(bool),
+}
+
+/// A JSON object, built up field by field, and then encoded.
+#[derive(Debug)]
+pub struct JsonObject<'a> {
+    fields: heapless::Vec<(&'a CStr, Value<'a>), MAX_FIELDS>,
+}
+
+impl<'a> JsonObject<'a> {
+    /// An empty object.
+    pub fn new() -> JsonObject<'a> {
+        JsonObject {
+            fields: heapless::Vec::new(),
+        }
+    }
+
+    fn add(&mut self, key: &'a CStr, value: Value<'a>) -> Result<(), JsonError> {
+        self.fields
+            .push((key, value))
+            .map_err(|_| JsonError::TooManyFields)
+    }
+
+    /// Add a string field.
+    pub fn add_str(&mut self, key: &'a CStr, val: &'a str) -> Result<(), JsonError> {
+        self.add(key, Value::Str(val))
+    }
+
+    /// Add an integer field.
+    pub fn add_i64(&mut self, key: &'a CStr, val: i64) -> Result<(), JsonError> {
+        self.add(key, Value::I64(val))
+    }
+
+    /// Add a boolean field.
+    pub fn add_bool(&mut self, key: &'a CStr, val: bool) -> Result<(), JsonError> {
+        self.add(key, Value::Bool(val))
+    }
+
+    /// Encode the object into `buf`, returning the length of the JSON text.
+    ///
+    /// The text is also NUL terminated, so `buf` needs room for one more byte.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, JsonError> {
+        // The library wants NUL terminated strings, so they are copied here.
+        let mut strings = [0u8; SCRATCH_SIZE];
+        let mut used = 0;
+        let mut slots = [EMPTY_SLOT; MAX_FIELDS];
+        let mut descrs: [raw::json_obj_descr; MAX_FIELDS] = unsafe { mem::zeroed() };
+
+        for (index, (key, value)) in self.fields.iter().enumerate() {
+            let kind = match *value {
+                Value::Str(text) => {
+                    let end = used + text.len();
+                    if end >= strings.len() || text.as_bytes().contains(&0) {
+                        return Err(JsonError::TooLarge);
+                    }
+                    strings[used..end].copy_from_slice(text.as_bytes());
+                    slots[index].string = strings[used..].as_ptr() as *const c_char;
+                    used = end + 1;
+                    Kind::Str
+                }
+                Value::I64(int) => {
+                    slots[index].int = int;
+                    Kind::I64
+                }
+                Value::Bool(boolean) => {
+                    slots[index].boolean = boolean;
+                    Kind::Bool
+                }
+            };
+            descrs[index] = descr(key, kind, index);
+        }
+
+        let ret = unsafe {
+            raw::json_obj_encode_buf(
+                descrs.as_ptr(),
+                self.fields.len(),
+                slots.as_ptr() as *const c_void,
+                buf.as_mut_ptr() as *mut c_char,
+                buf.len(),
+            )
+        };
+        if ret < 0 {
+            return Err(JsonError::Zephyr(ZephyrError::from_errno(ret)));
+        }
+        buf.iter()
+            .position(|&b| b == 0)
+            .ok_or(JsonError::TooLarge)
+    }
+
+    /// Decode the fields named in `schema` from the JSON object in `buf`.
+    ///
+    /// Fields that are in the input are written to their destinations, and marked as present.
+    /// Other keys in the input are ignored.
+    pub fn decode(buf: &[u8], schema: &mut [JsonDescr<'_>]) -> Result<(), JsonError> {
+        if schema.len() > MAX_FIELDS {
+            return Err(JsonError::TooManyFields);
+        }
+        // The library parses in place, so works on a copy.
+        let mut input = [0u8; SCRATCH_SIZE];
+        if buf.len() >= input.len() {
+            return Err(JsonError::TooLarge);
+        }
+        input[..buf.len()].copy_from_slice(buf);
+
+        let mut slots = [EMPTY_SLOT; MAX_FIELDS];
+        let mut descrs: [raw::json_obj_descr; MAX_FIELDS] = unsafe { mem::zeroed() };
+        for (index, field) in schema.iter().enumerate() {
+            descrs[index] = descr(field.key, field.dest.kind(), index);
+        }
+
+        let ret = unsafe {
+            raw::json_obj_parse(
+                input.as_mut_ptr() as *mut c_char,
+                buf.len(),
+                descrs.as_ptr(),
+                schema.len(),
+                slots.as_mut_ptr() as *mut c_void,
+            )
+        };
+        if ret < 0 {
+            return Err(JsonError::Zephyr(ZephyrError::from_errno(ret as i32)));
+        }
+
+        // Each bit of the result marks a field that was decoded.
+        for (index, field) in schema.iter_mut().enumerate() {
+            field.present = ret & (1 << index) != 0;
+            if !field.present {
+                continue;
+            }
+            // SAFETY: The slot was written by the parser, with the type of the field.
+            unsafe {
+                match &mut field.dest {
+                    JsonDest::Str(dest, len) => {
+                        let text = CStr::from_ptr(slots[index].string).to_bytes();
+                        let out = dest.get_mut(..text.len()).ok_or(JsonError::TooLarge)?;
+                        out.copy_from_slice(text);
+                        *len = text.len();
+                    }
+                    JsonDest::I64(dest) => **dest = slots[index].int,
+                    JsonDest::Bool(dest) => **dest = slots[index].boolean,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for JsonObject<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a decoded value goes.
+#[derive(Debug)]
+enum JsonDest<'a> {
+    /// A buffer for the string, and the length written to it.
+    Str(&'a mut [u8], usize),
+    I64(&'a mut i64),
+    Bool(&'a mut bool),
+}
+
+impl JsonDest<'_> {
+    fn kind(&self) -> Kind {
+        match self {
+            JsonDest::Str(..) => Kind::Str,
+            JsonDest::I64(_) => Kind::I64,
+            JsonDest::Bool(_) => Kind::Bool,
+        }
+    }
+}
+
+/// A field to decode, with [`JsonObject::decode`].
+#[derive(Debug)]
+pub struct JsonDescr<'a> {
+    key: &'a CStr,
+    dest: JsonDest<'a>,
+    present: bool,
+}
+
+impl<'a> JsonDescr<'a> {
+    /// A string field, copied into `buf`.
+    pub fn str(key: &'a CStr, buf: &'a mut [u8]) -> JsonDescr<'a> {
+        Self::with_dest(key, JsonDest::Str(buf, 0))
+    }
+
+    /// An integer field.
+    pub fn i64(key: &'a CStr, dest: &'a mut i64) -> JsonDescr<'a> {
+        Self::with_dest(key, JsonDest::I64(dest))
+    }
+
+    /// A boolean field.
+    pub fn bool(key: &'a CStr, dest: &'a mut bool) -> JsonDescr<'a> {
+        Self::with_dest(key, JsonDest::Bool(dest))
+    }
+
+    fn with_dest(key: &'a CStr, dest: JsonDest<'a>) -> JsonDescr<'a> {
+        JsonDescr {
+            key,
+            dest,
+            present: false,
+        }
+    }
+
+    /// Returns true if the field was in the decoded input.
+    pub fn is_present(&self) -> bool {
+        self.present
+    }
+
+    /// For a string field, the decoded string, if it was present and valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.dest {
+            JsonDest::Str(buf, len) if self.present => core::str::from_utf8(&buf[..*len]).ok(),
+            _ => None,
+        }
+    }
+}
@@ -11,29 +11,79 @@
 #![deny(missing_docs)]
 
 pub mod align;
+#[cfg(CONFIG_RUST_ALLOC)]
+pub mod alloc;
+pub mod arch;
+#[cfg(CONFIG_BT)]
+pub mod bluetooth;
+#[cfg(CONFIG_TINYCBOR)]
+pub mod cbor;
+#[cfg(CONFIG_CRC)]
+pub mod checksum;
+pub mod collections;
+#[cfg(CONFIG_SCHED_THREAD_USAGE)]
+pub mod cpu_stats;
+pub mod crypto;
+pub mod cstr;
+#[cfg(all(CONFIG_INIT_STACKS, CONFIG_THREAD_STACK_INFO))]
+pub mod debug;
 pub mod device;
+#[cfg(CONFIG_DISK_ACCESS)]
+pub mod disk;
+#[cfg(CONFIG_CACHE_MANAGEMENT)]
+pub mod dma;
+#[cfg(CONFIG_BASE64)]
+pub mod encoding;
 pub mod error;
+pub mod executor;
+pub mod fmt;
+#[cfg(CONFIG_FILE_SYSTEM)]
+pub mod fs;
+pub mod irq;
+#[cfg(CONFIG_JSON_LIBRARY)]
+pub mod json;
 #[cfg(CONFIG_RUST_ALLOC)]
 pub mod kio;
 pub mod logging;
+#[cfg(CONFIG_MCUBOOT_IMG_MANAGER)]
+pub mod mcuboot;
+#[cfg(all(CONFIG_MODEM, CONFIG_MODEM_BACKEND_UART, CONFIG_RUST_ALLOC))]
+pub mod modem;
+#[cfg(CONFIG_NETWORKING)]
+pub mod net;
 pub mod object;
+pub mod perf;
+#[cfg(CONFIG_USE_SEGGER_RTT)]
+pub mod rtt;
 #[cfg(CONFIG_RUST_ALLOC)]
 pub mod simpletls;
+#[cfg(all(CONFIG_MCUMGR, CONFIG_RUST_ALLOC))]
+pub mod smp;
 pub mod sync;
 pub mod sys;
+#[cfg(CONFIG_RUST_TEST_HARNESS)]
+pub mod testing;
 pub mod time;
 #[cfg(CONFIG_RUST_ALLOC)]
 pub mod timer;
+#[cfg(CONFIG_USB_DEVICE_STACK)]
+pub mod usb;
 #[cfg(CONFIG_RUST_ALLOC)]
 pub mod work;
+#[cfg(CONFIG_ZBUS)]
+pub mod zbus;
+#[cfg(CONFIG_ZLIB)]
+pub mod zlib;
 
-pub use error::{Error, Result};
+pub use error::{Error, Result, ZResult, ZephyrError};
 
 pub use logging::set_logger;
 
 /// Re-exported for local macro use.
 pub use paste::paste;
 
+pub use zephyr_macros::{main, thread, zephyr_test};
+
 // Bring in the generated kconfig module
 pub mod kconfig {
     //! Zephyr Kconfig values.
@@ -43,6 +93,12 @@ pub mod kconfig {
     //!
     //! **Note**: Unless you are viewing docs generated for a specific build, the values below are
     //! unlikely to directly correspond to those in a given build.
+    //!
+    //! In addition, there are `HAS_...` constants for commonly used features, such as
+    //! [`HAS_UART`], that are always defined, and are `true` if the feature is enabled.  These
+    //! allow code to check for a feature with a regular `if`.  For conditional compilation,
+    //! `zephyr_build::export_bool_kconfig` also provides a matching cfg, such as
+    //! `#[cfg(zephyr_has_uart)]`.
 
     // Don't enforce doc comments on the bindgen, as it isn't enforced within Zephyr.
     #![allow(missing_docs)]
@@ -57,6 +113,12 @@ pub mod devicetree {
     //! hierarchy here should match the device tree, with an additional top-level module "labels"
     //! that contains submodules for all of the labels.
     //!
+    //! Each node module also contains a `Node` type, and a `NODE` constant of that type.  The
+    //! simple properties of the node (single numbers and strings) are available as `const fn`
+    //! accessors on this type, so code can be written against a specific node, for example
+    //! `zephyr::devicetree::labels::i2c0::NODE.clock_frequency()`, without having to look anything
+    //! up by name at runtime.
+    //!
     //! **Note**: Unless you are viewing docs generated for a specific build, the values below are
     //! unlikely to directly correspond to those in a given build.
 
@@ -85,6 +147,9 @@ fn panic(info: &PanicInfo) -> ! {
     }
     let _ = info;
 
+    #[cfg(CONFIG_RUST_TEST_HARNESS)]
+    testing::on_panic();
+
     // Call into the wrapper for the system panic function.
     unsafe {
         extern "C" {
@@ -110,11 +175,6 @@ pub mod _export {
     pub type KStaticThreadStack = StaticKernelObject<StaticThreadStack>;
 }
 
-// Mark this as `pub` so the docs can be read.
-// If allocation has been requested, provide the allocator.
-#[cfg(CONFIG_RUST_ALLOC)]
-pub mod alloc_impl;
-
 #[cfg(CONFIG_RUST_ALLOC)]
 pub mod task {
     //! Provides the portable-atomic version of `alloc::task::Wake`, which uses the compatible
@@ -0,0 +1,69 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Interaction with the MCUboot bootloader.
+//!
+//! After a new image has been written to the secondary slot, [`mark_image_pending_install`] asks
+//! MCUboot to swap to it on the next reset.  A test swap will be reverted on the following reset
+//! unless the new image calls [`mark_image_confirmed`] once it has verified that it is working.
+//!
+//! Requires `CONFIG_MCUBOOT_IMG_MANAGER`.
+
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+
+/// Mark the running image as confirmed.
+///
+/// This makes a test swap permanent, preventing MCUboot from reverting to the previous image on
+/// the next reset.
+pub fn mark_image_confirmed() -> ZResult<()> {
+    ok_or_errno(unsafe { raw::boot_write_img_confirmed() })
+}
+
+/// Returns true if the running image has been confirmed.
+pub fn is_image_confirmed() -> bool {
+    unsafe { raw::boot_is_img_confirmed() }
+}
+
+/// Request that MCUboot install the image in the secondary slot on the next reset.
+///
+/// If `permanent` is false, the swap is a test, and will be reverted unless the new image is
+/// confirmed.  If `permanent` is true, the new image is installed without needing confirmation.
+pub fn mark_image_pending_install(permanent: bool) -> ZResult<()> {
+    let mode = if permanent {
+        raw::BOOT_UPGRADE_PERMANENT
+    } else {
+        raw::BOOT_UPGRADE_TEST
+    };
+    ok_or_errno(unsafe { raw::boot_request_upgrade(mode as i32) })
+}
+
+/// The swap MCUboot will perform on the next reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootSwapType {
+    /// No swap is pending.
+    None,
+    /// A test swap to the secondary image, which will be reverted unless confirmed.
+    Test,
+    /// A permanent swap to the secondary image.
+    Perm,
+    /// The previous test swap was not confirmed, and will be reverted.
+    Revert,
+    /// The swap information could not be read.
+    Fail,
+    /// A value not known to this wrapper.
+    Unknown(i32),
+}
+
+/// Query the swap that MCUboot will perform on the next reset.
+pub fn boot_swap_type() -> BootSwapType {
+    let swap = unsafe { raw::mcuboot_swap_type() };
+    match swap as u32 {
+        raw::BOOT_SWAP_TYPE_NONE => BootSwapType::None,
+        raw::BOOT_SWAP_TYPE_TEST => BootSwapType::Test,
+        raw::BOOT_SWAP_TYPE_PERM => BootSwapType::Perm,
+        raw::BOOT_SWAP_TYPE_REVERT => BootSwapType::Revert,
+        raw::BOOT_SWAP_TYPE_FAIL => BootSwapType::Fail,
+        _ => BootSwapType::Unknown(swap),
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Modem support.
+//!
+//! Zephyr's modem modules provide a "pipe" abstraction, a byte stream to a modem, with backends
+//! that implement the pipe over various transports.  [`ModemBackendUart`] wraps the UART backend,
+//! which is the common way cellular and other AT command modems are attached.
+//!
+//! Built on top of this, [`AtCmdClient`] is a simple client for sending AT commands, and
+//! collecting the response lines up to the final result code.
+//!
+//! This requires `CONFIG_MODEM` and `CONFIG_MODEM_BACKEND_UART`, along with `CONFIG_RUST_ALLOC`, as
+//! the backend must stay at a fixed address once initialized.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::fmt;
+use core::mem;
+use core::pin::Pin;
+
+use crate::device::uart::UartDevice;
+use crate::error::{ok_or_errno, ok_or_errno_val, ZResult, ZephyrError};
+use crate::raw;
+use crate::sys::sync::Semaphore;
+use crate::time::{Duration, Timeout};
+
+/// The UART backend for a modem pipe.
+pub struct ModemBackendUart {
+    /// The backend.  Zephyr holds pointers into this, so it is boxed.
+    backend: Pin<Box<UnsafeCell<raw::modem_backend_uart>>>,
+    /// The pipe, which lives within the backend.
+    pipe: *mut raw::modem_pipe,
+    /// Given from the pipe callback.  The callback holds a pointer to this.
+    events: Pin<Box<PipeEvents>>,
+    /// The UART is owned by the backend while it is in use.
+    _uart: UartDevice,
+}
+
+struct PipeEvents {
+    receive_ready: Semaphore,
+    transmit_idle: Semaphore,
+}
+
+// SAFETY: The modem pipe is safe to use from another thread, and all operations take `&mut self`.
+unsafe impl Send for ModemBackendUart {}
+
+impl ModemBackendUart {
+    /// Initialize the UART backend.
+    ///
+    /// The buffers are used by the backend for the data going in each direction.
+    pub fn new(
+        uart: UartDevice,
+        rx_buf: &'static mut [u8],
+        tx_buf: &'static mut [u8],
+    ) -> ModemBackendUart {
+        // SAFETY: The backend is initialized by `modem_backend_uart_init`.
+        let backend: Pin<Box<UnsafeCell<raw::modem_backend_uart>>> =
+            Box::pin(UnsafeCell::new(unsafe { mem::zeroed() }));
+        let config = raw::modem_backend_uart_config {
+            uart: uart.device,
+            receive_buf: rx_buf.as_mut_ptr(),
+            receive_buf_size: rx_buf.len(),
+            transmit_buf: tx_buf.as_mut_ptr(),
+            transmit_buf_size: tx_buf.len(),
+        };
+        let pipe = unsafe { raw::modem_backend_uart_init(backend.get(), &config) };
+
+        let events = Box::pin(PipeEvents {
+            receive_ready: Semaphore::new(0, 1).expect("Allocating semaphore"),
+            transmit_idle: Semaphore::new(0, 1).expect("Allocating semaphore"),
+        });
+        unsafe {
+            raw::modem_pipe_attach(
+                pipe,
+                Some(pipe_event),
+                &*events as *const PipeEvents as *mut c_void,
+            );
+        }
+
+        ModemBackendUart {
+            backend,
+            pipe,
+            events,
+            _uart: uart,
+        }
+    }
+
+    /// Open the pipe, waiting up to `timeout` for it to be ready.
+    pub fn open(&mut self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        let timeout: Timeout = timeout.into();
+        ok_or_errno(unsafe { raw::modem_pipe_open(self.pipe, timeout.0) })
+    }
+
+    /// Close the pipe, waiting up to `timeout` for it to finish.
+    pub fn close(&mut self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        let timeout: Timeout = timeout.into();
+        ok_or_errno(unsafe { raw::modem_pipe_close(self.pipe, timeout.0) })
+    }
+
+    /// Queue data to be sent to the modem.
+    ///
+    /// Returns the number of bytes that were accepted, which may be less than `buf.len()`, or even
+    /// zero, if the transmit buffer is full.
+    pub fn transmit(&mut self, buf: &[u8]) -> ZResult<usize> {
+        let count = unsafe { raw::modem_pipe_transmit(self.pipe, buf.as_ptr(), buf.len()) };
+        ok_or_errno_val(count, count as usize)
+    }
+
+    /// Read data that has been received from the modem.
+    ///
+    /// Does not block.  Returns the number of bytes read, which will be zero if nothing has been
+    /// received.
+    pub fn receive(&mut self, buf: &mut [u8]) -> ZResult<usize> {
+        let count = unsafe { raw::modem_pipe_receive(self.pipe, buf.as_mut_ptr(), buf.len()) };
+        ok_or_errno_val(count, count as usize)
+    }
+
+    /// Wait for the backend to indicate that received data is available.
+    pub fn wait_receive(&self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        self.events
+            .receive_ready
+            .take(timeout)
+            .map_err(ZephyrError::from)
+    }
+
+    /// Wait for the backend to indicate that all queued data has been sent.
+    pub fn wait_transmit_idle(&self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        self.events
+            .transmit_idle
+            .take(timeout)
+            .map_err(ZephyrError::from)
+    }
+}
+
+impl Drop for ModemBackendUart {
+    fn drop(&mut self) {
+        unsafe {
+            raw::modem_pipe_close(self.pipe, crate::sys::K_FOREVER);
+            raw::modem_pipe_release(self.pipe);
+        }
+        // The backend and events are freed after this, as Zephyr no longer references them.
+    }
+}
+
+impl fmt::Debug for ModemBackendUart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ModemBackendUart")
+    }
+}
+
+/// The pipe callback, invoked from the backend's work queue.
+unsafe extern "C" fn pipe_event(
+    _pipe: *mut raw::modem_pipe,
+    event: raw::modem_pipe_event,
+    user_data: *mut c_void,
+) {
+    let events = &*(user_data as *const PipeEvents);
+    match event {
+        raw::modem_pipe_event_MODEM_PIPE_EVENT_RECEIVE_READY => events.receive_ready.give(),
+        raw::modem_pipe_event_MODEM_PIPE_EVENT_TRANSMIT_IDLE => events.transmit_idle.give(),
+        _ => (),
+    }
+}
+
+/// A client for sending AT commands to a modem.
+pub struct AtCmdClient {
+    modem: ModemBackendUart,
+}
+
+/// The response to a successful AT command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtResponse {
+    /// The lines of the response, separated by newlines, without the echoed command, and the
+    /// final `OK`.
+    text: String,
+}
+
+impl AtResponse {
+    /// The response text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The individual lines of the response.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+}
+
+/// An error from an AT command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtError {
+    /// The modem did not finish responding before the timeout.
+    Timeout,
+    /// The modem responded with `ERROR`.
+    Error,
+    /// The modem responded with `+CME ERROR: <n>`.
+    Cme(u32),
+    /// The modem responded with `+CMS ERROR: <n>`.
+    Cms(u32),
+    /// There was an error from the pipe itself.
+    Pipe(ZephyrError),
+}
+
+impl From<ZephyrError> for AtError {
+    fn from(err: ZephyrError) -> AtError {
+        AtError::Pipe(err)
+    }
+}
+
+impl fmt::Display for AtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtError::Timeout => write!(f, "AT command timed out"),
+            AtError::Error => write!(f, "AT command returned ERROR"),
+            AtError::Cme(n) => write!(f, "AT command returned +CME ERROR: {}", n),
+            AtError::Cms(n) => write!(f, "AT command returned +CMS ERROR: {}", n),
+            AtError::Pipe(err) => write!(f, "modem pipe error: {}", err),
+        }
+    }
+}
+
+impl AtCmdClient {
+    /// Create a client using the given modem.  The pipe should already be open.
+    pub fn new(modem: ModemBackendUart) -> AtCmdClient {
+        AtCmdClient { modem }
+    }
+
+    /// Recover the underlying modem.
+    pub fn into_inner(self) -> ModemBackendUart {
+        self.modem
+    }
+
+    /// Send a command, and wait for its response.
+    ///
+    /// The command is given without the `\r` terminator, for example, `"AT+CSQ"`.  The response
+    /// lines are collected until the modem gives a final result code.  The whole command,
+    /// including sending it, must complete within `timeout`.
+    pub fn at_cmd(&mut self, cmd: &str, timeout: Duration) -> Result<AtResponse, AtError> {
+        let deadline = now_ticks() + timeout.ticks() as i64;
+
+        self.send_all(cmd.as_bytes(), deadline)?;
+        self.send_all(b"\r", deadline)?;
+
+        let mut text = String::new();
+        // Collected as bytes, as a multi-byte character may be split across reads.
+        let mut line = Vec::new();
+        let mut buf = [0u8; 64];
+        loop {
+            let count = self.modem.receive(&mut buf)?;
+            if count == 0 {
+                let remaining = remaining(deadline)?;
+                match self.modem.wait_receive(remaining) {
+                    Ok(()) => continue,
+                    Err(_) => return Err(AtError::Timeout),
+                }
+            }
+
+            for &byte in &buf[..count] {
+                if byte != b'\r' && byte != b'\n' {
+                    line.push(byte);
+                    continue;
+                }
+                let bytes = mem::take(&mut line);
+                let decoded = String::from_utf8_lossy(&bytes);
+                let done = decoded.trim();
+                if done.is_empty() || done == cmd {
+                    // Blank lines and the command echo are skipped.
+                } else if done == "OK" {
+                    return Ok(AtResponse { text });
+                } else if done == "ERROR" {
+                    return Err(AtError::Error);
+                } else if let Some(code) = done.strip_prefix("+CME ERROR:") {
+                    return Err(AtError::Cme(code.trim().parse().unwrap_or(0)));
+                } else if let Some(code) = done.strip_prefix("+CMS ERROR:") {
+                    return Err(AtError::Cms(code.trim().parse().unwrap_or(0)));
+                } else {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(done);
+                }
+            }
+        }
+    }
+
+    /// Transmit all of `data`, waiting for space in the transmit buffer as needed.
+    fn send_all(&mut self, mut data: &[u8], deadline: i64) -> Result<(), AtError> {
+        while !data.is_empty() {
+            let count = self.modem.transmit(data)?;
+            data = &data[count..];
+            if count == 0 {
+                let remaining = remaining(deadline)?;
+                if self.modem.wait_transmit_idle(remaining).is_err() {
+                    return Err(AtError::Timeout);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for AtCmdClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AtCmdClient")
+    }
+}
+
+fn now_ticks() -> i64 {
+    unsafe { raw::k_uptime_ticks() }
+}
+
+/// The time remaining until `deadline`, or a timeout error if it has passed.
+fn remaining(deadline: i64) -> Result<Duration, AtError> {
+    let remaining = deadline - now_ticks();
+    if remaining <= 0 {
+        Err(AtError::Timeout)
+    } else {
+        Ok(Duration::from_ticks(remaining as _))
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Networking.
+//!
+//! Wrappers around Zephyr's networking stack.  The BSD socket API, in [`socket`], requires
+//! `CONFIG_NET_SOCKETS`, and TLS sockets, in [`tls`], `CONFIG_NET_SOCKETS_SOCKOPT_TLS`.  The
+//! network interfaces themselves are configured with [`NetInterface`], and changes to them can be
+//! watched for with [`mgmt`].
+//!
+//! On top of the sockets, [`http`] has a simple HTTP client, with `CONFIG_HTTP_CLIENT`, and
+//! [`websocket`] a WebSocket client, with `CONFIG_WEBSOCKET_CLIENT`.  Services can be advertised
+//! with [`mdns`].  Thread networks are managed with [`openthread`].  Raw Ethernet frames are sent
+//! and received with [`ethernet`].
+//!
+//! Requires `CONFIG_NETWORKING`.
+
+use core::fmt;
+use core::mem;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::error::ZephyrError;
+use crate::raw;
+#[cfg(CONFIG_NET_SOCKETS)]
+use crate::time::Duration;
+
+#[cfg(CONFIG_NET_SOCKETS_PACKET)]
+pub mod ethernet;
+#[cfg(all(CONFIG_HTTP_CLIENT, CONFIG_RUST_ALLOC))]
+pub mod http;
+pub mod iface;
+#[cfg(all(CONFIG_MDNS_RESPONDER, CONFIG_RUST_ALLOC))]
+pub mod mdns;
+#[cfg(all(CONFIG_NET_MGMT_EVENT, CONFIG_RUST_ALLOC))]
+pub mod mgmt;
+#[cfg(CONFIG_NET_L2_OPENTHREAD)]
+pub mod openthread;
+#[cfg(CONFIG_NET_SOCKETS)]
+pub mod socket;
+#[cfg(CONFIG_NET_SOCKETS_SOCKOPT_TLS)]
+pub mod tls;
+#[cfg(all(CONFIG_WEBSOCKET_CLIENT, CONFIG_RUST_ALLOC))]
+pub mod websocket;
+
+pub use iface::NetInterface;
+#[cfg(all(CONFIG_NET_MGMT_EVENT, CONFIG_RUST_ALLOC))]
+pub use mgmt::{NetEvent, NetEventListener};
+
+/// An error from the network stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetError(pub ZephyrError);
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "net: {}", self.0)
+    }
+}
+
+impl embedded_io::Error for NetError {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        embedded_io::Error::kind(&self.0)
+    }
+}
+
+impl From<ZephyrError> for NetError {
+    fn from(err: ZephyrError) -> NetError {
+        NetError(err)
+    }
+}
+
+/// Convert an address to Zephyr's `in_addr`.
+pub(crate) fn in_addr(addr: Ipv4Addr) -> raw::in_addr {
+    // SAFETY: `in_addr` is plain data.
+    let mut raw_addr: raw::in_addr = unsafe { mem::zeroed() };
+    raw_addr.__bindgen_anon_1.s4_addr = addr.octets();
+    raw_addr
+}
+
+/// Convert an address to Zephyr's `in6_addr`.
+pub(crate) fn in6_addr(addr: Ipv6Addr) -> raw::in6_addr {
+    // SAFETY: `in6_addr` is plain data.
+    let mut raw_addr: raw::in6_addr = unsafe { mem::zeroed() };
+    raw_addr.__bindgen_anon_1.s6_addr = addr.octets();
+    raw_addr
+}
+
+/// Convert a timeout to the milliseconds that the socket based libraries take.
+#[cfg(CONFIG_NET_SOCKETS)]
+pub(crate) fn timeout_ms(timeout: Duration) -> i32 {
+    timeout.to_millis().min(i32::MAX as _) as i32
+}
+
+/// A URL split into its parts.
+#[cfg(any(CONFIG_HTTP_CLIENT, CONFIG_WEBSOCKET_CLIENT))]
+pub(crate) struct Url<'a> {
+    pub(crate) scheme: &'a str,
+    pub(crate) host: &'a str,
+    pub(crate) port: Option<u16>,
+    /// The path, including any query.  Always starts with `/`.
+    pub(crate) path: &'a str,
+}
+
+#[cfg(any(CONFIG_HTTP_CLIENT, CONFIG_WEBSOCKET_CLIENT))]
+impl<'a> Url<'a> {
+    pub(crate) fn parse(url: &'a str) -> Option<Url<'a>> {
+        let (scheme, rest) = url.split_once("://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => rest.split_at(pos),
+            None => (rest, "/"),
+        };
+        let (host, port) = if let Some(v6) = authority.strip_prefix('[') {
+            // An IPv6 address, such as `[2001:db8::1]:8080`.
+            let (host, rest) = v6.split_once(']')?;
+            match rest.strip_prefix(':') {
+                Some(port) => (host, Some(port.parse().ok()?)),
+                None if rest.is_empty() => (host, None),
+                None => return None,
+            }
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port.parse().ok()?)),
+                None => (authority, None),
+            }
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(Url {
+            scheme,
+            host,
+            port,
+            path,
+        })
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Raw Ethernet frames.
+//!
+//! An [`EthernetDevice`] sends and receives whole frames, headers included, on an Ethernet
+//! interface, below the IP stack, with a packet socket.  This is for protocols that Zephyr
+//! doesn't implement, such as EtherCAT, or a custom one on a dedicated link.  The IP stack still
+//! sees the frames it is interested in.
+//!
+//! Requires `CONFIG_NET_SOCKETS_PACKET`, along with the Ethernet driver.
+
+#[cfg(CONFIG_RUST_ALLOC)]
+extern crate alloc;
+
+use core::ffi::{c_int, c_void};
+use core::fmt;
+use core::mem;
+
+use super::socket::check_errno;
+use super::{NetError, NetInterface};
+use crate::error::ZephyrError;
+use crate::raw;
+
+/// The Ethernet protocol for all frames, in host order.
+const ETH_P_ALL: u16 = 0x0003;
+
+/// The largest frame, without the FCS, which the hardware handles.
+pub const MAX_FRAME: usize = 1514;
+
+/// Raw frame access to an Ethernet interface.
+pub struct EthernetDevice {
+    iface: NetInterface,
+    fd: c_int,
+}
+
+impl EthernetDevice {
+    /// Open the Ethernet interface `iface` for raw frames.
+    pub fn new(iface: NetInterface) -> Result<EthernetDevice, NetError> {
+        let family = raw::AF_PACKET as c_int;
+        let sock_type = raw::net_sock_type_SOCK_RAW as c_int;
+        let proto = ETH_P_ALL.to_be() as c_int;
+        let fd = check_errno(unsafe { raw::zsock_socket(family, sock_type, proto) })? as c_int;
+        let eth = EthernetDevice { iface, fd };
+
+        let addr = eth.link_addr(&[0; 6]);
+        check_errno(unsafe {
+            raw::zsock_bind(
+                eth.fd,
+                &addr as *const raw::sockaddr_ll as *const raw::sockaddr,
+                mem::size_of::<raw::sockaddr_ll>() as _,
+            )
+        })?;
+        Ok(eth)
+    }
+
+    /// The interface the frames are on.
+    pub fn iface(&self) -> NetInterface {
+        self.iface
+    }
+
+    /// The MAC address of the interface.
+    pub fn mac_addr(&self) -> [u8; 6] {
+        let mut mac = [0u8; 6];
+        // SAFETY: The link address belongs to the interface, which is static.
+        unsafe {
+            let link = raw::net_if_get_link_addr(self.iface.as_raw());
+            let len = ((*link).len as usize).min(mac.len());
+            if !(*link).addr.is_null() {
+                mac[..len].copy_from_slice(core::slice::from_raw_parts((*link).addr, len));
+            }
+        }
+        mac
+    }
+
+    fn link_addr(&self, dest: &[u8; 6]) -> raw::sockaddr_ll {
+        // SAFETY: The address is plain data.
+        let mut addr: raw::sockaddr_ll = unsafe { mem::zeroed() };
+        addr.sll_family = raw::AF_PACKET as _;
+        addr.sll_protocol = ETH_P_ALL.to_be();
+        addr.sll_ifindex = unsafe { raw::net_if_get_by_iface(self.iface.as_raw()) };
+        addr.sll_halen = dest.len() as _;
+        addr.sll_addr[..dest.len()].copy_from_slice(dest);
+        addr
+    }
+
+    /// Send a frame, which starts with the destination and source MAC addresses, and the
+    /// EtherType.
+    pub fn send_frame(&self, data: &[u8]) -> Result<(), NetError> {
+        if data.len() < 14 || data.len() > MAX_FRAME {
+            return Err(NetError(ZephyrError::Invalid));
+        }
+        let mut dest = [0u8; 6];
+        dest.copy_from_slice(&data[..6]);
+        let addr = self.link_addr(&dest);
+        check_errno(unsafe {
+            raw::zsock_sendto(
+                self.fd,
+                data.as_ptr() as *const c_void,
+                data.len(),
+                0,
+                &addr as *const raw::sockaddr_ll as *const raw::sockaddr,
+                mem::size_of::<raw::sockaddr_ll>() as _,
+            ) as c_int
+        })?;
+        Ok(())
+    }
+
+    /// Wait for the next frame, returning its length.  A frame larger than `buf` is cut short.
+    pub fn recv_frame(&self, buf: &mut [u8]) -> Result<usize, NetError> {
+        check_errno(unsafe {
+            raw::zsock_recv(self.fd, buf.as_mut_ptr() as *mut c_void, buf.len(), 0) as c_int
+        })
+    }
+
+    /// Call `cb` with each frame received, from `thread`.
+    ///
+    /// The device is moved to the thread, so the socket stays open as long as the thread uses it,
+    /// and is given to the callback, which can use it to send replies.  The callback runs on its
+    /// own thread, so it can block, but frames that arrive while it is busy wait in the socket, and
+    /// are dropped once the socket's buffers are full.  Receiving is retried after a transient
+    /// error.  Any other error ends the thread, closing the socket.  Returns the running thread.
+    #[cfg(CONFIG_RUST_ALLOC)]
+    pub fn set_receive_callback(
+        self,
+        thread: crate::sys::thread::Thread,
+        cb: fn(&EthernetDevice, &[u8]),
+    ) -> crate::sys::thread::Thread {
+        thread.spawn(move || {
+            let mut frame = alloc::vec![0u8; MAX_FRAME];
+            loop {
+                match self.recv_frame(&mut frame) {
+                    Ok(len) => cb(&self, &frame[..len]),
+                    Err(NetError(ZephyrError::Again | ZephyrError::Interrupted)) => (),
+                    Err(_) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Drop for EthernetDevice {
+    fn drop(&mut self) {
+        unsafe {
+            raw::zsock_close(self.fd);
+        }
+    }
+}
+
+impl fmt::Debug for EthernetDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EthernetDevice({})", self.fd)
+    }
+}
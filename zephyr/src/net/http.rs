@@ -0,0 +1,303 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! An HTTP/1.1 client.
+//!
+//! This uses Zephyr's `http_client` library to make simple requests, collecting the whole response
+//! body into memory.
+//!
+//! ```ignore
+//! let rsp = HttpClient::get("http://192.0.2.1:8080/status", Duration::secs_at_least(5))?;
+//! if rsp.status == 200 {
+//!     // Use rsp.body.
+//! }
+//! ```
+//!
+//! Only `http://` URLs are supported.  The host may be an address, or a name when the DNS resolver
+//! is enabled.
+//!
+//! Requires `CONFIG_HTTP_CLIENT` and `CONFIG_RUST_ALLOC`.
+
+extern crate alloc;
+
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::ffi::{c_char, c_int, c_void};
+use core::fmt;
+use core::mem;
+use core::ptr;
+
+use crate::error::ZephyrError;
+use crate::raw;
+use crate::time::Duration;
+
+use super::socket::{resolve, TcpStream};
+use super::{timeout_ms, NetError, Url};
+
+/// The number of custom headers a [`HeaderMap`] can hold.
+pub const MAX_HEADERS: usize = 8;
+
+/// The size of the buffer the response is received into.  The body is collected in pieces of at
+/// most this size.
+const RECV_BUF_SIZE: usize = 512;
+
+/// An error from an HTTP request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpError {
+    /// The URL could not be parsed.
+    InvalidUrl,
+    /// The URL scheme isn't supported.
+    Unsupported,
+    /// A [`HeaderMap`] is already holding [`MAX_HEADERS`] headers.
+    TooManyHeaders,
+    /// The connection, or the request itself, failed.
+    Net(NetError),
+}
+
+impl From<NetError> for HttpError {
+    fn from(err: NetError) -> HttpError {
+        HttpError::Net(err)
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::InvalidUrl => write!(f, "invalid URL"),
+            HttpError::Unsupported => write!(f, "unsupported URL scheme"),
+            HttpError::TooManyHeaders => write!(f, "too many headers"),
+            HttpError::Net(err) => write!(f, "http: {}", err),
+        }
+    }
+}
+
+/// Custom headers to send with a request.
+///
+/// This holds up to [`MAX_HEADERS`] headers, in a fixed size array.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderMap<'a> {
+    headers: heapless::Vec<(&'a str, &'a str), MAX_HEADERS>,
+}
+
+impl<'a> HeaderMap<'a> {
+    /// An empty set of headers.
+    pub fn new() -> HeaderMap<'a> {
+        HeaderMap {
+            headers: heapless::Vec::new(),
+        }
+    }
+
+    /// Add a header.  Headers are sent in the order they are added.
+    pub fn insert(&mut self, name: &'a str, value: &'a str) -> Result<(), HttpError> {
+        self.headers
+            .push((name, value))
+            .map_err(|_| HttpError::TooManyHeaders)
+    }
+
+    /// The value of the first header with the given name, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+
+    /// The number of headers.
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Returns true if there are no headers.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Iterate over the headers.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.headers.iter().copied()
+    }
+}
+
+/// The method of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// GET
+    Get,
+    /// POST
+    Post,
+    /// PUT
+    Put,
+    /// DELETE
+    Delete,
+    /// HEAD
+    Head,
+}
+
+impl Method {
+    fn raw(self) -> raw::http_method {
+        match self {
+            Method::Get => raw::http_method_HTTP_GET,
+            Method::Post => raw::http_method_HTTP_POST,
+            Method::Put => raw::http_method_HTTP_PUT,
+            Method::Delete => raw::http_method_HTTP_DELETE,
+            Method::Head => raw::http_method_HTTP_HEAD,
+        }
+    }
+}
+
+/// The response to a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// The status code, such as 200.
+    pub status: u16,
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+
+/// An HTTP client.
+///
+/// Each request makes a new connection, which is closed once the response has been received.
+pub struct HttpClient;
+
+impl HttpClient {
+    /// Make a GET request.
+    pub fn get(url: &str, timeout: Duration) -> Result<HttpResponse, HttpError> {
+        Self::request(Method::Get, url, &HeaderMap::new(), None, timeout)
+    }
+
+    /// Make a POST request, with the given body.
+    pub fn post(
+        url: &str,
+        body: &[u8],
+        content_type: &str,
+        timeout: Duration,
+    ) -> Result<HttpResponse, HttpError> {
+        Self::request(
+            Method::Post,
+            url,
+            &HeaderMap::new(),
+            Some((body, content_type)),
+            timeout,
+        )
+    }
+
+    /// Make a request, with custom headers, and an optional body and its content type.
+    ///
+    /// The `Host` header is always sent, and should not be in `headers`.
+    pub fn request(
+        method: Method,
+        url: &str,
+        headers: &HeaderMap<'_>,
+        body: Option<(&[u8], &str)>,
+        timeout: Duration,
+    ) -> Result<HttpResponse, HttpError> {
+        let url = Url::parse(url).ok_or(HttpError::InvalidUrl)?;
+        if url.scheme != "http" {
+            return Err(HttpError::Unsupported);
+        }
+        let port = url.port.unwrap_or(80);
+
+        let c_host = c_string(url.host)?;
+        let c_path = c_string(url.path)?;
+        let c_port = c_string(&alloc::format!("{}", port))?;
+        let c_content_type = match body {
+            Some((_, content_type)) => Some(c_string(content_type)?),
+            None => None,
+        };
+
+        // Each header is a complete line, and the list is terminated with a null.
+        let lines = headers
+            .iter()
+            .map(|(name, value)| {
+                let mut line = String::with_capacity(name.len() + value.len() + 4);
+                line.push_str(name);
+                line.push_str(": ");
+                line.push_str(value);
+                line.push_str("\r\n");
+                c_string(&line)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut header_ptrs: Vec<*const c_char> = lines.iter().map(|l| l.as_ptr()).collect();
+        header_ptrs.push(ptr::null());
+
+        let addr = resolve(&c_host, port)?;
+        let stream = TcpStream::connect(addr)?;
+
+        let mut recv_buf = vec![0u8; RECV_BUF_SIZE];
+        let mut state = ResponseState {
+            status: 0,
+            body: Vec::new(),
+        };
+
+        // SAFETY: The request is plain data, the fields not set here are meant to be zero.
+        let mut req: raw::http_request = unsafe { mem::zeroed() };
+        req.method = method.raw();
+        req.url = c_path.as_ptr();
+        req.host = c_host.as_ptr();
+        req.port = c_port.as_ptr();
+        req.protocol = c"HTTP/1.1".as_ptr();
+        req.optional_headers = header_ptrs.as_mut_ptr();
+        req.response = Some(response_cb);
+        req.recv_buf = recv_buf.as_mut_ptr();
+        req.recv_buf_len = recv_buf.len();
+        if let (Some((payload, _)), Some(content_type)) = (body, &c_content_type) {
+            req.payload = payload.as_ptr() as *const c_char;
+            req.payload_len = payload.len();
+            req.content_type_value = content_type.as_ptr();
+        }
+
+        // SAFETY: Everything the request points to lives until after the call returns, and the
+        // callback is only invoked during the call.
+        let ret = unsafe {
+            raw::http_client_req(
+                stream.as_raw_fd(),
+                &mut req,
+                timeout_ms(timeout),
+                &mut state as *mut ResponseState as *mut c_void,
+            )
+        };
+        if ret < 0 {
+            return Err(HttpError::Net(NetError(ZephyrError::from_errno(ret))));
+        }
+
+        Ok(HttpResponse {
+            status: state.status,
+            body: state.body,
+        })
+    }
+}
+
+impl fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HttpClient")
+    }
+}
+
+fn c_string(text: &str) -> Result<CString, HttpError> {
+    CString::new(text).map_err(|_| HttpError::InvalidUrl)
+}
+
+/// The response as it is received, given to the callback through `user_data`.
+struct ResponseState {
+    status: u16,
+    body: Vec<u8>,
+}
+
+/// Called by the client as each piece of the response is received.
+unsafe extern "C" fn response_cb(
+    rsp: *mut raw::http_response,
+    _final_data: raw::http_final_call,
+    user_data: *mut c_void,
+) -> c_int {
+    let state = &mut *(user_data as *mut ResponseState);
+    let rsp = &*rsp;
+    state.status = rsp.http_status_code;
+    if !rsp.body_frag_start.is_null() {
+        let frag = core::slice::from_raw_parts(rsp.body_frag_start, rsp.body_frag_len);
+        state.body.extend_from_slice(frag);
+    }
+    0
+}
@@ -0,0 +1,171 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Network interfaces.
+//!
+//! Interfaces are created by Zephyr, one for each network device.  This allows an application to
+//! configure them, such as setting a static address, or starting DHCP.
+
+use core::fmt;
+#[cfg(CONFIG_NET_IPV4)]
+use core::net::Ipv4Addr;
+#[cfg(CONFIG_NET_IPV6)]
+use core::net::Ipv6Addr;
+
+#[cfg(any(CONFIG_NET_IPV4, CONFIG_NET_IPV6))]
+use crate::error::ZephyrError;
+use crate::raw;
+
+#[cfg(any(CONFIG_NET_IPV4, CONFIG_NET_IPV6))]
+use super::NetError;
+
+/// A network interface.
+///
+/// This is a reference to the interface, which Zephyr allocates statically.  It can be freely
+/// copied.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NetInterface {
+    pub(crate) iface: *mut raw::net_if,
+}
+
+// SAFETY: The interfaces are static, and the net_if API does its own locking.
+unsafe impl Send for NetInterface {}
+unsafe impl Sync for NetInterface {}
+
+impl NetInterface {
+    /// The default interface.
+    pub fn default_iface() -> Option<NetInterface> {
+        let iface = unsafe { raw::net_if_get_default() };
+        if iface.is_null() {
+            None
+        } else {
+            Some(NetInterface { iface })
+        }
+    }
+
+    /// The default interface, if it supports IPv4.
+    pub fn default_ipv4() -> Option<NetInterface> {
+        Self::default_iface().filter(|iface| iface.flag_is_set(raw::net_if_flag_NET_IF_IPV4))
+    }
+
+    /// The default interface, if it supports IPv6.
+    pub fn default_ipv6() -> Option<NetInterface> {
+        Self::default_iface().filter(|iface| iface.flag_is_set(raw::net_if_flag_NET_IF_IPV6))
+    }
+
+    /// Wrap a raw interface pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be to a Zephyr network interface.
+    pub unsafe fn from_raw(iface: *mut raw::net_if) -> NetInterface {
+        NetInterface { iface }
+    }
+
+    /// The raw interface pointer.
+    pub fn as_raw(&self) -> *mut raw::net_if {
+        self.iface
+    }
+
+    /// Returns true if the interface is up.
+    pub fn is_up(&self) -> bool {
+        unsafe { raw::net_if_is_up(self.iface) }
+    }
+
+    fn flag_is_set(&self, flag: raw::net_if_flag) -> bool {
+        unsafe { raw::net_if_flag_is_set(self.iface, flag) }
+    }
+
+    /// Add a static IPv4 address to the interface, with the given prefix length (such as 24 for a
+    /// netmask of 255.255.255.0).
+    #[cfg(CONFIG_NET_IPV4)]
+    pub fn set_addr_ipv4(&self, addr: Ipv4Addr, prefix_len: u8) -> Result<(), NetError> {
+        if prefix_len > 32 {
+            return Err(NetError(ZephyrError::Invalid));
+        }
+        let raw_addr = super::in_addr(addr);
+        let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+        let mask = super::in_addr(Ipv4Addr::from(mask));
+        unsafe {
+            let ifaddr = raw::net_if_ipv4_addr_add(
+                self.iface,
+                &raw_addr,
+                raw::net_addr_type_NET_ADDR_MANUAL,
+                0,
+            );
+            if ifaddr.is_null() {
+                return Err(NetError(ZephyrError::NoMemory));
+            }
+            if !raw::net_if_ipv4_set_netmask_by_addr(self.iface, &raw_addr, &mask) {
+                return Err(NetError(ZephyrError::Invalid));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the default IPv4 gateway for the interface.
+    #[cfg(CONFIG_NET_IPV4)]
+    pub fn set_gateway_ipv4(&self, gw: Ipv4Addr) {
+        let gw = super::in_addr(gw);
+        unsafe {
+            raw::net_if_ipv4_set_gw(self.iface, &gw);
+        }
+    }
+
+    /// Add a static IPv6 address to the interface, and its prefix as on-link.
+    #[cfg(CONFIG_NET_IPV6)]
+    pub fn set_addr_ipv6(&self, addr: Ipv6Addr, prefix_len: u8) -> Result<(), NetError> {
+        if prefix_len > 128 {
+            return Err(NetError(ZephyrError::Invalid));
+        }
+        let raw_addr = super::in6_addr(addr);
+        unsafe {
+            let ifaddr = raw::net_if_ipv6_addr_add(
+                self.iface,
+                &raw_addr,
+                raw::net_addr_type_NET_ADDR_MANUAL,
+                0,
+            );
+            if ifaddr.is_null() {
+                return Err(NetError(ZephyrError::NoMemory));
+            }
+            let prefix = raw::net_if_ipv6_prefix_add(
+                self.iface,
+                &raw_addr,
+                prefix_len,
+                raw::NET_IPV6_ND_INFINITE_LIFETIME,
+            );
+            if prefix.is_null() {
+                return Err(NetError(ZephyrError::NoMemory));
+            }
+        }
+        Ok(())
+    }
+
+    /// Start the DHCPv4 client on this interface.
+    ///
+    /// The address is configured in the background.
+    ///
+    /// Requires `CONFIG_NET_DHCPV4`.
+    #[cfg(CONFIG_NET_DHCPV4)]
+    pub fn dhcpv4_start(&self) {
+        unsafe {
+            raw::net_dhcpv4_start(self.iface);
+        }
+    }
+
+    /// Stop the DHCPv4 client on this interface.
+    #[cfg(CONFIG_NET_DHCPV4)]
+    pub fn dhcpv4_stop(&self) {
+        unsafe {
+            raw::net_dhcpv4_stop(self.iface);
+        }
+    }
+}
+
+impl fmt::Debug for NetInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let index = unsafe { raw::net_if_get_by_iface(self.iface) };
+        write!(f, "NetInterface({})", index)
+    }
+}
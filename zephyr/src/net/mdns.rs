@@ -0,0 +1,160 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! mDNS service advertisement.
+//!
+//! Zephyr's mDNS responder answers queries for the device's hostname, and, with DNS-SD, for the
+//! services it offers.  Services are normally declared statically in C, with
+//! `DNS_SD_REGISTER_SERVICE`.  This instead adds them at runtime, through the responder's
+//! external records.
+//!
+//! ```ignore
+//! let txt = [(c"path", c"/")];
+//! let _http = MdnsResponder::add_service(c"My Device", c"_http", c"_tcp", 80, &txt)?;
+//! ```
+//!
+//! The service is advertised until its [`MdnsServiceHandle`] is dropped.
+//!
+//! Requires `CONFIG_MDNS_RESPONDER`, with `CONFIG_MDNS_RESPONDER_DNS_SD`, and `CONFIG_RUST_ALLOC`.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+
+use core::ffi::{c_char, CStr};
+use core::fmt;
+use core::ptr;
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+use crate::sync::{LazyLock, Mutex};
+
+/// The data a record points to.  Boxed, so the record's pointers stay valid as the list grows.
+struct Service {
+    id: u32,
+    instance: CString,
+    service: CString,
+    proto: CString,
+    /// The TXT record, as length prefixed `key=value` strings.
+    text: Vec<u8>,
+    /// The port, in network byte order.
+    port: u16,
+}
+
+/// The services that have been added, and the records given to the responder for them.
+struct Registry {
+    next_id: u32,
+    services: Vec<Box<Service>>,
+    records: Vec<raw::dns_sd_rec>,
+}
+
+// SAFETY: The records only point into the boxed services, which are owned by the registry.
+unsafe impl Send for Registry {}
+
+impl Registry {
+    /// Rebuild the records, and hand them to the responder.
+    fn publish(&mut self) -> ZResult<()> {
+        // Take the old records away from the responder first, as rebuilding may move them.
+        unsafe {
+            raw::mdns_responder_set_ext_records(ptr::null(), 0);
+        }
+        self.records.clear();
+        self.records.extend(self.services.iter().map(|s| raw::dns_sd_rec {
+            instance: s.instance.as_ptr(),
+            service: s.service.as_ptr(),
+            proto: s.proto.as_ptr(),
+            domain: c"local".as_ptr(),
+            text: s.text.as_ptr() as *const c_char,
+            text_size: s.text.len(),
+            port: &s.port,
+        }));
+        if self.records.is_empty() {
+            return Ok(());
+        }
+        let ret = unsafe {
+            raw::mdns_responder_set_ext_records(self.records.as_ptr(), self.records.len())
+        };
+        ok_or_errno(ret)
+    }
+}
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(|| {
+    Mutex::new(Registry {
+        next_id: 0,
+        services: Vec::new(),
+        records: Vec::new(),
+    })
+});
+
+/// The mDNS responder.
+pub struct MdnsResponder;
+
+impl MdnsResponder {
+    /// Advertise a service.
+    ///
+    /// `name` is the instance name shown to users, `service` and `proto` give the service type,
+    /// such as `c"_http"` and `c"_tcp"`.  The `txt` pairs are advertised as `key=value` strings.
+    pub fn add_service(
+        name: &CStr,
+        service: &CStr,
+        proto: &CStr,
+        port: u16,
+        txt: &[(&CStr, &CStr)],
+    ) -> ZResult<MdnsServiceHandle> {
+        let mut text = Vec::new();
+        for (key, value) in txt {
+            let len = key.to_bytes().len() + 1 + value.to_bytes().len();
+            let len = u8::try_from(len).map_err(|_| ZephyrError::Invalid)?;
+            text.push(len);
+            text.extend_from_slice(key.to_bytes());
+            text.push(b'=');
+            text.extend_from_slice(value.to_bytes());
+        }
+        // An empty TXT record is still a single empty string.
+        if text.is_empty() {
+            text.push(0);
+        }
+
+        let mut registry = REGISTRY.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.services.push(Box::new(Service {
+            id,
+            instance: name.into(),
+            service: service.into(),
+            proto: proto.into(),
+            text,
+            port: port.to_be(),
+        }));
+        if let Err(err) = registry.publish() {
+            registry.services.pop();
+            let _ = registry.publish();
+            return Err(err);
+        }
+        Ok(MdnsServiceHandle { id })
+    }
+}
+
+impl fmt::Debug for MdnsResponder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MdnsResponder")
+    }
+}
+
+/// A service being advertised.  The service is removed when this is dropped.
+#[derive(Debug)]
+pub struct MdnsServiceHandle {
+    id: u32,
+}
+
+impl Drop for MdnsServiceHandle {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock().unwrap();
+        registry.services.retain(|s| s.id != self.id);
+        if let Err(err) = registry.publish() {
+            log::warn!("Unable to update mDNS services: {}", err);
+        }
+    }
+}
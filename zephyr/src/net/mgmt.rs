@@ -0,0 +1,188 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Network management events.
+//!
+//! Zephyr's `net_mgmt` subsystem reports changes to the network, such as an address being added,
+//! or an interface going up.  A [`NetEventListener`] registers a callback for a set of these
+//! events.
+//!
+//! ```ignore
+//! let _listener = NetEventListener::for_events(&[NetEvent::Ipv4AddrAdd], |event, iface| {
+//!     printkln!("{:?} on {:?}", event, iface);
+//! });
+//! ```
+//!
+//! The event codes include the network layer they belong to, and Zephyr only matches a callback
+//! against a single layer, so the events given to one listener should all be from the same layer.
+//!
+//! Requires `CONFIG_NET_MGMT_EVENT` and `CONFIG_RUST_ALLOC`.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomPinned;
+use core::mem;
+use core::pin::Pin;
+
+use crate::raw;
+
+use super::NetInterface;
+
+/// The type Zephyr uses for event codes.
+type MgmtEvent = u32;
+
+/// The common network events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetEvent {
+    /// An interface has come up.
+    IfUp,
+    /// An interface has gone down.
+    IfDown,
+    /// An IPv4 address has been added.
+    Ipv4AddrAdd,
+    /// An IPv4 address has been removed.
+    Ipv4AddrDel,
+    /// DHCP has obtained a lease.
+    Ipv4DhcpBound,
+    /// An IPv6 address has been added.
+    Ipv6AddrAdd,
+    /// An IPv6 address has been removed.
+    Ipv6AddrDel,
+    /// The network is ready for use by applications.
+    L4Connected,
+    /// The network is no longer available for use by applications.
+    L4Disconnected,
+}
+
+const ALL_EVENTS: [NetEvent; 9] = [
+    NetEvent::IfUp,
+    NetEvent::IfDown,
+    NetEvent::Ipv4AddrAdd,
+    NetEvent::Ipv4AddrDel,
+    NetEvent::Ipv4DhcpBound,
+    NetEvent::Ipv6AddrAdd,
+    NetEvent::Ipv6AddrDel,
+    NetEvent::L4Connected,
+    NetEvent::L4Disconnected,
+];
+
+impl NetEvent {
+    /// The Zephyr event code, `NET_EVENT_*`.
+    pub fn code(self) -> u32 {
+        match self {
+            NetEvent::IfUp => raw::ZR_NET_EVENT_IF_UP,
+            NetEvent::IfDown => raw::ZR_NET_EVENT_IF_DOWN,
+            NetEvent::Ipv4AddrAdd => raw::ZR_NET_EVENT_IPV4_ADDR_ADD,
+            NetEvent::Ipv4AddrDel => raw::ZR_NET_EVENT_IPV4_ADDR_DEL,
+            NetEvent::Ipv4DhcpBound => raw::ZR_NET_EVENT_IPV4_DHCP_BOUND,
+            NetEvent::Ipv6AddrAdd => raw::ZR_NET_EVENT_IPV6_ADDR_ADD,
+            NetEvent::Ipv6AddrDel => raw::ZR_NET_EVENT_IPV6_ADDR_DEL,
+            NetEvent::L4Connected => raw::ZR_NET_EVENT_L4_CONNECTED,
+            NetEvent::L4Disconnected => raw::ZR_NET_EVENT_L4_DISCONNECTED,
+        }
+    }
+
+    /// The event with the given code, if it is one of the common events.
+    pub fn from_code(code: u32) -> Option<NetEvent> {
+        ALL_EVENTS.iter().copied().find(|event| event.code() == code)
+    }
+}
+
+/// How the listener's callback wants its events.
+#[derive(Clone, Copy)]
+enum Callback {
+    Raw(fn(event: u32, iface: &NetInterface)),
+    Typed(fn(event: NetEvent, iface: &NetInterface)),
+}
+
+/// A registered callback for network events.
+///
+/// The callback is called from the network management thread.  It is unregistered when this is
+/// dropped.
+pub struct NetEventListener {
+    inner: Pin<Box<ListenerInner>>,
+}
+
+struct ListenerInner {
+    cb: UnsafeCell<raw::net_mgmt_event_callback>,
+    callback: Callback,
+    _pin: PhantomPinned,
+}
+
+// SAFETY: Zephyr's callback list is protected by its own lock.
+unsafe impl Send for NetEventListener {}
+unsafe impl Sync for NetEventListener {}
+
+impl NetEventListener {
+    /// Register `callback` to be called for any of the `NET_EVENT_*` codes in `events_mask`.
+    pub fn new(events_mask: u32, callback: fn(event: u32, iface: &NetInterface)) -> Self {
+        Self::register(events_mask, Callback::Raw(callback))
+    }
+
+    /// Register `callback` to be called for any of `events`.
+    pub fn for_events(
+        events: &[NetEvent],
+        callback: fn(event: NetEvent, iface: &NetInterface),
+    ) -> Self {
+        let mask = events.iter().fold(0, |mask, event| mask | event.code());
+        Self::register(mask, Callback::Typed(callback))
+    }
+
+    fn register(mask: u32, callback: Callback) -> Self {
+        let inner = Box::pin(ListenerInner {
+            // SAFETY: will be initialized below, after this is pinned.
+            cb: unsafe { mem::zeroed() },
+            callback,
+            _pin: PhantomPinned,
+        });
+
+        // SAFETY: The callback is pinned, and is removed on drop.
+        unsafe {
+            raw::net_mgmt_init_event_callback(inner.cb.get(), Some(handler), mask as MgmtEvent);
+            raw::net_mgmt_add_event_callback(inner.cb.get());
+        }
+
+        NetEventListener { inner }
+    }
+}
+
+impl Drop for NetEventListener {
+    fn drop(&mut self) {
+        unsafe {
+            raw::net_mgmt_del_event_callback(self.inner.cb.get());
+        }
+    }
+}
+
+impl fmt::Debug for NetEventListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NetEventListener")
+    }
+}
+
+/// The event handler, called from C.
+unsafe extern "C" fn handler(
+    cb: *mut raw::net_mgmt_event_callback,
+    mgmt_event: MgmtEvent,
+    iface: *mut raw::net_if,
+) {
+    // SAFETY: The callback is within our pinned inner struct.
+    let this = &*cb
+        .cast::<u8>()
+        .sub(mem::offset_of!(ListenerInner, cb))
+        .cast::<ListenerInner>();
+    let iface = NetInterface::from_raw(iface);
+    let event = mgmt_event as u32;
+    match this.callback {
+        Callback::Raw(callback) => callback(event, &iface),
+        Callback::Typed(callback) => {
+            if let Some(event) = NetEvent::from_code(event) {
+                callback(event, &iface);
+            }
+        }
+    }
+}
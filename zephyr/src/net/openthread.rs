@@ -0,0 +1,236 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! OpenThread.
+//!
+//! Zephyr runs OpenThread as the L2 of an IEEE 802.15.4 network interface.  Once attached, the
+//! Thread network is an ordinary IPv6 interface, so traffic over it uses the usual sockets, such
+//! as [`UdpSocket`](super::socket::UdpSocket).  [`OpenThreadInterface`] covers the Thread specific
+//! parts: scanning for networks, and attaching to one.
+//!
+//! The network credentials, the dataset, are configured with Kconfig, or through the OpenThread
+//! API.
+//!
+//! Requires `CONFIG_NET_L2_OPENTHREAD`.
+
+use core::ffi::c_void;
+use core::fmt;
+use core::mem;
+use core::net::Ipv6Addr;
+
+use crate::raw;
+
+use super::NetInterface;
+
+/// The most addresses returned by [`OpenThreadInterface::get_ip6_address`].
+pub const MAX_ADDRS: usize = 8;
+
+/// An error from OpenThread.  These are OpenThread's own codes, not errno values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtError(pub u32);
+
+impl OtError {
+    /// OpenThread has not been set up on any interface.
+    pub const NOT_FOUND: OtError = OtError(raw::otError_OT_ERROR_NOT_FOUND as u32);
+}
+
+impl fmt::Display for OtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "openthread error {}", self.0)
+    }
+}
+
+fn check(err: raw::otError) -> Result<(), OtError> {
+    if err == raw::otError_OT_ERROR_NONE {
+        Ok(())
+    } else {
+        Err(OtError(err as u32))
+    }
+}
+
+/// The mode a device attaches to the network in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtLinkMode {
+    /// Keep the receiver on when idle.  Sleepy end devices turn this off.
+    pub rx_on_when_idle: bool,
+    /// A full Thread device, which can become a router.
+    pub full_thread_device: bool,
+    /// Request the full network data, rather than only the stable parts.
+    pub full_network_data: bool,
+}
+
+impl OtLinkMode {
+    /// A router capable device.
+    pub const ROUTER: OtLinkMode = OtLinkMode {
+        rx_on_when_idle: true,
+        full_thread_device: true,
+        full_network_data: true,
+    };
+
+    /// A minimal end device, that keeps its receiver on.
+    pub const END_DEVICE: OtLinkMode = OtLinkMode {
+        rx_on_when_idle: true,
+        full_thread_device: false,
+        full_network_data: false,
+    };
+
+    /// A sleepy end device, that polls its parent for messages.
+    pub const SLEEPY_END_DEVICE: OtLinkMode = OtLinkMode {
+        rx_on_when_idle: false,
+        full_thread_device: false,
+        full_network_data: false,
+    };
+}
+
+/// A network found by an active scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtActiveScanResult {
+    /// The extended address of the device that answered.
+    pub ext_address: [u8; 8],
+    /// The network name, NUL padded.
+    pub network_name: [u8; 16],
+    /// The extended PAN ID.
+    pub extended_pan_id: [u8; 8],
+    /// The PAN ID.
+    pub pan_id: u16,
+    /// The channel.
+    pub channel: u8,
+    /// The signal strength of the beacon, in dBm.
+    pub rssi: i8,
+    /// The link quality of the beacon.
+    pub lqi: u8,
+}
+
+impl OtActiveScanResult {
+    /// The network name, if it is valid UTF-8.
+    pub fn name(&self) -> Option<&str> {
+        let len = self.network_name.iter().position(|&b| b == 0).unwrap_or(16);
+        core::str::from_utf8(&self.network_name[..len]).ok()
+    }
+}
+
+/// The OpenThread interface.
+pub struct OpenThreadInterface {
+    ctx: *mut raw::openthread_context,
+}
+
+// SAFETY: All access to the instance is done with the OpenThread API mutex held.
+unsafe impl Send for OpenThreadInterface {}
+unsafe impl Sync for OpenThreadInterface {}
+
+impl OpenThreadInterface {
+    /// Get the OpenThread interface.
+    ///
+    /// The stack is initialized by Zephyr when the interface comes up, this only finds it.
+    pub fn new() -> Result<OpenThreadInterface, OtError> {
+        let ctx = unsafe { raw::openthread_get_default_context() };
+        if ctx.is_null() {
+            return Err(OtError::NOT_FOUND);
+        }
+        Ok(OpenThreadInterface { ctx })
+    }
+
+    /// Run `f` with the instance, and the API mutex held.
+    fn with_instance<R>(&self, f: impl FnOnce(*mut raw::otInstance) -> R) -> R {
+        unsafe {
+            raw::openthread_api_mutex_lock(self.ctx);
+            let result = f((*self.ctx).instance);
+            raw::openthread_api_mutex_unlock(self.ctx);
+            result
+        }
+    }
+
+    /// Scan all channels for networks.
+    ///
+    /// `callback` is called from the OpenThread thread, for each network found.
+    pub fn start_active_scan(&self, callback: fn(OtActiveScanResult)) -> Result<(), OtError> {
+        self.with_instance(|instance| {
+            check(unsafe {
+                raw::otLinkActiveScan(
+                    instance,
+                    0,
+                    0,
+                    Some(active_scan_result),
+                    callback as *mut c_void,
+                )
+            })
+        })
+    }
+
+    /// Attach to the network configured in the active dataset, in the given mode.
+    ///
+    /// This brings up IPv6 and starts Thread.  Attaching then happens in the background, and
+    /// shows up as the interface gaining addresses.
+    pub fn attach_to_network(&self, mode: OtLinkMode) -> Result<(), OtError> {
+        // SAFETY: The mode is a set of bitfields, all zero by default.
+        let mut config: raw::otLinkModeConfig = unsafe { mem::zeroed() };
+        config.set_mRxOnWhenIdle(mode.rx_on_when_idle);
+        config.set_mDeviceType(mode.full_thread_device);
+        config.set_mNetworkData(mode.full_network_data);
+        self.with_instance(|instance| unsafe {
+            check(raw::otThreadSetLinkMode(instance, config))?;
+            check(raw::otIp6SetEnabled(instance, true))?;
+            check(raw::otThreadSetEnabled(instance, true))
+        })
+    }
+
+    /// Detach from the network, and stop Thread.
+    pub fn detach(&self) -> Result<(), OtError> {
+        self.with_instance(|instance| check(unsafe { raw::otThreadSetEnabled(instance, false) }))
+    }
+
+    /// The unicast addresses of the interface.
+    ///
+    /// Unused entries are [`Ipv6Addr::UNSPECIFIED`].
+    pub fn get_ip6_address(&self) -> [Ipv6Addr; MAX_ADDRS] {
+        let mut addrs = [Ipv6Addr::UNSPECIFIED; MAX_ADDRS];
+        self.with_instance(|instance| unsafe {
+            let mut addr = raw::otIp6GetUnicastAddresses(instance);
+            for slot in addrs.iter_mut() {
+                if addr.is_null() {
+                    break;
+                }
+                *slot = Ipv6Addr::from((*addr).mAddress.mFields.m8);
+                addr = (*addr).mNext;
+            }
+        });
+        addrs
+    }
+
+    /// The network interface that OpenThread runs on.
+    pub fn iface(&self) -> NetInterface {
+        unsafe { NetInterface::from_raw((*self.ctx).iface) }
+    }
+}
+
+impl fmt::Debug for OpenThreadInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OpenThreadInterface")
+    }
+}
+
+/// Called by OpenThread for each scan result, with a null result at the end of the scan.
+unsafe extern "C" fn active_scan_result(
+    result: *mut raw::otActiveScanResult,
+    context: *mut c_void,
+) {
+    if result.is_null() {
+        return;
+    }
+    // SAFETY: The context is the callback given to `start_active_scan`.
+    let callback: fn(OtActiveScanResult) = mem::transmute(context);
+    let result = &*result;
+    let mut network_name = [0u8; 16];
+    for (dest, &src) in network_name.iter_mut().zip(result.mNetworkName.m8.iter()) {
+        *dest = src as u8;
+    }
+    callback(OtActiveScanResult {
+        ext_address: result.mExtAddress.m8,
+        network_name,
+        extended_pan_id: result.mExtendedPanId.m8,
+        pan_id: result.mPanId,
+        channel: result.mChannel,
+        rssi: result.mRssi,
+        lqi: result.mLqi,
+    });
+}
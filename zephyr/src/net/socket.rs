@@ -0,0 +1,401 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! BSD sockets.
+//!
+//! Zephyr's socket calls follow POSIX, returning -1 and setting `errno` on failure.  The wrappers
+//! here return a [`NetError`] instead.
+//!
+//! [`TcpStream`] is a connected TCP socket, and [`UdpSocket`] a datagram socket.  Both close
+//! themselves when dropped.
+
+use core::ffi::{c_int, c_void, CStr};
+use core::fmt;
+use core::mem;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use core::ptr;
+
+use bitflags::bitflags;
+
+use crate::error::ZephyrError;
+use crate::raw;
+
+use super::NetError;
+
+/// Convert the return from a socket call, using `errno` when it fails.
+pub(crate) fn check_errno(ret: c_int) -> Result<usize, NetError> {
+    if ret < 0 {
+        Err(last_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+/// The error from the most recent failed socket call on this thread.
+pub(crate) fn last_error() -> NetError {
+    NetError(ZephyrError::from_errno(unsafe { raw::zr_errno() }))
+}
+
+bitflags! {
+    /// The events that [`zsock_poll`] can wait for, and report.
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PollEvents: i16 {
+        /// There is data to read.
+        const IN = raw::ZSOCK_POLLIN as i16;
+        /// There is urgent data to read.
+        const PRI = raw::ZSOCK_POLLPRI as i16;
+        /// Writing will not block.
+        const OUT = raw::ZSOCK_POLLOUT as i16;
+        /// An error has occurred.  Only reported in `revents`.
+        const ERR = raw::ZSOCK_POLLERR as i16;
+        /// The peer has closed the connection.  Only reported in `revents`.
+        const HUP = raw::ZSOCK_POLLHUP as i16;
+        /// The file descriptor is not open.  Only reported in `revents`.
+        const NVAL = raw::ZSOCK_POLLNVAL as i16;
+    }
+}
+
+/// A socket to poll, and its events.
+///
+/// This has the same layout as `struct zsock_pollfd`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollFd {
+    /// The socket.  A negative value is ignored.
+    pub fd: i32,
+    /// The events to wait for.
+    pub events: PollEvents,
+    /// The events that occurred, filled in by [`zsock_poll`].
+    pub revents: PollEvents,
+}
+
+const _: () = assert!(core::mem::size_of::<PollFd>() == core::mem::size_of::<raw::zsock_pollfd>());
+
+impl PollFd {
+    /// Poll `fd` for `events`.
+    pub fn new(fd: i32, events: PollEvents) -> PollFd {
+        PollFd {
+            fd,
+            events,
+            revents: PollEvents::empty(),
+        }
+    }
+}
+
+/// Wait for events on a set of sockets.
+///
+/// Waits until at least one of the `fds` has one of its events, or `timeout_ms` milliseconds have
+/// passed.  A timeout of -1 waits forever, and 0 returns immediately.  Returns the number of
+/// entries that have a non-empty `revents`, which is zero on a timeout.
+pub fn zsock_poll(fds: &mut [PollFd], timeout_ms: i32) -> Result<usize, NetError> {
+    for fd in fds.iter_mut() {
+        fd.revents = PollEvents::empty();
+    }
+    // SAFETY: `PollFd` has the same layout as `zsock_pollfd`.
+    let ret = unsafe {
+        raw::zsock_poll(
+            fds.as_mut_ptr() as *mut raw::zsock_pollfd,
+            fds.len() as c_int,
+            timeout_ms,
+        )
+    };
+    check_errno(ret)
+}
+
+/// A socket address in the form the socket calls take.
+pub(crate) struct RawSockAddr {
+    addr: raw::sockaddr,
+    len: raw::socklen_t,
+}
+
+impl RawSockAddr {
+    pub(crate) fn new(addr: &SocketAddr) -> RawSockAddr {
+        // SAFETY: The address types are plain data, and `sockaddr` is large enough for either.
+        unsafe {
+            let mut raw_addr: raw::sockaddr = mem::zeroed();
+            let dest = &mut raw_addr as *mut raw::sockaddr;
+            let len = match addr {
+                SocketAddr::V4(v4) => {
+                    let mut sin: raw::sockaddr_in = mem::zeroed();
+                    sin.sin_family = raw::AF_INET as raw::sa_family_t;
+                    sin.sin_port = v4.port().to_be();
+                    sin.sin_addr = super::in_addr(*v4.ip());
+                    ptr::write_unaligned(dest as *mut raw::sockaddr_in, sin);
+                    mem::size_of::<raw::sockaddr_in>()
+                }
+                SocketAddr::V6(v6) => {
+                    let mut sin6: raw::sockaddr_in6 = mem::zeroed();
+                    sin6.sin6_family = raw::AF_INET6 as raw::sa_family_t;
+                    sin6.sin6_port = v6.port().to_be();
+                    sin6.sin6_addr = super::in6_addr(*v6.ip());
+                    sin6.sin6_scope_id = v6.scope_id() as _;
+                    ptr::write_unaligned(dest as *mut raw::sockaddr_in6, sin6);
+                    mem::size_of::<raw::sockaddr_in6>()
+                }
+            };
+            RawSockAddr {
+                addr: raw_addr,
+                len: len as raw::socklen_t,
+            }
+        }
+    }
+
+    pub(crate) fn family(&self) -> c_int {
+        self.addr.sa_family as c_int
+    }
+}
+
+/// Convert an address filled in by the stack.
+///
+/// # Safety
+///
+/// `addr` must point to a valid address, of the size its family says.
+unsafe fn from_raw_sockaddr(addr: *const raw::sockaddr) -> Result<SocketAddr, NetError> {
+    match (*addr).sa_family as u32 {
+        raw::AF_INET => {
+            let sin = ptr::read_unaligned(addr as *const raw::sockaddr_in);
+            let ip = Ipv4Addr::from(sin.sin_addr.__bindgen_anon_1.s4_addr);
+            Ok(SocketAddr::V4(SocketAddrV4::new(ip, u16::from_be(sin.sin_port))))
+        }
+        raw::AF_INET6 => {
+            let sin6 = ptr::read_unaligned(addr as *const raw::sockaddr_in6);
+            let ip = Ipv6Addr::from(sin6.sin6_addr.__bindgen_anon_1.s6_addr);
+            let port = u16::from_be(sin6.sin6_port);
+            let scope_id = sin6.sin6_scope_id as u32;
+            Ok(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id)))
+        }
+        _ => Err(NetError(ZephyrError::NotSupported)),
+    }
+}
+
+/// Look up the address of `host`, which may be a name, or an address in text form.
+///
+/// Returns the first address found.  Looking up names requires `CONFIG_DNS_RESOLVER`.
+pub fn resolve(host: &CStr, port: u16) -> Result<SocketAddr, NetError> {
+    let mut res: *mut raw::zsock_addrinfo = ptr::null_mut();
+    // SAFETY: `hints` is plain data.
+    let mut hints: raw::zsock_addrinfo = unsafe { mem::zeroed() };
+    hints.ai_socktype = raw::net_sock_type_SOCK_STREAM as c_int;
+    let ret = unsafe { raw::zsock_getaddrinfo(host.as_ptr(), ptr::null(), &hints, &mut res) };
+    if ret != 0 || res.is_null() {
+        // The getaddrinfo errors are their own codes, not errno values.
+        return Err(NetError(ZephyrError::NoEntry));
+    }
+
+    // SAFETY: getaddrinfo succeeded, so the first result is valid.
+    let addr = unsafe { from_raw_sockaddr((*res).ai_addr) }.map(|mut addr| {
+        addr.set_port(port);
+        addr
+    });
+    unsafe {
+        raw::zsock_freeaddrinfo(res);
+    }
+    addr
+}
+
+/// A connected TCP socket.
+pub struct TcpStream {
+    fd: c_int,
+}
+
+impl TcpStream {
+    /// Connect to the given address.
+    pub fn connect(addr: SocketAddr) -> Result<TcpStream, NetError> {
+        let addr = RawSockAddr::new(&addr);
+        let stream = Self::socket(addr.family(), raw::net_ip_protocol_IPPROTO_TCP as c_int)?;
+        stream.connect_raw(&addr)?;
+        Ok(stream)
+    }
+
+    /// Create an unconnected stream socket.
+    pub(crate) fn socket(family: c_int, proto: c_int) -> Result<TcpStream, NetError> {
+        let sock_type = raw::net_sock_type_SOCK_STREAM as c_int;
+        let fd = check_errno(unsafe { raw::zsock_socket(family, sock_type, proto) })?;
+        Ok(TcpStream { fd: fd as c_int })
+    }
+
+    pub(crate) fn connect_raw(&self, addr: &RawSockAddr) -> Result<(), NetError> {
+        check_errno(unsafe { raw::zsock_connect(self.fd, &addr.addr, addr.len) }).map(|_| ())
+    }
+
+    /// Set a socket option.
+    pub(crate) fn set_option<T: ?Sized>(
+        &self,
+        level: c_int,
+        name: c_int,
+        value: &T,
+    ) -> Result<(), NetError> {
+        let ret = unsafe {
+            raw::zsock_setsockopt(
+                self.fd,
+                level,
+                name,
+                value as *const T as *const c_void,
+                mem::size_of_val(value) as raw::socklen_t,
+            )
+        };
+        check_errno(ret).map(|_| ())
+    }
+
+    /// Read from the socket, returning the number of bytes read.  Returns 0 once the peer has
+    /// closed the connection.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, NetError> {
+        let ret = unsafe { raw::zsock_recv(self.fd, buf.as_mut_ptr() as *mut _, buf.len(), 0) };
+        check_errno(ret as c_int)
+    }
+
+    /// Write to the socket, returning the number of bytes written.
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, NetError> {
+        let ret = unsafe { raw::zsock_send(self.fd, buf.as_ptr() as *const _, buf.len(), 0) };
+        check_errno(ret as c_int)
+    }
+
+    /// Write all of `buf` to the socket.
+    pub fn write_all(&mut self, mut buf: &[u8]) -> Result<(), NetError> {
+        while !buf.is_empty() {
+            let count = self.write(buf)?;
+            buf = &buf[count..];
+        }
+        Ok(())
+    }
+
+    /// The socket's file descriptor, for use with [`zsock_poll`].
+    pub fn as_raw_fd(&self) -> i32 {
+        self.fd
+    }
+
+    /// Give up ownership of the socket, returning its file descriptor.  The socket will no longer
+    /// be closed on drop.
+    pub fn into_raw_fd(self) -> i32 {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl Drop for TcpStream {
+    fn drop(&mut self) {
+        unsafe {
+            raw::zsock_close(self.fd);
+        }
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TcpStream({})", self.fd)
+    }
+}
+
+impl embedded_io::ErrorType for TcpStream {
+    type Error = NetError;
+}
+
+impl embedded_io::Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, NetError> {
+        TcpStream::read(self, buf)
+    }
+}
+
+impl embedded_io::Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, NetError> {
+        TcpStream::write(self, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), NetError> {
+        Ok(())
+    }
+}
+
+/// A UDP socket.
+pub struct UdpSocket {
+    fd: c_int,
+}
+
+impl UdpSocket {
+    /// Create a socket bound to the given local address.  A port of 0 picks any free port.
+    pub fn bind(addr: SocketAddr) -> Result<UdpSocket, NetError> {
+        let addr = RawSockAddr::new(&addr);
+        let sock_type = raw::net_sock_type_SOCK_DGRAM as c_int;
+        let proto = raw::net_ip_protocol_IPPROTO_UDP as c_int;
+        let fd = check_errno(unsafe { raw::zsock_socket(addr.family(), sock_type, proto) })?;
+        let sock = UdpSocket { fd: fd as c_int };
+        check_errno(unsafe { raw::zsock_bind(sock.fd, &addr.addr, addr.len) })?;
+        Ok(sock)
+    }
+
+    /// Set the default destination, for [`send`](Self::send), and only receive from that address.
+    pub fn connect(&self, addr: SocketAddr) -> Result<(), NetError> {
+        let addr = RawSockAddr::new(&addr);
+        check_errno(unsafe { raw::zsock_connect(self.fd, &addr.addr, addr.len) }).map(|_| ())
+    }
+
+    /// Send a datagram to `addr`, returning the number of bytes sent.
+    pub fn send_to(&self, buf: &[u8], addr: SocketAddr) -> Result<usize, NetError> {
+        let addr = RawSockAddr::new(&addr);
+        let ret = unsafe {
+            raw::zsock_sendto(
+                self.fd,
+                buf.as_ptr() as *const _,
+                buf.len(),
+                0,
+                &addr.addr,
+                addr.len,
+            )
+        };
+        check_errno(ret as c_int)
+    }
+
+    /// Send a datagram to the connected address.
+    pub fn send(&self, buf: &[u8]) -> Result<usize, NetError> {
+        let ret = unsafe { raw::zsock_send(self.fd, buf.as_ptr() as *const _, buf.len(), 0) };
+        check_errno(ret as c_int)
+    }
+
+    /// Receive a datagram, returning its length and the address it came from.
+    ///
+    /// A datagram larger than `buf` is truncated.
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr), NetError> {
+        // SAFETY: Filled in by `zsock_recvfrom`.
+        let mut from: raw::sockaddr = unsafe { mem::zeroed() };
+        let mut from_len = mem::size_of::<raw::sockaddr>() as raw::socklen_t;
+        let ret = unsafe {
+            raw::zsock_recvfrom(
+                self.fd,
+                buf.as_mut_ptr() as *mut _,
+                buf.len(),
+                0,
+                &mut from,
+                &mut from_len,
+            )
+        };
+        let count = check_errno(ret as c_int)?;
+        Ok((count, unsafe { from_raw_sockaddr(&from) }?))
+    }
+
+    /// Receive a datagram from the connected address.
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize, NetError> {
+        let ret = unsafe { raw::zsock_recv(self.fd, buf.as_mut_ptr() as *mut _, buf.len(), 0) };
+        check_errno(ret as c_int)
+    }
+
+    /// The socket's file descriptor, for use with [`zsock_poll`].
+    pub fn as_raw_fd(&self) -> i32 {
+        self.fd
+    }
+}
+
+impl Drop for UdpSocket {
+    fn drop(&mut self) {
+        unsafe {
+            raw::zsock_close(self.fd);
+        }
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UdpSocket({})", self.fd)
+    }
+}
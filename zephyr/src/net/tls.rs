@@ -0,0 +1,228 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! TLS sockets.
+//!
+//! Zephyr implements TLS within the socket layer, with mbedTLS.  The certificates and keys are
+//! registered ahead of time, under a "security tag", with the TLS credentials API, and a TLS socket
+//! is then told which tags to use.  A [`TlsContext`] is one such tag, along with the credentials
+//! added to it.
+//!
+//! ```ignore
+//! static CA: &[u8] = concat!(include_str!("ca.pem"), "\0").as_bytes();
+//!
+//! let mut tls = TlsContext::new();
+//! tls.add_ca_cert(CA)?;
+//! tls.set_hostname(c"example.com");
+//! let mut stream = TcpStream::connect_tls(addr, &tls)?;
+//! ```
+//!
+//! Requires `CONFIG_NET_SOCKETS_SOCKOPT_TLS`.
+
+use core::ffi::{c_int, c_void, CStr};
+use core::fmt;
+use core::net::SocketAddr;
+
+use crate::error::ZephyrError;
+use crate::raw;
+use crate::sync::atomic::{AtomicI32, Ordering};
+
+use super::socket::{RawSockAddr, TcpStream};
+use super::NetError;
+
+/// An error setting up, or using, a TLS connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsError(pub ZephyrError);
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tls: {}", self.0)
+    }
+}
+
+impl From<NetError> for TlsError {
+    fn from(err: NetError) -> TlsError {
+        TlsError(err.0)
+    }
+}
+
+impl embedded_io::Error for TlsError {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        embedded_io::Error::kind(&self.0)
+    }
+}
+
+/// The security tags handed out by [`TlsContext::new`].  These start well above the small numbers
+/// that C code typically uses for its own tags.
+static NEXT_TAG: AtomicI32 = AtomicI32::new(0x5253_0000);
+
+const CA_CERTIFICATE: u32 = raw::tls_credential_type_TLS_CREDENTIAL_CA_CERTIFICATE;
+const CERTIFICATE: u32 = raw::tls_credential_type_TLS_CREDENTIAL_PUBLIC_CERTIFICATE;
+const PRIVATE_KEY: u32 = raw::tls_credential_type_TLS_CREDENTIAL_PRIVATE_KEY;
+
+/// A set of TLS credentials, under their own security tag.
+///
+/// Zephyr does not copy the credentials, so they must be `'static`.  PEM data must include a
+/// terminating NUL, DER data must not.  The credentials are removed when the context is dropped,
+/// so it should outlive any connections using it.
+pub struct TlsContext {
+    tag: raw::sec_tag_t,
+    hostname: Option<&'static CStr>,
+    /// The credential types that have been added, to remove on drop.
+    added: [bool; 3],
+}
+
+impl TlsContext {
+    /// Allocate a new security tag, with no credentials.
+    pub fn new() -> TlsContext {
+        TlsContext {
+            tag: NEXT_TAG.fetch_add(1, Ordering::Relaxed),
+            hostname: None,
+            added: [false; 3],
+        }
+    }
+
+    /// The security tag for this context.
+    pub fn tag(&self) -> raw::sec_tag_t {
+        self.tag
+    }
+
+    /// Add a CA certificate, used to verify the server.
+    pub fn add_ca_cert(&mut self, pem: &'static [u8]) -> Result<(), TlsError> {
+        self.add(0, CA_CERTIFICATE, pem)
+    }
+
+    /// Add a client certificate and its private key, for servers that require the client to
+    /// authenticate.
+    pub fn add_client_cert(
+        &mut self,
+        cert: &'static [u8],
+        key: &'static [u8],
+    ) -> Result<(), TlsError> {
+        self.add(1, CERTIFICATE, cert)?;
+        self.add(2, PRIVATE_KEY, key)
+    }
+
+    /// Set the hostname of the server, used to verify its certificate, and for SNI.
+    pub fn set_hostname(&mut self, hostname: &'static CStr) {
+        self.hostname = Some(hostname);
+    }
+
+    fn add(&mut self, slot: usize, kind: u32, cred: &'static [u8]) -> Result<(), TlsError> {
+        let ret = unsafe {
+            raw::tls_credential_add(self.tag, kind, cred.as_ptr() as *const c_void, cred.len())
+        };
+        if ret < 0 {
+            return Err(TlsError(ZephyrError::from_errno(ret)));
+        }
+        self.added[slot] = true;
+        Ok(())
+    }
+}
+
+impl Default for TlsContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TlsContext {
+    fn drop(&mut self) {
+        for (added, kind) in self.added.iter().zip([CA_CERTIFICATE, CERTIFICATE, PRIVATE_KEY]) {
+            if *added {
+                unsafe {
+                    raw::tls_credential_delete(self.tag, kind);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TlsContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsContext")
+            .field("tag", &self.tag)
+            .field("hostname", &self.hostname)
+            .finish()
+    }
+}
+
+impl TcpStream {
+    /// Connect to the given address with TLS, using the credentials in `ctx`.
+    ///
+    /// The TLS handshake happens as part of the connect, so a certificate that fails to verify
+    /// will be reported here.
+    pub fn connect_tls(addr: SocketAddr, ctx: &TlsContext) -> Result<TlsStream, TlsError> {
+        let addr = RawSockAddr::new(&addr);
+        let stream = TcpStream::socket(addr.family(), raw::IPPROTO_TLS_1_2 as c_int)?;
+        let tags: [raw::sec_tag_t; 1] = [ctx.tag];
+        stream.set_option(raw::SOL_TLS as c_int, raw::TLS_SEC_TAG_LIST as c_int, &tags)?;
+        if let Some(hostname) = ctx.hostname {
+            stream.set_option(
+                raw::SOL_TLS as c_int,
+                raw::TLS_HOSTNAME as c_int,
+                hostname.to_bytes_with_nul(),
+            )?;
+        }
+        stream.connect_raw(&addr)?;
+        Ok(TlsStream { stream })
+    }
+}
+
+/// A TCP connection with TLS.
+///
+/// Reads and writes carry the plain data, the encryption happens within Zephyr.
+pub struct TlsStream {
+    stream: TcpStream,
+}
+
+impl TlsStream {
+    /// Read decrypted data, returning the number of bytes read.  Returns 0 once the peer has
+    /// closed the connection.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, TlsError> {
+        Ok(self.stream.read(buf)?)
+    }
+
+    /// Write data to be encrypted, returning the number of bytes written.
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, TlsError> {
+        Ok(self.stream.write(buf)?)
+    }
+
+    /// Write all of `buf`.
+    pub fn write_all(&mut self, buf: &[u8]) -> Result<(), TlsError> {
+        Ok(self.stream.write_all(buf)?)
+    }
+
+    /// The socket's file descriptor, for use with [`zsock_poll`].
+    ///
+    /// [`zsock_poll`]: super::socket::zsock_poll
+    pub fn as_raw_fd(&self) -> i32 {
+        self.stream.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for TlsStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TlsStream({})", self.stream.as_raw_fd())
+    }
+}
+
+impl embedded_io::ErrorType for TlsStream {
+    type Error = TlsError;
+}
+
+impl embedded_io::Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, TlsError> {
+        TlsStream::read(self, buf)
+    }
+}
+
+impl embedded_io::Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, TlsError> {
+        TlsStream::write(self, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), TlsError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A WebSocket client.
+//!
+//! This wraps Zephyr's websocket library.  A connection starts as an HTTP request on a TCP socket,
+//! which is upgraded to a WebSocket, after which messages can be sent and received in either
+//! direction.
+//!
+//! ```ignore
+//! let mut ws = WebSocket::connect("ws://192.0.2.1:8080/data", &[], Duration::secs_at_least(5))?;
+//! ws.send(b"hello", WsDataType::Text, Duration::secs_at_least(1))?;
+//! let mut buf = [0u8; 256];
+//! let (len, kind) = ws.recv(&mut buf, Duration::secs_at_least(10))?;
+//! ws.close()?;
+//! ```
+//!
+//! Only `ws://` URLs are supported.
+//!
+//! Requires `CONFIG_WEBSOCKET_CLIENT` and `CONFIG_RUST_ALLOC`.
+
+extern crate alloc;
+
+use alloc::ffi::CString;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::ffi::{c_char, c_int};
+use core::fmt;
+use core::mem;
+use core::ptr;
+
+use crate::error::ZephyrError;
+use crate::raw;
+use crate::time::Duration;
+
+use super::socket::{resolve, TcpStream};
+use super::{timeout_ms, NetError, Url};
+
+/// The size of the buffer used for the handshake, and then for receiving.
+const BUF_SIZE: usize = 512;
+
+/// An error from a WebSocket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsError {
+    /// The URL could not be parsed.
+    InvalidUrl,
+    /// The URL scheme isn't supported.
+    Unsupported,
+    /// The peer closed the connection.
+    Closed,
+    /// The connection, or a send or receive, failed.
+    Net(NetError),
+}
+
+impl From<NetError> for WsError {
+    fn from(err: NetError) -> WsError {
+        WsError::Net(err)
+    }
+}
+
+impl fmt::Display for WsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WsError::InvalidUrl => write!(f, "invalid URL"),
+            WsError::Unsupported => write!(f, "unsupported URL scheme"),
+            WsError::Closed => write!(f, "websocket closed"),
+            WsError::Net(err) => write!(f, "websocket: {}", err),
+        }
+    }
+}
+
+/// The type of a WebSocket message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsDataType {
+    /// UTF-8 text.
+    Text,
+    /// Binary data.
+    Binary,
+    /// A ping, which the peer should answer with a pong.
+    Ping,
+    /// The answer to a ping.
+    Pong,
+}
+
+impl WsDataType {
+    fn opcode(self) -> raw::websocket_opcode {
+        match self {
+            WsDataType::Text => raw::websocket_opcode_WEBSOCKET_OPCODE_DATA_TEXT,
+            WsDataType::Binary => raw::websocket_opcode_WEBSOCKET_OPCODE_DATA_BINARY,
+            WsDataType::Ping => raw::websocket_opcode_WEBSOCKET_OPCODE_PING,
+            WsDataType::Pong => raw::websocket_opcode_WEBSOCKET_OPCODE_PONG,
+        }
+    }
+}
+
+/// A connected WebSocket.
+///
+/// The connection is closed when this is dropped.
+pub struct WebSocket {
+    /// The websocket, which owns the underlying TCP socket.  Set to -1 by `close`.
+    sock: c_int,
+    /// The websocket library receives into this, so it must live as long as the connection.
+    _buf: Vec<u8>,
+}
+
+impl WebSocket {
+    /// Connect to the WebSocket server at `url`.
+    ///
+    /// The `extra_headers` are sent with the upgrade request, for example, for authorization.
+    pub fn connect(
+        url: &str,
+        extra_headers: &[(&str, &str)],
+        timeout: Duration,
+    ) -> Result<WebSocket, WsError> {
+        let url = Url::parse(url).ok_or(WsError::InvalidUrl)?;
+        if url.scheme != "ws" {
+            return Err(WsError::Unsupported);
+        }
+        let port = url.port.unwrap_or(80);
+
+        // The Host header includes the port, when it isn't the default.
+        let host = match url.port {
+            Some(port) => format!("{}:{}", url.host, port),
+            None => String::from(url.host),
+        };
+        let c_host = CString::new(host).map_err(|_| WsError::InvalidUrl)?;
+        let c_path = CString::new(url.path).map_err(|_| WsError::InvalidUrl)?;
+        let c_name = CString::new(url.host).map_err(|_| WsError::InvalidUrl)?;
+
+        // Each header is a complete line, and the list is terminated with a null.
+        let lines = extra_headers
+            .iter()
+            .map(|(name, value)| CString::new(format!("{}: {}\r\n", name, value)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| WsError::InvalidUrl)?;
+        let mut header_ptrs: Vec<*const c_char> = lines.iter().map(|l| l.as_ptr()).collect();
+        header_ptrs.push(ptr::null());
+
+        let addr = resolve(&c_name, port)?;
+        let stream = TcpStream::connect(addr)?;
+
+        let mut buf = vec![0u8; BUF_SIZE];
+        // SAFETY: The request is plain data, the fields not set here are meant to be zero.
+        let mut req: raw::websocket_request = unsafe { mem::zeroed() };
+        req.host = c_host.as_ptr();
+        req.url = c_path.as_ptr();
+        req.optional_headers = header_ptrs.as_mut_ptr();
+        req.tmp_buf = buf.as_mut_ptr();
+        req.tmp_buf_len = buf.len();
+
+        // SAFETY: The strings live until the call returns, and the buffer until the websocket is
+        // closed.
+        let ret = unsafe {
+            raw::websocket_connect(
+                stream.as_raw_fd(),
+                &mut req,
+                timeout_ms(timeout),
+                ptr::null_mut(),
+            )
+        };
+        if ret < 0 {
+            return Err(WsError::Net(NetError(ZephyrError::from_errno(ret))));
+        }
+
+        // The websocket closes the TCP socket when it is disconnected.
+        let _ = stream.into_raw_fd();
+        Ok(WebSocket {
+            sock: ret,
+            _buf: buf,
+        })
+    }
+
+    /// Send a message.
+    pub fn send(
+        &mut self,
+        data: &[u8],
+        type_: WsDataType,
+        timeout: Duration,
+    ) -> Result<(), WsError> {
+        // Messages from a client must be masked.
+        let ret = unsafe {
+            raw::websocket_send_msg(
+                self.sock,
+                data.as_ptr(),
+                data.len(),
+                type_.opcode(),
+                true,
+                true,
+                timeout_ms(timeout),
+            )
+        };
+        if ret < 0 {
+            return Err(WsError::Net(NetError(ZephyrError::from_errno(ret))));
+        }
+        Ok(())
+    }
+
+    /// Receive a message into `buf`, returning its length and type.
+    ///
+    /// If the message is larger than `buf`, the rest of it is returned by the following calls,
+    /// with the same type.  When the peer closes the connection, returns [`WsError::Closed`].
+    pub fn recv(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> Result<(usize, WsDataType), WsError> {
+        let mut message_type: u32 = 0;
+        let mut remaining: u64 = 0;
+        let ret = unsafe {
+            raw::websocket_recv_msg(
+                self.sock,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut message_type,
+                &mut remaining,
+                timeout_ms(timeout),
+            )
+        };
+        if ret < 0 {
+            return Err(WsError::Net(NetError(ZephyrError::from_errno(ret))));
+        }
+
+        let kind = if message_type & raw::WEBSOCKET_FLAG_CLOSE != 0 {
+            return Err(WsError::Closed);
+        } else if message_type & raw::WEBSOCKET_FLAG_PING != 0 {
+            WsDataType::Ping
+        } else if message_type & raw::WEBSOCKET_FLAG_PONG != 0 {
+            WsDataType::Pong
+        } else if message_type & raw::WEBSOCKET_FLAG_TEXT != 0 {
+            WsDataType::Text
+        } else {
+            WsDataType::Binary
+        };
+        Ok((ret as usize, kind))
+    }
+
+    /// Close the connection.
+    pub fn close(mut self) -> Result<(), WsError> {
+        let sock = mem::replace(&mut self.sock, -1);
+        let ret = unsafe { raw::websocket_disconnect(sock) };
+        if ret < 0 {
+            return Err(WsError::Net(NetError(ZephyrError::from_errno(ret))));
+        }
+        Ok(())
+    }
+
+    /// The websocket's file descriptor, for use with [`zsock_poll`].
+    ///
+    /// [`zsock_poll`]: super::socket::zsock_poll
+    pub fn as_raw_fd(&self) -> i32 {
+        self.sock
+    }
+}
+
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        // A socket of -1 has already been closed.
+        if self.sock >= 0 {
+            unsafe {
+                raw::websocket_disconnect(self.sock);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for WebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WebSocket({})", self.sock)
+    }
+}
@@ -115,6 +115,10 @@ use crate::sync::atomic::{AtomicUsize, Ordering};
 ///     }
 /// }
 /// ```
+///
+/// For objects whose wrapper is a single `Fixed` pointer, `#[derive(KernelObject)]`, from
+/// `zephyr-macros`, generates this, along with the `StaticThing` alias and its `kobj_define!`
+/// rule.
 pub trait Wrapped {
     /// The wrapped type.  This is what `init_once()` on the StaticKernelObject will return after
     /// initialization.
@@ -128,6 +132,38 @@ pub trait Wrapped {
     fn get_wrapped(&self, args: Self::I) -> Self::T;
 }
 
+/// Wrapped kernel objects whose wrapper can be shared.
+///
+/// These are the objects, such as semaphores, whose wrapper is only a handle to the kernel object,
+/// with every use going through Zephyr.  Making another wrapper for an object that has already
+/// been initialized is like cloning the first one, and allows [`StaticKernelObject::try_init`] to
+/// give back the existing object.
+pub trait SharedWrapped: Wrapped {
+    /// Return another wrapper for this object, which must already be initialized.
+    fn get_existing(&self) -> Self::T;
+}
+
+/// The result of [`StaticKernelObject::try_init`].
+#[derive(Debug)]
+pub enum TryInitResult<T> {
+    /// This call initialized the object.
+    NewlyInit(T),
+    /// The object had already been initialized, and this is another handle to it.
+    AlreadyInit(T),
+    /// Another caller is initializing the object, and hasn't finished.
+    InitInProgress,
+}
+
+impl<T> TryInitResult<T> {
+    /// The object, from whichever call initialized it, if that has finished.
+    pub fn ok(self) -> Option<T> {
+        match self {
+            TryInitResult::NewlyInit(item) | TryInitResult::AlreadyInit(item) => Some(item),
+            TryInitResult::InitInProgress => None,
+        }
+    }
+}
+
 /// A state indicating an uninitialized kernel object.
 ///
 /// This must be zero, as kernel objects will
@@ -204,6 +240,51 @@ where
         self.init.store(KOBJ_INITIALIZED, Ordering::Release);
         Some(result)
     }
+
+    /// Returns true if the object has finished being initialized.
+    ///
+    /// This doesn't try to initialize the object, so can be used to check that an object is ready,
+    /// such as from an interrupt handler, before using it.
+    pub fn is_initialized(&self) -> bool {
+        self.init.load(Ordering::Acquire) == KOBJ_INITIALIZED
+    }
+
+    /// Initialize the object, or get the existing one if it is already initialized.
+    ///
+    /// Unlike [`init_once`](Self::init_once), this can be called from any number of places, with
+    /// each caller getting a handle to the same object.  The `args` are only used by the call that
+    /// does the initialization.  A call that races with the initialization returns
+    /// [`TryInitResult::InitInProgress`], as there is no object to give back yet.
+    pub fn try_init(&self, args: <Self as Wrapped>::I) -> TryInitResult<<Self as Wrapped>::T>
+    where
+        Self: SharedWrapped,
+    {
+        match self.init.compare_exchange(
+            KOBJ_UNINITIALIZED,
+            KOBJ_INITING,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                let result = self.get_wrapped(args);
+                self.init.store(KOBJ_INITIALIZED, Ordering::Release);
+                TryInitResult::NewlyInit(result)
+            }
+            Err(KOBJ_INITIALIZED) => TryInitResult::AlreadyInit(self.get_existing()),
+            Err(_) => TryInitResult::InitInProgress,
+        }
+    }
+}
+
+impl<T> Default for StaticKernelObject<T>
+where
+    StaticKernelObject<T>: Wrapped,
+{
+    fn default() -> Self {
+        // SAFETY: As with `new`, the object starts uninitialized, and the zero value of the Zephyr
+        // data is what Zephyr expects before the object is initialized.
+        unsafe { Self::new() }
+    }
 }
 
 /// Objects that can be fixed or allocated.
@@ -252,6 +333,11 @@ impl<T> Fixed<T> {
 /// ```
 #[macro_export]
 macro_rules! kobj_define {
+    // Memory slabs take the type of their blocks, as well as the count.
+    ($v:vis static $name:ident: StaticMemSlab<$t:ty, $count:tt>; $($rest:tt)*) => {
+        $crate::_kobj_mem_slab!($v, $name, $t, $count);
+        $crate::kobj_define!($($rest)*);
+    };
     ($v:vis static $name:ident: $type:tt; $($rest:tt)*) => {
         $crate::_kobj_rule!($v, $name, $type);
         $crate::kobj_define!($($rest)*);
@@ -274,20 +360,6 @@ macro_rules! kobj_define {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _kobj_rule {
-    // static NAME: StaticSemaphore;
-    ($v:vis, $name:ident, StaticSemaphore) => {
-        #[link_section = concat!("._k_sem.static.", stringify!($name), ".", file!(), line!())]
-        $v static $name: $crate::sys::sync::StaticSemaphore =
-            unsafe { ::core::mem::zeroed() };
-    };
-
-    // static NAMES: [StaticSemaphore; COUNT];
-    ($v:vis, $name:ident, [StaticSemaphore; $size:expr]) => {
-        #[link_section = concat!("._k_sem.static.", stringify!($name), ".", file!(), line!())]
-        $v static $name: [$crate::sys::sync::StaticSemaphore; $size] =
-            unsafe { ::core::mem::zeroed() };
-    };
-
     // static NAME: StaticMutex
     ($v:vis, $name:ident, StaticMutex) => {
         #[link_section = concat!("._k_mutex.static.", stringify!($name), ".", file!(), line!())]
@@ -374,6 +446,68 @@ macro_rules! _kobj_rule {
         $v static $name: [$crate::sys::queue::StaticQueue; $size] =
             unsafe { ::core::mem::zeroed() };
     };
+
+    // Pipes, with the size of their buffer.
+    ($v:vis, $name:ident, StaticPipe<$size:literal>) => {
+        $crate::_kobj_pipe!($v, $name, $size);
+    };
+    ($v:vis, $name:ident, StaticPipe<$size:ident>) => {
+        $crate::_kobj_pipe!($v, $name, $size);
+    };
+    ($v:vis, $name:ident, StaticPipe<{$size:expr}>) => {
+        $crate::_kobj_pipe!($v, $name, $size);
+    };
+
+    // Other kernel objects, such as semaphores, whose rules come from `#[derive(KernelObject)]`.
+    ($v:vis, $name:ident, $type:ident) => {
+        $crate::paste! {
+            $crate::[< _kobj_rule_ $type >]!($v, $name);
+        }
+    };
+
+    ($v:vis, $name:ident, [$type:ident; $size:expr]) => {
+        $crate::paste! {
+            $crate::[< _kobj_rule_ $type >]!($v, $name, $size);
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _kobj_pipe {
+    ($v:vis, $name: ident, $size:expr) => {
+        $crate::paste! {
+            // The buffer is only data, so is left to go in .bss.  We'll use the user name, with
+            // _BUF appended.
+            $v static [< $name _BUF >]: $crate::sys::pipe::PipeBuffer<{$size}> =
+                unsafe { ::core::mem::zeroed() };
+
+            #[link_section = concat!("._k_pipe.static.", stringify!($name), ".", file!(), line!())]
+            $v static $name: $crate::sys::pipe::StaticPipe =
+                $crate::sys::pipe::StaticPipe::new_from(&[< $name _BUF >]);
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _kobj_mem_slab {
+    ($v:vis, $name: ident, $t:ty, $count:tt) => {
+        $crate::paste! {
+            // The blocks are only written by the values put in them, so the buffer goes into the
+            // no-init linker section.  We'll use the user name, with _BUF appended.
+            #[link_section = concat!(".noinit.", stringify!($name), ".", file!(), line!())]
+            $v static [< $name _BUF >]: $crate::sys::mem_slab::SlabBuffer<
+                { $crate::sys::mem_slab::block_align::<$t>() },
+                { $crate::sys::mem_slab::block_size::<$t>() * $count },
+            > = unsafe { ::core::mem::zeroed() };
+
+            // Unlike most kernel objects, this is not put in the Zephyr section for its type, as
+            // Zephyr would try to initialize it at boot.  It is left in .bss.
+            $v static $name: $crate::sys::mem_slab::StaticMemSlab<$t> =
+                $crate::sys::mem_slab::StaticMemSlab::new_from(&[< $name _BUF >], $count);
+        }
+    };
 }
 
 #[doc(hidden)]
@@ -0,0 +1,82 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks on the performance of critical code.
+//!
+//! [`assert_max_size!`] puts a limit on the size of the compiled code of a function, such as an
+//! interrupt handler that must stay small:
+//!
+//! ```ignore
+//! fn uart_isr(_arg: *const c_void) {
+//!     // ...
+//! }
+//! zephyr::perf::assert_max_size!(uart_isr, 256);
+//! ```
+//!
+//! The size of a function is not known until it has been compiled, and Rust has no way of checking
+//! it at compile time.  So each limit is recorded in the image, and checked after the link, from
+//! the symbol table, by `etc/check-fn-size.py`.  A function over its limit fails the build, with
+//! an error naming the function, its size, and the limit.  The records take two words of flash
+//! for each limit.
+//!
+//! As the check is of the function's own symbol, code inlined into it counts towards its size,
+//! but calls out to other functions do not.  A function that is only ever inlined has no symbol,
+//! and fails the check, so these functions should be marked `#[inline(never)]`.
+//!
+//! The check is made when `CONFIG_RUST_FN_SIZE_CHECK` is enabled.  Otherwise, the macro just
+//! checks that its arguments are a function and a size, and records nothing.
+//!
+//! At runtime, a [`Profiler`] measures how long regions of code take, in cycles of the hardware
+//! cycle counter, keeping the minimum, maximum, and mean of each region.  The profiler needs
+//! `CONFIG_TIMER_HAS_64BIT_CYCLE_COUNTER`.
+
+pub use crate::assert_max_size;
+
+#[cfg(CONFIG_TIMER_HAS_64BIT_CYCLE_COUNTER)]
+mod profiler;
+
+#[cfg(CONFIG_TIMER_HAS_64BIT_CYCLE_COUNTER)]
+pub use profiler::{Profiler, ProfilerStats};
+
+/// A size limit on a function, made by [`assert_max_size!`].
+#[doc(hidden)]
+#[repr(C)]
+pub struct SizeLimit {
+    pub func: *const (),
+    pub max: usize,
+}
+
+// SAFETY: The record is only read by the build, never at runtime.
+unsafe impl Sync for SizeLimit {}
+
+/// Fail the build if the compiled code of `$func` is larger than `$max` bytes.
+///
+/// See the [`perf`](crate::perf) module for how this is checked.
+#[cfg(CONFIG_RUST_FN_SIZE_CHECK)]
+#[macro_export]
+macro_rules! assert_max_size {
+    ($func:path, $max:expr) => {
+        const _: () = {
+            #[used]
+            #[link_section = concat!("._rust_size_check.static.", file!(), line!())]
+            static LIMIT: $crate::perf::SizeLimit = $crate::perf::SizeLimit {
+                func: $func as *const (),
+                max: $max,
+            };
+        };
+    };
+}
+
+/// Fail the build if the compiled code of `$func` is larger than `$max` bytes.
+///
+/// `CONFIG_RUST_FN_SIZE_CHECK` is not enabled, so this does not check the size.
+#[cfg(not(CONFIG_RUST_FN_SIZE_CHECK))]
+#[macro_export]
+macro_rules! assert_max_size {
+    ($func:path, $max:expr) => {
+        const _: () = {
+            let _ = $func;
+            let _: usize = $max;
+        };
+    };
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Timing of code regions with the cycle counter.
+
+use core::fmt;
+
+use crate::printkln;
+use crate::raw::k_cycle_get_64;
+
+/// The statistics of one slot of a [`Profiler`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProfilerStats {
+    /// The number of times the region was measured.
+    pub count: u32,
+    /// The shortest time, in cycles.
+    pub min_cycles: u64,
+    /// The longest time, in cycles.
+    pub max_cycles: u64,
+    /// The mean time, in cycles.
+    pub mean_cycles: f64,
+    /// The variance of the times, in cycles squared.
+    pub variance_cycles: f64,
+}
+
+/// One region being measured.
+#[derive(Clone, Copy)]
+struct Slot {
+    name: Option<&'static str>,
+    /// The cycle count at [`Profiler::begin`], while the region is running.
+    start: Option<u64>,
+    count: u32,
+    min: u64,
+    max: u64,
+    mean: f64,
+    /// The sum of the squares of the differences from the mean, for Welford's method.
+    m2: f64,
+}
+
+const EMPTY_SLOT: Slot = Slot {
+    name: None,
+    start: None,
+    count: 0,
+    min: 0,
+    max: 0,
+    mean: 0.0,
+    m2: 0.0,
+};
+
+/// Times code regions, in `N` slots.
+///
+/// Each measurement is a call to [`begin`](Self::begin) and one to [`end`](Self::end) with the
+/// same slot.  The statistics are updated as each measurement is made, so the cost of a
+/// measurement doesn't depend on how many there have been.
+///
+/// ```ignore
+/// let mut prof = Profiler::<2>::new();
+/// prof.set_name(0, "read");
+/// prof.begin(0);
+/// sensor.read()?;
+/// prof.end(0);
+/// prof.print_all();
+/// ```
+pub struct Profiler<const N: usize> {
+    slots: [Slot; N],
+}
+
+impl<const N: usize> Profiler<N> {
+    /// A profiler with no measurements.
+    pub const fn new() -> Profiler<N> {
+        Profiler {
+            slots: [EMPTY_SLOT; N],
+        }
+    }
+
+    /// Give a slot a name, to be shown by [`print_all`](Self::print_all).
+    pub fn set_name(&mut self, slot: usize, name: &'static str) {
+        self.slots[slot].name = Some(name);
+    }
+
+    /// Start timing the region for `slot`.
+    ///
+    /// Panics if `slot` is not less than `N`.
+    #[inline]
+    pub fn begin(&mut self, slot: usize) {
+        self.slots[slot].start = Some(unsafe { k_cycle_get_64() });
+    }
+
+    /// Finish timing the region for `slot`, adding its time to the stats.
+    ///
+    /// Without a matching [`begin`](Self::begin), this does nothing.
+    #[inline]
+    pub fn end(&mut self, slot: usize) {
+        let now = unsafe { k_cycle_get_64() };
+        let slot = &mut self.slots[slot];
+        if let Some(start) = slot.start.take() {
+            slot.record(now.wrapping_sub(start));
+        }
+    }
+
+    /// The statistics of `slot`, which are all zero if it hasn't been measured.
+    pub fn stats(&self, slot: usize) -> ProfilerStats {
+        let slot = &self.slots[slot];
+        ProfilerStats {
+            count: slot.count,
+            min_cycles: slot.min,
+            max_cycles: slot.max,
+            mean_cycles: slot.mean,
+            variance_cycles: if slot.count > 1 {
+                slot.m2 / (slot.count - 1) as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Clear the measurements of every slot, keeping the names.
+    pub fn reset(&mut self) {
+        for slot in &mut self.slots {
+            *slot = Slot {
+                name: slot.name,
+                ..EMPTY_SLOT
+            };
+        }
+    }
+
+    /// Print a table of the statistics of each slot that has been measured.
+    pub fn print_all(&self) {
+        printkln!("{:>16} {:>8} {:>10} {:>10} {:>12}", "slot", "count", "min", "max", "mean");
+        for (index, slot) in self.slots.iter().enumerate() {
+            if slot.count == 0 {
+                continue;
+            }
+            let stats = self.stats(index);
+            match slot.name {
+                Some(name) => printkln!(
+                    "{:>16} {:>8} {:>10} {:>10} {:>12.1}",
+                    name,
+                    stats.count,
+                    stats.min_cycles,
+                    stats.max_cycles,
+                    stats.mean_cycles
+                ),
+                None => printkln!(
+                    "{:>16} {:>8} {:>10} {:>10} {:>12.1}",
+                    index,
+                    stats.count,
+                    stats.min_cycles,
+                    stats.max_cycles,
+                    stats.mean_cycles
+                ),
+            }
+        }
+    }
+}
+
+impl Slot {
+    fn record(&mut self, cycles: u64) {
+        if self.count == 0 {
+            self.min = cycles;
+            self.max = cycles;
+        } else {
+            self.min = self.min.min(cycles);
+            self.max = self.max.max(cycles);
+        }
+        self.count = self.count.saturating_add(1);
+        let value = cycles as f64;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+}
+
+impl<const N: usize> Default for Profiler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for Profiler<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Profiler<{}>", N)
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! SEGGER RTT channels, for streaming data to and from a debug probe.
+//!
+//! RTT moves data through ring buffers in RAM, which the probe reads and writes in the
+//! background, so it is much faster than a UART, and doesn't need one.  Channel 0 is set up by
+//! Zephyr.  Other channels, up to `CONFIG_SEGGER_RTT_MAX_NUM_UP_BUFFERS`, can be given buffers of
+//! their own with [`RttChannel::new`], for binary data, such as traces or sensor samples.
+//!
+//! [`RttWriter`] writes formatted text to channel 0:
+//!
+//! ```ignore
+//! use core::fmt::Write;
+//! writeln!(RttWriter, "adc: {}", sample).ok();
+//! ```
+//!
+//! Writes never block.  Data that doesn't fit, because the probe isn't reading fast enough, or at
+//! all, is dropped.
+//!
+//! Requires `CONFIG_USE_SEGGER_RTT`.
+
+use core::ffi::c_void;
+use core::fmt;
+use core::ptr;
+
+use crate::raw;
+
+/// An RTT channel, with an up buffer (to the host) of its own.
+pub struct RttChannel {
+    channel: u32,
+}
+
+impl RttChannel {
+    /// Configure the up buffer of `channel` to use `buf`.
+    ///
+    /// Returns `None` if the channel is beyond those configured.  The channel's name is left as it
+    /// was.  Configuring channel 0 replaces the buffer Zephyr set up for it.
+    pub fn new(channel: u8, buf: &'static mut [u8]) -> Option<RttChannel> {
+        let ret = unsafe {
+            raw::SEGGER_RTT_ConfigUpBuffer(
+                channel as u32,
+                ptr::null(),
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len() as u32,
+                raw::SEGGER_RTT_MODE_NO_BLOCK_SKIP,
+            )
+        };
+        if ret < 0 {
+            return None;
+        }
+        Some(RttChannel {
+            channel: channel as u32,
+        })
+    }
+
+    /// Configure the down buffer (from the host) of the channel to use `buf`, so that it can be
+    /// read from.
+    ///
+    /// Returns false if the channel is beyond the configured down buffers.
+    pub fn set_down_buffer(&mut self, buf: &'static mut [u8]) -> bool {
+        let ret = unsafe {
+            raw::SEGGER_RTT_ConfigDownBuffer(
+                self.channel,
+                ptr::null(),
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len() as u32,
+                raw::SEGGER_RTT_MODE_NO_BLOCK_SKIP,
+            )
+        };
+        ret >= 0
+    }
+
+    /// Write `data` to the host.  Returns the number of bytes written, which is either all of
+    /// them, or none, if there isn't space.
+    pub fn write(&self, data: &[u8]) -> usize {
+        unsafe {
+            raw::SEGGER_RTT_Write(self.channel, data.as_ptr() as *const c_void, data.len() as u32)
+                as usize
+        }
+    }
+
+    /// Read what the host has sent into `buf`.  Returns the number of bytes read, which is zero
+    /// if there is nothing waiting.
+    pub fn read(&self, buf: &mut [u8]) -> usize {
+        unsafe {
+            raw::SEGGER_RTT_Read(self.channel, buf.as_mut_ptr() as *mut c_void, buf.len() as u32)
+                as usize
+        }
+    }
+}
+
+impl fmt::Debug for RttChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RttChannel({})", self.channel)
+    }
+}
+
+/// Formatted output to RTT channel 0, which is what the probe shows as the terminal.
+///
+/// Text that doesn't fit in the buffer is dropped, rather than reported as an error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RttWriter;
+
+impl fmt::Write for RttWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        unsafe {
+            raw::SEGGER_RTT_Write(0, s.as_ptr() as *const c_void, s.len() as u32);
+        }
+        Ok(())
+    }
+}
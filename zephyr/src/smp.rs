@@ -0,0 +1,122 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! An SMP server, for mcumgr.
+//!
+//! The Simple Management Protocol (SMP) is used by host tools such as `mcumgr` to manage a device,
+//! most importantly, to upload new firmware images through the `img_mgmt` group.  Zephyr provides
+//! transports for UART, Bluetooth and UDP, but this allows the transport to be implemented in Rust,
+//! behind the [`SmpTransport`] trait.
+//!
+//! The management groups themselves (`img_mgmt`, `os_mgmt`, and so on) are registered by Zephyr
+//! at boot when they are enabled in Kconfig, so there is nothing to register here.  An application
+//! only needs to enable them, and then run the server:
+//!
+//! ```ignore
+//! SmpServer::run(MyTransport::new());
+//! ```
+//!
+//! Requires `CONFIG_MCUMGR` and `CONFIG_RUST_ALLOC`.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec;
+
+use core::mem;
+
+use crate::error::{ZResult, ZephyrError};
+use crate::raw;
+use crate::sync::OnceLock;
+
+/// A transport that carries SMP packets.
+///
+/// `recv` is called from the server thread, and `send` is called from the mcumgr work queue, so
+/// the two may be called at the same time.
+pub trait SmpTransport: Send + Sync {
+    /// Send a complete SMP packet.
+    fn send(&self, packet: &[u8]) -> ZResult<()>;
+
+    /// Wait for, and receive, a complete SMP packet into `buf`, returning its length.
+    fn recv(&self, buf: &mut [u8]) -> ZResult<usize>;
+
+    /// The largest packet the transport can send.
+    fn mtu(&self) -> u16 {
+        256
+    }
+}
+
+/// The transport the server is running on.  The Zephyr callbacks have no context argument, so this
+/// must be global.
+static TRANSPORT: OnceLock<&'static dyn SmpTransport> = OnceLock::new();
+
+/// The SMP server.
+pub struct SmpServer;
+
+impl SmpServer {
+    /// Run the SMP server over the given transport.
+    ///
+    /// This does not return, so should be run on its own thread.  Only one server can be run;
+    /// calling this a second time will panic.
+    pub fn run(transport: impl SmpTransport + 'static) -> ! {
+        let transport: &'static dyn SmpTransport = Box::leak(Box::new(transport));
+        if TRANSPORT.set(transport).is_err() {
+            panic!("SMP server already running");
+        }
+
+        // SAFETY: The transport struct is leaked, as Zephyr keeps a reference to it.
+        let smpt: &'static mut raw::smp_transport = Box::leak(Box::new(unsafe { mem::zeroed() }));
+        smpt.functions.output = Some(smp_output);
+        smpt.functions.get_mtu = Some(smp_get_mtu);
+        let ret = unsafe { raw::smp_transport_init(smpt) };
+        if ret < 0 {
+            panic!("smp_transport_init: {}", ZephyrError::from_errno(ret));
+        }
+
+        let mut buf = vec![0u8; crate::kconfig::CONFIG_MCUMGR_TRANSPORT_NETBUF_SIZE as usize];
+        loop {
+            let len = match transport.recv(&mut buf) {
+                Ok(len) => len,
+                Err(err) => {
+                    log::warn!("SMP receive error: {}", err);
+                    continue;
+                }
+            };
+            unsafe {
+                let nb = raw::smp_packet_alloc();
+                if nb.is_null() {
+                    log::warn!("SMP: no packet buffers, dropping request");
+                    continue;
+                }
+                let simple = &mut (*nb).__bindgen_anon_1.b;
+                if len > simple.size as usize {
+                    log::warn!("SMP: request too large, dropping");
+                    raw::smp_packet_free(nb);
+                    continue;
+                }
+                raw::net_buf_simple_add_mem(simple, buf.as_ptr() as *const _, len);
+                // The request is processed on the mcumgr work queue, which frees the buffer.
+                raw::smp_rx_req(smpt, nb);
+            }
+        }
+    }
+}
+
+/// Called by mcumgr to send a response.  Takes ownership of the buffer.
+unsafe extern "C" fn smp_output(nb: *mut raw::net_buf) -> i32 {
+    let simple = &(*nb).__bindgen_anon_1.b;
+    let packet = core::slice::from_raw_parts(simple.data, simple.len as usize);
+    let result = match TRANSPORT.get() {
+        Some(transport) => transport.send(packet),
+        None => Err(ZephyrError::NoDevice),
+    };
+    raw::smp_packet_free(nb);
+    match result {
+        Ok(()) => 0,
+        Err(err) => -(err.errno() as i32),
+    }
+}
+
+unsafe extern "C" fn smp_get_mtu(_nb: *const raw::net_buf) -> u16 {
+    TRANSPORT.get().map(|t| t.mtu()).unwrap_or(0)
+}
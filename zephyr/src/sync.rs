@@ -8,7 +8,14 @@
 // Channels are currently only available with allocation.  Bounded channels later might be
 // available.
 #[cfg(CONFIG_RUST_ALLOC)]
+pub mod broadcast;
+#[cfg(CONFIG_RUST_ALLOC)]
 pub mod channel;
+pub mod msgq;
+#[cfg(CONFIG_RUST_ALLOC)]
+pub mod oneshot;
+#[cfg(CONFIG_RUST_ALLOC)]
+pub mod watch;
 
 pub mod atomic {
     //! Re-export portable atomic.
@@ -30,10 +37,32 @@ pub use portable_atomic_util::Arc;
 #[cfg(CONFIG_RUST_ALLOC)]
 pub use portable_atomic_util::Weak;
 
+#[cfg(CONFIG_RUST_ALLOC)]
+mod barrier;
+
+#[cfg(CONFIG_RUST_ALLOC)]
+pub use barrier::{Barrier, BarrierWaitResult};
+
 mod mutex;
 
-pub use mutex::{Condvar, LockResult, Mutex, MutexGuard, TryLockError, TryLockResult};
+pub use mutex::{
+    Condvar, LockResult, Mutex, MutexGuard, TryLockError, TryLockResult, WaitTimeoutResult,
+};
+
+mod notify;
+
+pub use notify::Notify;
+
+mod once;
+
+pub use once::{LazyLock, OnceLock};
 
 mod spinmutex;
 
 pub use spinmutex::{SpinMutex, SpinMutexGuard};
+
+#[cfg(CONFIG_RUST_ALLOC)]
+mod zarc;
+
+#[cfg(CONFIG_RUST_ALLOC)]
+pub use zarc::{ZephyrArc, ZephyrWeak};
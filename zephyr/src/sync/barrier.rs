@@ -0,0 +1,97 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Barriers.
+//!
+//! Modeled after [`std::sync::Barrier`](https://doc.rust-lang.org/stable/std/sync/struct.Barrier.html).
+//!
+//! A simpler barrier can be built from just a counter and a semaphore that the last thread gives
+//! to release the others.  But such a barrier can't safely be reused, as a thread that passes the
+//! barrier and immediately waits again can take a count meant for the previous round.  Instead,
+//! this uses a [`Mutex`] and [`Condvar`] with a generation count, the same as std, so that the
+//! barrier can be used repeatedly.
+
+use core::fmt;
+
+use crate::sync::{Condvar, Mutex};
+
+/// A barrier enables multiple threads to synchronize the beginning of some computation.
+pub struct Barrier {
+    lock: Mutex<BarrierState>,
+    cvar: Condvar,
+    num_threads: usize,
+}
+
+struct BarrierState {
+    /// How many threads are currently waiting.
+    count: usize,
+    /// Incremented each time the barrier releases its waiters.
+    generation: usize,
+}
+
+/// Returned by [`Barrier::wait`] when all threads in the barrier have rendezvoused.
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Returns `true` if this thread is the "leader thread" for the call to [`Barrier::wait`].
+    ///
+    /// Only one thread will have `true` returned from their result, all other threads will have
+    /// `false` returned.  The leader is the last thread to arrive.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl fmt::Debug for BarrierWaitResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BarrierWaitResult")
+            .field("is_leader", &self.is_leader())
+            .finish()
+    }
+}
+
+impl Barrier {
+    /// Creates a new barrier that can block a given number of threads.
+    ///
+    /// A barrier will block `n`-1 threads which call [`wait`] and then wake up all threads at once
+    /// when the `n`th thread calls [`wait`].
+    ///
+    /// [`wait`]: Self::wait
+    pub fn new(n: usize) -> Barrier {
+        Barrier {
+            lock: Mutex::new(BarrierState {
+                count: 0,
+                generation: 0,
+            }),
+            cvar: Condvar::new(),
+            num_threads: n,
+        }
+    }
+
+    /// Blocks the current thread until all threads have rendezvoused here.
+    ///
+    /// Barriers are re-usable after all threads have rendezvoused once, and can be used
+    /// continuously.
+    pub fn wait(&self) -> BarrierWaitResult {
+        let mut lock = self.lock.lock().unwrap();
+        let local_gen = lock.generation;
+        lock.count += 1;
+        if lock.count < self.num_threads {
+            while local_gen == lock.generation {
+                lock = self.cvar.wait(lock).unwrap();
+            }
+            BarrierWaitResult(false)
+        } else {
+            lock.count = 0;
+            lock.generation = lock.generation.wrapping_add(1);
+            self.cvar.notify_all();
+            BarrierWaitResult(true)
+        }
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Barrier {{ num_threads: {} }}", self.num_threads)
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Broadcast channels.
+//!
+//! A broadcast channel delivers every message to every receiver, unlike [`channel`], where each
+//! message goes to just one of them.  This is modeled after
+//! [`tokio::sync::broadcast`](https://docs.rs/tokio/latest/tokio/sync/broadcast/index.html).
+//!
+//! The messages are kept in a ring of `CAP` slots, with each receiver keeping its own place in it.
+//! Sending never blocks: once the ring is full, each message replaces the oldest one, and a
+//! receiver that has fallen more than `CAP` messages behind misses those that were replaced.  It
+//! finds out with [`RecvError::Lagged`], and then carries on from the oldest message still held.
+//!
+//! ```ignore
+//! let (tx, mut rx1) = broadcast::channel::<Event, 8>();
+//! let mut rx2 = tx.subscribe();
+//! tx.send(Event::ButtonPressed);
+//! assert_eq!(rx1.recv(Forever), Ok(Event::ButtonPressed));
+//! assert_eq!(rx2.recv(Forever), Ok(Event::ButtonPressed));
+//! ```
+//!
+//! [`channel`]: crate::sync::channel
+
+use core::fmt;
+
+use crate::sync::{Arc, Condvar, Mutex};
+use crate::time::Timeout;
+
+/// Create a new broadcast channel, holding up to `CAP` messages.
+///
+/// `CAP` must not be zero.
+pub fn channel<T: Clone, const CAP: usize>() -> (BroadcastSender<T, CAP>, BroadcastReceiver<T, CAP>)
+{
+    assert!(CAP > 0, "broadcast channel needs room for a message");
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            slots: core::array::from_fn(|_| None),
+            head: 0,
+            receivers: 1,
+            closed: false,
+        }),
+        sent: Condvar::new(),
+    });
+    (
+        BroadcastSender {
+            shared: shared.clone(),
+        },
+        BroadcastReceiver { shared, next: 0 },
+    )
+}
+
+/// The state shared between the sender and all of the receivers.
+struct Shared<T, const CAP: usize> {
+    state: Mutex<State<T, CAP>>,
+    sent: Condvar,
+}
+
+struct State<T, const CAP: usize> {
+    /// Message `n` is held in `slots[n % CAP]`, until it is replaced by message `n + CAP`.
+    slots: [Option<T>; CAP],
+    /// The number of messages sent so far.
+    head: u64,
+    /// The number of receivers.
+    receivers: usize,
+    /// Set when the sender is dropped.
+    closed: bool,
+}
+
+/// An error receiving from a broadcast channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// No message was sent before the timeout.
+    Timeout,
+    /// The receiver fell behind, and this many messages were lost.  The next receive gets the
+    /// oldest message still held.
+    Lagged(u64),
+    /// The sender has been dropped, and every message it sent has been received.
+    Closed,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Timeout => write!(f, "broadcast: timeout"),
+            RecvError::Lagged(count) => write!(f, "broadcast: lagged by {}", count),
+            RecvError::Closed => write!(f, "broadcast: closed"),
+        }
+    }
+}
+
+/// The sending side of a broadcast channel.
+pub struct BroadcastSender<T, const CAP: usize> {
+    shared: Arc<Shared<T, CAP>>,
+}
+
+impl<T: Clone, const CAP: usize> BroadcastSender<T, CAP> {
+    /// Send `value` to every receiver.
+    ///
+    /// If the ring is full, this replaces the oldest message, which receivers that haven't got to
+    /// it yet will miss.
+    pub fn send(&self, value: T) {
+        let mut state = self.shared.state.lock().unwrap();
+        let slot = (state.head % CAP as u64) as usize;
+        state.slots[slot] = Some(value);
+        state.head += 1;
+        drop(state);
+        self.shared.sent.notify_all();
+    }
+
+    /// Get a new receiver for this channel.
+    ///
+    /// The new receiver only gets the messages sent after it was made.
+    pub fn subscribe(&self) -> BroadcastReceiver<T, CAP> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.receivers += 1;
+        BroadcastReceiver {
+            shared: self.shared.clone(),
+            next: state.head,
+        }
+    }
+
+    /// The number of receivers.
+    ///
+    /// As sending never blocks, this can be used to hold back messages when nobody is listening.
+    pub fn subscriber_count(&self) -> usize {
+        self.shared.state.lock().unwrap().receivers
+    }
+}
+
+impl<T, const CAP: usize> Drop for BroadcastSender<T, CAP> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.sent.notify_all();
+    }
+}
+
+impl<T, const CAP: usize> fmt::Debug for BroadcastSender<T, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BroadcastSender")
+    }
+}
+
+/// The receiving side of a broadcast channel.
+///
+/// Receivers can be cloned, and the clone carries on from the same place.
+pub struct BroadcastReceiver<T, const CAP: usize> {
+    shared: Arc<Shared<T, CAP>>,
+    /// The number of the next message to receive.
+    next: u64,
+}
+
+impl<T: Clone, const CAP: usize> BroadcastReceiver<T, CAP> {
+    /// Receive the next message this receiver hasn't seen, waiting up to `timeout` for one to be
+    /// sent.
+    pub fn recv(&mut self, timeout: impl Into<Timeout>) -> Result<T, RecvError> {
+        let timeout: Timeout = timeout.into();
+        let mut state = self.shared.state.lock().unwrap();
+        while state.head == self.next {
+            if state.closed {
+                return Err(RecvError::Closed);
+            }
+            let (next, result) = self.shared.sent.wait_timeout(state, timeout).unwrap();
+            state = next;
+            if result.timed_out() && state.head == self.next {
+                return Err(RecvError::Timeout);
+            }
+        }
+
+        let oldest = state.head.saturating_sub(CAP as u64);
+        if self.next < oldest {
+            let lost = oldest - self.next;
+            self.next = oldest;
+            return Err(RecvError::Lagged(lost));
+        }
+        let slot = (self.next % CAP as u64) as usize;
+        self.next += 1;
+        Ok(state.slots[slot].clone().unwrap())
+    }
+
+    /// The number of messages sent that this receiver hasn't received yet, up to `CAP`.
+    pub fn len(&self) -> usize {
+        let head = self.shared.state.lock().unwrap().head;
+        (head - self.next).min(CAP as u64) as usize
+    }
+
+    /// Returns true if there are no messages waiting for this receiver.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T, const CAP: usize> Clone for BroadcastReceiver<T, CAP> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().receivers += 1;
+        BroadcastReceiver {
+            shared: self.shared.clone(),
+            next: self.next,
+        }
+    }
+}
+
+impl<T, const CAP: usize> Drop for BroadcastReceiver<T, CAP> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().receivers -= 1;
+    }
+}
+
+impl<T, const CAP: usize> fmt::Debug for BroadcastReceiver<T, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BroadcastReceiver {{ next: {} }}", self.next)
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Channels built on Zephyr's `k_msgq`.
+//!
+//! Unlike the channels in [`channel`], which pass pointers to individually allocated messages
+//! through a `k_queue`, these channels copy the messages themselves into a fixed-size ring buffer
+//! managed by a `k_msgq`.  The capacity of the channel is fixed when it is created, and there is no
+//! allocation per message.
+//!
+//! A channel can either be allocated on the heap, with [`channel`](fn@channel), or declared
+//! statically with [`static_channel!`].  Static channels do not require allocation at all.
+//!
+//! As with [`channel`], dropping all of the senders (or receivers) does not wake up the other side
+//! of the channel.
+//!
+//! Note that these `k_msgq` objects are not registered with the kernel object system, and as
+//! such, these channels can only be used from kernel threads.
+//!
+//! [`channel`]: crate::sync::channel
+//! [`static_channel!`]: crate::static_channel
+
+#[cfg(CONFIG_RUST_ALLOC)]
+extern crate alloc;
+
+#[cfg(CONFIG_RUST_ALLOC)]
+use alloc::boxed::Box;
+
+use core::cell::UnsafeCell;
+use core::ffi::{c_char, c_void};
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+
+use crate::object::{KOBJ_INITING, KOBJ_UNINITIALIZED};
+use crate::raw::{k_msgq, k_msgq_get, k_msgq_init, k_msgq_put};
+use crate::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(CONFIG_RUST_ALLOC)]
+use crate::sync::Arc;
+#[cfg(CONFIG_RUST_ALLOC)]
+use crate::sys::K_NO_WAIT;
+use crate::time::Timeout;
+
+/// Create a channel holding up to `CAP` messages, with the queue allocated from the heap.
+#[cfg(CONFIG_RUST_ALLOC)]
+pub fn channel<T: Send, const CAP: usize>() -> (Sender<T>, Receiver<T>) {
+    let owned = Arc::new(Owned::<T> {
+        // SAFETY: Zeroed memory is a valid uninitialized `k_msgq`, which is initialized below.
+        msgq: unsafe { mem::zeroed() },
+        buf: (0..CAP).map(|_| MaybeUninit::uninit()).collect(),
+    });
+    let msgq = owned.msgq.get();
+    unsafe {
+        // SAFETY: The Arc keeps both the k_msgq and the buffer at a fixed address for as long as
+        // either the sender or the receiver exist.
+        k_msgq_init(
+            msgq,
+            owned.buf.as_ptr() as *mut c_char,
+            mem::size_of::<T>(),
+            CAP as u32,
+        );
+    }
+    (
+        Sender {
+            msgq,
+            _owner: Some(owned.clone()),
+            _phantom: PhantomData,
+        },
+        Receiver {
+            msgq,
+            _owner: Some(owned),
+            _phantom: PhantomData,
+        },
+    )
+}
+
+/// The heap allocated portion of a channel.
+#[cfg(CONFIG_RUST_ALLOC)]
+struct Owned<T> {
+    msgq: UnsafeCell<k_msgq>,
+    buf: Box<[MaybeUninit<T>]>,
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl<T> Drop for Owned<T> {
+    fn drop(&mut self) {
+        // Drop any messages that are still in the queue.
+        let mut msg = MaybeUninit::<T>::uninit();
+        while unsafe { k_msgq_get(self.msgq.get(), msg.as_mut_ptr() as *mut c_void, K_NO_WAIT) }
+            == 0
+        {
+            // SAFETY: A successful get copied a complete message into `msg`.
+            unsafe { msg.assume_init_drop() };
+        }
+    }
+}
+
+/// A statically allocated channel holding up to `CAP` messages.
+///
+/// Generally, this should be declared using [`static_channel!`], which ensures that
+/// [`init_once`] is called only once.
+///
+/// [`static_channel!`]: crate::static_channel
+/// [`init_once`]: Self::init_once
+pub struct StaticChannel<T, const CAP: usize> {
+    msgq: UnsafeCell<k_msgq>,
+    buf: UnsafeCell<[MaybeUninit<T>; CAP]>,
+    init: AtomicUsize,
+}
+
+// SAFETY: All access to the buffer is through the `k_msgq`, which handles synchronization.
+unsafe impl<T: Send, const CAP: usize> Sync for StaticChannel<T, CAP> {}
+
+impl<T: Send, const CAP: usize> StaticChannel<T, CAP> {
+    /// Construct a new static channel.  [`init_once`] must be called before use.
+    ///
+    /// [`init_once`]: Self::init_once
+    pub const fn new() -> Self {
+        StaticChannel {
+            // SAFETY: Zeroed memory is a valid uninitialized `k_msgq`.
+            msgq: unsafe { mem::zeroed() },
+            // SAFETY: An array of `MaybeUninit` does not need initialization.
+            buf: UnsafeCell::new(unsafe { MaybeUninit::uninit().assume_init() }),
+            init: AtomicUsize::new(KOBJ_UNINITIALIZED),
+        }
+    }
+
+    /// Initialize the channel, returning the sender and receiver.
+    ///
+    /// Will return `None` if called more than once.
+    pub fn init_once(&'static self) -> Option<(Sender<T>, Receiver<T>)> {
+        if self
+            .init
+            .compare_exchange(
+                KOBJ_UNINITIALIZED,
+                KOBJ_INITING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_err()
+        {
+            return None;
+        }
+        let msgq = self.msgq.get();
+        unsafe {
+            // SAFETY: Both the k_msgq and the buffer are static.
+            k_msgq_init(
+                msgq,
+                self.buf.get() as *mut c_char,
+                mem::size_of::<T>(),
+                CAP as u32,
+            );
+        }
+        // There is no further initialization state.  Leaving this in INITING is sufficient to
+        // prevent another call.
+        Some((
+            Sender {
+                msgq,
+                #[cfg(CONFIG_RUST_ALLOC)]
+                _owner: None,
+                _phantom: PhantomData,
+            },
+            Receiver {
+                msgq,
+                #[cfg(CONFIG_RUST_ALLOC)]
+                _owner: None,
+                _phantom: PhantomData,
+            },
+        ))
+    }
+}
+
+/// Declare a static channel, returning the `(Sender, Receiver)` pair.
+///
+/// ```ignore
+/// let (tx, rx) = zephyr::static_channel!(Message, 8);
+/// ```
+///
+/// Each invocation of this macro declares a single static channel, and as such will panic if the
+/// same invocation is evaluated more than once.
+#[macro_export]
+macro_rules! static_channel {
+    ($type:ty, $cap:expr) => {{
+        static CHANNEL: $crate::sync::msgq::StaticChannel<$type, { $cap }> =
+            $crate::sync::msgq::StaticChannel::new();
+        CHANNEL
+            .init_once()
+            .expect("static_channel! initialized more than once")
+    }};
+}
+
+/// The sending side of a msgq channel.
+///
+/// Senders can be cloned, and shared between threads.  Sending with [`NoWait`] is safe from IRQ
+/// context.
+///
+/// [`NoWait`]: crate::time::NoWait
+pub struct Sender<T> {
+    msgq: *mut k_msgq,
+    #[cfg(CONFIG_RUST_ALLOC)]
+    _owner: Option<Arc<Owned<T>>>,
+    _phantom: PhantomData<T>,
+}
+
+// SAFETY: The `k_msgq` is safe to use from multiple threads.  Messages are moved between threads,
+// so need to be `Send`.
+unsafe impl<T: Send> Send for Sender<T> {}
+unsafe impl<T: Send> Sync for Sender<T> {}
+
+impl<T> Sender<T> {
+    /// Send a message, waiting up to `timeout` for there to be room in the queue.
+    ///
+    /// If the message could not be sent in time, it is returned in the error.
+    pub fn send(&self, msg: T, timeout: impl Into<Timeout>) -> Result<(), SendError<T>> {
+        let timeout: Timeout = timeout.into();
+        let msg = MaybeUninit::new(msg);
+        // SAFETY: The msgq copies the bytes of the message.  Once copied, ownership of the message
+        // belongs to the queue.
+        let res = unsafe { k_msgq_put(self.msgq, msg.as_ptr() as *const c_void, timeout.0) };
+        if res == 0 {
+            Ok(())
+        } else {
+            // SAFETY: The message was not copied, so it is still ours.
+            Err(SendError(unsafe { msg.assume_init() }))
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            msgq: self.msgq,
+            #[cfg(CONFIG_RUST_ALLOC)]
+            _owner: self._owner.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "msgq::Sender {:?}", self.msgq)
+    }
+}
+
+/// The receiving side of a msgq channel.
+///
+/// Receivers can be cloned, and shared between threads.  Receiving with [`NoWait`] is safe from
+/// IRQ context, as long as dropping the message is also IRQ safe.
+///
+/// [`NoWait`]: crate::time::NoWait
+pub struct Receiver<T> {
+    msgq: *mut k_msgq,
+    #[cfg(CONFIG_RUST_ALLOC)]
+    _owner: Option<Arc<Owned<T>>>,
+    _phantom: PhantomData<T>,
+}
+
+// SAFETY: See Sender.
+unsafe impl<T: Send> Send for Receiver<T> {}
+unsafe impl<T: Send> Sync for Receiver<T> {}
+
+impl<T> Receiver<T> {
+    /// Receive a message, waiting up to `timeout` for one to arrive.
+    pub fn recv(&self, timeout: impl Into<Timeout>) -> Result<T, RecvError> {
+        let timeout: Timeout = timeout.into();
+        let mut msg = MaybeUninit::<T>::uninit();
+        let res = unsafe { k_msgq_get(self.msgq, msg.as_mut_ptr() as *mut c_void, timeout.0) };
+        if res == 0 {
+            // SAFETY: A successful get copied a complete message, which was written by `send`.
+            Ok(unsafe { msg.assume_init() })
+        } else {
+            Err(RecvError)
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Receiver {
+            msgq: self.msgq,
+            #[cfg(CONFIG_RUST_ALLOC)]
+            _owner: self._owner.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "msgq::Receiver {:?}", self.msgq)
+    }
+}
+
+/// An error returned from [`Sender::send`].
+///
+/// The message could not be sent before the timeout.  The error contains the message, so it can be
+/// recovered.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "SendError(..)".fmt(f)
+    }
+}
+
+/// An error returned from [`Receiver::recv`].
+///
+/// No message was available before the timeout.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RecvError;
@@ -13,7 +13,7 @@ use core::{
 };
 
 use crate::sys::sync as sys;
-use crate::time::{Forever, NoWait};
+use crate::time::{Forever, NoWait, Timeout};
 
 /// Until poisoning is implemented, mutexes never return an error, and we just get back the guard.
 pub type LockResult<Guard> = Result<Guard, Infallible>;
@@ -214,8 +214,29 @@ impl Condvar {
     }
 
     // TODO: wait_while
-    // TODO: wait_timeout_ms
-    // TODO: wait_timeout
+
+    /// Waits on this condition variable for a notification, timing out after the given time.
+    ///
+    /// The semantics of this function are equivalent to [`wait`] except that the thread will be
+    /// blocked for roughly no longer than `timeout`.  The returned [`WaitTimeoutResult`] indicates
+    /// whether the timeout elapsed.
+    ///
+    /// As with [`wait`], spurious wakeups are possible, and the predicate should be checked again
+    /// after this returns.
+    ///
+    /// [`wait`]: Self::wait
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        timeout: impl Into<Timeout>,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        let timed_out = self
+            .inner
+            .wait_timeout(&guard.lock.inner, timeout)
+            .is_err();
+        Ok((guard, WaitTimeoutResult(timed_out)))
+    }
+
     // TODO: wait_timeout_while
 
     /// Wakes up one blocked thread on this condvar.
@@ -239,6 +260,21 @@ impl Condvar {
     }
 }
 
+/// A type indicating whether a timed wait on a condition variable returned due to a time out or not.
+///
+/// Returned by the [`wait_timeout`] method.
+///
+/// [`wait_timeout`]: Condvar::wait_timeout
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Returns `true` if the wait was known to have timed out.
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
 #[cfg(CONFIG_RUST_ALLOC)]
 impl Default for Condvar {
     fn default() -> Self {
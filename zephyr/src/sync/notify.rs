@@ -0,0 +1,72 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Thread wakeup notification.
+//!
+//! A [`Notify`] is used to wake a thread, when there is no data to pass along.  It is a binary
+//! semaphore (a `k_sem` with a count limit of one), but the different name communicates intent:
+//! "something happened", rather than a counted resource.  Multiple notifications before the waiter
+//! runs are coalesced into a single wakeup.
+
+use core::fmt;
+
+use crate::error::{ZResult, ZephyrError};
+use crate::sys::sync::Semaphore;
+use crate::time::Timeout;
+
+/// A notification primitive, to wake a waiting thread.
+pub struct Notify {
+    sem: Semaphore,
+}
+
+impl Notify {
+    /// Construct a new `Notify`, using a dynamically allocated semaphore.
+    #[cfg(CONFIG_RUST_ALLOC)]
+    pub fn new() -> Notify {
+        Notify {
+            sem: Semaphore::new(0, 1).expect("Allocating semaphore"),
+        }
+    }
+
+    /// Construct a new `Notify` from an existing semaphore.
+    ///
+    /// The semaphore should have been initialized with a count of 0, and a limit of 1, for example
+    /// from a `StaticSemaphore` with `init_once((0, 1))`.
+    pub const fn new_from(sem: Semaphore) -> Notify {
+        Notify { sem }
+    }
+
+    /// Notify the waiter.
+    ///
+    /// If no thread is currently waiting, the notification is stored, and the next call to
+    /// [`wait`] will return immediately.  This is callable from IRQ context.
+    ///
+    /// [`wait`]: Self::wait
+    pub fn notify(&self) {
+        self.sem.give();
+    }
+
+    /// Wait for a notification.
+    ///
+    /// Waits up to `timeout` for [`notify`] to be called, returning [`ZephyrError::Again`] if the
+    /// time expires, or [`ZephyrError::Busy`] if there is no notification and `timeout` is
+    /// `NoWait`.
+    ///
+    /// [`notify`]: Self::notify
+    pub fn wait(&self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        self.sem.take(timeout).map_err(ZephyrError::from)
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Notify")
+    }
+}
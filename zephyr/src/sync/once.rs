@@ -0,0 +1,216 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! One-time initialization.
+//!
+//! [`OnceLock`] and [`LazyLock`] are modeled after
+//! [`std::sync::OnceLock`](https://doc.rust-lang.org/stable/std/sync/struct.OnceLock.html) and
+//! [`std::sync::LazyLock`](https://doc.rust-lang.org/stable/std/sync/struct.LazyLock.html).  They
+//! use the same atomic state machine as [`StaticKernelObject`], going from
+//! [`KOBJ_UNINITIALIZED`], through [`KOBJ_INITING`] to [`KOBJ_INITIALIZED`].
+//!
+//! Threads that find another thread in the middle of initialization sleep for a tick at a time
+//! until the initialization finishes.  A sleep is used instead of a yield so that a lower priority
+//! thread doing the initialization is still able to run.  As such, these cannot be waited on from
+//! interrupt context, although [`OnceLock::get`] is always safe to call.
+//!
+//! [`StaticKernelObject`]: crate::object::StaticKernelObject
+//! [`KOBJ_UNINITIALIZED`]: crate::object::KOBJ_UNINITIALIZED
+//! [`KOBJ_INITING`]: crate::object::KOBJ_INITING
+//! [`KOBJ_INITIALIZED`]: crate::object::KOBJ_INITIALIZED
+
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit, ops::Deref};
+
+use crate::object::{KOBJ_INITIALIZED, KOBJ_INITING, KOBJ_UNINITIALIZED};
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::time::Duration;
+
+/// A cell that can be written to only once.
+pub struct OnceLock<T> {
+    /// The value, valid only once `state` is `KOBJ_INITIALIZED`.
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// The initialization state.
+    state: AtomicUsize,
+}
+
+// SAFETY: Same reasoning as std.  Sharing the lock shares `&T`, and the value can be set from any
+// thread, and thus sent.
+unsafe impl<T: Sync + Send> Sync for OnceLock<T> {}
+unsafe impl<T: Send> Send for OnceLock<T> {}
+
+impl<T> OnceLock<T> {
+    /// Create a new, uninitialized, `OnceLock`.
+    pub const fn new() -> Self {
+        OnceLock {
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            state: AtomicUsize::new(KOBJ_UNINITIALIZED),
+        }
+    }
+
+    /// Get a reference to the value, if it has been initialized.
+    ///
+    /// This does not wait.  If another thread is in the middle of initializing, returns `None`.
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) == KOBJ_INITIALIZED {
+            // SAFETY: The value has been written, and will no longer be modified.
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Set the contents of the cell to `value`.
+    ///
+    /// If the cell was already initialized, or is being initialized, returns `Err(value)`.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.begin_init() {
+            self.finish_init(value);
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Get the contents of the cell, initializing it with `f` if it is uninitialized.
+    ///
+    /// If another thread is initializing the cell, this waits for that to complete.  Only a single
+    /// `f` will be called, even if multiple threads call this at the same time.
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        if let Some(value) = self.get() {
+            return value;
+        }
+
+        if self.begin_init() {
+            self.finish_init(f());
+        } else {
+            while self.state.load(Ordering::Acquire) != KOBJ_INITIALIZED {
+                crate::time::sleep(Duration::from_ticks(1));
+            }
+        }
+
+        // SAFETY: One of the two paths above guarantees the value has been initialized.
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+
+    /// Consume the `OnceLock`, returning the value, if it was initialized.
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+
+    /// Take the value out of the `OnceLock`, leaving it uninitialized.
+    ///
+    /// As this takes `&mut self`, there can be no other users of the lock.
+    pub fn take(&mut self) -> Option<T> {
+        if *self.state.get_mut() == KOBJ_INITIALIZED {
+            *self.state.get_mut() = KOBJ_UNINITIALIZED;
+            // SAFETY: The value was initialized, and the state has been reset so that it will not
+            // be read or dropped again.
+            Some(unsafe { self.value.get_mut().assume_init_read() })
+        } else {
+            None
+        }
+    }
+
+    /// Attempt to move from uninitialized to initializing.  Returns true if this caller is now
+    /// responsible for the initialization.
+    fn begin_init(&self) -> bool {
+        self.state
+            .compare_exchange(
+                KOBJ_UNINITIALIZED,
+                KOBJ_INITING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+
+    /// Store the value and mark initialization as complete.  Must only be called after
+    /// `begin_init` returned true.
+    fn finish_init(&self, value: T) {
+        // SAFETY: `begin_init` gives this thread exclusive access to the value.
+        unsafe { (*self.value.get()).write(value) };
+        self.state.store(KOBJ_INITIALIZED, Ordering::Release);
+    }
+}
+
+impl<T> Default for OnceLock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for OnceLock<T> {
+    fn drop(&mut self) {
+        let _ = self.take();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => write!(f, "OnceLock({:?})", value),
+            None => write!(f, "OnceLock(<uninit>)"),
+        }
+    }
+}
+
+/// A value that is initialized on first access.
+///
+/// ```ignore
+/// static CONFIG: LazyLock<Config> = LazyLock::new(|| Config::load_from_flash());
+/// ```
+pub struct LazyLock<T, F = fn() -> T> {
+    /// The value, once it has been computed.
+    once: OnceLock<T>,
+    /// The initializer.  Taken by whichever thread performs the initialization.
+    init: UnsafeCell<Option<F>>,
+}
+
+// SAFETY: The initializer is only accessed by the single thread that wins the initialization, so
+// it only needs to be Send.
+unsafe impl<T: Sync + Send, F: Send> Sync for LazyLock<T, F> {}
+
+impl<T, F: FnOnce() -> T> LazyLock<T, F> {
+    /// Create a new lazy value, with the given initializer.
+    pub const fn new(init: F) -> Self {
+        LazyLock {
+            once: OnceLock::new(),
+            init: UnsafeCell::new(Some(init)),
+        }
+    }
+
+    /// Force evaluation of the lazy value, returning a reference to it.
+    ///
+    /// This is the same as the `Deref` implementation.
+    pub fn force(this: &Self) -> &T {
+        this.once.get_or_init(|| {
+            // SAFETY: `get_or_init` only calls this closure from the single thread that performs
+            // initialization, so there is no other access to `init`.
+            let init = unsafe { (*this.init.get()).take() };
+            match init {
+                Some(init) => init(),
+                None => unreachable!("LazyLock initializer called twice"),
+            }
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyLock<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        LazyLock::force(self)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for LazyLock<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.once.get() {
+            Some(value) => write!(f, "LazyLock({:?})", value),
+            None => write!(f, "LazyLock(<uninit>)"),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! One-shot channels.
+//!
+//! A one-shot channel carries a single message from a sender to a receiver.  A common use is for an
+//! interrupt handler to hand a result to a waiting thread, for example, "DMA complete".
+//!
+//! The channel is built around a binary semaphore, with the value stored alongside it.  Sending
+//! consumes the sender, so a channel can only ever carry one message.
+//!
+//! ## IRQ safety
+//!
+//! [`OneshotSender::send`] can be called from IRQ context.  Note however that if the sender is
+//! holding the last reference to the channel (the receiver has been dropped), the channel will be
+//! freed from IRQ context, which requires the allocator to be safe to use from there.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+
+use crate::sync::Arc;
+use crate::sys::sync::Semaphore;
+use crate::time::Timeout;
+
+/// Create a new one-shot channel.
+pub fn channel<T: Send>() -> (OneshotSender<T>, OneshotReceiver<T>) {
+    let inner = Arc::new(Inner {
+        sem: Semaphore::new(0, 1).unwrap(),
+        value: UnsafeCell::new(None),
+    });
+    (
+        OneshotSender {
+            inner: Some(inner.clone()),
+        },
+        OneshotReceiver { inner, done: false },
+    )
+}
+
+struct Inner<T> {
+    /// Given once the value has been written (or the sender dropped).
+    sem: Semaphore,
+    /// The value.  Written by the sender before giving the semaphore, and read by the receiver
+    /// only after taking it.
+    value: UnsafeCell<Option<T>>,
+}
+
+// SAFETY: Access to the value is ordered by the semaphore.
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+/// The sending half of a one-shot channel.
+pub struct OneshotSender<T> {
+    /// The channel.  Removed when the message is sent.
+    inner: Option<Arc<Inner<T>>>,
+}
+
+impl<T> OneshotSender<T> {
+    /// Send the value, waking the receiver.
+    pub fn send(mut self, value: T) {
+        if let Some(inner) = self.inner.take() {
+            Self::complete(inner, Some(value));
+        }
+    }
+
+    fn complete(inner: Arc<Inner<T>>, value: Option<T>) {
+        // SAFETY: The receiver does not look at the value until the semaphore has been given.
+        unsafe { *inner.value.get() = value };
+        inner.sem.give();
+    }
+}
+
+impl<T> Drop for OneshotSender<T> {
+    fn drop(&mut self) {
+        // If the sender is dropped without sending, wake the receiver so it can see that the
+        // channel is closed.
+        if let Some(inner) = self.inner.take() {
+            Self::complete(inner, None);
+        }
+    }
+}
+
+impl<T> fmt::Debug for OneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OneshotSender")
+    }
+}
+
+/// The receiving half of a one-shot channel.
+pub struct OneshotReceiver<T> {
+    inner: Arc<Inner<T>>,
+    /// Set once the semaphore has been taken.
+    done: bool,
+}
+
+impl<T> OneshotReceiver<T> {
+    /// Wait up to `timeout` for the value to be sent.
+    ///
+    /// Returns [`RecvError::Timeout`] if nothing was sent in time, in which case `recv` can be
+    /// called again.  Once the value has been received, or the sender was dropped without sending,
+    /// further calls return [`RecvError::Closed`].
+    pub fn recv(&mut self, timeout: impl Into<Timeout>) -> Result<T, RecvError> {
+        if !self.done {
+            // The semaphore take only fails if the time expires (EAGAIN), or with `NoWait` (EBUSY).
+            if self.inner.sem.take(timeout).is_err() {
+                return Err(RecvError::Timeout);
+            }
+            self.done = true;
+        }
+        // SAFETY: The semaphore has been taken, so the sender has finished with the value.
+        unsafe { (*self.inner.value.get()).take() }.ok_or(RecvError::Closed)
+    }
+}
+
+impl<T> fmt::Debug for OneshotReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OneshotReceiver")
+    }
+}
+
+/// An error returned from [`OneshotReceiver::recv`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvError {
+    /// No value was sent before the timeout.
+    Timeout,
+    /// The value has already been received, or the sender was dropped without sending one.
+    Closed,
+}
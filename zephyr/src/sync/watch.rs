@@ -0,0 +1,155 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watch channels.
+//!
+//! A watch channel holds a single value, the most recently sent one, rather than a queue of
+//! messages.  This is modeled after
+//! [`tokio::sync::watch`](https://docs.rs/tokio/latest/tokio/sync/watch/index.html).  Receivers can
+//! read the current value at any time, and can wait for the value to change.  Intermediate values
+//! that are sent between reads are not seen by the receiver.
+//!
+//! This is useful for things like configuration, or state, where only the latest value is
+//! meaningful.
+
+use core::fmt;
+
+use zephyr_sys::{k_ticks_t, k_timeout_t};
+
+use crate::error::{ZResult, ZephyrError};
+use crate::sync::{Arc, Condvar, Mutex};
+use crate::time::Timeout;
+
+/// Create a new watch channel, holding the `initial` value.
+pub fn channel<T: Clone>(initial: T) -> (WatchSender<T>, WatchReceiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            value: initial,
+            version: 0,
+        }),
+        changed: Condvar::new(),
+    });
+    (
+        WatchSender {
+            shared: shared.clone(),
+        },
+        WatchReceiver { shared, seen: 0 },
+    )
+}
+
+/// The state shared between the sender and all of the receivers.
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+}
+
+struct State<T> {
+    value: T,
+    /// Incremented each time a new value is sent.
+    version: u64,
+}
+
+/// The sending side of a watch channel.
+pub struct WatchSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> WatchSender<T> {
+    /// Replace the current value, waking any receivers waiting in [`WatchReceiver::changed`].
+    pub fn send(&self, value: T) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.value = value;
+        state.version += 1;
+        drop(state);
+        self.shared.changed.notify_all();
+    }
+
+    /// Get a new receiver for this channel.
+    ///
+    /// The current value will be considered already seen by the new receiver.
+    pub fn subscribe(&self) -> WatchReceiver<T> {
+        let seen = self.shared.state.lock().unwrap().version;
+        WatchReceiver {
+            shared: self.shared.clone(),
+            seen,
+        }
+    }
+}
+
+impl<T> fmt::Debug for WatchSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WatchSender")
+    }
+}
+
+/// The receiving side of a watch channel.
+///
+/// Each receiver tracks the last version of the value it has seen.  Receivers can be cloned, and
+/// the clone starts with the same seen version.
+#[derive(Clone)]
+pub struct WatchReceiver<T> {
+    shared: Arc<Shared<T>>,
+    seen: u64,
+}
+
+impl<T: Clone> WatchReceiver<T> {
+    /// Return a clone of the current value, marking it as seen.
+    pub fn recv(&mut self) -> T {
+        let state = self.shared.state.lock().unwrap();
+        self.seen = state.version;
+        state.value.clone()
+    }
+
+    /// Returns true if a new value has been sent since this receiver last saw one.
+    pub fn has_changed(&self) -> bool {
+        self.shared.state.lock().unwrap().version != self.seen
+    }
+
+    /// Wait for a new value to be sent.
+    ///
+    /// Returns immediately if a value has been sent that this receiver hasn't seen yet.  Otherwise,
+    /// waits up to `timeout` for a new value, returning [`ZephyrError::TimedOut`] if none arrives.
+    /// This marks the new value as seen, use [`recv`] to read it.
+    ///
+    /// [`recv`]: Self::recv
+    pub fn changed(&mut self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        let timeout: Timeout = timeout.into();
+        // A relative timeout is turned into a deadline, so that wakeups that don't give this
+        // receiver a new value don't restart the full wait.  `K_NO_WAIT`, `K_FOREVER`, and absolute
+        // timeouts are used as they are.
+        let deadline = (timeout.0.ticks > 0).then(|| now_ticks() + timeout.0.ticks as i64);
+        let mut state = self.shared.state.lock().unwrap();
+        while state.version == self.seen {
+            let wait = match deadline {
+                Some(deadline) => {
+                    let left = deadline - now_ticks();
+                    if left <= 0 {
+                        return Err(ZephyrError::TimedOut);
+                    }
+                    Timeout(k_timeout_t {
+                        ticks: left as k_ticks_t,
+                    })
+                }
+                None => timeout,
+            };
+            let (next, result) = self.shared.changed.wait_timeout(state, wait).unwrap();
+            state = next;
+            if result.timed_out() && state.version == self.seen {
+                return Err(ZephyrError::TimedOut);
+            }
+        }
+        self.seen = state.version;
+        Ok(())
+    }
+}
+
+impl<T> fmt::Debug for WatchReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WatchReceiver {{ seen: {} }}", self.seen)
+    }
+}
+
+/// The current uptime, in ticks.
+fn now_ticks() -> i64 {
+    unsafe { crate::raw::k_uptime_ticks() }
+}
@@ -0,0 +1,230 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A spinlock based `Arc`.
+//!
+//! [`Arc`] is re-exported from `portable-atomic-util`, which uses atomic instructions when the
+//! target has them, and otherwise falls back on the `critical-section` implementation.  The
+//! [`ZephyrArc`] here instead protects its reference counts with a Zephyr `k_spinlock`, which is
+//! available on every target Zephyr supports, including those without atomic compare-and-swap
+//! instructions, such as Cortex-M0.  In addition, the lock is per-allocation, rather than the
+//! single global lock used by the critical section.
+//!
+//! The API mirrors that of [`Arc`], including [`ZephyrWeak`] references, which can be used to break
+//! reference cycles.
+//!
+//! [`Arc`]: crate::sync::Arc
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::Deref;
+use core::ptr::NonNull;
+
+use crate::sync::SpinMutex;
+
+/// A thread-safe reference-counting pointer, with the counts protected by a spinlock.
+pub struct ZephyrArc<T: ?Sized> {
+    ptr: NonNull<ArcInner<T>>,
+}
+
+/// The reference counts.
+struct Counts {
+    /// The number of `ZephyrArc` references.
+    strong: usize,
+    /// The number of `ZephyrWeak` references, plus one for all of the strong references combined.
+    /// The allocation is freed when this reaches zero.
+    weak: usize,
+}
+
+struct ArcInner<T: ?Sized> {
+    counts: SpinMutex<Counts>,
+    /// The data, which is dropped when the strong count reaches zero, even though the allocation
+    /// may survive for the weak references.
+    data: ManuallyDrop<T>,
+}
+
+// SAFETY: As with `Arc`, sharing requires both Send and Sync of the data, as the data can be
+// dropped from any thread holding the last reference.
+unsafe impl<T: ?Sized + Sync + Send> Send for ZephyrArc<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for ZephyrArc<T> {}
+
+impl<T> ZephyrArc<T> {
+    /// Construct a new `ZephyrArc<T>`.
+    pub fn new(data: T) -> ZephyrArc<T> {
+        let inner = Box::new(ArcInner {
+            counts: SpinMutex::new(Counts { strong: 1, weak: 1 }),
+            data: ManuallyDrop::new(data),
+        });
+        ZephyrArc {
+            // SAFETY: Box never returns a null pointer.
+            ptr: unsafe { NonNull::new_unchecked(Box::into_raw(inner)) },
+        }
+    }
+}
+
+impl<T: ?Sized> ZephyrArc<T> {
+    fn inner(&self) -> &ArcInner<T> {
+        // SAFETY: The inner allocation stays valid as long as there is a strong reference.
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// Gets the number of strong pointers to this allocation.
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().counts.lock().unwrap().strong
+    }
+
+    /// Gets the number of weak pointers to this allocation.
+    pub fn weak_count(this: &Self) -> usize {
+        // Don't count the implicit weak reference held by the strong references.
+        this.inner().counts.lock().unwrap().weak - 1
+    }
+
+    /// Creates a new [`ZephyrWeak`] pointer to this allocation.
+    pub fn downgrade(this: &Self) -> ZephyrWeak<T> {
+        this.inner().counts.lock().unwrap().weak += 1;
+        ZephyrWeak { ptr: this.ptr }
+    }
+
+    /// Returns `true` if the two `ZephyrArc`s point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        core::ptr::addr_eq(this.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+
+    /// Returns a mutable reference into the given `ZephyrArc`, if there are no other `ZephyrArc`
+    /// or [`ZephyrWeak`] pointers to the same allocation.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        let unique = {
+            let counts = this.inner().counts.lock().unwrap();
+            counts.strong == 1 && counts.weak == 1
+        };
+        if unique {
+            // SAFETY: This is the only reference, and the `&mut` prevents another being made.
+            Some(unsafe { &mut *(*this.ptr.as_ptr()).data })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for ZephyrArc<T> {
+    fn clone(&self) -> Self {
+        self.inner().counts.lock().unwrap().strong += 1;
+        ZephyrArc { ptr: self.ptr }
+    }
+}
+
+impl<T: ?Sized> Deref for ZephyrArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &*self.inner().data
+    }
+}
+
+impl<T: ?Sized> Drop for ZephyrArc<T> {
+    fn drop(&mut self) {
+        let last = {
+            let mut counts = self.inner().counts.lock().unwrap();
+            counts.strong -= 1;
+            counts.strong == 0
+        };
+        if last {
+            // SAFETY: This was the last strong reference, so nothing else can access the data.
+            // Weak references can no longer upgrade, as the strong count is zero.
+            unsafe { ManuallyDrop::drop(&mut (*self.ptr.as_ptr()).data) };
+            // Release the weak reference held collectively by the strong references.
+            drop(ZephyrWeak { ptr: self.ptr });
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ZephyrArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for ZephyrArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: Default> Default for ZephyrArc<T> {
+    fn default() -> Self {
+        ZephyrArc::new(T::default())
+    }
+}
+
+/// A weak reference to a [`ZephyrArc`] allocation.
+///
+/// A weak reference does not keep the data alive.  [`upgrade`] returns a new `ZephyrArc` if there
+/// are still strong references.
+///
+/// [`upgrade`]: Self::upgrade
+pub struct ZephyrWeak<T: ?Sized> {
+    ptr: NonNull<ArcInner<T>>,
+}
+
+// SAFETY: Same as ZephyrArc, as a weak reference can become a strong one.
+unsafe impl<T: ?Sized + Sync + Send> Send for ZephyrWeak<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for ZephyrWeak<T> {}
+
+impl<T: ?Sized> ZephyrWeak<T> {
+    fn counts(&self) -> &SpinMutex<Counts> {
+        // SAFETY: The weak count keeps the allocation itself (but not necessarily the data) valid.
+        unsafe { &self.ptr.as_ref().counts }
+    }
+
+    /// Attempts to upgrade to a [`ZephyrArc`].
+    ///
+    /// Returns `None` if all of the strong references have been dropped.  The check and the
+    /// increment of the strong count are done while holding the spinlock, so a concurrent drop of
+    /// the last strong reference can't race with this.
+    pub fn upgrade(&self) -> Option<ZephyrArc<T>> {
+        let mut counts = self.counts().lock().unwrap();
+        if counts.strong == 0 {
+            None
+        } else {
+            counts.strong += 1;
+            Some(ZephyrArc { ptr: self.ptr })
+        }
+    }
+
+    /// Gets the number of strong pointers to this allocation.
+    pub fn strong_count(&self) -> usize {
+        self.counts().lock().unwrap().strong
+    }
+}
+
+impl<T: ?Sized> Clone for ZephyrWeak<T> {
+    fn clone(&self) -> Self {
+        self.counts().lock().unwrap().weak += 1;
+        ZephyrWeak { ptr: self.ptr }
+    }
+}
+
+impl<T: ?Sized> Drop for ZephyrWeak<T> {
+    fn drop(&mut self) {
+        let last = {
+            let mut counts = self.counts().lock().unwrap();
+            counts.weak -= 1;
+            counts.weak == 0
+        };
+        if last {
+            // SAFETY: There are no more references of either kind, and the data has already been
+            // dropped.  The lock has been released.
+            drop(unsafe { Box::from_raw(self.ptr.as_ptr()) });
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for ZephyrWeak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(ZephyrWeak)")
+    }
+}
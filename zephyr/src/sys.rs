@@ -11,6 +11,11 @@
 
 use zephyr_sys::k_timeout_t;
 
+#[cfg(CONFIG_DYNAMIC_OBJECTS)]
+pub mod dynamic;
+pub mod mem_slab;
+pub mod mempool;
+pub mod pipe;
 pub mod queue;
 pub mod sync;
 pub mod thread;
@@ -31,6 +36,13 @@ pub const K_FOREVER: k_timeout_t = k_timeout_t { ticks: -1 };
 /// performed immediately.
 pub const K_NO_WAIT: k_timeout_t = k_timeout_t { ticks: 0 };
 
+/// A Zephyr `k_timeout_t`, with conversions from [`Duration`](crate::time::Duration), and to and
+/// from the raw type, for calling C APIs directly.
+///
+/// This is the same type that the wrappers take timeouts as, through `impl Into<Timeout>`, so
+/// durations given to them are converted the same way.
+pub use crate::time::Timeout as KTimeout;
+
 /// Return the current uptime of the system in ms.
 ///
 /// Direct Zephyr call.  Precision is limited by the system tick timer.
@@ -42,19 +54,26 @@ pub fn uptime_get() -> i64 {
 pub mod critical {
     //! Zephyr implementation of critical sections.
     //!
-    //! Critical sections from Rust are handled with a single Zephyr spinlock.  This doesn't allow
-    //! any nesting, but neither does the `critical-section` crate.
+    //! Critical sections from Rust are handled with a single Zephyr spinlock.  A spinlock, rather
+    //! than `irq_lock`, is what keeps out the other CPUs on SMP systems, as well as interrupts on
+    //! this one.
     //!
     //! This provides the underlying critical section crate, which is useful for external crates
-    //! that want this interface.  However, it isn't a particularly hygienic interface to use.  For
-    //! something a bit nicer, please see [`sync::SpinMutex`].
+    //! that want this interface, such as `embedded-hal-bus`.  However, it isn't a particularly
+    //! hygienic interface to use.  For something a bit nicer, please see [`sync::SpinMutex`].
+    //!
+    //! The `critical-section` crate allows critical sections to be nested, which Zephyr spinlocks
+    //! do not.  So the thread holding the lock is recorded, and a nested acquire from it returns
+    //! without touching the spinlock, leaving the outermost release to unlock it.
     //!
     //! [`sync::SpinMutex`]: crate::sync::SpinMutex
 
-    use core::{ffi::c_int, ptr::addr_of_mut};
+    use core::{ffi::c_int, ptr::addr_of_mut, ptr::null_mut};
 
     use critical_section::RawRestoreState;
-    use zephyr_sys::{k_spin_lock, k_spin_unlock, k_spinlock, k_spinlock_key_t};
+    use zephyr_sys::{k_current_get, k_spin_lock, k_spin_unlock, k_spinlock, k_spinlock_key_t};
+
+    use crate::sync::atomic::{AtomicPtr, Ordering};
 
     struct ZephyrCriticalSection;
     critical_section::set_impl!(ZephyrCriticalSection);
@@ -62,13 +81,30 @@ pub mod critical {
     // The critical section shares a single spinlock.
     static mut LOCK: k_spinlock = unsafe { core::mem::zeroed() };
 
+    /// The thread holding the lock, or null.  From an interrupt handler, this is the thread that
+    /// was interrupted, which can't run elsewhere while it is.
+    static OWNER: AtomicPtr<zephyr_sys::k_thread> = AtomicPtr::new(null_mut());
+
+    /// The restore state returned by a nested acquire.  Interrupt lock keys are small values.
+    const NESTED: RawRestoreState = RawRestoreState::MAX;
+
     unsafe impl critical_section::Impl for ZephyrCriticalSection {
         unsafe fn acquire() -> RawRestoreState {
+            let current = k_current_get();
+            // Only this thread can have set the owner to itself, so there is no race if it has.
+            if OWNER.load(Ordering::Relaxed) == current {
+                return NESTED;
+            }
             let res = k_spin_lock(addr_of_mut!(LOCK));
+            OWNER.store(current, Ordering::Relaxed);
             res.key as RawRestoreState
         }
 
         unsafe fn release(token: RawRestoreState) {
+            if token == NESTED {
+                return;
+            }
+            OWNER.store(null_mut(), Ordering::Relaxed);
             k_spin_unlock(
                 addr_of_mut!(LOCK),
                 k_spinlock_key_t {
@@ -0,0 +1,309 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dynamically allocated kernel objects.
+//!
+//! Most kernel objects in Rust are either declared statically with [`kobj_define!`], or are
+//! allocated from the Rust heap.  Objects allocated from the Rust heap are not known to the Zephyr
+//! kernel object system, and as such, can not be used from user mode threads.
+//!
+//! When `CONFIG_DYNAMIC_OBJECTS` is enabled, Zephyr is able to allocate kernel objects at runtime,
+//! through `k_object_alloc`, and these objects are tracked by the kernel the same way that static
+//! objects are.  The types here wrap these objects, freeing them with `k_object_free` when they
+//! are dropped.
+//!
+//! [`kobj_define!`]: crate::kobj_define
+
+use core::ffi::{c_int, c_void};
+use core::fmt;
+use core::ops::Deref;
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::object::Fixed;
+use crate::raw::{
+    k_msgq, k_msgq_alloc_init, k_msgq_cleanup, k_msgq_get, k_msgq_num_used_get, k_msgq_purge,
+    k_msgq_put, k_mutex, k_mutex_init, k_object_alloc, k_object_free, k_objects_K_OBJ_MSGQ,
+    k_objects_K_OBJ_MUTEX, k_objects_K_OBJ_SEM, k_objects_K_OBJ_TIMER, k_sem, k_sem_init, k_timer,
+    k_timer_init, k_timer_start, k_timer_status_get, k_timer_status_sync, k_timer_stop,
+};
+use crate::sys::sync::{Mutex, Semaphore};
+use crate::time::Timeout;
+
+/// The kernel was unable to allocate an object.
+///
+/// This is returned when `k_object_alloc` returns NULL, which typically indicates that the
+/// kernel heap (or thread resource pool) is exhausted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unable to allocate kernel object")
+    }
+}
+
+impl core::error::Error for AllocError {}
+
+/// The allocators here return a [`ZephyrError`], with an allocation failure as
+/// [`ZephyrError::NoMemory`].
+impl From<AllocError> for ZephyrError {
+    fn from(_: AllocError) -> ZephyrError {
+        ZephyrError::NoMemory
+    }
+}
+
+/// Allocate a kernel object of the given type, returning it as a pointer to `T`.
+///
+/// # Safety
+///
+/// `otype` must be the kernel object type that corresponds with `T`.
+unsafe fn alloc<T>(otype: u32) -> core::result::Result<*mut T, AllocError> {
+    let ptr = k_object_alloc(otype as _) as *mut T;
+    if ptr.is_null() {
+        Err(AllocError)
+    } else {
+        Ok(ptr)
+    }
+}
+
+/// A dynamically allocated Zephyr semaphore.
+///
+/// Derefs to [`Semaphore`], which provides the operations.  The underlying object is freed when
+/// this is dropped.
+pub struct DynSemaphore {
+    sem: Semaphore,
+}
+
+impl DynSemaphore {
+    /// Allocate and initialize a new semaphore.
+    ///
+    /// The arguments are the same as [`Semaphore::new`].  An error from the initialization itself,
+    /// such as an invalid limit, is returned, and the object freed.
+    pub fn alloc(initial_count: u32, limit: u32) -> ZResult<DynSemaphore> {
+        let ptr = unsafe { alloc::<k_sem>(k_objects_K_OBJ_SEM)? };
+        let this = DynSemaphore {
+            sem: Semaphore {
+                item: Fixed::Static(ptr),
+            },
+        };
+        unsafe {
+            // SAFETY: The object was just allocated by the kernel.  If the init fails, the drop of
+            // `this` will free it.
+            ok_or_errno(k_sem_init(ptr, initial_count, limit))?;
+        }
+        Ok(this)
+    }
+}
+
+impl Deref for DynSemaphore {
+    type Target = Semaphore;
+
+    fn deref(&self) -> &Semaphore {
+        &self.sem
+    }
+}
+
+impl Drop for DynSemaphore {
+    fn drop(&mut self) {
+        unsafe { k_object_free(self.sem.item.get() as *mut c_void) }
+    }
+}
+
+impl fmt::Debug for DynSemaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::DynSemaphore {:?}", self.sem.item.get())
+    }
+}
+
+/// A dynamically allocated Zephyr mutex.
+///
+/// Derefs to [`Mutex`], which provides the operations.  The underlying object is freed when this
+/// is dropped.
+pub struct DynMutex {
+    mutex: Mutex,
+}
+
+impl DynMutex {
+    /// Allocate and initialize a new mutex, in the unlocked state.
+    pub fn alloc() -> ZResult<DynMutex> {
+        let ptr = unsafe { alloc::<k_mutex>(k_objects_K_OBJ_MUTEX)? };
+        unsafe {
+            // SAFETY: `k_mutex_init` always succeeds.
+            k_mutex_init(ptr);
+        }
+        Ok(DynMutex {
+            mutex: Mutex {
+                item: Fixed::Static(ptr),
+            },
+        })
+    }
+}
+
+impl Deref for DynMutex {
+    type Target = Mutex;
+
+    fn deref(&self) -> &Mutex {
+        &self.mutex
+    }
+}
+
+impl Drop for DynMutex {
+    fn drop(&mut self) {
+        unsafe { k_object_free(self.mutex.item.get() as *mut c_void) }
+    }
+}
+
+impl fmt::Debug for DynMutex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::DynMutex {:?}", self.mutex.item.get())
+    }
+}
+
+/// A dynamically allocated Zephyr timer.
+///
+/// This is a simple timer, with no expiry callback, equivalent to a [`SimpleTimer`].  But, unlike
+/// that type, the timer can be started and stopped repeatedly without changing type, as the
+/// object will be freed when this is dropped.  Dropping will stop the timer if it is running.
+///
+/// [`SimpleTimer`]: crate::timer::SimpleTimer
+pub struct DynTimer {
+    item: *mut k_timer,
+}
+
+// SAFETY: As with the other timers, this is Send, but not Sync.
+unsafe impl Send for DynTimer {}
+
+impl DynTimer {
+    /// Allocate and initialize a new timer.  The timer will not be running.
+    pub fn alloc() -> ZResult<DynTimer> {
+        let item = unsafe { alloc::<k_timer>(k_objects_K_OBJ_TIMER)? };
+        unsafe {
+            k_timer_init(item, None, None);
+        }
+        Ok(DynTimer { item })
+    }
+
+    /// Start the timer.
+    ///
+    /// The `delay` is the time before the first expiration, and `period` the time between
+    /// subsequent expirations.  Starting a running timer restarts it, resetting the count.
+    pub fn start(&mut self, delay: impl Into<Timeout>, period: impl Into<Timeout>) {
+        unsafe { k_timer_start(self.item, delay.into().0, period.into().0) }
+    }
+
+    /// Stop the timer.
+    pub fn stop(&mut self) {
+        unsafe { k_timer_stop(self.item) }
+    }
+
+    /// Read the number of times the timer has fired since the count was last read.
+    pub fn read_count(&mut self) -> u32 {
+        unsafe { k_timer_status_get(self.item) }
+    }
+
+    /// Read the count from the timer, waiting for it to fire if it has not yet.
+    pub fn read_count_wait(&mut self) -> u32 {
+        unsafe { k_timer_status_sync(self.item) }
+    }
+}
+
+impl Drop for DynTimer {
+    fn drop(&mut self) {
+        unsafe {
+            k_timer_stop(self.item);
+            k_object_free(self.item as *mut c_void);
+        }
+    }
+}
+
+impl fmt::Debug for DynTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::DynTimer {:?}", self.item)
+    }
+}
+
+/// A dynamically allocated Zephyr message queue.
+///
+/// The message queue is allocated with `k_object_alloc`, and the buffer for the messages is
+/// allocated from the calling thread's resource pool using `k_msgq_alloc_init`.  Messages are a
+/// fixed number of bytes, given at allocation time.
+///
+/// This is a low level interface, working with raw bytes.
+pub struct DynMsgq {
+    item: *mut k_msgq,
+    msg_size: usize,
+}
+
+// SAFETY: Message queues are safe to use from multiple threads, with the synchronization handled by
+// Zephyr.
+unsafe impl Send for DynMsgq {}
+unsafe impl Sync for DynMsgq {}
+
+impl DynMsgq {
+    /// Allocate a new message queue holding up to `max_msgs` messages of `msg_size` bytes.
+    ///
+    /// A failure to allocate the buffer is returned as well, and the queue freed.
+    pub fn alloc(msg_size: usize, max_msgs: u32) -> ZResult<DynMsgq> {
+        let item = unsafe { alloc::<k_msgq>(k_objects_K_OBJ_MSGQ)? };
+        if let Err(err) = ok_or_errno(unsafe { k_msgq_alloc_init(item, msg_size, max_msgs) }) {
+            unsafe { k_object_free(item as *mut c_void) };
+            return Err(err);
+        }
+        Ok(DynMsgq { item, msg_size })
+    }
+
+    /// The size of each message, in bytes.
+    pub fn msg_size(&self) -> usize {
+        self.msg_size
+    }
+
+    /// Put a message into the queue.
+    ///
+    /// `data` must be exactly `msg_size` bytes long.  Waits up to `timeout` for space.
+    pub fn put(&self, data: &[u8], timeout: impl Into<Timeout>) -> ZResult<()> {
+        assert_eq!(data.len(), self.msg_size);
+        let timeout: Timeout = timeout.into();
+        ok_or_errno(unsafe { k_msgq_put(self.item, data.as_ptr() as *const c_void, timeout.0) })
+    }
+
+    /// Get a message from the queue.
+    ///
+    /// `data` must be exactly `msg_size` bytes long.  Waits up to `timeout` for a message.
+    pub fn get(&self, data: &mut [u8], timeout: impl Into<Timeout>) -> ZResult<()> {
+        assert_eq!(data.len(), self.msg_size);
+        let timeout: Timeout = timeout.into();
+        ok_or_errno(unsafe { k_msgq_get(self.item, data.as_mut_ptr() as *mut c_void, timeout.0) })
+    }
+
+    /// The number of messages currently in the queue.
+    pub fn len(&self) -> usize {
+        unsafe { k_msgq_num_used_get(self.item) as usize }
+    }
+
+    /// Returns true if there are no messages in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Discard all messages in the queue.  Any waiting senders are woken with an error.
+    pub fn purge(&self) {
+        unsafe { k_msgq_purge(self.item) }
+    }
+}
+
+impl Drop for DynMsgq {
+    fn drop(&mut self) {
+        unsafe {
+            // The cleanup can only fail if there are threads waiting, which can't happen, as they
+            // would need a reference.
+            let _: c_int = k_msgq_cleanup(self.item);
+            k_object_free(self.item as *mut c_void);
+        }
+    }
+}
+
+impl fmt::Debug for DynMsgq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::DynMsgq {:?}", self.item)
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lightweight wrapper around Zephyr's `k_mem_slab`.
+//!
+//! A memory slab hands out fixed size blocks from a buffer, which makes allocation and freeing
+//! quick and predictable.  Here each slab holds values of a single type, and the blocks are
+//! given out as [`SlabBox`]es, which free the block when dropped.
+//!
+//! Slabs are declared statically, with the type and the number of blocks:
+//!
+//! ```ignore
+//! kobj_define! {
+//!     static PACKETS: StaticMemSlab<Packet, 8>;
+//! }
+//!
+//! let packets = PACKETS.init_once(()).unwrap();
+//! let packet = packets.alloc(Packet::new(), Forever)?;
+//! ```
+//!
+//! The blocks are sized and aligned for the type, rounded up to the word size that Zephyr needs.
+//! The `kobj_define!` macro declares the buffer as a separate static, in the no-init section, as
+//! it is only written to by the values placed in it.
+
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+use zephyr_sys::{
+    k_mem_slab, k_mem_slab_alloc, k_mem_slab_free, k_mem_slab_init, k_mem_slab_num_free_get,
+    k_mem_slab_num_used_get,
+};
+
+use crate::align::{AlignAs, AlignAsStruct, AlignAsTrait};
+use crate::error::{ok_or_errno, ZResult};
+use crate::object::{StaticKernelObject, Wrapped};
+use crate::sync::atomic::AtomicUsize;
+use crate::time::Timeout;
+
+/// The alignment of the blocks holding a `T`.
+#[doc(hidden)]
+pub const fn block_align<T>() -> usize {
+    let align = mem::align_of::<T>();
+    let word = mem::size_of::<*mut c_void>();
+    if align > word {
+        align
+    } else {
+        word
+    }
+}
+
+/// The size of the blocks holding a `T`.
+#[doc(hidden)]
+pub const fn block_size<T>() -> usize {
+    let size = mem::size_of::<T>();
+    let size = if size == 0 { 1 } else { size };
+    size.next_multiple_of(block_align::<T>())
+}
+
+/// A memory slab, holding blocks of type `T`.
+pub struct MemSlab<T> {
+    slab: *mut k_mem_slab,
+    _phantom: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Sync for MemSlab<T> {}
+unsafe impl<T: Send> Send for MemSlab<T> {}
+
+impl<T> MemSlab<T> {
+    /// Allocate a block, and move `value` into it.
+    ///
+    /// Waits up to `timeout` for a block to be freed if they are all in use.  If none is, `value`
+    /// is dropped, and this fails with [`ZephyrError::NoMemory`], or [`ZephyrError::Again`] if
+    /// there was a timeout.
+    ///
+    /// [`ZephyrError::NoMemory`]: crate::error::ZephyrError::NoMemory
+    /// [`ZephyrError::Again`]: crate::error::ZephyrError::Again
+    pub fn alloc<D>(&self, value: T, timeout: D) -> ZResult<SlabBox<'_, T>>
+    where
+        D: Into<Timeout>,
+    {
+        let timeout: Timeout = timeout.into();
+        let mut block: *mut c_void = ptr::null_mut();
+        ok_or_errno(unsafe { k_mem_slab_alloc(self.slab, &mut block, timeout.0) })?;
+        let block = block as *mut T;
+        // SAFETY: The block is sized and aligned for a `T`, and not used by anything else.
+        unsafe { block.write(value) };
+        Ok(SlabBox { slab: self, block })
+    }
+
+    /// The number of blocks that are free.
+    pub fn num_free(&self) -> u32 {
+        unsafe { k_mem_slab_num_free_get(self.slab) }
+    }
+
+    /// The number of blocks that are in use.
+    pub fn num_used(&self) -> u32 {
+        unsafe { k_mem_slab_num_used_get(self.slab) }
+    }
+}
+
+impl<T> fmt::Debug for MemSlab<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::MemSlab {:?}", self.slab)
+    }
+}
+
+/// A value in a block of a [`MemSlab`], which is freed when this is dropped.
+pub struct SlabBox<'a, T> {
+    slab: &'a MemSlab<T>,
+    block: *mut T,
+}
+
+unsafe impl<T: Send> Send for SlabBox<'_, T> {}
+unsafe impl<T: Sync> Sync for SlabBox<'_, T> {}
+
+impl<T> Deref for SlabBox<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.block }
+    }
+}
+
+impl<T> DerefMut for SlabBox<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.block }
+    }
+}
+
+impl<T> Drop for SlabBox<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.block);
+            k_mem_slab_free(self.slab.slab, self.block as *mut c_void);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SlabBox<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// The buffer of a static memory slab.
+///
+/// This is declared by [`kobj_define`](crate::kobj_define), and shouldn't be used directly.
+#[doc(hidden)]
+pub struct SlabBuffer<const ALIGN: usize, const SIZE: usize>
+where
+    AlignAsStruct: AlignAsTrait<ALIGN>,
+{
+    #[allow(dead_code)]
+    align: AlignAs<ALIGN>,
+    pub data: UnsafeCell<[u8; SIZE]>,
+}
+
+unsafe impl<const ALIGN: usize, const SIZE: usize> Sync for SlabBuffer<ALIGN, SIZE> where
+    AlignAsStruct: AlignAsTrait<ALIGN>
+{
+}
+
+/// A `k_mem_slab`, along with the buffer it will be given when initialized.
+#[doc(hidden)]
+pub struct RawStaticMemSlab<T> {
+    slab: k_mem_slab,
+    buffer: *mut u8,
+    count: usize,
+    _phantom: PhantomData<T>,
+}
+
+/// A statically defined Zephyr `k_mem_slab`, holding blocks of type `T`.
+///
+/// This should be declared as follows:
+/// ```
+/// kobj_define! {
+///     static MY_SLAB: StaticMemSlab<MyStruct, 8>;
+/// }
+///
+/// let my_slab = MY_SLAB.init_once(()).unwrap();
+///
+/// let item = my_slab.alloc(MyStruct::new(), Forever).unwrap();
+/// ```
+///
+/// Zephyr initializes the slabs declared in C at boot, by walking the `k_mem_slab` linker
+/// section, which would find this struct's extra fields.  So these are not placed there, and
+/// can't be granted to userspace threads.
+pub type StaticMemSlab<T> = StaticKernelObject<RawStaticMemSlab<T>>;
+
+unsafe impl<T: Send> Sync for StaticMemSlab<T> {}
+
+impl<T> Wrapped for StaticMemSlab<T> {
+    type T = MemSlab<T>;
+
+    /// Memory slab initializers take no argument, as the buffer is declared with the slab.
+    type I = ();
+
+    fn get_wrapped(&self, _arg: Self::I) -> MemSlab<T> {
+        let raw = self.value.get();
+        let slab = unsafe { ptr::addr_of_mut!((*raw).slab) };
+        let ret = unsafe {
+            k_mem_slab_init(
+                slab,
+                (*raw).buffer as *mut c_void,
+                block_size::<T>(),
+                (*raw).count as u32,
+            )
+        };
+        // The buffer is laid out by `kobj_define`, so this can only fail if that is wrong.
+        assert!(ret == 0, "k_mem_slab_init failed: {}", ret);
+        MemSlab {
+            slab,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> StaticMemSlab<T> {
+    /// Construct a StaticMemSlab around its buffer, which must hold `count` blocks.
+    ///
+    /// This is not intended to be directly called, but is used by the [`kobj_define`] macro.
+    ///
+    /// [`kobj_define`]: crate::kobj_define
+    #[doc(hidden)]
+    pub const fn new_from<const ALIGN: usize, const SIZE: usize>(
+        buffer: &SlabBuffer<ALIGN, SIZE>,
+        count: usize,
+    ) -> Self
+    where
+        AlignAsStruct: AlignAsTrait<ALIGN>,
+    {
+        assert!(ALIGN >= block_align::<T>() && SIZE >= block_size::<T>() * count);
+        Self {
+            value: UnsafeCell::new(RawStaticMemSlab {
+                slab: unsafe { mem::zeroed() },
+                buffer: buffer.data.get() as *mut u8,
+                count,
+                _phantom: PhantomData,
+            }),
+            init: AtomicUsize::new(0),
+        }
+    }
+}
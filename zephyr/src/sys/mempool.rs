@@ -0,0 +1,144 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Memory pools.
+//!
+//! Older versions of Zephyr had a `k_mem_pool` API that allocated blocks from a dedicated region of
+//! memory.  This has been replaced by `k_heap`.  [`MemPool`] provides the old style of interface,
+//! `alloc` and `free`, on top of a `k_heap` that manages a buffer contained within the pool
+//! itself.
+//!
+//! `MemPool` can be declared as a static, and is initialized on first use:
+//!
+//! ```ignore
+//! static POOL: MemPool<4096> = MemPool::new();
+//!
+//! let block = POOL.alloc(Layout::new::<[u32; 16]>(), Forever).unwrap();
+//! // ...
+//! unsafe { POOL.free(block) };
+//! ```
+//!
+//! `MemPool` also implements [`GlobalAlloc`].  The `core::alloc::Allocator` trait, which would allow
+//! `Vec<T, &MemPool>`, is not yet stable, and is therefore not implemented.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::fmt;
+use core::mem;
+use core::ptr::{null_mut, NonNull};
+
+use crate::object::{KOBJ_INITIALIZED, KOBJ_INITING, KOBJ_UNINITIALIZED};
+use crate::raw::{k_heap, k_heap_aligned_alloc, k_heap_free, k_heap_init};
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::time::{Duration, NoWait, Timeout};
+
+/// A memory pool of `SIZE` bytes, backed by a Zephyr `k_heap`.
+///
+/// Note that some of the `SIZE` bytes are used by the heap for bookkeeping, so the total amount
+/// that can be allocated will be somewhat less than this.
+pub struct MemPool<const SIZE: usize> {
+    /// The Zephyr heap that manages `mem`.
+    heap: UnsafeCell<k_heap>,
+    /// The memory managed by the heap.  The heap requires this to be 8-byte aligned.
+    mem: UnsafeCell<Aligned<SIZE>>,
+    /// Initialization state, using the same states as the kernel objects.
+    init: AtomicUsize,
+}
+
+#[repr(C, align(8))]
+struct Aligned<const SIZE: usize>([u8; SIZE]);
+
+// SAFETY: The `k_heap` does its own locking, and can be used from any thread.
+unsafe impl<const SIZE: usize> Sync for MemPool<SIZE> {}
+unsafe impl<const SIZE: usize> Send for MemPool<SIZE> {}
+
+impl<const SIZE: usize> MemPool<SIZE> {
+    /// Construct a new memory pool.  Heap initialization is deferred until the first allocation.
+    pub const fn new() -> Self {
+        MemPool {
+            // SAFETY: Zeroed memory is a valid uninitialized k_heap, and it is initialized before
+            // being used.
+            heap: unsafe { mem::zeroed() },
+            mem: UnsafeCell::new(Aligned([0; SIZE])),
+            init: AtomicUsize::new(KOBJ_UNINITIALIZED),
+        }
+    }
+
+    /// Get the heap, initializing it if that hasn't happened yet.
+    fn heap(&self) -> *mut k_heap {
+        loop {
+            match self.init.compare_exchange(
+                KOBJ_UNINITIALIZED,
+                KOBJ_INITING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    unsafe {
+                        // SAFETY: The atomic ensures only a single thread performs the init.
+                        k_heap_init(self.heap.get(), self.mem.get() as *mut c_void, SIZE);
+                    }
+                    self.init.store(KOBJ_INITIALIZED, Ordering::Release);
+                    break;
+                }
+                Err(KOBJ_INITIALIZED) => break,
+                // Another thread is initializing, let it finish.  This sleeps, rather than
+                // yielding, so that a lower priority thread can run.
+                Err(_) => {
+                    crate::time::sleep(Duration::from_ticks(1));
+                }
+            }
+        }
+        self.heap.get()
+    }
+
+    /// Allocate a block of memory from the pool.
+    ///
+    /// If there is no memory available, waits up to `timeout` for another thread to free enough.
+    /// Returns `None` if the allocation could not be satisfied in that time.
+    pub fn alloc(&self, layout: Layout, timeout: impl Into<Timeout>) -> Option<NonNull<u8>> {
+        let timeout: Timeout = timeout.into();
+        let ptr = unsafe {
+            k_heap_aligned_alloc(self.heap(), layout.align(), layout.size(), timeout.0)
+        };
+        NonNull::new(ptr as *mut u8)
+    }
+
+    /// Return a block of memory to the pool.
+    ///
+    /// # Safety
+    ///
+    /// The block must have been returned by [`alloc`] on this same pool, and not already freed.
+    ///
+    /// [`alloc`]: Self::alloc
+    pub unsafe fn free(&self, block: NonNull<u8>) {
+        k_heap_free(self.heap(), block.as_ptr() as *mut c_void)
+    }
+}
+
+impl<const SIZE: usize> Default for MemPool<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<const SIZE: usize> GlobalAlloc for MemPool<SIZE> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.alloc(layout, NoWait)
+            .map(|p| p.as_ptr())
+            .unwrap_or(null_mut())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+        if let Some(ptr) = NonNull::new(ptr) {
+            self.free(ptr)
+        }
+    }
+}
+
+impl<const SIZE: usize> fmt::Debug for MemPool<SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::MemPool<{}>", SIZE)
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lightweight wrapper around Zephyr's `k_pipe`.
+//!
+//! A pipe carries a stream of bytes between threads, through a ring buffer.  Writers block while
+//! the buffer is full, and readers while it is empty, up to their timeouts.
+//!
+//! Pipes are declared statically, with the size of their buffer:
+//!
+//! ```ignore
+//! kobj_define! {
+//!     static LOG_PIPE: StaticPipe<256>;
+//! }
+//!
+//! let pipe = LOG_PIPE.init_once(()).unwrap();
+//! pipe.write(b"hello", Forever)?;
+//! ```
+//!
+//! As with thread stacks, `kobj_define!` declares the buffer as a separate static, alongside the
+//! pipe, so the `k_pipe` can be placed in its kernel object section while the buffer stays in
+//! zero-initialized memory.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem;
+use core::ptr;
+
+use zephyr_sys::{k_pipe, k_pipe_close, k_pipe_init, k_pipe_read, k_pipe_reset, k_pipe_write};
+
+use crate::error::{ok_or_errno_val, ZResult};
+use crate::object::{Fixed, StaticKernelObject, Wrapped};
+use crate::sync::atomic::AtomicUsize;
+use crate::time::Timeout;
+
+/// A wrapper around a Zephyr `k_pipe` object.
+pub struct Pipe {
+    pub(crate) item: Fixed<k_pipe>,
+}
+
+unsafe impl Sync for Pipe {}
+unsafe impl Send for Pipe {}
+
+impl Pipe {
+    /// Write up to `data.len()` bytes to the pipe.
+    ///
+    /// Waits up to `timeout` for room in the buffer, and returns the number of bytes written,
+    /// which may be less than asked for if the timeout is reached part way.
+    pub fn write<T>(&self, data: &[u8], timeout: T) -> ZResult<usize>
+    where
+        T: Into<Timeout>,
+    {
+        let timeout: Timeout = timeout.into();
+        let ret = unsafe { k_pipe_write(self.item.get(), data.as_ptr(), data.len(), timeout.0) };
+        ok_or_errno_val(ret, ret as usize)
+    }
+
+    /// Read up to `buf.len()` bytes from the pipe.
+    ///
+    /// Waits up to `timeout` for data, and returns the number of bytes read.
+    pub fn read<T>(&self, buf: &mut [u8], timeout: T) -> ZResult<usize>
+    where
+        T: Into<Timeout>,
+    {
+        let timeout: Timeout = timeout.into();
+        let ret = unsafe { k_pipe_read(self.item.get(), buf.as_mut_ptr(), buf.len(), timeout.0) };
+        ok_or_errno_val(ret, ret as usize)
+    }
+
+    /// Discard the data in the pipe, and make any waiting readers and writers fail with
+    /// [`ZephyrError::Canceled`].
+    ///
+    /// [`ZephyrError::Canceled`]: crate::error::ZephyrError::Canceled
+    pub fn reset(&self) {
+        unsafe { k_pipe_reset(self.item.get()) }
+    }
+
+    /// Close the pipe.  Writes fail from then on, and reads fail once the pipe is empty.
+    pub fn close(&self) {
+        unsafe { k_pipe_close(self.item.get()) }
+    }
+}
+
+impl fmt::Debug for Pipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sys::Pipe {:?}", self.item.get())
+    }
+}
+
+/// The buffer of a static pipe.
+///
+/// This is declared by [`kobj_define`](crate::kobj_define), and shouldn't be used directly.
+#[doc(hidden)]
+pub struct PipeBuffer<const SIZE: usize> {
+    pub data: UnsafeCell<[u8; SIZE]>,
+}
+
+unsafe impl<const SIZE: usize> Sync for PipeBuffer<SIZE> {}
+
+/// A `k_pipe`, along with the buffer it will be given when initialized.
+#[doc(hidden)]
+pub struct RawStaticPipe {
+    pipe: k_pipe,
+    buffer: *mut u8,
+    size: usize,
+}
+
+/// A statically defined Zephyr `k_pipe`.
+///
+/// This should be declared as follows:
+/// ```
+/// kobj_define! {
+///     static MY_PIPE: StaticPipe<256>;
+/// }
+///
+/// let my_pipe = MY_PIPE.init_once(()).unwrap();
+///
+/// my_pipe.write(b"data", Forever);
+/// ```
+pub type StaticPipe = StaticKernelObject<RawStaticPipe>;
+
+unsafe impl Sync for StaticPipe {}
+
+impl Wrapped for StaticPipe {
+    type T = Pipe;
+
+    /// Pipe initializers take no argument, as the buffer is declared with the pipe.
+    type I = ();
+
+    fn get_wrapped(&self, _arg: Self::I) -> Pipe {
+        let raw = self.value.get();
+        let pipe = unsafe { ptr::addr_of_mut!((*raw).pipe) };
+        unsafe {
+            k_pipe_init(pipe, (*raw).buffer, (*raw).size);
+        }
+        Pipe {
+            item: Fixed::Static(pipe),
+        }
+    }
+}
+
+impl StaticPipe {
+    /// Construct a StaticPipe around its buffer.
+    ///
+    /// This is not intended to be directly called, but is used by the [`kobj_define`] macro.
+    ///
+    /// [`kobj_define`]: crate::kobj_define
+    #[doc(hidden)]
+    pub const fn new_from<const SIZE: usize>(buffer: &PipeBuffer<SIZE>) -> Self {
+        Self {
+            value: UnsafeCell::new(RawStaticPipe {
+                pipe: unsafe { mem::zeroed() },
+                buffer: buffer.data.get() as *mut u8,
+                size: SIZE,
+            }),
+            init: AtomicUsize::new(0),
+        }
+    }
+}
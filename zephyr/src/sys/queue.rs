@@ -13,7 +13,7 @@ use zephyr_sys::{k_queue, k_queue_append, k_queue_get, k_queue_init};
 
 #[cfg(CONFIG_RUST_ALLOC)]
 use crate::error::Result;
-use crate::object::{Fixed, StaticKernelObject, Wrapped};
+use crate::object::{Fixed, SharedWrapped, StaticKernelObject, Wrapped};
 use crate::time::Timeout;
 
 /// A wrapper around a Zephyr `k_queue` object.
@@ -104,6 +104,14 @@ impl Wrapped for StaticKernelObject<k_queue> {
     }
 }
 
+impl SharedWrapped for StaticKernelObject<k_queue> {
+    fn get_existing(&self) -> Queue {
+        Queue {
+            item: Fixed::Static(self.value.get()),
+        }
+    }
+}
+
 /// A statically defined Zephyr `k_queue`.
 ///
 /// This should be declared as follows:
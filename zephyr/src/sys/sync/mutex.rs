@@ -8,7 +8,7 @@
 //!
 //! [`object`]: crate::object
 
-use crate::object::{Fixed, StaticKernelObject, Wrapped};
+use crate::object::{Fixed, SharedWrapped, StaticKernelObject, Wrapped};
 use crate::sys::K_FOREVER;
 use crate::{
     error::{to_result_void, Result},
@@ -46,7 +46,7 @@ use core::mem;
 /// [`sync::Mutex`]: http://example.com/TODO
 pub struct Mutex {
     /// The raw Zephyr mutex.
-    item: Fixed<k_mutex>,
+    pub(crate) item: Fixed<k_mutex>,
 }
 
 impl Mutex {
@@ -118,6 +118,14 @@ impl Wrapped for StaticKernelObject<k_mutex> {
     }
 }
 
+impl SharedWrapped for StaticKernelObject<k_mutex> {
+    fn get_existing(&self) -> Mutex {
+        Mutex {
+            item: Fixed::Static(self.value.get()),
+        }
+    }
+}
+
 impl fmt::Debug for Mutex {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "sys::Mutex {:?}", self.item.get())
@@ -167,7 +175,19 @@ impl Condvar {
         }
     }
 
-    // TODO: timeout.
+    /// Wait for someone else using this mutex/condvar pair to notify, with a timeout.
+    ///
+    /// The same caveats as [`wait`] apply.  Returns an error if the timeout expires before a
+    /// notification is received.
+    ///
+    /// [`wait`]: Self::wait
+    pub fn wait_timeout<T>(&self, lock: &Mutex, timeout: T) -> Result<()>
+    where
+        T: Into<Timeout>,
+    {
+        let timeout: Timeout = timeout.into();
+        to_result_void(unsafe { k_condvar_wait(self.item.get(), lock.item.get(), timeout.0) })
+    }
 
     /// Wake a single thread waiting on this condition variable.
     pub fn notify_one(&self) {
@@ -206,3 +226,11 @@ impl Wrapped for StaticCondvar {
         }
     }
 }
+
+impl SharedWrapped for StaticCondvar {
+    fn get_existing(&self) -> Condvar {
+        Condvar {
+            item: Fixed::Static(self.value.get()),
+        }
+    }
+}
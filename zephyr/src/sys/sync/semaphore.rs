@@ -22,6 +22,7 @@ use core::pin::Pin;
 #[cfg(CONFIG_RUST_ALLOC)]
 use core::task::{Context, Poll};
 
+use zephyr_macros::KernelObject;
 #[cfg(CONFIG_RUST_ALLOC)]
 use zephyr_sys::ETIMEDOUT;
 
@@ -30,8 +31,9 @@ use crate::kio::ContextExt;
 #[cfg(CONFIG_RUST_ALLOC)]
 use crate::time::NoWait;
 use crate::{
-    error::{to_result_void, Result},
-    object::{Fixed, StaticKernelObject, Wrapped},
+    error::{to_result_void, Result, ZResult},
+    irq::IrqContext,
+    object::{Fixed, SharedWrapped, StaticKernelObject},
     raw::{k_sem, k_sem_count_get, k_sem_give, k_sem_init, k_sem_reset, k_sem_take},
     time::Timeout,
 };
@@ -39,6 +41,13 @@ use crate::{
 pub use crate::raw::K_SEM_MAX_LIMIT;
 
 /// A zephyr `k_sem` usable from safe Rust code.
+///
+/// The initializer for a [`StaticSemaphore`] is the initial count, and the count limit (which can
+/// be [`K_SEM_MAX_LIMIT`]).  Initializing it returns an error if the limit is zero, or less than
+/// the count.
+#[derive(KernelObject)]
+#[kobj(init_fn = "k_sem_init", linker_section = "._k_sem.static")]
+#[kobj(args = "(c_uint, c_uint)", errno)]
 pub struct Semaphore {
     /// The raw Zephyr `k_sem`.
     pub(crate) item: Fixed<k_sem>,
@@ -101,6 +110,29 @@ impl Semaphore {
         unsafe { k_sem_give(self.item.get()) }
     }
 
+    /// Give a semaphore, from an interrupt handler.
+    ///
+    /// This is the same operation as [`give`], which Zephyr allows from any context, but requiring
+    /// the [`IrqContext`] makes it explicit that this call is intended to be made from an ISR.
+    ///
+    /// [`give`]: Self::give
+    pub fn give_in_isr(&self, _ctx: IrqContext<'_>) {
+        unsafe { k_sem_give(self.item.get()) }
+    }
+
+    /// Give a semaphore, from an interrupt handler, without an [`IrqContext`].
+    ///
+    /// # Safety
+    ///
+    /// Must only be called from interrupt context.  Prefer [`give_in_isr`] where a token is
+    /// available.
+    ///
+    /// [`give_in_isr`]: Self::give_in_isr
+    pub unsafe fn give_from_isr(&self) {
+        debug_assert!(crate::irq::in_isr(), "give_from_isr called from thread context");
+        k_sem_give(self.item.get())
+    }
+
     /// Resets a semaphor's count to zero.
     ///
     /// This resets the count to zero.  Any outstanding [`take`] calls will be aborted with
@@ -115,7 +147,18 @@ impl Semaphore {
     ///
     /// Returns the current count.
     pub fn count_get(&self) -> usize {
-        unsafe { k_sem_count_get(self.item.get()) as usize }
+        self.count() as usize
+    }
+
+    /// Get a semaphore's count.
+    ///
+    /// The same as [`count_get`], but returning the count as the `u32` Zephyr uses.  This is
+    /// useful for monitoring backpressure, such as how many items are waiting to be handled.
+    /// Note that the count may change as soon as this returns.
+    ///
+    /// [`count_get`]: Self::count_get
+    pub fn count(&self) -> u32 {
+        unsafe { k_sem_count_get(self.item.get()) }
     }
 }
 
@@ -153,33 +196,11 @@ impl<'a> Future for SemTake<'a> {
     }
 }
 
-/// A static Zephyr `k_sem`.
-///
-/// This is intended to be used from within the `kobj_define!` macro.  It declares a static ksem
-/// that will be properly registered with the Zephyr kernel object system.  Call [`init_once`] to
-/// get the [`Semaphore`] that is represents.
-///
-/// [`init_once`]: StaticKernelObject::init_once
-pub type StaticSemaphore = StaticKernelObject<k_sem>;
-
-unsafe impl Sync for StaticSemaphore {}
-
-impl Wrapped for StaticKernelObject<k_sem> {
-    type T = Semaphore;
-
-    /// The initializer for Semaphores is the initial count, and the count limit (which can be
-    /// K_SEM_MAX_LIMIT, re-exported here.
-    type I = (c_uint, c_uint);
-
-    // TODO: Thoughts about how to give parameters to the initialzation.
-    fn get_wrapped(&self, arg: Self::I) -> Semaphore {
-        let ptr = self.value.get();
-        unsafe {
-            k_sem_init(ptr, arg.0, arg.1);
-        }
-        Semaphore {
-            item: Fixed::Static(ptr),
-        }
+impl SharedWrapped for StaticKernelObject<k_sem> {
+    fn get_existing(&self) -> ZResult<Semaphore> {
+        Ok(Semaphore {
+            item: Fixed::Static(self.value.get()),
+        })
     }
 }
 
@@ -38,6 +38,9 @@
 #[cfg(CONFIG_RUST_ALLOC)]
 extern crate alloc;
 
+#[cfg(CONFIG_RUST_ALLOC)]
+pub mod pool;
+
 #[cfg(CONFIG_RUST_ALLOC)]
 use alloc::boxed::Box;
 use core::{
@@ -46,16 +49,25 @@ use core::{
     mem,
 };
 
+#[cfg(CONFIG_SCHED_CPU_MASK)]
+use super::K_FOREVER;
 use super::K_NO_WAIT;
 use zephyr_sys::{
-    k_thread, k_thread_create, k_thread_entry_t, k_thread_name_set, k_tid_t,
-    z_thread_stack_element, ZR_STACK_ALIGN, ZR_STACK_RESERVED,
+    k_thread, k_thread_create, k_thread_entry_t, k_thread_join, k_thread_name_set,
+    k_thread_priority_set, k_tid_t, z_thread_stack_element, ZR_STACK_ALIGN, ZR_STACK_RESERVED,
+};
+#[cfg(CONFIG_SCHED_CPU_MASK)]
+use zephyr_sys::{
+    k_thread_cpu_mask_clear, k_thread_cpu_mask_enable, k_thread_cpu_mask_enable_all,
+    k_thread_cpu_pin, k_thread_start,
 };
 
 use crate::{
     align::AlignAs,
+    error::{ok_or_errno, ZResult, ZephyrError},
     object::{StaticKernelObject, Wrapped},
     sync::atomic::AtomicUsize,
+    time::Timeout,
 };
 
 /// Adjust the stack size for alignment.  Note that, unlike the C code, we don't include the
@@ -91,6 +103,19 @@ pub struct ThreadStack {
     pub size: usize,
 }
 
+impl ThreadStack {
+    /// The total memory used by a stack declared as `ThreadStack<SIZE>` with `kobj_define!`.
+    ///
+    /// This adds what Zephyr reserves beyond the usable stack, such as an MPU guard, and the
+    /// padding for the stack's alignment, so it can be used for RAM budgets, or checked with
+    /// [`static_assert!`](crate::static_assert) on tight systems.  Thread local storage, if
+    /// enabled, is carved out of the usable stack, so is not added.
+    pub const fn alloc_size(size: usize) -> usize {
+        let elements = (stack_len(size) + ZR_STACK_RESERVED).next_multiple_of(ZR_STACK_ALIGN);
+        elements * mem::size_of::<z_thread_stack_element>()
+    }
+}
+
 #[doc(hidden)]
 pub struct StaticThreadStack {
     pub base: *mut z_thread_stack_element,
@@ -175,9 +200,12 @@ impl StaticKernelObject<StaticThreadStack> {
 ///
 /// This wraps a `k_thread` type within Rust.  This value is returned from
 /// [`StaticThread::init_once`] and represents an initialized thread that hasn't been started.
+/// Spawning the thread returns it again, as a handle to the running thread.
 pub struct Thread {
     raw: *mut k_thread,
     stack: ThreadStack,
+    /// Set once the thread has been spawned.
+    started: bool,
 
     /// The initial priority of this thread.
     priority: c_int,
@@ -185,6 +213,32 @@ pub struct Thread {
     options: u32,
     /// The name to be given to the thread, if desired.
     name: Option<&'static CStr>,
+    /// The CPUs this thread may run on, if this has been changed from the default.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    cpu_mask: Option<u32>,
+}
+
+/// An identifier for a thread.
+///
+/// Unlike a [`Thread`], this can be copied, and used to refer to a thread from elsewhere.  Use
+/// [`Thread::id`] to get the id of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadId(k_tid_t);
+
+// SAFETY: The id is only passed to kernel calls, which can be made from any thread.
+unsafe impl Send for ThreadId {}
+unsafe impl Sync for ThreadId {}
+
+impl ThreadId {
+    /// The id of the currently running thread.
+    pub fn current() -> ThreadId {
+        ThreadId(unsafe { zephyr_sys::k_current_get() })
+    }
+
+    /// The underlying Zephyr thread id.
+    pub fn as_raw(&self) -> k_tid_t {
+        self.0
+    }
 }
 
 /// A statically defined thread.
@@ -199,17 +253,31 @@ impl Wrapped for StaticKernelObject<k_thread> {
         Thread {
             raw: self.value.get(),
             stack,
+            started: false,
             priority: 0,
             options: 0,
             name: None,
+            #[cfg(CONFIG_SCHED_CPU_MASK)]
+            cpu_mask: None,
         }
     }
 }
 
 impl Thread {
-    /// Set the priority the thread will be created at.
+    /// The id of this thread, which can still be used to refer to it after it has been spawned.
+    pub fn id(&self) -> ThreadId {
+        ThreadId(self.raw)
+    }
+
+    /// Set the priority of the thread.
+    ///
+    /// Before the thread is spawned, this is the priority it will be created at.  Afterwards, this
+    /// changes the priority of the running thread.
     pub fn set_priority(&mut self, priority: c_int) {
         self.priority = priority;
+        if self.started {
+            unsafe { k_thread_priority_set(self.raw, priority) }
+        }
     }
 
     /// Set the value of the options passed to thread creation.
@@ -220,9 +288,98 @@ impl Thread {
     /// Set a name for this thread.
     ///
     /// Attempts to set the name of this thread, if Zephyr if configured to do so.  Has no effect
-    /// otherwise.
+    /// otherwise.  This may be called before or after the thread is spawned.
     pub fn set_name(&mut self, name: &'static CStr) {
         self.name = Some(name);
+        if self.started {
+            self.set_thread_name(self.raw);
+        }
+    }
+
+    /// Wait for a spawned thread to exit.
+    ///
+    /// Returns [`ZephyrError::Again`] if the thread is still running at the end of the timeout,
+    /// and [`ZephyrError::Busy`] if called on a thread that has not been spawned.
+    pub fn join(&self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        if !self.started {
+            return Err(ZephyrError::Busy);
+        }
+        let timeout: Timeout = timeout.into();
+        ok_or_errno(unsafe { k_thread_join(self.raw, timeout.0) })
+    }
+
+    /// Restrict this thread to only run on the given CPU.
+    ///
+    /// This, and the other `cpu_` methods, configure the thread's CPU mask, which is applied when
+    /// the thread is spawned, before it starts running.  Zephyr does not allow the mask to be
+    /// changed on a running thread, so these return [`ZephyrError::Busy`] once it has been spawned.
+    ///
+    /// A mask that would leave the thread with no CPU to run on returns [`ZephyrError::Invalid`],
+    /// as does a CPU number that is out of range.  With `CONFIG_SCHED_CPU_MASK_PIN_ONLY`, only a
+    /// mask of a single CPU is allowed, and others return [`ZephyrError::NotSupported`].  In each
+    /// case, the mask is left unchanged.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    pub fn cpu_pin(&mut self, cpu: u32) -> ZResult<()> {
+        self.set_cpu_mask(Self::cpu_bit(cpu)?)
+    }
+
+    /// Allow this thread to run on the given CPU, in addition to those already enabled.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    pub fn cpu_mask_enable(&mut self, cpu: u32) -> ZResult<()> {
+        self.set_cpu_mask(self.current_cpu_mask() | Self::cpu_bit(cpu)?)
+    }
+
+    /// Prevent this thread from running on the given CPU.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    pub fn cpu_mask_disable(&mut self, cpu: u32) -> ZResult<()> {
+        self.set_cpu_mask(self.current_cpu_mask() & !Self::cpu_bit(cpu)?)
+    }
+
+    /// Allow this thread to run on any CPU.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    pub fn cpu_mask_enable_all(&mut self) -> ZResult<()> {
+        self.set_cpu_mask(Self::all_cpus())
+    }
+
+    /// Check, and then set, the mask to be applied when the thread is spawned.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    fn set_cpu_mask(&mut self, mask: u32) -> ZResult<()> {
+        if self.started {
+            return Err(ZephyrError::Busy);
+        }
+        if mask == 0 {
+            return Err(ZephyrError::Invalid);
+        }
+        #[cfg(CONFIG_SCHED_CPU_MASK_PIN_ONLY)]
+        if mask.count_ones() != 1 {
+            return Err(ZephyrError::NotSupported);
+        }
+        self.cpu_mask = Some(mask);
+        Ok(())
+    }
+
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    fn all_cpus() -> u32 {
+        let num = crate::kconfig::CONFIG_MP_MAX_NUM_CPUS as u32;
+        if num >= 32 {
+            !0
+        } else {
+            (1 << num) - 1
+        }
+    }
+
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    fn current_cpu_mask(&self) -> u32 {
+        self.cpu_mask.unwrap_or_else(Self::all_cpus)
+    }
+
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    fn cpu_bit(cpu: u32) -> ZResult<u32> {
+        if cpu < crate::kconfig::CONFIG_MP_MAX_NUM_CPUS as u32 {
+            Ok(1 << cpu)
+        } else {
+            Err(ZephyrError::Invalid)
+        }
     }
 
     /// Simple thread spawn.  This is unsafe because of the raw values being used.  This can be
@@ -234,14 +391,58 @@ impl Thread {
     /// values through this interface is difficult to get right, and it is generally recommended to
     /// use [`spawn`].
     ///
+    /// Returns the thread, which can be used to [`join`] it, or to change its priority or name.
+    ///
     /// [`spawn`]: Self::spawn
+    /// [`join`]: Self::join
     pub unsafe fn simple_spawn(
         mut self,
         child: k_thread_entry_t,
         p1: *mut c_void,
         p2: *mut c_void,
         p3: *mut c_void,
+    ) -> Thread {
+        self.create(child, p1, p2, p3);
+        self
+    }
+
+    #[cfg(CONFIG_RUST_ALLOC)]
+    /// Spawn a thread, with a closure.
+    ///
+    /// This requires allocation to be able to safely pass the closure to the other thread.  As with
+    /// [`simple_spawn`](Self::simple_spawn), the running thread is returned.
+    pub fn spawn<F: FnOnce() + Send + 'static>(mut self, child: F) -> Thread {
+        use core::ptr::null_mut;
+
+        let child: closure::Closure = Box::new(child);
+        let child = Box::into_raw(Box::new(closure::ThreadData { closure: child }));
+        unsafe {
+            self.create(
+                Some(closure::child),
+                child as *mut c_void,
+                null_mut(),
+                null_mut(),
+            );
+        }
+        self
+    }
+
+    /// Create the thread, applying the configured settings, and start it.
+    ///
+    /// If a CPU mask has been set, the thread is created without starting, so that the mask can be
+    /// applied first.
+    unsafe fn create(
+        &mut self,
+        child: k_thread_entry_t,
+        p1: *mut c_void,
+        p2: *mut c_void,
+        p3: *mut c_void,
     ) {
+        #[cfg(CONFIG_SCHED_CPU_MASK)]
+        let delay = if self.cpu_mask.is_some() { K_FOREVER } else { K_NO_WAIT };
+        #[cfg(not(CONFIG_SCHED_CPU_MASK))]
+        let delay = K_NO_WAIT;
+
         let tid = k_thread_create(
             self.raw,
             self.stack.base,
@@ -252,36 +453,38 @@ impl Thread {
             p3,
             self.priority,
             self.options,
-            K_NO_WAIT,
+            delay,
         );
 
         self.set_thread_name(tid);
-    }
 
-    #[cfg(CONFIG_RUST_ALLOC)]
-    /// Spawn a thread, with a closure.
-    ///
-    /// This requires allocation to be able to safely pass the closure to the other thread.
-    pub fn spawn<F: FnOnce() + Send + 'static>(mut self, child: F) {
-        use core::ptr::null_mut;
+        #[cfg(CONFIG_SCHED_CPU_MASK)]
+        if let Some(mask) = self.cpu_mask {
+            // The mask was checked when it was set, and the thread hasn't started, so Zephyr has
+            // no reason to reject it.
+            let result = Self::apply_cpu_mask(tid, mask);
+            debug_assert!(result.is_ok(), "Setting cpu mask: {:?}", result);
+            k_thread_start(tid);
+        }
 
-        let child: closure::Closure = Box::new(child);
-        let child = Box::into_raw(Box::new(closure::ThreadData { closure: child }));
-        unsafe {
-            let tid = k_thread_create(
-                self.raw,
-                self.stack.base,
-                self.stack.size,
-                Some(closure::child),
-                child as *mut c_void,
-                null_mut(),
-                null_mut(),
-                self.priority,
-                self.options,
-                K_NO_WAIT,
-            );
+        self.started = true;
+    }
 
-            self.set_thread_name(tid);
+    /// Apply the CPU mask to a thread that has been created, but not started.
+    #[cfg(CONFIG_SCHED_CPU_MASK)]
+    unsafe fn apply_cpu_mask(tid: k_tid_t, mask: u32) -> ZResult<()> {
+        if mask.count_ones() == 1 {
+            ok_or_errno(k_thread_cpu_pin(tid, mask.trailing_zeros() as c_int))
+        } else if mask == Self::all_cpus() {
+            ok_or_errno(k_thread_cpu_mask_enable_all(tid))
+        } else {
+            ok_or_errno(k_thread_cpu_mask_clear(tid))?;
+            for cpu in 0..32 {
+                if mask & (1 << cpu) != 0 {
+                    ok_or_errno(k_thread_cpu_mask_enable(tid, cpu))?;
+                }
+            }
+            Ok(())
         }
     }
 
@@ -0,0 +1,181 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A pool of reusable threads.
+//!
+//! The threads and stacks in Zephyr are generally statically allocated, which makes it awkward to
+//! run short lived work on threads of its own.  A [`SpawnPool`] takes ownership of a fixed set of
+//! initialized, but not yet started, threads, and runs closures on whichever one is free.  Once the
+//! closure returns, its thread becomes available for another spawn.
+//!
+//! ```
+//! kobj_define! {
+//!     static WORKERS: [StaticThread; 4];
+//!     static WORKER_STACKS: [ThreadStack<2048>; 4];
+//! }
+//!
+//! let threads = core::array::from_fn(|i| {
+//!     WORKERS[i].init_once(WORKER_STACKS[i].init_once(()).unwrap()).unwrap()
+//! });
+//! let pool = SpawnPool::new(threads);
+//! let handle = pool.spawn(|| { /* ... */ }).unwrap();
+//! handle.join(Forever).unwrap();
+//! ```
+//!
+//! The pool is built from [`Thread`]s, rather than just stacks, as a stack is always paired with a
+//! `k_thread` in this crate.  Any priority, name, or other settings made on the `Thread` before
+//! giving it to the pool are used for every closure that runs on it.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::fmt;
+use core::ptr::null_mut;
+
+use zephyr_sys::k_thread_join;
+
+use super::{closure, Thread};
+use crate::error::{ZResult, ZephyrError};
+use crate::sync::atomic::{AtomicU8, Ordering};
+use crate::sync::Arc;
+use crate::sys::sync::Semaphore;
+use crate::sys::K_FOREVER;
+use crate::time::Timeout;
+
+/// The thread has never been started.
+const FREE: u8 = 0;
+/// A closure is running on the thread (or one is being started).
+const RUNNING: u8 = 1;
+/// The closure has returned.  The thread may not have fully exited yet.
+const FINISHED: u8 = 2;
+
+/// A fixed set of `N` threads that closures can be spawned onto.
+pub struct SpawnPool<const N: usize> {
+    slots: [Slot; N],
+}
+
+struct Slot {
+    /// The state of this slot, shared with the running closure so that it can mark completion.
+    state: Arc<AtomicU8>,
+    /// The thread.  Only accessed by the spawner that moved `state` to `RUNNING`.
+    thread: UnsafeCell<Thread>,
+}
+
+// SAFETY: The threads are only touched by a single spawner, as arbitrated by the slot state.
+unsafe impl<const N: usize> Send for SpawnPool<N> {}
+unsafe impl<const N: usize> Sync for SpawnPool<N> {}
+
+impl<const N: usize> SpawnPool<N> {
+    /// Create a pool from the given threads.
+    ///
+    /// The threads should not have been started.
+    pub fn new(threads: [Thread; N]) -> SpawnPool<N> {
+        SpawnPool {
+            slots: threads.map(|thread| Slot {
+                state: Arc::new(AtomicU8::new(FREE)),
+                thread: UnsafeCell::new(thread),
+            }),
+        }
+    }
+
+    /// Run the closure on a free thread from the pool.
+    ///
+    /// Returns [`PoolFull`] if all of the threads are busy.
+    pub fn spawn<F>(&self, child: F) -> Result<PoolHandle, PoolFull>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let slot = self.claim().ok_or(PoolFull)?;
+
+        let done = Arc::new(Semaphore::new(0, 1).expect("Allocating semaphore"));
+        let state = slot.state.clone();
+        let thread_done = done.clone();
+        let child: closure::Closure = Box::new(move || {
+            child();
+            state.store(FINISHED, Ordering::Release);
+            thread_done.give();
+        });
+        let child = Box::into_raw(Box::new(closure::ThreadData { closure: child }));
+
+        unsafe {
+            // SAFETY: The slot was claimed, so nothing else is using this thread.
+            let thread = &mut *slot.thread.get();
+            thread.create(
+                Some(closure::child),
+                child as *mut c_void,
+                null_mut(),
+                null_mut(),
+            );
+        }
+
+        Ok(PoolHandle { done })
+    }
+
+    /// Find an available slot, and mark it as running.
+    fn claim(&self) -> Option<&Slot> {
+        for slot in &self.slots {
+            if slot
+                .state
+                .compare_exchange(FREE, RUNNING, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return Some(slot);
+            }
+            if slot
+                .state
+                .compare_exchange(FINISHED, RUNNING, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                // The closure has returned, but the thread may still be exiting.  This won't wait
+                // long, but the thread object can't be reused until it is done.
+                unsafe {
+                    k_thread_join((*slot.thread.get()).raw, K_FOREVER);
+                }
+                return Some(slot);
+            }
+        }
+        None
+    }
+}
+
+impl<const N: usize> fmt::Debug for SpawnPool<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SpawnPool<{}>", N)
+    }
+}
+
+/// A handle to a closure spawned on a [`SpawnPool`].
+pub struct PoolHandle {
+    done: Arc<Semaphore>,
+}
+
+impl PoolHandle {
+    /// Wait for the closure to finish.
+    ///
+    /// Returns an error if the closure has not returned before the timeout.  Once the closure has
+    /// finished, this will always return `Ok`.
+    pub fn join(&self, timeout: impl Into<Timeout>) -> ZResult<()> {
+        self.done.take(timeout).map_err(ZephyrError::from)?;
+        // Leave the semaphore given, so that further joins also succeed.
+        self.done.give();
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PoolHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PoolHandle")
+    }
+}
+
+/// Returned from [`SpawnPool::spawn`] when all of the threads are in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolFull;
+
+impl fmt::Display for PoolFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "all threads in the pool are busy")
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! A test harness for running Rust tests on Zephyr.
+//!
+//! Functions marked with `#[zephyr_test]` are collected by the linker into a table, and
+//! [`zephyr_test_runner!`] runs each of them in turn, on a thread of its own:
+//!
+//! ```ignore
+//! use zephyr::zephyr_test;
+//!
+//! #[zephyr_test]
+//! fn addition() {
+//!     assert_eq!(1 + 1, 2);
+//! }
+//!
+//! #[zephyr::main]
+//! fn main() {
+//!     zephyr::zephyr_test_runner!();
+//! }
+//! ```
+//!
+//! A test passes if it returns.  If it panics, the panic handler aborts the test thread, rather
+//! than halting the system, and the test is counted as failed.  The runner prints a line for each
+//! test, and a summary, ending with "All tests passed" if all of them did, which is what the
+//! console harness in the tests' `testcase.yaml` looks for.
+//!
+//! The tests are run in the order of their names.  Each test gets a stack of
+//! `CONFIG_RUST_TEST_STACK_SIZE` bytes, and runs at the priority of the thread calling the runner.
+//!
+//! Requires `CONFIG_RUST_TEST_HARNESS`.
+
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+use core::slice;
+
+use crate::raw;
+use crate::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use crate::sys::K_FOREVER;
+
+/// An entry in the table of tests, made by `#[zephyr_test]`.
+#[doc(hidden)]
+#[repr(C)]
+pub struct TestCase {
+    pub name: &'static str,
+    pub func: fn(),
+}
+
+extern "C" {
+    static _rust_test_list_start: TestCase;
+    static _rust_test_list_end: TestCase;
+}
+
+crate::kobj_define! {
+    static TEST_THREAD: StaticThread;
+    static TEST_STACK: ThreadStack<{ crate::kconfig::CONFIG_RUST_TEST_STACK_SIZE as usize }>;
+}
+
+/// The thread running the current test, while there is one.
+static TEST_TID: AtomicPtr<raw::k_thread> = AtomicPtr::new(ptr::null_mut());
+
+/// Set when the current test panics.
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+/// All of the tests declared in the application.
+fn tests() -> &'static [TestCase] {
+    // SAFETY: The linker places the test entries contiguously between these symbols.
+    unsafe {
+        let start = ptr::addr_of!(_rust_test_list_start);
+        let end = ptr::addr_of!(_rust_test_list_end);
+        let len = (end as usize - start as usize) / mem::size_of::<TestCase>();
+        slice::from_raw_parts(start, len)
+    }
+}
+
+/// Run all of the tests, printing the results.  Returns the number that failed.
+///
+/// This is what [`zephyr_test_runner!`] calls, and can only be called once.
+pub fn run_tests() -> usize {
+    let stack = TEST_STACK.init_once(()).expect("Test runner already run");
+    let tests = tests();
+    let mut failed = 0;
+
+    crate::printkln!("Running {} tests", tests.len());
+    for test in tests {
+        FAILED.store(false, Ordering::Release);
+        // SAFETY: The thread is only used for one test at a time, and the join below waits for
+        // each test to finish, or be aborted, before the thread is created again.
+        unsafe {
+            let priority = raw::k_thread_priority_get(raw::k_current_get());
+            let tid = raw::k_thread_create(
+                TEST_THREAD.value.get(),
+                stack.base,
+                stack.size,
+                Some(test_entry),
+                test.func as *mut c_void,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                priority,
+                0,
+                K_FOREVER,
+            );
+            // The thread is only started once its id is stored, so that a panic in the test is
+            // always recognized by `on_panic`.
+            TEST_TID.store(tid, Ordering::Release);
+            raw::k_thread_start(tid);
+            raw::k_thread_join(tid, K_FOREVER);
+        }
+        TEST_TID.store(ptr::null_mut(), Ordering::Release);
+
+        if FAILED.load(Ordering::Acquire) {
+            crate::printkln!("test {} ... FAILED", test.name);
+            failed += 1;
+        } else {
+            crate::printkln!("test {} ... ok", test.name);
+        }
+    }
+
+    crate::printkln!(
+        "test result: {} passed; {} failed",
+        tests.len() - failed,
+        failed
+    );
+    if failed == 0 {
+        crate::printkln!("All tests passed");
+    }
+    failed
+}
+
+unsafe extern "C" fn test_entry(func: *mut c_void, _p2: *mut c_void, _p3: *mut c_void) {
+    let func: fn() = mem::transmute(func);
+    func();
+}
+
+/// Called by the panic handler.  If the panic is in a test, mark it failed and end its thread.
+pub(crate) fn on_panic() {
+    // SAFETY: Aborting the current thread doesn't return, and the runner is waiting on a join.
+    unsafe {
+        let current = raw::k_current_get();
+        if current == TEST_TID.load(Ordering::Acquire) {
+            FAILED.store(true, Ordering::Release);
+            raw::k_thread_abort(current);
+        }
+    }
+}
+
+/// Run all of the `#[zephyr_test]` functions, and report the results.
+///
+/// Evaluates to the number of tests that failed.
+#[macro_export]
+macro_rules! zephyr_test_runner {
+    () => {
+        $crate::testing::run_tests()
+    };
+}
@@ -28,6 +28,8 @@ use zephyr_sys::{k_ticks_t, k_timeout_t, k_uptime_ticks};
 
 use core::fmt::Debug;
 
+use crate::error::{ZResult, ZephyrError};
+
 // The system ticks, is mostly a constant, but there are some boards that use a dynamic tick
 // frequency, and thus need to read this at runtime.
 #[cfg(CONFIG_TIMER_READS_ITS_FREQUENCY_AT_RUNTIME)]
@@ -88,6 +90,40 @@ impl PartialEq for Timeout {
 
 impl Eq for Timeout {}
 
+impl Timeout {
+    /// A timeout that never expires, the same as `K_FOREVER`.
+    pub const FOREVER: Timeout = Timeout(crate::sys::K_FOREVER);
+
+    /// A timeout that doesn't wait at all, the same as `K_NO_WAIT`.
+    pub const NO_WAIT: Timeout = Timeout(crate::sys::K_NO_WAIT);
+}
+
+// These allow a timeout to be passed directly to, or taken from, the C API.
+impl From<Timeout> for k_timeout_t {
+    fn from(value: Timeout) -> k_timeout_t {
+        value.0
+    }
+}
+
+impl From<k_timeout_t> for Timeout {
+    fn from(value: k_timeout_t) -> Timeout {
+        Timeout(value)
+    }
+}
+
+/// A timeout is only a `Duration` if it is relative.  `K_NO_WAIT` is a zero duration, and
+/// `K_FOREVER` and absolute timeouts fail with [`ZephyrError::Invalid`].
+impl TryFrom<Timeout> for Duration {
+    type Error = ZephyrError;
+
+    fn try_from(value: Timeout) -> ZResult<Duration> {
+        if value.0.ticks < 0 {
+            return Err(ZephyrError::Invalid);
+        }
+        Ok(Duration::from_ticks(checked_cast(value.0.ticks)))
+    }
+}
+
 // `From` allows methods to take a time of various types and convert it into a Zephyr timeout.
 impl From<Duration> for Timeout {
     fn from(value: Duration) -> Timeout {
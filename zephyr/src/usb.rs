@@ -0,0 +1,29 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! USB device support, with Zephyr's USB device stack.
+//!
+//! The classes are set up first, and then the stack is started with [`enable`], which makes the
+//! device visible to the host.  The classes and their instances are chosen by Kconfig, such as
+//! `CONFIG_USB_DEVICE_HID`, and each class instance is a device named after the class, such as
+//! `HID_0`.
+
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+
+#[cfg(CONFIG_USB_DEVICE_HID)]
+pub mod hid;
+#[cfg(CONFIG_USB_MASS_STORAGE)]
+pub mod msc;
+
+/// Start the USB device stack, after the classes have been set up.
+///
+/// Calling this again, once the stack is running, fails with `EALREADY`.
+pub fn enable() -> ZResult<()> {
+    ok_or_errno(unsafe { raw::usb_enable(None) })
+}
+
+/// Stop the USB device stack.
+pub fn disable() -> ZResult<()> {
+    ok_or_errno(unsafe { raw::usb_disable() })
+}
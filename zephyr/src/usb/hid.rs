@@ -0,0 +1,151 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! USB Human Interface Devices, such as keyboards, mice and gamepads.
+//!
+//! ```ignore
+//! static REPORT_DESC: [u8; 52] = [/* ... */];
+//!
+//! let hid = HidDevice::new(&REPORT_DESC)?;
+//! hid.set_output_callback(|report| { /* such as the keyboard LEDs */ })?;
+//! zephyr::usb::enable()?;
+//! hid.submit_report(&[0, 0, 4, 0, 0, 0, 0, 0])?;
+//! ```
+
+use core::ffi::{c_int, CStr};
+use core::fmt;
+use core::mem;
+
+use crate::error::{ok_or_errno, ZResult, ZephyrError};
+use crate::raw;
+use crate::sync::SpinMutex;
+
+/// The largest output report passed to an output callback.
+pub const MAX_OUTPUT_REPORT: usize = 64;
+
+/// The most HID devices with an output callback.
+const MAX_CALLBACKS: usize = 4;
+
+/// The output callbacks, by device.
+static CALLBACKS: SpinMutex<heapless::Vec<(usize, fn(&[u8])), MAX_CALLBACKS>> =
+    SpinMutex::new(heapless::Vec::new());
+
+/// The operations for all of the devices, which pass output reports to their callback.
+static OPS: Ops = Ops(raw::hid_ops {
+    set_report: Some(set_report),
+    #[cfg(CONFIG_ENABLE_HID_INT_OUT_EP)]
+    int_out_ready: Some(int_out_ready),
+    // SAFETY: The other operations are optional, and zero is `None`.
+    ..unsafe { mem::zeroed() }
+});
+
+struct Ops(raw::hid_ops);
+
+// SAFETY: The operations are only read, by the USB stack.
+unsafe impl Sync for Ops {}
+
+/// A HID class instance.
+pub struct HidDevice {
+    device: *const raw::device,
+}
+
+// SAFETY: The HID calls can be made from any thread.
+unsafe impl Send for HidDevice {}
+unsafe impl Sync for HidDevice {}
+
+impl HidDevice {
+    /// Set up the first HID instance, `HID_0`, with the given report descriptor, which describes
+    /// the reports the device sends and receives.
+    ///
+    /// This must be done before the stack is started with [`enable`](super::enable).
+    pub fn new(report_desc: &'static [u8]) -> ZResult<HidDevice> {
+        Self::with_name(c"HID_0", report_desc)
+    }
+
+    /// Set up the HID instance with the given device name.
+    pub fn with_name(name: &CStr, report_desc: &'static [u8]) -> ZResult<HidDevice> {
+        let device = unsafe { raw::device_get_binding(name.as_ptr()) };
+        if device.is_null() {
+            return Err(ZephyrError::NoDevice);
+        }
+        unsafe {
+            raw::usb_hid_register_device(
+                device,
+                report_desc.as_ptr(),
+                report_desc.len(),
+                &OPS.0,
+            );
+            ok_or_errno(raw::usb_hid_init(device))?;
+        }
+        Ok(HidDevice { device })
+    }
+
+    /// Send an input report to the host.
+    ///
+    /// This fails with `EAGAIN` while the last report is still waiting to be read by the host.
+    pub fn submit_report(&self, data: &[u8]) -> ZResult<()> {
+        let mut written = 0;
+        ok_or_errno(unsafe {
+            raw::hid_int_ep_write(self.device, data.as_ptr(), data.len() as u32, &mut written)
+        })
+    }
+
+    /// Call `cb` with each output report from the host, such as the LED state for a keyboard.
+    ///
+    /// The callback is called from the USB stack, which may be in interrupt context, so it should
+    /// be short.  Setting a callback again replaces the earlier one.
+    pub fn set_output_callback(&self, cb: fn(&[u8])) -> ZResult<()> {
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let key = self.device as usize;
+        if let Some(entry) = callbacks.iter_mut().find(|(dev, _)| *dev == key) {
+            entry.1 = cb;
+            return Ok(());
+        }
+        callbacks.push((key, cb)).map_err(|_| ZephyrError::NoMemory)
+    }
+}
+
+impl fmt::Debug for HidDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HidDevice({:?})", self.device)
+    }
+}
+
+/// Pass an output report to the device's callback, if it has one.
+fn deliver(dev: *const raw::device, report: &[u8]) {
+    let key = dev as usize;
+    let cb = CALLBACKS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(dev, _)| *dev == key)
+        .map(|(_, cb)| *cb);
+    if let Some(cb) = cb {
+        cb(report);
+    }
+}
+
+/// A SET_REPORT request from the host, on the control endpoint.
+unsafe extern "C" fn set_report(
+    dev: *const raw::device,
+    _setup: *mut raw::usb_setup_packet,
+    len: *mut i32,
+    data: *mut *mut u8,
+) -> c_int {
+    let len = (*len).max(0) as usize;
+    if !(*data).is_null() {
+        deliver(dev, core::slice::from_raw_parts(*data, len));
+    }
+    0
+}
+
+/// An output report has arrived on the interrupt OUT endpoint.
+#[cfg(CONFIG_ENABLE_HID_INT_OUT_EP)]
+unsafe extern "C" fn int_out_ready(dev: *const raw::device) {
+    let mut buf = [0u8; MAX_OUTPUT_REPORT];
+    let mut read = 0;
+    let ret = raw::hid_int_ep_read(dev, buf.as_mut_ptr(), buf.len() as u32, &mut read);
+    if ret == 0 {
+        deliver(dev, &buf[..read as usize]);
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! The USB Mass Storage Class, which shows a disk to the host as a USB drive.
+//!
+//! The class serves the disk named by `CONFIG_MASS_STORAGE_DISK_NAME`.  The device should not
+//! use the disk itself while the host has it mounted, as neither side knows about the other's
+//! changes.
+
+use crate::disk::DiskDevice;
+use crate::error::{ZResult, ZephyrError};
+use crate::kconfig::CONFIG_MASS_STORAGE_DISK_NAME;
+
+/// The mass storage class, serving a disk.
+#[derive(Debug)]
+pub struct UsbMsc {
+    disk: DiskDevice,
+}
+
+impl UsbMsc {
+    /// The mass storage class for `disk`.
+    pub fn new(disk: &DiskDevice) -> UsbMsc {
+        UsbMsc { disk: *disk }
+    }
+
+    /// The disk being served.
+    pub fn disk(&self) -> &DiskDevice {
+        &self.disk
+    }
+
+    /// Start the USB stack, so the host sees the drive.
+    ///
+    /// Fails with `EINVAL` if the disk is not the one the class was configured for, by
+    /// `CONFIG_MASS_STORAGE_DISK_NAME`, as that can only be set at build time.
+    pub fn enable(&self) -> ZResult<()> {
+        if self.disk.name().to_bytes() != CONFIG_MASS_STORAGE_DISK_NAME.as_bytes() {
+            return Err(ZephyrError::Invalid);
+        }
+        super::enable()
+    }
+}
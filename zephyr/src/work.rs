@@ -161,7 +161,9 @@
 //!
 //! Although Zephyr has 3 types of work queues, the `k_work_poll` is sufficient to implement all of
 //! the behavior, and this implementation only implements this type.  Non Future work could be built
-//! around the other work types.
+//! around the other work types.  [`PollWork`] exposes `k_work_poll` directly, for a plain callback
+//! triggered by events, such as a semaphore given from an interrupt handler, and [`WorkItem`] and
+//! [`DelayedWorkItem`] are plain callbacks for the [`SystemWorkQueue`].
 //!
 //! As such, this means that manually constructed work is still built using `Future`.  The `_async`
 //! primitives throughout this crate can be used just as readily by hand-written Futures as by async
@@ -177,10 +179,14 @@
 
 extern crate alloc;
 
+use alloc::{boxed::Box, vec::Vec};
+
 use core::{
     cell::UnsafeCell,
-    ffi::{c_int, c_uint, CStr},
+    ffi::{c_int, c_uint, c_void, CStr},
+    fmt,
     future::Future,
+    marker::{PhantomData, PhantomPinned},
     mem,
     pin::Pin,
     ptr,
@@ -188,14 +194,24 @@ use core::{
 };
 
 use zephyr_sys::{
-    k_poll_signal, k_poll_signal_check, k_poll_signal_init, k_poll_signal_raise,
-    k_poll_signal_reset, k_work, k_work_init, k_work_q, k_work_queue_config, k_work_queue_init,
-    k_work_queue_start, k_work_submit, k_work_submit_to_queue, ETIMEDOUT,
+    k_poll_event, k_poll_event_init, k_poll_modes_K_POLL_MODE_NOTIFY_ONLY, k_poll_signal,
+    k_poll_signal_check, k_poll_signal_init, k_poll_signal_raise, k_poll_signal_reset, k_work,
+    k_work_cancel_delayable_sync, k_work_cancel_sync, k_work_delayable, k_work_init,
+    k_work_init_delayable, k_work_poll, k_work_poll_cancel, k_work_poll_init,
+    k_work_poll_submit_to_queue, k_work_q, k_work_queue_config, k_work_queue_init,
+    k_work_queue_start, k_work_schedule, k_work_submit, k_work_submit_to_queue, k_work_sync,
+    ZR_POLL_TYPE_DATA_AVAILABLE, ZR_POLL_TYPE_SEM_AVAILABLE, ZR_POLL_TYPE_SIGNAL, ETIMEDOUT,
 };
 
 use crate::{
-    error::to_result_void, kio::ContextExt, object::Fixed, simpletls::StaticTls, sync::Arc,
-    sys::thread::ThreadStack, time::Timeout,
+    error::{ok_or_errno, to_result_void, ZResult},
+    kio::ContextExt,
+    object::Fixed,
+    simpletls::StaticTls,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    sync::Arc,
+    sys::{queue::Queue, sync::Semaphore, thread::ThreadStack},
+    time::{Duration, Timeout},
 };
 
 pub mod futures;
@@ -676,3 +692,383 @@ impl<T: SimpleAction + Send> Work<T> {
         &self.action
     }
 }
+
+/// Work that is triggered by `k_poll` events.
+///
+/// This wraps Zephyr's `k_work_poll`.  When submitted, the work waits for any one of a set of
+/// [`PollEvent`]s, or a timeout, and then the callback is run on the work queue.  Since a semaphore
+/// can be given, or a [`Signal`] raised, from an interrupt handler, this is the usual way to move
+/// the handling of an interrupt event onto a work queue thread.
+///
+/// Unlike [`Work`], this does not take ownership of any data.  The callback is a plain function
+/// that is given the `user_data` pointer (null for [`PollWork::new`]), and it is up to the caller
+/// to make sure that whatever it points to is valid while the work is submitted.  The work is only
+/// run once per submission, and must be submitted again to wait for the next event.
+///
+/// The objects referenced by the events must live as long as the `PollWork`.  Dropping the
+/// `PollWork` cancels it, waiting for the callback to finish if it is already running.
+pub struct PollWork<'a> {
+    inner: Pin<Box<PollWorkInner>>,
+    _events: PhantomData<&'a ()>,
+}
+
+struct PollWorkInner {
+    work: UnsafeCell<k_work_poll>,
+    /// The events being waited for.  Zephyr holds a pointer to these while the work is submitted.
+    events: UnsafeCell<Vec<k_poll_event>>,
+    callback: fn(*mut c_void),
+    user_data: *mut c_void,
+    _pin: PhantomPinned,
+}
+
+impl<'a> PollWork<'a> {
+    /// Create a new poll work item that will call `callback` with a null pointer.
+    pub fn new(callback: fn(*mut c_void)) -> PollWork<'a> {
+        Self::with_user_data(callback, ptr::null_mut())
+    }
+
+    /// Create a new poll work item that will call `callback` with `user_data`.
+    pub fn with_user_data(callback: fn(*mut c_void), user_data: *mut c_void) -> PollWork<'a> {
+        let inner = Box::pin(PollWorkInner {
+            // SAFETY: will be initialized below, after this is pinned.
+            work: unsafe { mem::zeroed() },
+            events: UnsafeCell::new(Vec::new()),
+            callback,
+            user_data,
+            _pin: PhantomPinned,
+        });
+
+        // SAFETY: Initializes above zero-initialized struct, which is pinned.
+        unsafe {
+            k_work_poll_init(inner.work.get(), Some(Self::handler));
+        }
+
+        PollWork {
+            inner,
+            _events: PhantomData,
+        }
+    }
+
+    /// Submit this work to `queue`, to be run when any of `events` trigger, or when `timeout`
+    /// expires.
+    ///
+    /// The events are copied, so the slice does not need to outlive this call.  If the work is
+    /// still waiting from a previous submission, that submission is cancelled first.
+    pub fn submit_to_queue(
+        &mut self,
+        queue: &'a WorkQueue,
+        events: &[PollEvent<'a>],
+        timeout: Duration,
+    ) -> ZResult<()> {
+        let timeout: Timeout = timeout.into();
+        let work = self.inner.work.get();
+
+        // SAFETY: Cancelling makes sure Zephyr is no longer using the events, so they can be
+        // replaced.  An error just means the work wasn't waiting.
+        unsafe {
+            k_work_poll_cancel(work);
+        }
+
+        // SAFETY: Zephyr isn't referencing the events now, and `&mut self` keeps anyone else from
+        // doing so.
+        let raw_events = unsafe { &mut *self.inner.events.get() };
+        raw_events.clear();
+        for event in events {
+            // SAFETY: Fill with zeroed memory, initialization happens in the init function next.
+            raw_events.push(unsafe { mem::zeroed() });
+            let ev = raw_events.last_mut().unwrap();
+            unsafe {
+                k_poll_event_init(
+                    ev,
+                    event.kind,
+                    k_poll_modes_K_POLL_MODE_NOTIFY_ONLY as i32,
+                    event.obj,
+                );
+            }
+        }
+
+        // SAFETY: The work and events are pinned, and the lifetime keeps the objects the events
+        // refer to valid.  Drop makes sure the work is no longer pending.
+        ok_or_errno(unsafe {
+            k_work_poll_submit_to_queue(
+                queue.item.get(),
+                work,
+                raw_events.as_mut_ptr(),
+                raw_events.len() as c_int,
+                timeout.0,
+            )
+        })
+    }
+
+    /// Cancel the work, if it is still waiting for events.
+    ///
+    /// Returns an error if the work was not waiting, which includes if it has already been
+    /// triggered and is queued or running.
+    pub fn cancel(&mut self) -> ZResult<()> {
+        ok_or_errno(unsafe { k_work_poll_cancel(self.inner.work.get()) })
+    }
+
+    /// Callback, through C, when the work has been triggered.
+    extern "C" fn handler(work: *mut k_work) {
+        // The `k_work` is the first field of the `k_work_poll`.
+        // SAFETY: The work pointer comes from our pinned inner struct, which outlives the work.
+        let this = unsafe {
+            &*work
+                .cast::<u8>()
+                .sub(mem::offset_of!(PollWorkInner, work))
+                .cast::<PollWorkInner>()
+        };
+        (this.callback)(this.user_data);
+    }
+}
+
+impl Drop for PollWork<'_> {
+    fn drop(&mut self) {
+        let work = self.inner.work.get();
+        // SAFETY: If the events have already triggered, the work may be queued or running, so wait
+        // for that to finish as well before freeing it.
+        unsafe {
+            k_work_poll_cancel(work);
+            let mut sync: k_work_sync = mem::zeroed();
+            k_work_cancel_sync(ptr::addr_of_mut!((*work).work), &mut sync);
+        }
+    }
+}
+
+/// An event that a [`PollWork`] can wait for.
+#[derive(Clone, Copy)]
+pub struct PollEvent<'a> {
+    kind: u32,
+    obj: *mut c_void,
+    _obj: PhantomData<&'a ()>,
+}
+
+impl<'a> PollEvent<'a> {
+    /// An event that triggers when the semaphore is available to take.
+    ///
+    /// The semaphore is not taken, so the callback will usually want to do that.
+    pub fn semaphore(sem: &'a Semaphore) -> PollEvent<'a> {
+        Self::from_raw(ZR_POLL_TYPE_SEM_AVAILABLE, sem.item.get() as *mut c_void)
+    }
+
+    /// An event that triggers when the signal is raised.
+    pub fn signal(signal: &'a Signal) -> PollEvent<'a> {
+        Self::from_raw(ZR_POLL_TYPE_SIGNAL, signal.item.get() as *mut c_void)
+    }
+
+    /// An event that triggers when the queue has data.
+    pub fn queue(queue: &'a Queue) -> PollEvent<'a> {
+        Self::from_raw(ZR_POLL_TYPE_DATA_AVAILABLE, queue.item.get() as *mut c_void)
+    }
+
+    fn from_raw(kind: u32, obj: *mut c_void) -> PollEvent<'a> {
+        PollEvent {
+            kind,
+            obj,
+            _obj: PhantomData,
+        }
+    }
+}
+
+impl fmt::Debug for PollEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PollEvent({})", self.kind)
+    }
+}
+
+impl fmt::Debug for PollWork<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PollWork")
+    }
+}
+
+/// Count of the work submitted through [`SystemWorkQueue`] that has not run yet.
+static SYS_PENDING: AtomicU32 = AtomicU32::new(0);
+
+/// The system work queue, `k_sys_work_q`, started by Zephyr itself.
+///
+/// This queue is shared with Zephyr and its drivers, so work run on it should be short, and
+/// should not block.  [`WorkItem`] and [`DelayedWorkItem`] are plain callbacks that can be
+/// resubmitted without allocating each time.
+///
+/// Zephyr does not provide a count of the work waiting on a queue, so
+/// [`pending_count`](Self::pending_count) counts the work submitted through here that has not yet
+/// run.  Work that Zephyr or C code submits is not included.
+pub struct SystemWorkQueue;
+
+impl SystemWorkQueue {
+    /// Submit `item` to the system work queue.
+    ///
+    /// Returns true if the item was queued, and false if it was already waiting to run.
+    pub fn submit(item: &mut WorkItem) -> bool {
+        let inner = &*item.inner;
+        // SAFETY: The work is pinned, and dropping the item cancels it.
+        let ret = unsafe { k_work_submit(inner.work.get()) };
+        inner.mark_queued(ret > 0)
+    }
+
+    /// Schedule `item` to be submitted to the system work queue after `delay`.
+    ///
+    /// Returns true if the item was scheduled, and false if it was already scheduled or waiting
+    /// to run, in which case the earlier delay is kept.  Scheduled items count as pending.
+    pub fn submit_delayed(item: &mut DelayedWorkItem, delay: Duration) -> bool {
+        let timeout: Timeout = delay.into();
+        let inner = &*item.inner;
+        // SAFETY: The work is pinned, and dropping the item cancels it.
+        let ret = unsafe { k_work_schedule(inner.work.get(), timeout.0) };
+        inner.mark_queued(ret > 0)
+    }
+
+    /// The number of items submitted through [`SystemWorkQueue`] that have not yet run.
+    pub fn pending_count() -> u32 {
+        SYS_PENDING.load(Ordering::Acquire)
+    }
+}
+
+/// The parts of a system work item that Zephyr holds a pointer to.
+struct SysWorkInner<W> {
+    work: UnsafeCell<W>,
+    callback: fn(*mut c_void),
+    user_data: *mut c_void,
+    /// Set while the work is counted in [`SYS_PENDING`].
+    queued: AtomicBool,
+    _pin: PhantomPinned,
+}
+
+impl<W> SysWorkInner<W> {
+    fn new(callback: fn(*mut c_void), user_data: *mut c_void) -> Pin<Box<Self>> {
+        Box::pin(SysWorkInner {
+            // SAFETY: will be initialized by the caller, after this is pinned.
+            work: unsafe { mem::zeroed() },
+            callback,
+            user_data,
+            queued: AtomicBool::new(false),
+            _pin: PhantomPinned,
+        })
+    }
+
+    /// Count the work as pending if the submission queued it.  Returns `enqueued`.
+    fn mark_queued(&self, enqueued: bool) -> bool {
+        if enqueued && !self.queued.swap(true, Ordering::AcqRel) {
+            SYS_PENDING.fetch_add(1, Ordering::AcqRel);
+        }
+        enqueued
+    }
+
+    /// Stop counting the work as pending, once it has run or been cancelled.
+    fn mark_done(&self) {
+        if self.queued.swap(false, Ordering::AcqRel) {
+            SYS_PENDING.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Callback, through C, when the work runs.
+    extern "C" fn handler(work: *mut k_work) {
+        // The `k_work` is the first field of both `k_work` and `k_work_delayable`.
+        // SAFETY: The work pointer comes from our pinned inner struct, which outlives the work.
+        let this = unsafe {
+            &*work
+                .cast::<u8>()
+                .sub(mem::offset_of!(Self, work))
+                .cast::<Self>()
+        };
+        this.mark_done();
+        (this.callback)(this.user_data);
+    }
+}
+
+/// A work item for the [`SystemWorkQueue`], that calls a function each time it runs.
+///
+/// As with [`PollWork`], the callback is given a `user_data` pointer, null for
+/// [`WorkItem::new`], and it is up to the caller to keep whatever it points to valid.  The item
+/// is deliberately not `Clone`, so the same work can't be submitted twice through two handles.
+/// Dropping the item cancels it, waiting for the callback if it is running.
+pub struct WorkItem {
+    inner: Pin<Box<SysWorkInner<k_work>>>,
+}
+
+// SAFETY: Zephyr's work API can be used from any thread.
+unsafe impl Send for WorkItem {}
+unsafe impl Sync for WorkItem {}
+
+impl WorkItem {
+    /// Create a work item that will call `callback` with a null pointer.
+    pub fn new(callback: fn(*mut c_void)) -> WorkItem {
+        Self::with_user_data(callback, ptr::null_mut())
+    }
+
+    /// Create a work item that will call `callback` with `user_data`.
+    pub fn with_user_data(callback: fn(*mut c_void), user_data: *mut c_void) -> WorkItem {
+        let inner = SysWorkInner::new(callback, user_data);
+        // SAFETY: Initializes the zeroed work, which is pinned.
+        unsafe {
+            k_work_init(inner.work.get(), Some(SysWorkInner::<k_work>::handler));
+        }
+        WorkItem { inner }
+    }
+}
+
+impl Drop for WorkItem {
+    fn drop(&mut self) {
+        // SAFETY: Once this returns, the work is neither queued nor running.
+        unsafe {
+            let mut sync: k_work_sync = mem::zeroed();
+            k_work_cancel_sync(self.inner.work.get(), &mut sync);
+        }
+        self.inner.mark_done();
+    }
+}
+
+impl fmt::Debug for WorkItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WorkItem({:p})", self.inner.work.get())
+    }
+}
+
+/// A work item for the [`SystemWorkQueue`] that can be submitted after a delay.
+///
+/// This wraps a `k_work_delayable`, and otherwise behaves as [`WorkItem`].
+pub struct DelayedWorkItem {
+    inner: Pin<Box<SysWorkInner<k_work_delayable>>>,
+}
+
+// SAFETY: Zephyr's work API can be used from any thread.
+unsafe impl Send for DelayedWorkItem {}
+unsafe impl Sync for DelayedWorkItem {}
+
+impl DelayedWorkItem {
+    /// Create a delayed work item that will call `callback` with a null pointer.
+    pub fn new(callback: fn(*mut c_void)) -> DelayedWorkItem {
+        Self::with_user_data(callback, ptr::null_mut())
+    }
+
+    /// Create a delayed work item that will call `callback` with `user_data`.
+    pub fn with_user_data(callback: fn(*mut c_void), user_data: *mut c_void) -> DelayedWorkItem {
+        let inner = SysWorkInner::new(callback, user_data);
+        // SAFETY: Initializes the zeroed work, which is pinned.
+        unsafe {
+            k_work_init_delayable(
+                inner.work.get(),
+                Some(SysWorkInner::<k_work_delayable>::handler),
+            );
+        }
+        DelayedWorkItem { inner }
+    }
+}
+
+impl Drop for DelayedWorkItem {
+    fn drop(&mut self) {
+        // SAFETY: Once this returns, the work is neither scheduled, queued, nor running.
+        unsafe {
+            let mut sync: k_work_sync = mem::zeroed();
+            k_work_cancel_delayable_sync(self.inner.work.get(), &mut sync);
+        }
+        self.inner.mark_done();
+    }
+}
+
+impl fmt::Debug for DelayedWorkItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DelayedWorkItem({:p})", self.inner.work.get())
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Zbus publish/subscribe.
+//!
+//! Zbus channels hold a single message of a fixed type, and notify their observers when a new
+//! message is published.  In C, channels and observers are declared with `ZBUS_CHAN_DEFINE` and
+//! `ZBUS_SUBSCRIBER_DEFINE`.  The [`zbus_chan_define!`] and [`zbus_subscriber_define!`] macros are
+//! the Rust equivalents, declaring a [`ZbusChannel`] or [`ZbusSubscriber`] static.
+//!
+//! ```ignore
+//! #[derive(Clone, Copy)]
+//! struct Reading {
+//!     temp: i32,
+//! }
+//!
+//! zbus_chan_define!(static READINGS: Reading = Reading { temp: 0 });
+//! zbus_subscriber_define!(static LOGGER: 4);
+//!
+//! READINGS.subscribe(&LOGGER)?;
+//! READINGS.publish(&Reading { temp: 21 })?;
+//!
+//! let msg = LOGGER.wait_msg(Duration::secs_at_least(1))?;
+//! if let Some(reading) = msg.read(&READINGS) {
+//!     // ...
+//! }
+//! ```
+//!
+//! The Rust channels are not in zbus's iterable sections, so are not visible to C code that looks
+//! channels up, and observers can only be added at runtime, which requires
+//! `CONFIG_ZBUS_RUNTIME_OBSERVERS`.
+//!
+//! Requires `CONFIG_ZBUS`.
+
+use core::cell::UnsafeCell;
+use core::ffi::{c_char, c_void, CStr};
+use core::fmt;
+use core::mem;
+use core::ptr;
+
+use crate::error::{ok_or_errno, ZResult};
+use crate::raw;
+use crate::sync::OnceLock;
+use crate::time::{Duration, Forever, Timeout};
+
+/// A zbus channel, holding a message of type `T`.
+///
+/// Declared with [`zbus_chan_define!`].  The channel holds pointers to itself once it has been
+/// used, so all of the methods take `&'static self`.
+pub struct ZbusChannel<T> {
+    #[allow(dead_code)]
+    name: &'static CStr,
+    chan: UnsafeCell<raw::zbus_channel>,
+    data: UnsafeCell<raw::zbus_channel_data>,
+    message: UnsafeCell<T>,
+    /// Set once the zbus structures have been set up.
+    init: OnceLock<()>,
+}
+
+// SAFETY: The message is only accessed by zbus, with the channel's semaphore held.
+unsafe impl<T: Send> Sync for ZbusChannel<T> {}
+
+impl<T: Copy> ZbusChannel<T> {
+    /// A channel holding `initial` until the first publish.  Use [`zbus_chan_define!`] instead.
+    #[doc(hidden)]
+    pub const fn new(name: &'static CStr, initial: T) -> ZbusChannel<T> {
+        ZbusChannel {
+            name,
+            // SAFETY: Zeroed memory is a valid unused channel, and it is set up on first use.
+            chan: unsafe { mem::zeroed() },
+            data: unsafe { mem::zeroed() },
+            message: UnsafeCell::new(initial),
+            init: OnceLock::new(),
+        }
+    }
+
+    /// Get the channel, setting it up if that hasn't happened yet.
+    fn raw(&'static self) -> *const raw::zbus_channel {
+        self.init.get_or_init(|| unsafe {
+            // SAFETY: Only done once, before zbus has seen the channel.
+            let chan = &mut *self.chan.get();
+            #[cfg(CONFIG_ZBUS_CHANNEL_NAME)]
+            {
+                chan.name = self.name.as_ptr();
+            }
+            chan.message = self.message.get() as *mut c_void;
+            chan.message_size = mem::size_of::<T>();
+            chan.data = self.data.get();
+            // With no static observers, the zeroed observer indices are an empty range, and the
+            // zeroed runtime observer list is empty.
+            raw::k_sem_init(&mut (*self.data.get()).sem, 1, 1);
+        });
+        self.chan.get()
+    }
+
+    /// Publish a new message, notifying the observers.
+    pub fn publish(&'static self, val: &T) -> ZResult<()> {
+        self.publish_timeout(val, Forever)
+    }
+
+    /// Publish a new message, waiting up to `timeout` for the channel to be free.
+    pub fn publish_timeout<D>(&'static self, val: &T, timeout: D) -> ZResult<()>
+    where
+        D: Into<Timeout>,
+    {
+        let timeout: Timeout = timeout.into();
+        let msg = val as *const T as *const c_void;
+        ok_or_errno(unsafe { raw::zbus_chan_pub(self.raw(), msg, timeout.0) })
+    }
+
+    /// Read the current message.
+    pub fn read(&'static self) -> ZResult<T> {
+        let mut val = mem::MaybeUninit::<T>::uninit();
+        let timeout: Timeout = Forever.into();
+        let msg = val.as_mut_ptr() as *mut c_void;
+        ok_or_errno(unsafe { raw::zbus_chan_read(self.raw(), msg, timeout.0) })?;
+        // SAFETY: zbus copied a whole message into `val`.
+        Ok(unsafe { val.assume_init() })
+    }
+
+    /// Add a subscriber, which is notified of each publish.
+    ///
+    /// Requires `CONFIG_ZBUS_RUNTIME_OBSERVERS`.
+    #[cfg(CONFIG_ZBUS_RUNTIME_OBSERVERS)]
+    pub fn subscribe<const N: usize>(
+        &'static self,
+        subscriber: &'static ZbusSubscriber<N>,
+    ) -> ZResult<()> {
+        let timeout: Timeout = Forever.into();
+        ok_or_errno(unsafe { raw::zbus_chan_add_obs(self.raw(), subscriber.raw(), timeout.0) })
+    }
+
+    /// Remove a subscriber added with [`subscribe`](Self::subscribe).
+    #[cfg(CONFIG_ZBUS_RUNTIME_OBSERVERS)]
+    pub fn unsubscribe<const N: usize>(
+        &'static self,
+        subscriber: &'static ZbusSubscriber<N>,
+    ) -> ZResult<()> {
+        let timeout: Timeout = Forever.into();
+        ok_or_errno(unsafe { raw::zbus_chan_rm_obs(self.raw(), subscriber.raw(), timeout.0) })
+    }
+}
+
+impl<T> fmt::Debug for ZbusChannel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZbusChannel({:?})", self.name)
+    }
+}
+
+/// A subscriber, queueing a notification for up to `N` publishes.
+///
+/// Declared with [`zbus_subscriber_define!`].
+pub struct ZbusSubscriber<const N: usize> {
+    #[allow(dead_code)]
+    name: &'static CStr,
+    obs: UnsafeCell<raw::zbus_observer>,
+    data: UnsafeCell<raw::zbus_observer_data>,
+    queue: UnsafeCell<raw::k_msgq>,
+    buffer: UnsafeCell<[*const raw::zbus_channel; N]>,
+    /// Set once the zbus structures have been set up.
+    init: OnceLock<()>,
+}
+
+// SAFETY: The queue is only accessed through zbus and the k_msgq API.
+unsafe impl<const N: usize> Sync for ZbusSubscriber<N> {}
+
+impl<const N: usize> ZbusSubscriber<N> {
+    /// An empty subscriber.  Use [`zbus_subscriber_define!`] instead.
+    #[doc(hidden)]
+    pub const fn new(name: &'static CStr) -> ZbusSubscriber<N> {
+        ZbusSubscriber {
+            name,
+            // SAFETY: Zeroed memory is a valid unused observer, and it is set up on first use.
+            obs: unsafe { mem::zeroed() },
+            data: unsafe { mem::zeroed() },
+            queue: unsafe { mem::zeroed() },
+            buffer: UnsafeCell::new([ptr::null(); N]),
+            init: OnceLock::new(),
+        }
+    }
+
+    /// Get the observer, setting it up if that hasn't happened yet.
+    fn raw(&'static self) -> *const raw::zbus_observer {
+        self.init.get_or_init(|| unsafe {
+            // SAFETY: Only done once, before zbus has seen the observer.
+            raw::k_msgq_init(
+                self.queue.get(),
+                self.buffer.get() as *mut c_char,
+                mem::size_of::<*const raw::zbus_channel>(),
+                N as u32,
+            );
+            let data = &mut *self.data.get();
+            data.enabled = true;
+            #[cfg(CONFIG_ZBUS_PRIORITY_BOOST)]
+            {
+                data.priority = crate::kconfig::CONFIG_NUM_PREEMPT_PRIORITIES as i32 - 1;
+            }
+            let obs = &mut *self.obs.get();
+            #[cfg(CONFIG_ZBUS_OBSERVER_NAME)]
+            {
+                obs.name = self.name.as_ptr();
+            }
+            obs.type_ = raw::zbus_observer_type_ZBUS_OBSERVER_SUBSCRIBER_TYPE;
+            obs.data = self.data.get();
+            obs.__bindgen_anon_1.queue = self.queue.get();
+        });
+        self.obs.get()
+    }
+
+    /// Wait up to `timeout` for a channel this is subscribed to to be published.
+    ///
+    /// Returns [`ZephyrError::Again`](crate::error::ZephyrError::Again) on timeout.
+    pub fn wait_msg(&'static self, timeout: Duration) -> ZResult<ZbusMessage> {
+        let timeout: Timeout = timeout.into();
+        let mut chan: *const raw::zbus_channel = ptr::null();
+        ok_or_errno(unsafe { raw::zbus_sub_wait(self.raw(), &mut chan, timeout.0) })?;
+        Ok(ZbusMessage { chan })
+    }
+}
+
+impl<const N: usize> fmt::Debug for ZbusSubscriber<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZbusSubscriber({:?})", self.name)
+    }
+}
+
+/// A notification that a channel was published.
+///
+/// The subscriber only learns which channel was published; the message itself is read from the
+/// channel, and may have been replaced by a later publish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZbusMessage {
+    chan: *const raw::zbus_channel,
+}
+
+impl ZbusMessage {
+    /// Returns true if the notification is for `channel`.
+    pub fn is<T: Copy>(&self, channel: &'static ZbusChannel<T>) -> bool {
+        self.chan == channel.raw()
+    }
+
+    /// Read the message from `channel`, or None if the notification is for another channel.
+    pub fn read<T: Copy>(&self, channel: &'static ZbusChannel<T>) -> Option<T> {
+        if self.is(channel) {
+            channel.read().ok()
+        } else {
+            None
+        }
+    }
+
+    /// The raw channel that was published.
+    pub fn as_raw(&self) -> *const raw::zbus_channel {
+        self.chan
+    }
+}
+
+/// Declare a static [`ZbusChannel`], the equivalent of `ZBUS_CHAN_DEFINE`.
+///
+/// ```ignore
+/// zbus_chan_define!(static READINGS: Reading = Reading { temp: 0 });
+/// ```
+#[macro_export]
+macro_rules! zbus_chan_define {
+    ($v:vis static $name:ident: $type:ty = $init:expr $(;)?) => {
+        $v static $name: $crate::zbus::ZbusChannel<$type> =
+            $crate::zbus::ZbusChannel::new($crate::cstr!(stringify!($name)), $init);
+    };
+}
+
+/// Declare a static [`ZbusSubscriber`], with room for `N` pending notifications, the equivalent
+/// of `ZBUS_SUBSCRIBER_DEFINE`.
+///
+/// ```ignore
+/// zbus_subscriber_define!(static LOGGER: 4);
+/// ```
+#[macro_export]
+macro_rules! zbus_subscriber_define {
+    ($v:vis static $name:ident: $size:expr $(;)?) => {
+        $v static $name: $crate::zbus::ZbusSubscriber<{ $size }> =
+            $crate::zbus::ZbusSubscriber::new($crate::cstr!(stringify!($name)));
+    };
+}
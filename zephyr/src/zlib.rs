@@ -0,0 +1,418 @@
+// Copyright (c) 2024 Linaro LTD
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression with zlib.
+//!
+//! These wrap the zlib library from Zephyr's zlib module, which is commonly used to compress
+//! firmware images for over-the-air updates.  [`Deflate::compress`] and [`Inflate::decompress`]
+//! handle a whole buffer at once.  For payloads that are too large to hold in memory, such as an
+//! image being written to flash as it arrives, [`DeflateStream`] and [`InflateStream`] process the
+//! data in chunks.  The streams require `CONFIG_RUST_ALLOC`, as zlib requires the stream state to
+//! stay at a fixed address between calls.
+//!
+//! [`ZlibDecompressor`] wraps an [`InflateStream`] with a buffer of its own for the input, for
+//! data that arrives in chunks of whatever size the transport delivers, such as the body of an
+//! HTTP response with a `deflate` content encoding.
+//!
+//! The output is in the zlib format (RFC 1950).
+
+use core::ffi::c_int;
+use core::fmt;
+use core::mem;
+
+#[cfg(CONFIG_RUST_ALLOC)]
+extern crate alloc;
+#[cfg(CONFIG_RUST_ALLOC)]
+use alloc::boxed::Box;
+
+use crate::raw::{
+    self, deflate, deflateEnd, deflateInit_, inflate, inflateEnd, inflateInit_, z_stream,
+    ZLIB_VERSION,
+};
+
+// Bindgen gives the positive codes as u32, and the negative ones as i32, so bring them all to the
+// type zlib actually returns.
+const Z_OK: c_int = raw::Z_OK as c_int;
+const Z_STREAM_END: c_int = raw::Z_STREAM_END as c_int;
+const Z_NEED_DICT: c_int = raw::Z_NEED_DICT as c_int;
+const Z_STREAM_ERROR: c_int = raw::Z_STREAM_ERROR as c_int;
+const Z_DATA_ERROR: c_int = raw::Z_DATA_ERROR as c_int;
+const Z_MEM_ERROR: c_int = raw::Z_MEM_ERROR as c_int;
+const Z_BUF_ERROR: c_int = raw::Z_BUF_ERROR as c_int;
+const Z_VERSION_ERROR: c_int = raw::Z_VERSION_ERROR as c_int;
+const Z_NO_FLUSH: c_int = raw::Z_NO_FLUSH as c_int;
+const Z_SYNC_FLUSH: c_int = raw::Z_SYNC_FLUSH as c_int;
+const Z_FINISH: c_int = raw::Z_FINISH as c_int;
+const Z_DEFAULT_COMPRESSION: c_int = raw::Z_DEFAULT_COMPRESSION as c_int;
+
+/// An error from zlib.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZlibError {
+    /// The stream state was inconsistent, or a parameter was invalid.
+    Stream,
+    /// The input data was corrupted, or incomplete.
+    Data,
+    /// There was not enough memory.
+    Memory,
+    /// There was not enough room in the output buffer, or the input ended early.
+    Buffer,
+    /// The zlib library version does not match the headers.
+    Version,
+    /// A buffer was larger than zlib can describe in a single call.
+    TooLarge,
+    /// Some other error code from zlib.
+    Other(i32),
+}
+
+impl ZlibError {
+    fn from_code(code: c_int) -> ZlibError {
+        match code {
+            Z_STREAM_ERROR => ZlibError::Stream,
+            Z_DATA_ERROR | Z_NEED_DICT => ZlibError::Data,
+            Z_MEM_ERROR => ZlibError::Memory,
+            Z_BUF_ERROR => ZlibError::Buffer,
+            Z_VERSION_ERROR => ZlibError::Version,
+            other => ZlibError::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for ZlibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZlibError::Stream => write!(f, "zlib stream error"),
+            ZlibError::Data => write!(f, "invalid or incomplete compressed data"),
+            ZlibError::Memory => write!(f, "zlib out of memory"),
+            ZlibError::Buffer => write!(f, "output buffer too small"),
+            ZlibError::Version => write!(f, "zlib version mismatch"),
+            ZlibError::TooLarge => write!(f, "buffer too large for zlib"),
+            ZlibError::Other(code) => write!(f, "zlib error {}", code),
+        }
+    }
+}
+
+/// One-shot compression.
+pub struct Deflate;
+
+impl Deflate {
+    /// Compress all of `input` into `output`, with the default compression level.
+    ///
+    /// Returns the number of bytes written to `output`.  If `output` is too small to hold the
+    /// compressed data, returns [`ZlibError::Buffer`].
+    pub fn compress(input: &[u8], output: &mut [u8]) -> Result<usize, ZlibError> {
+        Self::compress_level(input, output, Z_DEFAULT_COMPRESSION)
+    }
+
+    /// Compress all of `input` into `output`, with the given compression level (0-9).
+    pub fn compress_level(
+        input: &[u8],
+        output: &mut [u8],
+        level: i32,
+    ) -> Result<usize, ZlibError> {
+        // SAFETY: The stream doesn't move while in use, and is ended before returning.
+        unsafe {
+            let mut strm: z_stream = mem::zeroed();
+            deflate_init(&mut strm, level)?;
+            let result = set_buffers(&mut strm, input, output).and_then(|()| {
+                match deflate(&mut strm, Z_FINISH) {
+                    Z_STREAM_END => Ok(strm.total_out as usize),
+                    Z_OK | Z_BUF_ERROR => Err(ZlibError::Buffer),
+                    code => Err(ZlibError::from_code(code)),
+                }
+            });
+            deflateEnd(&mut strm);
+            result
+        }
+    }
+}
+
+/// One-shot decompression.
+pub struct Inflate;
+
+impl Inflate {
+    /// Decompress all of `input` into `output`.
+    ///
+    /// Returns the number of bytes written to `output`.  If `output` is too small, or the input is
+    /// truncated, returns [`ZlibError::Buffer`].
+    pub fn decompress(input: &[u8], output: &mut [u8]) -> Result<usize, ZlibError> {
+        // SAFETY: The stream doesn't move while in use, and is ended before returning.
+        unsafe {
+            let mut strm: z_stream = mem::zeroed();
+            inflate_init(&mut strm)?;
+            let result = set_buffers(&mut strm, input, output).and_then(|()| {
+                match inflate(&mut strm, Z_FINISH) {
+                    Z_STREAM_END => Ok(strm.total_out as usize),
+                    Z_OK | Z_BUF_ERROR => Err(ZlibError::Buffer),
+                    code => Err(ZlibError::from_code(code)),
+                }
+            });
+            inflateEnd(&mut strm);
+            result
+        }
+    }
+}
+
+/// How a stream should flush its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flush {
+    /// Let zlib decide how much to output.  This gives the best compression.
+    None,
+    /// Output everything so far, aligned to a byte boundary, so that the receiver can decompress
+    /// all of the data given so far.
+    Sync,
+    /// There is no more input.  Finish the stream.
+    Finish,
+}
+
+impl Flush {
+    fn code(self) -> c_int {
+        match self {
+            Flush::None => Z_NO_FLUSH,
+            Flush::Sync => Z_SYNC_FLUSH,
+            Flush::Finish => Z_FINISH,
+        }
+    }
+}
+
+/// The result of processing a chunk with a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// How many bytes of the input were used.  Any remaining input should be given again on the
+    /// next call.
+    pub consumed: usize,
+    /// How many bytes were written to the output.
+    pub produced: usize,
+    /// True once the end of the stream has been reached.
+    pub done: bool,
+}
+
+/// Streaming compression.
+#[cfg(CONFIG_RUST_ALLOC)]
+pub struct DeflateStream {
+    strm: Box<z_stream>,
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl DeflateStream {
+    /// Start a new compression stream, with the given compression level (0-9, or -1 for the
+    /// default).
+    pub fn new(level: i32) -> Result<DeflateStream, ZlibError> {
+        // SAFETY: The stream is boxed, so it stays at the same address.
+        unsafe {
+            let mut strm: Box<z_stream> = Box::new(mem::zeroed());
+            deflate_init(&mut strm, level)?;
+            Ok(DeflateStream { strm })
+        }
+    }
+
+    /// Compress a chunk of data.
+    ///
+    /// Call repeatedly with more input, and then with [`Flush::Finish`] (and possibly empty input)
+    /// until the returned progress is `done`.
+    pub fn compress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        flush: Flush,
+    ) -> Result<Progress, ZlibError> {
+        // SAFETY: The buffers are only used within this call.
+        unsafe { run(&mut self.strm, input, output, |s| deflate(s, flush.code())) }
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl Drop for DeflateStream {
+    fn drop(&mut self) {
+        unsafe {
+            deflateEnd(&mut *self.strm);
+        }
+    }
+}
+
+/// Streaming decompression.
+#[cfg(CONFIG_RUST_ALLOC)]
+pub struct InflateStream {
+    strm: Box<z_stream>,
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl InflateStream {
+    /// Start a new decompression stream.
+    pub fn new() -> Result<InflateStream, ZlibError> {
+        // SAFETY: The stream is boxed, so it stays at the same address.
+        unsafe {
+            let mut strm: Box<z_stream> = Box::new(mem::zeroed());
+            inflate_init(&mut strm)?;
+            Ok(InflateStream { strm })
+        }
+    }
+
+    /// Decompress a chunk of data.
+    ///
+    /// Call repeatedly with more input until the returned progress is `done`.
+    pub fn decompress(&mut self, input: &[u8], output: &mut [u8]) -> Result<Progress, ZlibError> {
+        // SAFETY: The buffers are only used within this call.
+        unsafe { run(&mut self.strm, input, output, |s| inflate(s, Z_NO_FLUSH)) }
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl Drop for InflateStream {
+    fn drop(&mut self) {
+        unsafe {
+            inflateEnd(&mut *self.strm);
+        }
+    }
+}
+
+// SAFETY: The streams own their state, and zlib has no thread affinity.
+#[cfg(CONFIG_RUST_ALLOC)]
+unsafe impl Send for DeflateStream {}
+#[cfg(CONFIG_RUST_ALLOC)]
+unsafe impl Send for InflateStream {}
+
+/// The input buffer size of a [`ZlibDecompressor`].
+#[cfg(CONFIG_RUST_ALLOC)]
+pub const DECOMPRESSOR_INPUT_SIZE: usize = 512;
+
+/// Decompression of data fed in as it arrives.
+///
+/// Input is given with [`feed`](Self::feed), which copies as much as fits into the
+/// decompressor's buffer, and decompressed with [`decompress`](Self::decompress), which makes room
+/// for more.  The zlib stream is set up on the first call to `decompress`, so errors setting it up
+/// are reported there.
+///
+/// The decompressor is not `Send`, so it stays with the thread that feeds it.  [`InflateStream`]
+/// can be used instead where the stream needs to move between threads.
+#[cfg(CONFIG_RUST_ALLOC)]
+pub struct ZlibDecompressor {
+    stream: Option<InflateStream>,
+    input: [u8; DECOMPRESSOR_INPUT_SIZE],
+    /// The start of the input not yet consumed.
+    start: usize,
+    /// The end of the input fed so far.
+    end: usize,
+    done: bool,
+    _not_send: core::marker::PhantomData<*const ()>,
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl ZlibDecompressor {
+    /// A decompressor, with nothing fed to it yet.
+    pub fn new() -> ZlibDecompressor {
+        ZlibDecompressor {
+            stream: None,
+            input: [0; DECOMPRESSOR_INPUT_SIZE],
+            start: 0,
+            end: 0,
+            done: false,
+            _not_send: core::marker::PhantomData,
+        }
+    }
+
+    /// Add compressed data.  Returns how much of `chunk` was taken, which is less than all of it
+    /// once the buffer is full.  The rest should be fed again after some has been decompressed.
+    pub fn feed(&mut self, chunk: &[u8]) -> usize {
+        if self.start > 0 {
+            self.input.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        let count = chunk.len().min(self.input.len() - self.end);
+        self.input[self.end..self.end + count].copy_from_slice(&chunk[..count]);
+        self.end += count;
+        count
+    }
+
+    /// Decompress as much of the input fed so far as fits in `output`.
+    ///
+    /// Returns the number of bytes written to `output`, and true once the end of the compressed
+    /// stream has been reached, after which any further input is ignored.
+    pub fn decompress(&mut self, output: &mut [u8]) -> Result<(usize, bool), ZlibError> {
+        if self.done {
+            return Ok((0, true));
+        }
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            stream => stream.insert(InflateStream::new()?),
+        };
+        let progress = stream.decompress(&self.input[self.start..self.end], output)?;
+        self.start += progress.consumed;
+        self.done = progress.done;
+        Ok((progress.produced, progress.done))
+    }
+
+    /// The amount of input fed, but not yet decompressed.
+    pub fn pending(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl Default for ZlibDecompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(CONFIG_RUST_ALLOC)]
+impl fmt::Debug for ZlibDecompressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZlibDecompressor({} pending)", self.pending())
+    }
+}
+
+/// The size of the stream struct, which the init functions use to confirm the headers match.
+const STREAM_SIZE: c_int = mem::size_of::<z_stream>() as c_int;
+
+unsafe fn deflate_init(strm: &mut z_stream, level: i32) -> Result<(), ZlibError> {
+    match deflateInit_(strm, level, ZLIB_VERSION.as_ptr() as *const _, STREAM_SIZE) {
+        Z_OK => Ok(()),
+        code => Err(ZlibError::from_code(code)),
+    }
+}
+
+unsafe fn inflate_init(strm: &mut z_stream) -> Result<(), ZlibError> {
+    match inflateInit_(strm, ZLIB_VERSION.as_ptr() as *const _, STREAM_SIZE) {
+        Z_OK => Ok(()),
+        code => Err(ZlibError::from_code(code)),
+    }
+}
+
+/// Point the stream at the given buffers.
+unsafe fn set_buffers(
+    strm: &mut z_stream,
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<(), ZlibError> {
+    strm.next_in = input.as_ptr() as *mut _;
+    strm.avail_in = input.len().try_into().map_err(|_| ZlibError::TooLarge)?;
+    strm.next_out = output.as_mut_ptr();
+    strm.avail_out = output.len().try_into().map_err(|_| ZlibError::TooLarge)?;
+    Ok(())
+}
+
+/// Run a single step of a stream over the given buffers.
+#[cfg(CONFIG_RUST_ALLOC)]
+unsafe fn run(
+    strm: &mut z_stream,
+    input: &[u8],
+    output: &mut [u8],
+    step: impl FnOnce(&mut z_stream) -> c_int,
+) -> Result<Progress, ZlibError> {
+    set_buffers(strm, input, output)?;
+    let code = step(strm);
+    let progress = Progress {
+        consumed: input.len() - strm.avail_in as usize,
+        produced: output.len() - strm.avail_out as usize,
+        done: code == Z_STREAM_END,
+    };
+    // Don't leave pointers to the buffers in the stream.
+    strm.next_in = core::ptr::null_mut();
+    strm.next_out = core::ptr::null_mut();
+    match code {
+        // A buffer error just means no progress was possible, which the caller can see.
+        Z_OK | Z_STREAM_END | Z_BUF_ERROR => Ok(progress),
+        code => Err(ZlibError::from_code(code)),
+    }
+}